@@ -4,6 +4,7 @@ import puppeteer from 'puppeteer-extra';
 import StealthPlugin from 'puppeteer-extra-plugin-stealth';
 import { Page, Browser } from 'puppeteer';
 import { logger } from './utils/logger';
+import { closeBrowserOnStop } from './utils/control';
 import * as path from 'path';
 import * as os from 'os';
 
@@ -238,6 +239,9 @@ async function sendMessageToAgent(agentName: string, message: string): Promise<v
       ]
     });
 
+    // Listens for the rest of the process, including keep-alive mode
+    closeBrowserOnStop(browser);
+
     const page: Page = await browser.newPage();
 
     // Set viewport and user agent to match your working code
@@ -704,6 +708,7 @@ async function sendMessageAndGetResponse(agentName: string, message: string, ses
 // Initial message (creates new browser session)
 async function sendInitialMessage(agent: AgentConfig, message: string): Promise<string> {
   let browser: Browser | null = null;
+  let stopListening: (() => void) | null = null;
 
   try {
     logger.info(`Initializing ${agent.name} automation to get response...`);
@@ -725,6 +730,8 @@ async function sendInitialMessage(agent: AgentConfig, message: string): Promise<
       ]
     });
 
+    stopListening = closeBrowserOnStop(browser);
+
     const page: Page = await browser.newPage();
 
     // Set viewport and user agent
@@ -805,6 +812,7 @@ async function sendInitialMessage(agent: AgentConfig, message: string): Promise<
     logger.error('Error in sendMessageAndGetResponse:', error);
     throw error;
   } finally {
+    stopListening?.();
     if (browser) {
       logger.info('Closing browser...');
       await browser.close();
@@ -0,0 +1,36 @@
+import * as readline from 'readline';
+import { Browser } from 'puppeteer';
+import { logger } from './logger';
+
+/**
+ * Line the backend writes to stdin to ask for a clean shutdown. Must match
+ * BROWSER_CHAT_STOP_COMMAND in crates/executors/src/actions/browser_chat_request.rs.
+ */
+export const STOP_COMMAND = 'stop';
+
+/**
+ * Close `browser` and exit when the backend sends a stop request on stdin, so the
+ * profile is flushed to disk and no window outlives the process. Returns a function
+ * that stops listening, which lets the process exit once it is done on its own.
+ */
+export function closeBrowserOnStop(browser: Browser): () => void {
+  const control = readline.createInterface({ input: process.stdin, terminal: false });
+  let stopping = false;
+
+  control.on('line', async (line) => {
+    if (line.trim() !== STOP_COMMAND || stopping) return;
+    stopping = true;
+    logger.info('Stop requested by backend, closing browser...');
+    try {
+      await browser.close();
+    } catch (err) {
+      logger.debug('Error closing browser on stop request:', err);
+    }
+    process.exit(0);
+  });
+
+  return () => {
+    control.close();
+    process.stdin.destroy();
+  };
+}
@@ -0,0 +1,7 @@
+-- Optional regex used to detect the dev server URL in its output
+ALTER TABLE projects
+    ADD COLUMN dev_server_url_pattern TEXT;
+
+-- URL the dev server announced once it started listening
+ALTER TABLE execution_processes
+    ADD COLUMN dev_server_url TEXT;
@@ -0,0 +1,4 @@
+-- Token usage and estimated cost reported by the coding agent; NULL when not reported
+ALTER TABLE executor_sessions ADD COLUMN input_tokens INTEGER;
+ALTER TABLE executor_sessions ADD COLUMN output_tokens INTEGER;
+ALTER TABLE executor_sessions ADD COLUMN cost_usd REAL;
@@ -0,0 +1,2 @@
+-- Optional directory within the project repo that an attempt is scoped to (monorepo packages)
+ALTER TABLE task_attempts ADD COLUMN subpath TEXT;
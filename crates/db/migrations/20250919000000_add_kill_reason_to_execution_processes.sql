@@ -0,0 +1,5 @@
+-- Why a process was stopped early: user, timeout, shutdown, or unknown
+ALTER TABLE execution_processes ADD COLUMN kill_reason TEXT;
+
+-- Processes killed before the reason was tracked
+UPDATE execution_processes SET kill_reason = 'unknown' WHERE status = 'killed';
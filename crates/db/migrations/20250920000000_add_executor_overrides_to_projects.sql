@@ -0,0 +1,2 @@
+-- Per-project extra params/env for executors, keyed by executor name (JSON object)
+ALTER TABLE projects ADD COLUMN executor_overrides TEXT NOT NULL DEFAULT '{}';
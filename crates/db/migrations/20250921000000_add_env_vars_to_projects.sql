@@ -0,0 +1,2 @@
+-- Environment variables injected into every execution for the project (JSON object)
+ALTER TABLE projects ADD COLUMN env_vars TEXT NOT NULL DEFAULT '{}';
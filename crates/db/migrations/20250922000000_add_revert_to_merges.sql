@@ -0,0 +1,3 @@
+-- Reverting a merge records the revert commit made on the target branch
+ALTER TABLE merges ADD COLUMN revert_commit TEXT;
+ALTER TABLE merges ADD COLUMN reverted_at TEXT;
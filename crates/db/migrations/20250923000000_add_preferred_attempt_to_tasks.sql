@@ -0,0 +1,2 @@
+-- The attempt promoted as canonical for a task; NULL means the newest attempt
+ALTER TABLE tasks ADD COLUMN preferred_attempt_id BLOB REFERENCES task_attempts(id) ON DELETE SET NULL;
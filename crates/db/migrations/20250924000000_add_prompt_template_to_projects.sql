@@ -0,0 +1,2 @@
+-- Template for the prompt sent to coding agents, with {placeholder} variables
+ALTER TABLE projects ADD COLUMN prompt_template TEXT;
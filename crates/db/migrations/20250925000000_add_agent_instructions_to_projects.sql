@@ -0,0 +1,2 @@
+-- Standing instructions for coding agents, passed as a system prompt where supported
+ALTER TABLE projects ADD COLUMN agent_instructions TEXT;
@@ -0,0 +1,2 @@
+-- How many times a coding agent action was re-run after a retryable provider error
+ALTER TABLE execution_processes ADD COLUMN retry_count INTEGER NOT NULL DEFAULT 0;
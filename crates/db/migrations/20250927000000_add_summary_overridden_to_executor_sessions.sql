@@ -0,0 +1,2 @@
+-- Summaries edited by the user, which automatic summary extraction must not replace
+ALTER TABLE executor_sessions ADD COLUMN summary_overridden BOOLEAN NOT NULL DEFAULT 0;
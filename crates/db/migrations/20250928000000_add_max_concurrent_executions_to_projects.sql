@@ -0,0 +1,2 @@
+-- Per-project cap on concurrently running coding agents, on top of the global limit
+ALTER TABLE projects ADD COLUMN max_concurrent_executions INTEGER;
@@ -0,0 +1,2 @@
+-- Attempts run in a disposable temp dir, without a git worktree or branch
+ALTER TABLE task_attempts ADD COLUMN scratch BOOLEAN NOT NULL DEFAULT 0;
@@ -12,6 +12,8 @@ use super::{task::Task, task_attempt::TaskAttempt};
 #[sqlx(type_name = "execution_process_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum ExecutionProcessStatus {
+    /// Waiting for a rate limit or concurrency slot before it is spawned
+    Queued,
     Running,
     Completed,
     Failed,
@@ -29,6 +31,21 @@ pub enum ExecutionProcessRunReason {
     BrowserChat,
 }
 
+/// Why a process was stopped before it finished on its own
+#[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "execution_process_kill_reason", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionProcessKillReason {
+    /// Stopped at the user's request (stop, delete, or dev server restart)
+    User,
+    /// Exceeded its configured execution timeout
+    Timeout,
+    /// Still running when the server shut down
+    Shutdown,
+    /// Killed before the reason was recorded
+    Unknown,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct ExecutionProcess {
     pub id: Uuid,
@@ -38,8 +55,14 @@ pub struct ExecutionProcess {
     pub executor_action: sqlx::types::Json<ExecutorActionField>,
     /// Git HEAD commit OID captured after the process ends
     pub after_head_commit: Option<String>,
+    /// URL announced by a dev server process once it started listening
+    pub dev_server_url: Option<String>,
     pub status: ExecutionProcessStatus,
     pub exit_code: Option<i64>,
+    /// Set when the process was stopped rather than exiting on its own
+    pub kill_reason: Option<ExecutionProcessKillReason>,
+    /// How many times the action was re-run after a retryable failure before this run
+    pub retry_count: i64,
     /// dropped: true if this process is excluded from the current
     /// history view (due to restore/trimming). Hidden from logs/timeline;
     /// still listed in the Processes tab.
@@ -90,8 +113,11 @@ impl ExecutionProcess {
                 run_reason as "run_reason!: ExecutionProcessRunReason",
                 executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                 after_head_commit,
+                dev_server_url,
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
+                kill_reason as "kill_reason: ExecutionProcessKillReason",
+                retry_count as "retry_count!: i64",
                 dropped as "dropped!: bool",
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
@@ -134,8 +160,11 @@ impl ExecutionProcess {
                 run_reason as "run_reason!: ExecutionProcessRunReason",
                 executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                 after_head_commit,
+                dev_server_url,
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
+                kill_reason as "kill_reason: ExecutionProcessKillReason",
+                retry_count as "retry_count!: i64",
                 dropped as "dropped!: bool",
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
@@ -162,8 +191,11 @@ impl ExecutionProcess {
                 run_reason as "run_reason!: ExecutionProcessRunReason",
                 executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                 after_head_commit,
+                dev_server_url,
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
+                kill_reason as "kill_reason: ExecutionProcessKillReason",
+                retry_count as "retry_count!: i64",
                 dropped as "dropped!: bool",
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
@@ -178,7 +210,52 @@ impl ExecutionProcess {
         .await
     }
 
-    /// Find running execution processes
+    /// A page of the attempt's execution processes, newest first, optionally narrowed to
+    /// one status and/or run reason. Processes created in the same instant are ordered by
+    /// id so pages don't overlap.
+    pub async fn find_by_task_attempt_filtered(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        status: Option<ExecutionProcessStatus>,
+        run_reason: Option<ExecutionProcessRunReason>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT 
+                id as "id!: Uuid", 
+                task_attempt_id as "task_attempt_id!: Uuid", 
+                run_reason as "run_reason!: ExecutionProcessRunReason",
+                executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                after_head_commit,
+                dev_server_url,
+                status as "status!: ExecutionProcessStatus",
+                exit_code,
+                kill_reason as "kill_reason: ExecutionProcessKillReason",
+                retry_count as "retry_count!: i64",
+                dropped as "dropped!: bool",
+                started_at as "started_at!: DateTime<Utc>",
+                completed_at as "completed_at?: DateTime<Utc>",
+                created_at as "created_at!: DateTime<Utc>", 
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes 
+               WHERE task_attempt_id = $1
+                 AND ($2 IS NULL OR status = $2)
+                 AND ($3 IS NULL OR run_reason = $3)
+               ORDER BY created_at DESC, id DESC
+               LIMIT $4 OFFSET $5"#,
+            task_attempt_id,
+            status,
+            run_reason,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find running and queued execution processes
     pub async fn find_running(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             ExecutionProcess,
@@ -188,15 +265,18 @@ impl ExecutionProcess {
                 run_reason as "run_reason!: ExecutionProcessRunReason",
                 executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                 after_head_commit,
+                dev_server_url,
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
+                kill_reason as "kill_reason: ExecutionProcessKillReason",
+                retry_count as "retry_count!: i64",
                 dropped as "dropped!: bool",
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
                 updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes 
-               WHERE status = 'running' 
+               WHERE status IN ('running', 'queued') 
                ORDER BY created_at ASC"#
         )
         .fetch_all(pool)
@@ -216,8 +296,11 @@ impl ExecutionProcess {
                 ep.run_reason as "run_reason!: ExecutionProcessRunReason",
                 ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                 ep.after_head_commit,
+                ep.dev_server_url,
                 ep.status as "status!: ExecutionProcessStatus",
                 ep.exit_code,
+                ep.kill_reason as "kill_reason: ExecutionProcessKillReason",
+                ep.retry_count as "retry_count!: i64",
                 ep.dropped as "dropped!: bool",
                 ep.started_at as "started_at!: DateTime<Utc>",
                 ep.completed_at as "completed_at?: DateTime<Utc>",
@@ -265,6 +348,44 @@ impl ExecutionProcess {
         Ok(row.and_then(|r| r.session_id))
     }
 
+    /// The attempt's newest still-running process, optionally only among those started
+    /// for `run_reason`
+    pub async fn find_running_by_task_attempt(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        run_reason: Option<&ExecutionProcessRunReason>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT 
+                id as "id!: Uuid", 
+                task_attempt_id as "task_attempt_id!: Uuid", 
+                run_reason as "run_reason!: ExecutionProcessRunReason",
+                executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                after_head_commit,
+                dev_server_url,
+                status as "status!: ExecutionProcessStatus",
+                exit_code,
+                kill_reason as "kill_reason: ExecutionProcessKillReason",
+                retry_count as "retry_count!: i64",
+                dropped as "dropped!: bool",
+                started_at as "started_at!: DateTime<Utc>",
+                completed_at as "completed_at?: DateTime<Utc>",
+                created_at as "created_at!: DateTime<Utc>", 
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes 
+               WHERE task_attempt_id = ?1 
+               AND status = 'running'
+               AND (?2 IS NULL OR run_reason = ?2)
+               ORDER BY created_at DESC 
+               LIMIT 1"#,
+            task_attempt_id,
+            run_reason
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
     /// Find latest execution process by task attempt and run reason
     pub async fn find_latest_by_task_attempt_and_run_reason(
         pool: &SqlitePool,
@@ -279,8 +400,11 @@ impl ExecutionProcess {
                 run_reason as "run_reason!: ExecutionProcessRunReason",
                 executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                 after_head_commit,
+                dev_server_url,
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
+                kill_reason as "kill_reason: ExecutionProcessKillReason",
+                retry_count as "retry_count!: i64",
                 dropped as "dropped!: bool",
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
@@ -321,8 +445,11 @@ impl ExecutionProcess {
                 run_reason as "run_reason!: ExecutionProcessRunReason",
                 executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                 after_head_commit,
+                dev_server_url,
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
+                kill_reason as "kill_reason: ExecutionProcessKillReason",
+                retry_count as "retry_count!: i64",
                 dropped as "dropped!: bool",
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
@@ -357,8 +484,12 @@ impl ExecutionProcess {
         id: Uuid,
         status: ExecutionProcessStatus,
         exit_code: Option<i64>,
+        kill_reason: Option<ExecutionProcessKillReason>,
     ) -> Result<(), sqlx::Error> {
-        let completed_at = if matches!(status, ExecutionProcessStatus::Running) {
+        let completed_at = if matches!(
+            status,
+            ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+        ) {
             None
         } else {
             Some(Utc::now())
@@ -366,10 +497,11 @@ impl ExecutionProcess {
 
         sqlx::query!(
             r#"UPDATE execution_processes 
-               SET status = $1, exit_code = $2, completed_at = $3
-               WHERE id = $4"#,
+               SET status = $1, exit_code = $2, kill_reason = $3, completed_at = $4
+               WHERE id = $5"#,
             status,
             exit_code,
+            kill_reason,
             completed_at,
             id
         )
@@ -397,6 +529,42 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Record that this process re-runs an action `retry_count` times over
+    pub async fn update_retry_count(
+        pool: &SqlitePool,
+        id: Uuid,
+        retry_count: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes 
+               SET retry_count = $1 
+               WHERE id = $2"#,
+            retry_count,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the URL a dev server announced in its output
+    pub async fn update_dev_server_url(
+        pool: &SqlitePool,
+        id: Uuid,
+        dev_server_url: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes 
+               SET dev_server_url = $1 
+               WHERE id = $2"#,
+            dev_server_url,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn delete_by_task_attempt_id(
         pool: &SqlitePool,
         task_attempt_id: Uuid,
@@ -473,3 +641,144 @@ impl ExecutionProcess {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{create_execution_process, create_task_attempt, test_pool};
+
+    fn ids(processes: Vec<ExecutionProcess>) -> Vec<Uuid> {
+        processes.iter().map(|p| p.id).collect()
+    }
+
+    #[tokio::test]
+    async fn execution_history_filters_by_run_reason_and_pages_newest_first() {
+        let pool = &test_pool().await;
+        let (_, task_attempt) = create_task_attempt(pool).await;
+        let mut processes = Vec::new();
+        for run_reason in [
+            ExecutionProcessRunReason::SetupScript,
+            ExecutionProcessRunReason::CodingAgent,
+            ExecutionProcessRunReason::CodingAgent,
+        ] {
+            processes.push(create_execution_process(pool, &task_attempt, run_reason).await);
+        }
+        ExecutionProcess::update_completion(
+            pool,
+            processes[1].id,
+            ExecutionProcessStatus::Failed,
+            Some(1),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let coding_agent = ExecutionProcess::find_by_task_attempt_filtered(
+            pool,
+            task_attempt.id,
+            None,
+            Some(ExecutionProcessRunReason::CodingAgent),
+            10,
+            0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(coding_agent), vec![processes[2].id, processes[1].id]);
+
+        let failed = ExecutionProcess::find_by_task_attempt_filtered(
+            pool,
+            task_attempt.id,
+            Some(ExecutionProcessStatus::Failed),
+            None,
+            10,
+            0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(failed), vec![processes[1].id]);
+
+        let second_page = ExecutionProcess::find_by_task_attempt_filtered(
+            pool,
+            task_attempt.id,
+            None,
+            None,
+            2,
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(second_page), vec![processes[0].id]);
+    }
+
+    #[tokio::test]
+    async fn execution_history_pages_do_not_overlap_when_created_together() {
+        let pool = &test_pool().await;
+        let (_, task_attempt) = create_task_attempt(pool).await;
+        for _ in 0..4 {
+            create_execution_process(pool, &task_attempt, ExecutionProcessRunReason::CodingAgent)
+                .await;
+        }
+        sqlx::query("UPDATE execution_processes SET created_at = ? WHERE task_attempt_id = ?")
+            .bind(Utc::now())
+            .bind(task_attempt.id)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let mut paged = Vec::new();
+        for offset in 0..4 {
+            let page = ExecutionProcess::find_by_task_attempt_filtered(
+                pool,
+                task_attempt.id,
+                None,
+                None,
+                1,
+                offset,
+            )
+            .await
+            .unwrap();
+            paged.extend(ids(page));
+        }
+        paged.sort();
+        paged.dedup();
+        assert_eq!(paged.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn running_process_lookup_is_none_once_everything_completed() {
+        let pool = &test_pool().await;
+        let (_, task_attempt) = create_task_attempt(pool).await;
+        let execution_process =
+            create_execution_process(pool, &task_attempt, ExecutionProcessRunReason::SetupScript)
+                .await;
+
+        let running = ExecutionProcess::find_running_by_task_attempt(pool, task_attempt.id, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(running.id, execution_process.id);
+        let dev_server = Some(&ExecutionProcessRunReason::DevServer);
+        assert!(
+            ExecutionProcess::find_running_by_task_attempt(pool, task_attempt.id, dev_server)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        ExecutionProcess::update_completion(
+            pool,
+            execution_process.id,
+            ExecutionProcessStatus::Killed,
+            None,
+            Some(ExecutionProcessKillReason::User),
+        )
+        .await
+        .unwrap();
+        assert!(
+            ExecutionProcess::find_running_by_task_attempt(pool, task_attempt.id, None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
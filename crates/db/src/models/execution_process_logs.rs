@@ -70,6 +70,27 @@ impl ExecutionProcessLogs {
         .await
     }
 
+    /// Delete the logs of processes that finished before `cutoff`. Logs of running processes
+    /// are never deleted.
+    pub async fn delete_completed_before(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"DELETE FROM execution_process_logs
+               WHERE execution_id IN (
+                   SELECT id FROM execution_processes
+                   WHERE status != 'running'
+                     AND completed_at IS NOT NULL
+                     AND completed_at < $1
+               )"#,
+            cutoff
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     /// Parse JSONL logs back into Vec<LogMsg>
     pub fn parse_logs(&self) -> Result<Vec<LogMsg>, serde_json::Error> {
         let mut messages = Vec::new();
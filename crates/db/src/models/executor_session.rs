@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use executors::logs::usage::TokenUsage;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
 use ts_rs::TS;
@@ -12,6 +13,11 @@ pub struct ExecutorSession {
     pub session_id: Option<String>, // External session ID from Claude/Amp
     pub prompt: Option<String>,     // The prompt sent to the executor
     pub summary: Option<String>,    // Final assistant message/summary
+    /// The summary was set by the user and is kept over the extracted one
+    pub summary_overridden: bool,
+    pub input_tokens: Option<i64>,
+    pub output_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -44,6 +50,10 @@ impl ExecutorSession {
                 session_id, 
                 prompt,
                 summary,
+                summary_overridden as "summary_overridden!: bool",
+                input_tokens,
+                output_tokens,
+                cost_usd,
                 created_at as "created_at!: DateTime<Utc>", 
                 updated_at as "updated_at!: DateTime<Utc>"
                FROM executor_sessions 
@@ -68,6 +78,10 @@ impl ExecutorSession {
                 session_id,
                 prompt,
                 summary,
+                summary_overridden as "summary_overridden!: bool",
+                input_tokens,
+                output_tokens,
+                cost_usd,
                 created_at as "created_at!: DateTime<Utc>",
                 updated_at as "updated_at!: DateTime<Utc>"
                FROM executor_sessions
@@ -79,7 +93,6 @@ impl ExecutorSession {
     }
 
     /// Find all executor sessions for a task attempt
-    #[allow(dead_code)]
     pub async fn find_by_task_attempt_id(
         pool: &SqlitePool,
         task_attempt_id: Uuid,
@@ -93,6 +106,10 @@ impl ExecutorSession {
                 session_id, 
                 prompt,
                 summary,
+                summary_overridden as "summary_overridden!: bool",
+                input_tokens,
+                output_tokens,
+                cost_usd,
                 created_at as "created_at!: DateTime<Utc>", 
                 updated_at as "updated_at!: DateTime<Utc>"
                FROM executor_sessions 
@@ -133,6 +150,10 @@ impl ExecutorSession {
                 session_id,
                 prompt,
                 summary,
+                summary_overridden as "summary_overridden!: bool",
+                input_tokens,
+                output_tokens,
+                cost_usd,
                 created_at as "created_at!: DateTime<Utc>",
                 updated_at as "updated_at!: DateTime<Utc>""#,
             session_id,
@@ -191,7 +212,7 @@ impl ExecutorSession {
         Ok(())
     }
 
-    /// Update executor session summary
+    /// Update executor session summary, unless the user has overridden it
     pub async fn update_summary(
         pool: &SqlitePool,
         execution_process_id: Uuid,
@@ -201,7 +222,7 @@ impl ExecutorSession {
         sqlx::query!(
             r#"UPDATE executor_sessions 
                SET summary = $1, updated_at = $2 
-               WHERE execution_process_id = $3"#,
+               WHERE execution_process_id = $3 AND summary_overridden = 0"#,
             summary,
             now,
             execution_process_id
@@ -212,6 +233,102 @@ impl ExecutorSession {
         Ok(())
     }
 
+    /// Replace the summary with one written by the user, which later automatic updates
+    /// leave in place. Returns the updated session, or `None` if the process has none.
+    pub async fn set_summary_override(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        summary: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query_as!(
+            ExecutorSession,
+            r#"UPDATE executor_sessions
+               SET summary = $1, summary_overridden = 1, updated_at = $2
+               WHERE execution_process_id = $3
+               RETURNING
+                id as "id!: Uuid",
+                task_attempt_id as "task_attempt_id!: Uuid",
+                execution_process_id as "execution_process_id!: Uuid",
+                session_id,
+                prompt,
+                summary,
+                summary_overridden as "summary_overridden!: bool",
+                input_tokens,
+                output_tokens,
+                cost_usd,
+                created_at as "created_at!: DateTime<Utc>",
+                updated_at as "updated_at!: DateTime<Utc>""#,
+            summary,
+            now,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record token usage and estimated cost reported by the executor
+    pub async fn update_usage(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        usage: &TokenUsage,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"UPDATE executor_sessions
+               SET input_tokens = $1, output_tokens = $2, cost_usd = $3, updated_at = $4
+               WHERE execution_process_id = $5"#,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cost_usd,
+            now,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Token usage for an execution process, if it has an executor session
+    pub async fn usage(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<TokenUsage>, sqlx::Error> {
+        sqlx::query_as!(
+            TokenUsage,
+            r#"SELECT
+                input_tokens,
+                output_tokens,
+                cost_usd
+               FROM executor_sessions
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Total token usage across all executor sessions of a task attempt.
+    /// Fields stay null if no session reported them.
+    pub async fn usage_for_task_attempt(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<TokenUsage, sqlx::Error> {
+        sqlx::query_as!(
+            TokenUsage,
+            r#"SELECT
+                SUM(input_tokens) as "input_tokens: i64",
+                SUM(output_tokens) as "output_tokens: i64",
+                SUM(cost_usd) as "cost_usd: f64"
+               FROM executor_sessions
+               WHERE task_attempt_id = $1"#,
+            task_attempt_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
     /// Delete executor sessions for a task attempt (cleanup)
     pub async fn delete_by_task_attempt_id(
         pool: &SqlitePool,
@@ -134,7 +134,12 @@ impl Image {
         Ok(())
     }
 
-    pub async fn find_orphaned_images(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+    /// Images not linked to any task that were created before `created_before`.
+    /// Recent uploads are excluded since they are linked once the task is saved.
+    pub async fn find_orphaned_images(
+        pool: &SqlitePool,
+        created_before: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Image,
             r#"SELECT i.id as "id!: Uuid",
@@ -147,11 +152,20 @@ impl Image {
                       i.updated_at as "updated_at!: DateTime<Utc>"
                FROM images i
                LEFT JOIN task_images ti ON i.id = ti.image_id
-               WHERE ti.task_id IS NULL"#
+               WHERE ti.task_id IS NULL
+                 AND datetime(i.created_at) < datetime($1)"#,
+            created_before
         )
         .fetch_all(pool)
         .await
     }
+
+    /// File names of every image known to the database
+    pub async fn find_all_file_paths(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(r#"SELECT file_path as "file_path!" FROM images"#)
+            .fetch_all(pool)
+            .await
+    }
 }
 
 impl TaskImage {
@@ -28,6 +28,9 @@ pub struct DirectMerge {
     pub merge_commit: String,
     pub target_branch_name: String,
     pub created_at: DateTime<Utc>,
+    /// Set once the merge has been reverted on the target branch
+    pub revert_commit: Option<String>,
+    pub reverted_at: Option<DateTime<Utc>>,
 }
 
 /// PR merge - represents a pull request merge
@@ -38,6 +41,9 @@ pub struct PrMerge {
     pub created_at: DateTime<Utc>,
     pub target_branch_name: String,
     pub pr_info: PullRequestInfo,
+    /// Set once the merged PR has been reverted on the target branch
+    pub revert_commit: Option<String>,
+    pub reverted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -69,6 +75,8 @@ struct MergeRow {
     pr_merged_at: Option<DateTime<Utc>>,
     pr_merge_commit_sha: Option<String>,
     created_at: DateTime<Utc>,
+    revert_commit: Option<String>,
+    reverted_at: Option<DateTime<Utc>>,
 }
 
 impl Merge {
@@ -79,6 +87,34 @@ impl Merge {
         }
     }
 
+    pub fn id(&self) -> Uuid {
+        match self {
+            Merge::Direct(direct) => direct.id,
+            Merge::Pr(pr) => pr.id,
+        }
+    }
+
+    pub fn task_attempt_id(&self) -> Uuid {
+        match self {
+            Merge::Direct(direct) => direct.task_attempt_id,
+            Merge::Pr(pr) => pr.task_attempt_id,
+        }
+    }
+
+    pub fn target_branch_name(&self) -> &str {
+        match self {
+            Merge::Direct(direct) => &direct.target_branch_name,
+            Merge::Pr(pr) => &pr.target_branch_name,
+        }
+    }
+
+    pub fn revert_commit(&self) -> Option<&str> {
+        match self {
+            Merge::Direct(direct) => direct.revert_commit.as_deref(),
+            Merge::Pr(pr) => pr.revert_commit.as_deref(),
+        }
+    }
+
     /// Create a direct merge record
     pub async fn create_direct(
         pool: &SqlitePool,
@@ -104,6 +140,8 @@ impl Merge {
                 pr_status as "pr_status?: MergeStatus",
                 pr_merged_at as "pr_merged_at?: DateTime<Utc>",
                 pr_merge_commit_sha,
+                revert_commit,
+                reverted_at as "reverted_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>",
                 target_branch_name as "target_branch_name!: String"
             "#,
@@ -143,6 +181,8 @@ impl Merge {
                 pr_status as "pr_status?: MergeStatus",
                 pr_merged_at as "pr_merged_at?: DateTime<Utc>",
                 pr_merge_commit_sha,
+                revert_commit,
+                reverted_at as "reverted_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>",
                 target_branch_name as "target_branch_name!: String"
             "#,
@@ -172,6 +212,8 @@ impl Merge {
                 pr_status as "pr_status?: MergeStatus",
                 pr_merged_at as "pr_merged_at?: DateTime<Utc>",
                 pr_merge_commit_sha,
+                revert_commit,
+                reverted_at as "reverted_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>",
                 target_branch_name as "target_branch_name!: String"
                FROM merges 
@@ -213,6 +255,55 @@ impl Merge {
 
         Ok(())
     }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query_as!(
+            MergeRow,
+            r#"SELECT 
+                id as "id!: Uuid",
+                task_attempt_id as "task_attempt_id!: Uuid",
+                merge_type as "merge_type!: MergeType",
+                merge_commit,
+                pr_number,
+                pr_url,
+                pr_status as "pr_status?: MergeStatus",
+                pr_merged_at as "pr_merged_at?: DateTime<Utc>",
+                pr_merge_commit_sha,
+                revert_commit,
+                reverted_at as "reverted_at?: DateTime<Utc>",
+                target_branch_name as "target_branch_name!: String",
+                created_at as "created_at!: DateTime<Utc>"
+            FROM merges 
+            WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(Into::into))
+    }
+
+    /// Record the commit that reverted this merge on its target branch
+    pub async fn mark_reverted(
+        pool: &SqlitePool,
+        merge_id: Uuid,
+        revert_commit: &str,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"UPDATE merges 
+            SET revert_commit = $1, reverted_at = $2
+            WHERE id = $3"#,
+            revert_commit,
+            now,
+            merge_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Find all merges for a task attempt (returns both direct and PR merges)
     pub async fn find_by_task_attempt_id(
         pool: &SqlitePool,
@@ -231,6 +322,8 @@ impl Merge {
                 pr_status as "pr_status?: MergeStatus",
                 pr_merged_at as "pr_merged_at?: DateTime<Utc>",
                 pr_merge_commit_sha,
+                revert_commit,
+                reverted_at as "reverted_at?: DateTime<Utc>",
                 target_branch_name as "target_branch_name!: String",
                 created_at as "created_at!: DateTime<Utc>"
             FROM merges 
@@ -267,6 +360,8 @@ impl From<MergeRow> for DirectMerge {
                 .expect("direct merge must have merge_commit"),
             target_branch_name: row.target_branch_name,
             created_at: row.created_at,
+            revert_commit: row.revert_commit,
+            reverted_at: row.reverted_at,
         }
     }
 }
@@ -285,6 +380,8 @@ impl From<MergeRow> for PrMerge {
                 merge_commit_sha: row.pr_merge_commit_sha,
             },
             created_at: row.created_at,
+            revert_commit: row.revert_commit,
+            reverted_at: row.reverted_at,
         }
     }
 }
@@ -7,3 +7,5 @@ pub mod project;
 pub mod task;
 pub mod task_attempt;
 pub mod task_template;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
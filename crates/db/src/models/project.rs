@@ -1,10 +1,12 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use chrono::{DateTime, Utc};
+use executors::command::ExecutorOverrides;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
 use thiserror::Error;
 use ts_rs::TS;
+use utils::text::expand_placeholders;
 use uuid::Uuid;
 
 #[derive(Debug, Error)]
@@ -19,6 +21,24 @@ pub enum ProjectError {
     GitRepoCheckFailed(String),
     #[error("Failed to create project: {0}")]
     CreateFailed(String),
+    #[error("Invalid project environment: {0}")]
+    InvalidEnvVars(String),
+    #[error("Invalid prompt template: {0}")]
+    InvalidPromptTemplate(String),
+    #[error("Invalid dev server URL pattern: {0}")]
+    InvalidDevServerUrlPattern(String),
+}
+
+/// Placeholders a project's `prompt_template` can use
+pub const PROMPT_TEMPLATE_VARIABLES: [&str; 4] =
+    ["title", "description", "repo_path", "base_branch"];
+
+/// Reject templates with placeholders other than `PROMPT_TEMPLATE_VARIABLES`
+pub fn validate_prompt_template(template: &str) -> Result<(), String> {
+    expand_placeholders(template, |name| {
+        PROMPT_TEMPLATE_VARIABLES.contains(&name).then(String::new)
+    })
+    .map(|_| ())
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
@@ -30,6 +50,26 @@ pub struct Project {
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    /// Regex used to find the dev server URL in its output; the first capture
+    /// group (or the whole match) is taken as the URL
+    pub dev_server_url_pattern: Option<String>,
+    /// Extra params and environment per executor, applied when it is spawned
+    #[ts(type = "{ [key in BaseCodingAgent]?: ExecutorOverride }")]
+    pub executor_overrides: sqlx::types::Json<ExecutorOverrides>,
+    /// Environment variables set for every execution in the project. Executor-specific
+    /// env takes precedence; these take precedence over the host environment.
+    #[ts(type = "{ [key in string]?: string }")]
+    pub env_vars: sqlx::types::Json<HashMap<String, String>>,
+    /// Template for agent prompts; see `PROMPT_TEMPLATE_VARIABLES`. Unset means the
+    /// default task prompt.
+    pub prompt_template: Option<String>,
+    /// Standing instructions for coding agents (coding standards, forbidden actions).
+    /// Sent as a system prompt where the agent has one, otherwise ahead of the prompt.
+    pub agent_instructions: Option<String>,
+    /// Most coding agents of this project that may run at once, within the global
+    /// limit. Unset means only the global limit applies.
+    #[ts(type = "number | null")]
+    pub max_concurrent_executions: Option<i64>,
 
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
@@ -46,6 +86,7 @@ pub struct CreateProject {
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    pub dev_server_url_pattern: Option<String>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -56,6 +97,7 @@ pub struct UpdateProject {
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    pub dev_server_url_pattern: Option<String>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -67,6 +109,15 @@ pub struct ProjectWithBranch {
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    pub dev_server_url_pattern: Option<String>,
+    #[ts(type = "{ [key in BaseCodingAgent]?: ExecutorOverride }")]
+    pub executor_overrides: ExecutorOverrides,
+    #[ts(type = "{ [key in string]?: string }")]
+    pub env_vars: HashMap<String, String>,
+    pub prompt_template: Option<String>,
+    pub agent_instructions: Option<String>,
+    #[ts(type = "number | null")]
+    pub max_concurrent_executions: Option<i64>,
     pub current_branch: Option<String>,
 
     #[ts(type = "Date")]
@@ -85,6 +136,12 @@ impl ProjectWithBranch {
             dev_script: project.dev_script,
             cleanup_script: project.cleanup_script,
             copy_files: project.copy_files,
+            dev_server_url_pattern: project.dev_server_url_pattern,
+            executor_overrides: project.executor_overrides.0,
+            env_vars: project.env_vars.0,
+            prompt_template: project.prompt_template,
+            agent_instructions: project.agent_instructions,
+            max_concurrent_executions: project.max_concurrent_executions,
             current_branch,
             created_at: project.created_at,
             updated_at: project.updated_at,
@@ -110,7 +167,7 @@ impl Project {
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
         )
         .fetch_all(pool)
         .await
@@ -121,7 +178,7 @@ impl Project {
         sqlx::query_as!(
             Project,
             r#"
-            SELECT p.id as "id!: Uuid", p.name, p.git_repo_path, p.setup_script, p.dev_script, p.cleanup_script, p.copy_files, 
+            SELECT p.id as "id!: Uuid", p.name, p.git_repo_path, p.setup_script, p.dev_script, p.cleanup_script, p.copy_files, p.dev_server_url_pattern, p.executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", p.env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", p.prompt_template, p.agent_instructions, p.max_concurrent_executions,
                    p.created_at as "created_at!: DateTime<Utc>", p.updated_at as "updated_at!: DateTime<Utc>"
             FROM projects p
             WHERE p.id IN (
@@ -141,7 +198,7 @@ impl Project {
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
             id
         )
         .fetch_optional(pool)
@@ -154,7 +211,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
             git_repo_path
         )
         .fetch_optional(pool)
@@ -168,7 +225,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
             git_repo_path,
             exclude_id
         )
@@ -183,14 +240,15 @@ impl Project {
     ) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             project_id,
             data.name,
             data.git_repo_path,
             data.setup_script,
             data.dev_script,
             data.cleanup_script,
-            data.copy_files
+            data.copy_files,
+            data.dev_server_url_pattern
         )
         .fetch_one(pool)
         .await
@@ -206,17 +264,96 @@ impl Project {
         dev_script: Option<String>,
         cleanup_script: Option<String>,
         copy_files: Option<String>,
+        dev_server_url_pattern: Option<String>,
     ) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5, cleanup_script = $6, copy_files = $7 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5, cleanup_script = $6, copy_files = $7, dev_server_url_pattern = $8 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             name,
             git_repo_path,
             setup_script,
             dev_script,
             cleanup_script,
-            copy_files
+            copy_files,
+            dev_server_url_pattern
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update_executor_overrides(
+        pool: &SqlitePool,
+        id: Uuid,
+        executor_overrides: &ExecutorOverrides,
+    ) -> Result<Self, sqlx::Error> {
+        let executor_overrides = sqlx::types::Json(executor_overrides);
+        sqlx::query_as!(
+            Project,
+            r#"UPDATE projects SET executor_overrides = $2 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            executor_overrides
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update_env_vars(
+        pool: &SqlitePool,
+        id: Uuid,
+        env_vars: &HashMap<String, String>,
+    ) -> Result<Self, sqlx::Error> {
+        let env_vars = sqlx::types::Json(env_vars);
+        sqlx::query_as!(
+            Project,
+            r#"UPDATE projects SET env_vars = $2 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            env_vars
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update_prompt_template(
+        pool: &SqlitePool,
+        id: Uuid,
+        prompt_template: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Project,
+            r#"UPDATE projects SET prompt_template = $2 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            prompt_template
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update_agent_instructions(
+        pool: &SqlitePool,
+        id: Uuid,
+        agent_instructions: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Project,
+            r#"UPDATE projects SET agent_instructions = $2 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            agent_instructions
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update_max_concurrent_executions(
+        pool: &SqlitePool,
+        id: Uuid,
+        max_concurrent_executions: Option<i64>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Project,
+            r#"UPDATE projects SET max_concurrent_executions = $2 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, dev_server_url_pattern, executor_overrides as "executor_overrides!: sqlx::types::Json<ExecutorOverrides>", env_vars as "env_vars!: sqlx::types::Json<HashMap<String, String>>", prompt_template, agent_instructions, max_concurrent_executions, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            max_concurrent_executions
         )
         .fetch_one(pool)
         .await
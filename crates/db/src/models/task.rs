@@ -1,10 +1,14 @@
+use std::sync::LazyLock;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool, Type};
+use tokio::sync::broadcast;
 use ts_rs::TS;
+use utils::text::expand_placeholders;
 use uuid::Uuid;
 
-use super::project::Project;
+use super::{executor_session::ExecutorSession, project::Project, task_attempt::TaskAttempt};
 
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
 #[sqlx(type_name = "task_status", rename_all = "lowercase")]
@@ -17,6 +21,29 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+impl TaskStatus {
+    /// Human readable status, as shown on the board
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "To do",
+            TaskStatus::InProgress => "In progress",
+            TaskStatus::InReview => "In review",
+            TaskStatus::Done => "Done",
+            TaskStatus::Cancelled => "Cancelled",
+        }
+    }
+}
+
+/// Parent summaries are often whole final messages; keep the prompt preamble short
+const PARENT_OUTCOME_MAX_CHARS: usize = 500;
+
+fn truncate_chars(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &s[..idx]),
+        None => s.to_string(),
+    }
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct Task {
     pub id: Uuid,
@@ -25,10 +52,39 @@ pub struct Task {
     pub description: Option<String>,
     pub status: TaskStatus,
     pub parent_task_attempt: Option<Uuid>, // Foreign key to parent TaskAttempt
+    /// Attempt promoted as the canonical one, which the board and new work default to
+    pub preferred_attempt_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Board-level change to a task. Published by the model so the service layer can
+/// forward it (e.g. to a webhook) without the DB layer knowing about it.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "event")]
+pub enum TaskEvent {
+    #[serde(rename = "task.status_changed")]
+    StatusChanged {
+        task: Task,
+        old_status: TaskStatus,
+        new_status: TaskStatus,
+    },
+}
+
+impl TaskEvent {
+    pub fn task(&self) -> &Task {
+        match self {
+            TaskEvent::StatusChanged { task, .. } => task,
+        }
+    }
+}
+
+/// Events are dropped when nobody is subscribed; slow subscribers see `Lagged`
+const TASK_EVENT_CAPACITY: usize = 256;
+
+static TASK_EVENTS: LazyLock<broadcast::Sender<TaskEvent>> =
+    LazyLock::new(|| broadcast::channel(TASK_EVENT_CAPACITY).0);
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct TaskWithAttemptStatus {
     pub id: Uuid,
@@ -37,11 +93,13 @@ pub struct TaskWithAttemptStatus {
     pub description: Option<String>,
     pub status: TaskStatus,
     pub parent_task_attempt: Option<Uuid>,
+    pub preferred_attempt_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub has_in_progress_attempt: bool,
     pub has_merged_attempt: bool,
     pub last_attempt_failed: bool,
+    /// Executor of the active attempt
     pub executor: String,
 }
 
@@ -72,6 +130,70 @@ impl Task {
         }
     }
 
+    /// The prompt for running `task_attempt`: the project's `prompt_template` filled in, or
+    /// the `to_prompt` format when the project has none
+    pub fn to_templated_prompt(&self, project: &Project, task_attempt: &TaskAttempt) -> String {
+        let Some(template) = &project.prompt_template else {
+            return self.to_prompt();
+        };
+        let rendered = expand_placeholders(template, |name| match name {
+            "title" => Some(self.title.clone()),
+            "description" => Some(self.description.clone().unwrap_or_default()),
+            "repo_path" => Some(project.git_repo_path.to_string_lossy().to_string()),
+            "base_branch" => Some(task_attempt.base_branch.clone()),
+            _ => None,
+        });
+        // Templates are validated when saved, so this only trips on rows edited by hand
+        rendered.unwrap_or_else(|e| {
+            tracing::warn!("Ignoring prompt template of project {}: {}", project.id, e);
+            self.to_prompt()
+        })
+    }
+
+    /// Like `to_templated_prompt`, but for tasks created from another task's attempt,
+    /// prepends a short summary of the parent task and how that attempt ended. Falls back
+    /// to the plain prompt when there is no parent or it no longer exists.
+    pub async fn to_prompt_with_context(
+        &self,
+        pool: &SqlitePool,
+        task_attempt: &TaskAttempt,
+    ) -> Result<String, sqlx::Error> {
+        let prompt = match self.parent_project(pool).await? {
+            Some(project) => self.to_templated_prompt(&project, task_attempt),
+            None => self.to_prompt(),
+        };
+        let Some(parent_attempt_id) = self.parent_task_attempt else {
+            return Ok(prompt);
+        };
+        let Some(parent_attempt) = TaskAttempt::find_by_id(pool, parent_attempt_id).await? else {
+            return Ok(prompt);
+        };
+        let Some(parent_task) = parent_attempt.parent_task(pool).await? else {
+            return Ok(prompt);
+        };
+
+        // The latest agent summary is the best record of what the parent attempt did
+        let outcome = ExecutorSession::find_by_task_attempt_id(pool, parent_attempt_id)
+            .await?
+            .into_iter()
+            .rev()
+            .find_map(|session| session.summary.filter(|s| !s.trim().is_empty()));
+
+        let mut context = format!(
+            "This task follows up on a parent task.\nParent task: {}\nParent status: {}\n",
+            parent_task.title,
+            parent_task.status.label()
+        );
+        if let Some(outcome) = outcome {
+            context.push_str(&format!(
+                "Parent outcome: {}\n",
+                truncate_chars(outcome.trim(), PARENT_OUTCOME_MAX_CHARS)
+            ));
+        }
+
+        Ok(format!("{context}\n{prompt}"))
+    }
+
     pub async fn parent_project(&self, pool: &SqlitePool) -> Result<Option<Project>, sqlx::Error> {
         Project::find_by_id(pool, self.project_id).await
     }
@@ -88,6 +210,7 @@ impl Task {
   t.description,
   t.status                        AS "status!: TaskStatus",
   t.parent_task_attempt           AS "parent_task_attempt: Uuid",
+  t.preferred_attempt_id          AS "preferred_attempt_id: Uuid",
   t.created_at                    AS "created_at!: DateTime<Utc>",
   t.updated_at                    AS "updated_at!: DateTime<Utc>",
 
@@ -114,12 +237,18 @@ impl Task {
   ) IN ('failed','killed') THEN 1 ELSE 0 END
                                  AS "last_attempt_failed!: i64",
 
-  ( SELECT ta.executor
-      FROM task_attempts ta
-      WHERE ta.task_id = t.id
-     ORDER BY ta.created_at DESC
-      LIMIT 1
-    )                               AS "executor!: String"
+  COALESCE(
+    ( SELECT ta.executor
+        FROM task_attempts ta
+        WHERE ta.id = t.preferred_attempt_id
+    ),
+    ( SELECT ta.executor
+        FROM task_attempts ta
+        WHERE ta.task_id = t.id
+       ORDER BY ta.created_at DESC
+        LIMIT 1
+    )
+  )                                 AS "executor!: String"
 
 FROM tasks t
 WHERE t.project_id = $1
@@ -138,6 +267,7 @@ ORDER BY t.created_at DESC"#,
                 description: rec.description,
                 status: rec.status,
                 parent_task_attempt: rec.parent_task_attempt,
+                preferred_attempt_id: rec.preferred_attempt_id,
                 created_at: rec.created_at,
                 updated_at: rec.updated_at,
                 has_in_progress_attempt: rec.has_in_progress_attempt != 0,
@@ -153,7 +283,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", preferred_attempt_id as "preferred_attempt_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
                WHERE id = $1"#,
             id
@@ -165,7 +295,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", preferred_attempt_id as "preferred_attempt_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
                WHERE rowid = $1"#,
             rowid
@@ -181,7 +311,7 @@ ORDER BY t.created_at DESC"#,
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", preferred_attempt_id as "preferred_attempt_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
                WHERE id = $1 AND project_id = $2"#,
             id,
@@ -200,7 +330,7 @@ ORDER BY t.created_at DESC"#,
             Task,
             r#"INSERT INTO tasks (id, project_id, title, description, status, parent_task_attempt) 
                VALUES ($1, $2, $3, $4, $5, $6) 
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", preferred_attempt_id as "preferred_attempt_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             task_id,
             data.project_id,
             data.title,
@@ -221,12 +351,13 @@ ORDER BY t.created_at DESC"#,
         status: TaskStatus,
         parent_task_attempt: Option<Uuid>,
     ) -> Result<Self, sqlx::Error> {
-        sqlx::query_as!(
+        let old_status = Self::find_by_id(pool, id).await?.map(|task| task.status);
+        let task = sqlx::query_as!(
             Task,
             r#"UPDATE tasks 
                SET title = $3, description = $4, status = $5, parent_task_attempt = $6 
                WHERE id = $1 AND project_id = $2 
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", preferred_attempt_id as "preferred_attempt_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             project_id,
             title,
@@ -235,14 +366,21 @@ ORDER BY t.created_at DESC"#,
             parent_task_attempt
         )
         .fetch_one(pool)
-        .await
+        .await?;
+
+        if let Some(old_status) = old_status {
+            Self::publish_status_change(&task, old_status);
+        }
+        Ok(task)
     }
 
+    /// Set the task's status, publishing a `TaskEvent::StatusChanged` when it changes
     pub async fn update_status(
         pool: &SqlitePool,
         id: Uuid,
         status: TaskStatus,
     ) -> Result<(), sqlx::Error> {
+        let old_status = Self::find_by_id(pool, id).await?.map(|task| task.status);
         sqlx::query!(
             "UPDATE tasks SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
             id,
@@ -250,6 +388,46 @@ ORDER BY t.created_at DESC"#,
         )
         .execute(pool)
         .await?;
+
+        if let Some(old_status) = old_status
+            && old_status != status
+            && let Some(task) = Self::find_by_id(pool, id).await?
+        {
+            Self::publish_status_change(&task, old_status);
+        }
+        Ok(())
+    }
+
+    fn publish_status_change(task: &Task, old_status: TaskStatus) {
+        if task.status == old_status {
+            return;
+        }
+        // An error only means there are no subscribers right now
+        let _ = TASK_EVENTS.send(TaskEvent::StatusChanged {
+            task: task.clone(),
+            old_status,
+            new_status: task.status.clone(),
+        });
+    }
+
+    /// Receive task events published from now on
+    pub fn subscribe_events() -> broadcast::Receiver<TaskEvent> {
+        TASK_EVENTS.subscribe()
+    }
+
+    /// Promote `attempt_id` to the task's canonical attempt
+    pub async fn set_preferred_attempt(
+        pool: &SqlitePool,
+        id: Uuid,
+        attempt_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE tasks SET preferred_attempt_id = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            id,
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
         Ok(())
     }
 
@@ -282,7 +460,7 @@ ORDER BY t.created_at DESC"#,
         // Find both children and parent for this attempt
         sqlx::query_as!(
             Task,
-            r#"SELECT DISTINCT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT DISTINCT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.preferred_attempt_id as "preferred_attempt_id: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks t
                WHERE (
                    -- Find children: tasks that have this attempt as parent
@@ -304,3 +482,139 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        execution_process::ExecutionProcessRunReason,
+        executor_session::CreateExecutorSession,
+        test_support::{create_execution_process, create_task_attempt, test_pool},
+    };
+
+    /// A task in the parent's project created from `parent_attempt`
+    async fn create_child_task(
+        pool: &SqlitePool,
+        parent: &Task,
+        parent_attempt: &TaskAttempt,
+    ) -> Task {
+        Task::create(
+            pool,
+            &CreateTask {
+                project_id: parent.project_id,
+                title: "child".to_string(),
+                description: None,
+                parent_task_attempt: Some(parent_attempt.id),
+                image_ids: None,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn child_task_prompt_starts_with_the_parent_context() {
+        let pool = &test_pool().await;
+        let (parent, parent_attempt) = create_task_attempt(pool).await;
+        Task::update_status(pool, parent.id, TaskStatus::InReview)
+            .await
+            .unwrap();
+        let execution_process = create_execution_process(
+            pool,
+            &parent_attempt,
+            ExecutionProcessRunReason::CodingAgent,
+        )
+        .await;
+        ExecutorSession::create(
+            pool,
+            &CreateExecutorSession {
+                task_attempt_id: parent_attempt.id,
+                execution_process_id: execution_process.id,
+                prompt: None,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        ExecutorSession::update_summary(pool, execution_process.id, &"x".repeat(600))
+            .await
+            .unwrap();
+        let child = create_child_task(pool, &parent, &parent_attempt).await;
+
+        let prompt = child
+            .to_prompt_with_context(pool, &parent_attempt)
+            .await
+            .unwrap();
+
+        let outcome = format!(
+            "Parent outcome: {}…\n",
+            "x".repeat(PARENT_OUTCOME_MAX_CHARS)
+        );
+        assert_eq!(
+            prompt,
+            format!(
+                "This task follows up on a parent task.\nParent task: task\nParent status: In review\n{outcome}\nchild"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn tasks_without_a_live_parent_get_the_plain_prompt() {
+        let pool = &test_pool().await;
+        let (parent, parent_attempt) = create_task_attempt(pool).await;
+        assert_eq!(
+            parent
+                .to_prompt_with_context(pool, &parent_attempt)
+                .await
+                .unwrap(),
+            parent.to_prompt()
+        );
+
+        // A parent attempt that no longer exists
+        let mut orphan = create_child_task(pool, &parent, &parent_attempt).await;
+        orphan.parent_task_attempt = Some(Uuid::new_v4());
+        assert_eq!(
+            orphan
+                .to_prompt_with_context(pool, &parent_attempt)
+                .await
+                .unwrap(),
+            orphan.to_prompt()
+        );
+    }
+
+    #[tokio::test]
+    async fn task_status_change_publishes_exactly_one_event() {
+        let pool = &test_pool().await;
+        let (task, _) = create_task_attempt(pool).await;
+        let mut events = Task::subscribe_events();
+
+        Task::update_status(pool, task.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+        // Setting the same status again is not a change
+        Task::update_status(pool, task.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+
+        // Other tests publish on the same channel, so only count this task's events
+        let mut ours = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if event.task().id == task.id {
+                ours.push(event);
+            }
+        }
+        assert!(matches!(
+            ours.as_slice(),
+            [TaskEvent::StatusChanged {
+                old_status: TaskStatus::Todo,
+                new_status: TaskStatus::InProgress,
+                ..
+            }]
+        ));
+        assert_eq!(
+            serde_json::to_value(&ours[0]).unwrap()["event"],
+            "task.status_changed"
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use chrono::{DateTime, Utc};
 use executors::executors::BaseCodingAgent;
 use serde::{Deserialize, Serialize};
@@ -6,7 +8,14 @@ use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{project::Project, task::Task};
+use super::{
+    execution_process::{
+        ExecutionProcess, ExecutionProcessKillReason, ExecutionProcessRunReason,
+        ExecutionProcessStatus,
+    },
+    project::Project,
+    task::Task,
+};
 
 #[derive(Debug, Error)]
 pub enum TaskAttemptError {
@@ -20,6 +29,10 @@ pub enum TaskAttemptError {
     ValidationError(String),
     #[error("Branch not found: {0}")]
     BranchNotFound(String),
+    #[error("Task already has the maximum of {0} attempts")]
+    AttemptLimitReached(u32),
+    #[error("Scratch attempts have no worktree or branch")]
+    ScratchAttempt,
 }
 
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
@@ -41,9 +54,11 @@ pub struct TaskAttempt {
     pub container_ref: Option<String>, // Path to a worktree (local), or cloud container id
     pub branch: Option<String>,        // Git branch name for this task attempt
     pub base_branch: String,           // Base branch this attempt is based on
+    pub subpath: Option<String>,       // Directory within the repo the attempt is scoped to
     pub executor: String, // Name of the base coding agent to use ("AMP", "CLAUDE_CODE",
     // "GEMINI", etc.)
     pub worktree_deleted: bool, // Flag indicating if worktree has been cleaned up
+    pub scratch: bool,          // Runs in a disposable temp dir with no worktree or branch
     pub setup_completed_at: Option<DateTime<Utc>>, // When setup script was last completed
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -79,10 +94,43 @@ pub struct TaskAttemptContext {
     pub project: Project,
 }
 
+/// One execution process in an attempt's lifecycle
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutionTimelineEntry {
+    pub execution_process_id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    pub status: ExecutionProcessStatus,
+    pub exit_code: Option<i64>,
+    pub kill_reason: Option<ExecutionProcessKillReason>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Run time so far for processes that haven't finished
+    #[ts(type = "number")]
+    pub duration_ms: i64,
+}
+
+impl ExecutionTimelineEntry {
+    /// Summarize `process`, measuring unfinished ones up to `now`
+    pub fn new(process: ExecutionProcess, now: DateTime<Utc>) -> Self {
+        Self {
+            execution_process_id: process.id,
+            duration_ms: (process.completed_at.unwrap_or(now) - process.started_at)
+                .num_milliseconds(),
+            run_reason: process.run_reason,
+            status: process.status,
+            exit_code: process.exit_code,
+            kill_reason: process.kill_reason,
+            started_at: process.started_at,
+            completed_at: process.completed_at,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateTaskAttempt {
     pub executor: BaseCodingAgent,
     pub base_branch: String,
+    pub subpath: Option<String>,
 }
 
 impl TaskAttempt {
@@ -90,6 +138,20 @@ impl TaskAttempt {
         Task::find_by_id(pool, self.task_id).await
     }
 
+    /// Every execution process of the attempt (setup, coding agent, cleanup, ...) in the
+    /// order they were created, with how long each ran
+    pub async fn execution_timeline(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+    ) -> Result<Vec<ExecutionTimelineEntry>, sqlx::Error> {
+        let now = Utc::now();
+        let processes = ExecutionProcess::find_by_task_attempt_id(pool, attempt_id).await?;
+        Ok(processes
+            .into_iter()
+            .map(|process| ExecutionTimelineEntry::new(process, now))
+            .collect())
+    }
+
     /// Fetch all task attempts, optionally filtered by task_id. Newest first.
     pub async fn fetch_all(
         pool: &SqlitePool,
@@ -103,8 +165,10 @@ impl TaskAttempt {
                               container_ref,
                               branch,
                               base_branch,
+                              subpath,
                               executor AS "executor!",
                               worktree_deleted AS "worktree_deleted!: bool",
+                              scratch AS "scratch!: bool",
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
@@ -123,8 +187,10 @@ impl TaskAttempt {
                               container_ref,
                               branch,
                               base_branch,
+                              subpath,
                               executor AS "executor!",
                               worktree_deleted AS "worktree_deleted!: bool",
+                              scratch AS "scratch!: bool",
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
@@ -154,8 +220,10 @@ impl TaskAttempt {
                        ta.container_ref,
                        ta.branch,
                        ta.base_branch,
+                       ta.subpath,
                        ta.executor AS "executor!",
                        ta.worktree_deleted  AS "worktree_deleted!: bool",
+                       ta.scratch           AS "scratch!: bool",
                        ta.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
                        ta.created_at        AS "created_at!: DateTime<Utc>",
                        ta.updated_at        AS "updated_at!: DateTime<Utc>"
@@ -236,6 +304,56 @@ impl TaskAttempt {
         Ok(())
     }
 
+    /// Mark several worktrees as deleted in a single transaction
+    pub async fn mark_worktrees_deleted(
+        pool: &SqlitePool,
+        attempt_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for attempt_id in attempt_ids {
+            sqlx::query!(
+                "UPDATE task_attempts SET worktree_deleted = TRUE, updated_at = datetime('now') WHERE id = ?",
+                attempt_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Fail with `ScratchAttempt` for operations that need the attempt's branch
+    pub fn ensure_not_scratch(&self) -> Result<(), TaskAttemptError> {
+        if self.scratch {
+            return Err(TaskAttemptError::ScratchAttempt);
+        }
+        Ok(())
+    }
+
+    /// Mark the attempt as a scratch attempt, running outside any worktree
+    pub async fn mark_scratch(pool: &SqlitePool, attempt_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE task_attempts SET scratch = TRUE, updated_at = datetime('now') WHERE id = ?",
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record that the setup script just finished successfully in this attempt's worktree
+    pub async fn mark_setup_completed(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE task_attempts SET setup_completed_at = datetime('now'), updated_at = datetime('now') WHERE id = ?",
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             TaskAttempt,
@@ -244,8 +362,10 @@ impl TaskAttempt {
                        container_ref,
                        branch,
                        base_branch,
+                       subpath,
                        executor AS "executor!",
                        worktree_deleted  AS "worktree_deleted!: bool",
+                       scratch           AS "scratch!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
@@ -257,6 +377,39 @@ impl TaskAttempt {
         .await
     }
 
+    /// The attempt in `project_id` that owns `branch`. Branch names are generated per
+    /// attempt, so a match is normally unique; the newest attempt wins otherwise.
+    pub async fn find_by_branch(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        branch: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttempt,
+            r#"SELECT  ta.id                AS "id!: Uuid",
+                       ta.task_id           AS "task_id!: Uuid",
+                       ta.container_ref,
+                       ta.branch,
+                       ta.base_branch,
+                       ta.subpath,
+                       ta.executor AS "executor!",
+                       ta.worktree_deleted  AS "worktree_deleted!: bool",
+                       ta.scratch           AS "scratch!: bool",
+                       ta.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       ta.created_at        AS "created_at!: DateTime<Utc>",
+                       ta.updated_at        AS "updated_at!: DateTime<Utc>"
+               FROM    task_attempts ta
+               JOIN    tasks t ON t.id = ta.task_id
+               WHERE   t.project_id = $1 AND ta.branch = $2
+               ORDER BY ta.created_at DESC
+               LIMIT 1"#,
+            project_id,
+            branch
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             TaskAttempt,
@@ -265,8 +418,10 @@ impl TaskAttempt {
                        container_ref,
                        branch,
                        base_branch,
+                       subpath,
                        executor AS "executor!",
                        worktree_deleted  AS "worktree_deleted!: bool",
+                       scratch           AS "scratch!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
@@ -315,18 +470,14 @@ impl TaskAttempt {
             .collect())
     }
 
-    pub async fn container_ref_exists(
-        pool: &SqlitePool,
-        container_ref: &str,
-    ) -> Result<bool, sqlx::Error> {
-        let result = sqlx::query!(
-            r#"SELECT EXISTS(SELECT 1 FROM task_attempts WHERE container_ref = ?) as "exists!: bool""#,
-            container_ref
+    /// Every container ref recorded on an attempt, for checking many paths at once
+    pub async fn all_container_refs(pool: &SqlitePool) -> Result<HashSet<String>, sqlx::Error> {
+        let refs = sqlx::query_scalar!(
+            r#"SELECT container_ref AS "container_ref!" FROM task_attempts WHERE container_ref IS NOT NULL"#
         )
-        .fetch_one(pool)
+        .fetch_all(pool)
         .await?;
-
-        Ok(result.exists)
+        Ok(refs.into_iter().collect())
     }
 
     /// Find task attempts that are expired (72+ hours since last activity) and eligible for worktree cleanup
@@ -378,6 +529,89 @@ impl TaskAttempt {
             .collect())
     }
 
+    /// Attempts with a worktree and no running processes, least recently
+    /// active first. Candidates for eviction when worktrees use too much disk.
+    pub async fn find_evictable_for_cleanup(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(Uuid, String, String)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"
+            SELECT ta.id as "attempt_id!: Uuid", ta.container_ref, p.git_repo_path as "git_repo_path!"
+            FROM task_attempts ta
+            LEFT JOIN execution_processes ep ON ta.id = ep.task_attempt_id AND ep.completed_at IS NOT NULL
+            JOIN tasks t ON ta.task_id = t.id
+            JOIN projects p ON t.project_id = p.id
+            WHERE ta.worktree_deleted = FALSE
+                AND ta.container_ref IS NOT NULL
+                -- Exclude attempts with any running processes (in progress)
+                AND ta.id NOT IN (
+                    SELECT DISTINCT ep2.task_attempt_id
+                    FROM execution_processes ep2
+                    WHERE ep2.completed_at IS NULL
+                )
+            GROUP BY ta.id, ta.container_ref, p.git_repo_path, ta.updated_at
+            ORDER BY datetime(MAX(
+                CASE
+                    WHEN ep.completed_at IS NOT NULL THEN ep.completed_at
+                    ELSE ta.updated_at
+                END
+            )) ASC
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|r| {
+                r.container_ref
+                    .map(|path| (r.attempt_id, path, r.git_repo_path))
+            })
+            .collect())
+    }
+
+    /// Attempts of done tasks whose work was merged (directly or by a merged PR, and
+    /// not since reverted) but still have a worktree, with no running processes
+    pub async fn find_merged_for_cleanup(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(Uuid, String, String)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"
+            SELECT ta.id as "attempt_id!: Uuid", ta.container_ref, p.git_repo_path as "git_repo_path!"
+            FROM task_attempts ta
+            JOIN tasks t ON ta.task_id = t.id
+            JOIN projects p ON t.project_id = p.id
+            WHERE ta.worktree_deleted = FALSE
+                AND ta.container_ref IS NOT NULL
+                AND t.status = 'done'
+                AND EXISTS (
+                    SELECT 1
+                    FROM merges m
+                    WHERE m.task_attempt_id = ta.id
+                        AND m.reverted_at IS NULL
+                        AND (m.merge_type = 'direct' OR m.pr_status = 'merged')
+                )
+                -- Exclude attempts with any running processes (in progress)
+                AND ta.id NOT IN (
+                    SELECT DISTINCT ep.task_attempt_id
+                    FROM execution_processes ep
+                    WHERE ep.completed_at IS NULL
+                )
+            ORDER BY ta.created_at ASC
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|r| {
+                r.container_ref
+                    .map(|path| (r.attempt_id, path, r.git_repo_path))
+            })
+            .collect())
+    }
+
     pub async fn create(
         pool: &SqlitePool,
         data: &CreateTaskAttempt,
@@ -388,14 +622,15 @@ impl TaskAttempt {
         // Insert the record into the database
         Ok(sqlx::query_as!(
             TaskAttempt,
-            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, base_branch, executor, worktree_deleted, setup_completed_at)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, base_branch, executor as "executor!",  worktree_deleted as "worktree_deleted!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, base_branch, subpath, executor, worktree_deleted, setup_completed_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, base_branch, subpath, executor as "executor!",  worktree_deleted as "worktree_deleted!: bool", scratch as "scratch!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             attempt_id,
             task_id,
             Option::<String>::None, // Container isn't known yet
             Option::<String>::None, // branch name isn't known yet
             data.base_branch,
+            data.subpath,
             data.executor,
             false, // worktree_deleted is false during creation
             Option::<DateTime<Utc>>::None // setup_completed_at is None during creation
@@ -404,6 +639,38 @@ impl TaskAttempt {
         .await?)
     }
 
+    /// Create an attempt unless the task already has `max_attempts` attempts whose
+    /// worktree hasn't been deleted. `None` means no limit. The count and insert are one
+    /// statement, so concurrent creates can't both slip under the limit.
+    pub async fn create_within_limit(
+        pool: &SqlitePool,
+        data: &CreateTaskAttempt,
+        task_id: Uuid,
+        max_attempts: Option<u32>,
+    ) -> Result<Self, TaskAttemptError> {
+        let Some(max_attempts) = max_attempts else {
+            return Self::create(pool, data, task_id).await;
+        };
+        let attempt_id = Uuid::new_v4();
+        let limit = i64::from(max_attempts);
+        sqlx::query_as!(
+            TaskAttempt,
+            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, base_branch, subpath, executor, worktree_deleted, setup_completed_at)
+               SELECT $1, $2, NULL, NULL, $3, $4, $5, FALSE, NULL
+               WHERE (SELECT COUNT(*) FROM task_attempts WHERE task_id = $2 AND worktree_deleted = FALSE) < $6
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, base_branch, subpath, executor as "executor!",  worktree_deleted as "worktree_deleted!: bool", scratch as "scratch!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            attempt_id,
+            task_id,
+            data.base_branch,
+            data.subpath,
+            data.executor,
+            limit
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(TaskAttemptError::AttemptLimitReached(max_attempts))
+    }
+
     pub async fn update_base_branch(
         pool: &SqlitePool,
         attempt_id: Uuid,
@@ -440,3 +707,69 @@ impl TaskAttempt {
         Ok((result.attempt_id, result.task_id, result.project_id))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{create_task_attempt, test_pool};
+
+    #[tokio::test]
+    async fn attempts_beyond_the_limit_are_rejected() {
+        let pool = &test_pool().await;
+        let (task, first) = create_task_attempt(pool).await;
+        let data = CreateTaskAttempt {
+            executor: BaseCodingAgent::ClaudeCode,
+            base_branch: "main".to_string(),
+            subpath: None,
+        };
+
+        for _ in 1..3 {
+            TaskAttempt::create_within_limit(pool, &data, task.id, Some(3))
+                .await
+                .unwrap();
+        }
+        let err = TaskAttempt::create_within_limit(pool, &data, task.id, Some(3))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TaskAttemptError::AttemptLimitReached(3)));
+
+        // Attempts whose worktree was deleted no longer count towards the limit
+        TaskAttempt::mark_worktree_deleted(pool, first.id)
+            .await
+            .unwrap();
+        TaskAttempt::create_within_limit(pool, &data, task.id, Some(3))
+            .await
+            .unwrap();
+        TaskAttempt::create_within_limit(pool, &data, task.id, None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn attempt_is_found_by_its_branch_within_its_project() {
+        let pool = &test_pool().await;
+        let (task, task_attempt) = create_task_attempt(pool).await;
+        TaskAttempt::update_branch(pool, task_attempt.id, "vk/1234-task")
+            .await
+            .unwrap();
+
+        let found = TaskAttempt::find_by_branch(pool, task.project_id, "vk/1234-task")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, task_attempt.id);
+        assert!(
+            TaskAttempt::find_by_branch(pool, task.project_id, "no-such-branch")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // Branches are scoped to their project
+        assert!(
+            TaskAttempt::find_by_branch(pool, Uuid::new_v4(), "vk/1234-task")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType,
+        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+    },
+    executors::BaseCodingAgent,
+};
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use uuid::Uuid;
+
+use super::{
+    execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason},
+    project::{CreateProject, Project},
+    task::{CreateTask, Task},
+    task_attempt::{CreateTaskAttempt, TaskAttempt},
+};
+
+/// A migrated in-memory database. A single connection so every query sees the same one.
+pub async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    pool
+}
+
+/// Insert a project with a unique repo path
+pub async fn create_project(pool: &SqlitePool) -> Project {
+    Project::create(
+        pool,
+        &CreateProject {
+            name: "project".to_string(),
+            // Repo paths are unique per project
+            git_repo_path: format!("/tmp/project-{}", Uuid::new_v4()),
+            use_existing_repo: true,
+            setup_script: None,
+            dev_script: None,
+            cleanup_script: None,
+            copy_files: None,
+            dev_server_url_pattern: None,
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap()
+}
+
+/// Insert a task titled `title` into `project_id`
+pub async fn create_task(pool: &SqlitePool, project_id: Uuid, title: &str) -> Task {
+    Task::create(
+        pool,
+        &CreateTask {
+            project_id,
+            title: title.to_string(),
+            description: None,
+            parent_task_attempt: None,
+            image_ids: None,
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap()
+}
+
+/// Insert an attempt on `task_id` based on `main`
+pub async fn create_attempt(pool: &SqlitePool, task_id: Uuid) -> TaskAttempt {
+    TaskAttempt::create(
+        pool,
+        &CreateTaskAttempt {
+            executor: BaseCodingAgent::ClaudeCode,
+            base_branch: "main".to_string(),
+            subpath: None,
+        },
+        task_id,
+    )
+    .await
+    .unwrap()
+}
+
+/// Insert a project with one task and an attempt on it
+pub async fn create_task_attempt(pool: &SqlitePool) -> (Task, TaskAttempt) {
+    let project = create_project(pool).await;
+    let task = create_task(pool, project.id, "task").await;
+    let task_attempt = create_attempt(pool, task.id).await;
+    (task, task_attempt)
+}
+
+/// Insert a running process of `task_attempt` for `run_reason`
+pub async fn create_execution_process(
+    pool: &SqlitePool,
+    task_attempt: &TaskAttempt,
+    run_reason: ExecutionProcessRunReason,
+) -> ExecutionProcess {
+    ExecutionProcess::create(
+        pool,
+        &CreateExecutionProcess {
+            task_attempt_id: task_attempt.id,
+            executor_action: ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script: "true".to_string(),
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::SetupScript,
+                }),
+                None,
+            ),
+            run_reason,
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap()
+}
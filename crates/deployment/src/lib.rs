@@ -6,7 +6,10 @@ use axum::response::sse::Event;
 use db::{
     DBService,
     models::{
-        execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+        execution_process::{
+            ExecutionProcess, ExecutionProcessKillReason, ExecutionProcessRunReason,
+            ExecutionProcessStatus,
+        },
         task::{Task, TaskStatus},
         task_attempt::{TaskAttempt, TaskAttemptError},
     },
@@ -26,6 +29,7 @@ use services::services::{
     filesystem_watcher::FilesystemWatcherError,
     git::{GitService, GitServiceError},
     image::{ImageError, ImageService},
+    notification::NotificationService,
     pr_monitor::PrMonitorService,
     sentry::SentryService,
     worktree_manager::WorktreeError,
@@ -85,7 +89,7 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn analytics(&self) -> &Option<AnalyticsService>;
 
-    fn container(&self) -> &impl ContainerService;
+    fn container(&self) -> &(impl ContainerService + Clone + Send + Sync + 'static);
 
     fn auth(&self) -> &AuthService;
 
@@ -115,7 +119,11 @@ pub trait Deployment: Clone + Send + Sync + 'static {
     async fn spawn_pr_monitor_service(&self) -> tokio::task::JoinHandle<()> {
         let db = self.db().clone();
         let config = self.config().clone();
-        PrMonitorService::spawn(db, config).await
+        PrMonitorService::spawn(db, config, self.container().clone()).await
+    }
+
+    fn spawn_task_event_webhooks(&self) -> tokio::task::JoinHandle<()> {
+        NotificationService::spawn_task_event_webhooks(self.config().clone())
     }
 
     async fn track_if_analytics_allowed(&self, event_name: &str, properties: Value) {
@@ -143,6 +151,7 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 process.id,
                 ExecutionProcessStatus::Failed,
                 None, // No exit code for orphaned processes
+                Some(ExecutionProcessKillReason::Shutdown),
             )
             .await
             {
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Token counts and cost reported by a coding agent. Fields are `None` when
+ * the agent doesn't report them.
+ */
+export type TokenUsage = { input_tokens: bigint | null, output_tokens: bigint | null, cost_usd: number | null, };
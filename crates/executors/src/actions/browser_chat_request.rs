@@ -1,16 +1,21 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
 use async_trait::async_trait;
 use command_group::AsyncGroupChild;
 use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 
 use crate::{
-    actions::Executable,
+    actions::{Executable, ExecutionPreview},
     executors::ExecutorError,
     profile::ExecutorProfileId,
 };
 
+/// Line written to a running automation's stdin to ask it to close the browser (saving the
+/// session profile) and exit. See browser-automation/README.md for the control protocol.
+pub const BROWSER_CHAT_STOP_COMMAND: &str = "stop";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 #[serde(rename_all = "lowercase")]
 pub enum BrowserChatAgentType {
@@ -26,11 +31,32 @@ pub struct BrowserChatRequest {
     pub executor_profile_id: ExecutorProfileId,
     /// Optional session ID for follow-up messages to existing browser sessions
     pub session_id: Option<String>,
+    /// Node.js binary to run the automation with, resolved from config when the execution
+    /// starts; `node` from PATH when unset
+    #[serde(skip)]
+    #[ts(skip)]
+    pub node_path: Option<String>,
+}
+
+impl BrowserChatRequest {
+    /// Ask an automation spawned by this action to shut down on its own. Returns false if
+    /// the control channel is already gone, in which case the caller should just kill it.
+    pub async fn request_stop(child: &mut AsyncGroupChild) -> bool {
+        let Some(stdin) = child.inner().stdin.as_mut() else {
+            return false;
+        };
+        let line = format!("{BROWSER_CHAT_STOP_COMMAND}\n");
+        stdin.write_all(line.as_bytes()).await.is_ok() && stdin.flush().await.is_ok()
+    }
 }
 
 #[async_trait]
 impl Executable for BrowserChatRequest {
-    async fn spawn(&self, _current_dir: &Path) -> Result<AsyncGroupChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        _current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
         use std::process::Stdio;
         use tokio::process::Command;
         use command_group::AsyncCommandGroup;
@@ -42,8 +68,8 @@ impl Executable for BrowserChatRequest {
         };
 
         // Build the Node.js command to run browser automation
-        let mut cmd = Command::new("node");
-        cmd.arg(format!("./browser-automation/{}", script_name))
+        let mut cmd = Command::new(self.node_path.as_deref().unwrap_or("node"));
+        cmd.arg(format!("./browser-automation/{script_name}"))
            .arg("--agent")
            .arg(agent_arg)
            .arg("--message")
@@ -54,6 +80,9 @@ impl Executable for BrowserChatRequest {
             cmd.arg("--session-id").arg(session_id);
         }
         
+        cmd.envs(project_env);
+
+        // stdin stays open as the control channel for `request_stop`
         cmd.stdin(Stdio::piped())
            .stdout(Stdio::piped())
            .stderr(Stdio::piped());
@@ -62,4 +91,14 @@ impl Executable for BrowserChatRequest {
         let child = cmd.group_spawn().map_err(ExecutorError::Io)?;
         Ok(child)
     }
+
+    fn preview(
+        &self,
+        _current_dir: &Path,
+        _project_env: &HashMap<String, String>,
+    ) -> Result<ExecutionPreview, ExecutorError> {
+        Err(ExecutorError::PreviewNotSupported(
+            "browser chat requests".to_string(),
+        ))
+    }
 }
\ No newline at end of file
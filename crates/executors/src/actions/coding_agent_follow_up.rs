@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
 use async_trait::async_trait;
 use command_group::AsyncGroupChild;
@@ -6,7 +6,8 @@ use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 use crate::{
-    actions::Executable,
+    actions::{Executable, ExecutionPreview},
+    command::ExecutorOverride,
     executors::{ExecutorError, StandardCodingAgentExecutor},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
@@ -19,6 +20,12 @@ pub struct CodingAgentFollowUpRequest {
     #[serde(alias = "profile_variant_label")]
     // Backwards compatability with ProfileVariantIds, esp stored in DB under ExecutorAction
     pub executor_profile_id: ExecutorProfileId,
+    /// Project-level extra params and environment for the executor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor_override: Option<ExecutorOverride>,
+    /// Project-level standing instructions for the agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_instructions: Option<String>,
 }
 
 impl CodingAgentFollowUpRequest {
@@ -30,16 +37,41 @@ impl CodingAgentFollowUpRequest {
 
 #[async_trait]
 impl Executable for CodingAgentFollowUpRequest {
-    async fn spawn(&self, current_dir: &Path) -> Result<AsyncGroupChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
         let executor_profile_id = self.get_executor_profile_id();
         let agent = ExecutorConfigs::get_cached()
             .get_coding_agent(&executor_profile_id)
             .ok_or(ExecutorError::UnknownExecutorType(
                 executor_profile_id.to_string(),
             ))?;
+        let agent = match &self.executor_override {
+            Some(executor_override) => agent.with_override(executor_override)?,
+            None => agent,
+        };
+        let agent = agent.with_project_env(project_env);
+        // Without a system prompt the instructions already went out with the first prompt
+        let agent = match &self.agent_instructions {
+            Some(instructions) => agent.with_system_prompt(instructions),
+            None => agent,
+        };
 
         agent
             .spawn_follow_up(current_dir, &self.prompt, &self.session_id)
             .await
     }
+
+    fn preview(
+        &self,
+        _current_dir: &Path,
+        _project_env: &HashMap<String, String>,
+    ) -> Result<ExecutionPreview, ExecutorError> {
+        // Some agents rebuild the command from session state at spawn time
+        Err(ExecutorError::PreviewNotSupported(
+            "follow-up requests".to_string(),
+        ))
+    }
 }
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
 use async_trait::async_trait;
 use command_group::AsyncGroupChild;
@@ -6,11 +6,15 @@ use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 use crate::{
-    actions::Executable,
-    executors::{ExecutorError, StandardCodingAgentExecutor},
+    actions::{Executable, ExecutionPreview},
+    command::ExecutorOverride,
+    executors::{CodingAgent, ExecutorError, StandardCodingAgentExecutor},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 
+/// What agents print when asked to resume a session they don't have, lowercased
+const SESSION_NOT_FOUND_MARKERS: &[&str] = &["no conversation found", "session not found"];
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct CodingAgentInitialRequest {
     pub prompt: String,
@@ -18,18 +22,88 @@ pub struct CodingAgentInitialRequest {
     #[serde(alias = "profile_variant_label")]
     // Backwards compatability with ProfileVariantIds, esp stored in DB under ExecutorAction
     pub executor_profile_id: ExecutorProfileId,
+    /// Project-level extra params and environment for the executor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor_override: Option<ExecutorOverride>,
+    /// Agent session to continue instead of starting a fresh one, e.g. the parent attempt's
+    /// session for a continuation task. Falls back to a fresh session if it can't be resumed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_session_id: Option<String>,
+    /// Project-level standing instructions for the agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_instructions: Option<String>,
 }
 
 #[async_trait]
 impl Executable for CodingAgentInitialRequest {
-    async fn spawn(&self, current_dir: &Path) -> Result<AsyncGroupChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let (agent, prompt) = self.resolve_agent(project_env)?;
+
+        if let Some(session_id) = &self.resume_session_id {
+            match agent
+                .spawn_follow_up(current_dir, &prompt, session_id)
+                .await
+            {
+                Ok(child) => return Ok(child),
+                Err(e) => tracing::warn!(
+                    "Could not resume session {session_id}, starting a fresh one: {e}"
+                ),
+            }
+        }
+
+        agent.spawn(current_dir, &prompt).await
+    }
+
+    /// Previews a fresh session; a `resume_session_id` is not reflected
+    fn preview(
+        &self,
+        current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<ExecutionPreview, ExecutorError> {
+        let (agent, prompt) = self.resolve_agent(project_env)?;
+        agent.preview(current_dir, &prompt)
+    }
+}
+
+impl CodingAgentInitialRequest {
+    /// Whether agent `output` says the session it was told to resume doesn't exist. The
+    /// agent spawns fine in that case and exits right away, so a failed spawn doesn't cover it.
+    pub fn session_not_found(output: &str) -> bool {
+        let output = output.to_lowercase();
+        SESSION_NOT_FOUND_MARKERS
+            .iter()
+            .any(|marker| output.contains(marker))
+    }
+
+    /// The configured agent with the project's override, environment and instructions
+    /// applied, plus the prompt to send it. Agents without a system prompt get the
+    /// instructions ahead of the task prompt instead.
+    fn resolve_agent(
+        &self,
+        project_env: &HashMap<String, String>,
+    ) -> Result<(CodingAgent, String), ExecutorError> {
         let executor_profile_id = self.executor_profile_id.clone();
         let agent = ExecutorConfigs::get_cached()
             .get_coding_agent(&executor_profile_id)
             .ok_or(ExecutorError::UnknownExecutorType(
                 executor_profile_id.to_string(),
             ))?;
+        let agent = match &self.executor_override {
+            Some(executor_override) => agent.with_override(executor_override)?,
+            None => agent,
+        };
+        let agent = agent.with_project_env(project_env);
 
-        agent.spawn(current_dir, &self.prompt).await
+        Ok(match &self.agent_instructions {
+            Some(instructions) if agent.supports_system_prompt() => {
+                (agent.with_system_prompt(instructions), self.prompt.clone())
+            }
+            Some(instructions) => (agent, format!("{instructions}\n\n{}", self.prompt)),
+            None => (agent, self.prompt.clone()),
+        })
     }
 }
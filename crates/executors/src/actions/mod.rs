@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
 
 use async_trait::async_trait;
 use command_group::AsyncGroupChild;
@@ -49,15 +52,52 @@ impl ExecutorAction {
     }
 }
 
+/// What `spawn` would run, worked out without starting anything
+#[derive(Debug, Clone, Serialize, PartialEq, TS)]
+pub struct ExecutionPreview {
+    /// Command line handed to the platform shell
+    pub command: String,
+    pub working_dir: String,
+    /// Environment set on top of the inherited one, with values masked
+    pub env: BTreeMap<String, String>,
+    /// Text fed to the process on stdin
+    pub prompt: Option<String>,
+}
+
 #[async_trait]
 #[enum_dispatch(ExecutorActionType)]
 pub trait Executable {
-    async fn spawn(&self, current_dir: &Path) -> Result<AsyncGroupChild, ExecutorError>;
+    /// Spawn the action in `current_dir`. `project_env` is layered over the inherited host
+    /// environment; anything the action sets for itself takes precedence over it.
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<AsyncGroupChild, ExecutorError>;
+
+    /// Resolve what `spawn` would run with the same arguments, without spawning
+    fn preview(
+        &self,
+        current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<ExecutionPreview, ExecutorError>;
 }
 
 #[async_trait]
 impl Executable for ExecutorAction {
-    async fn spawn(&self, current_dir: &Path) -> Result<AsyncGroupChild, ExecutorError> {
-        self.typ.spawn(current_dir).await
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        self.typ.spawn(current_dir, project_env).await
+    }
+
+    fn preview(
+        &self,
+        current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<ExecutionPreview, ExecutorError> {
+        self.typ.preview(current_dir, project_env)
     }
 }
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
 use async_trait::async_trait;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
@@ -7,7 +7,11 @@ use tokio::process::Command;
 use ts_rs::TS;
 use utils::shell::get_shell_command;
 
-use crate::{actions::Executable, executors::ExecutorError};
+use crate::{
+    actions::{Executable, ExecutionPreview},
+    command::masked_env,
+    executors::ExecutorError,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub enum ScriptRequestLanguage {
@@ -30,7 +34,11 @@ pub struct ScriptRequest {
 
 #[async_trait]
 impl Executable for ScriptRequest {
-    async fn spawn(&self, current_dir: &Path) -> Result<AsyncGroupChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
         let (shell_cmd, shell_arg) = get_shell_command();
         let mut command = Command::new(shell_cmd);
         command
@@ -39,10 +47,24 @@ impl Executable for ScriptRequest {
             .stderr(std::process::Stdio::piped())
             .arg(shell_arg)
             .arg(&self.script)
-            .current_dir(current_dir);
+            .current_dir(current_dir)
+            .envs(project_env);
 
         let child = command.group_spawn()?;
 
         Ok(child)
     }
+
+    fn preview(
+        &self,
+        current_dir: &Path,
+        project_env: &HashMap<String, String>,
+    ) -> Result<ExecutionPreview, ExecutorError> {
+        Ok(ExecutionPreview {
+            command: self.script.clone(),
+            working_dir: current_dir.to_string_lossy().to_string(),
+            env: masked_env(project_env),
+            prompt: None,
+        })
+    }
 }
@@ -1,7 +1,39 @@
+use std::collections::{BTreeMap, HashMap};
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+use crate::executors::BaseCodingAgent;
+
+/// Reject environment variable names the OS wouldn't accept
+pub fn validate_env(env: &HashMap<String, String>) -> Result<(), String> {
+    match env.keys().find(|k| k.is_empty() || k.contains(['=', '\0'])) {
+        Some(key) => Err(format!("Invalid environment variable name: {key:?}")),
+        None => Ok(()),
+    }
+}
+
+/// Render environment variables for logging with their values hidden, as they
+/// often hold secrets
+pub fn mask_env(env: &HashMap<String, String>) -> String {
+    let mut keys: Vec<&str> = env.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    keys.iter()
+        .map(|key| format!("{key}=***"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Environment variable names with their values hidden, for showing to users
+pub fn masked_env<K: ToString, V>(
+    env: impl IntoIterator<Item = (K, V)>,
+) -> BTreeMap<String, String> {
+    env.into_iter()
+        .map(|(key, _)| (key.to_string(), "***".to_string()))
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema, Default)]
 pub struct CmdOverrides {
     #[schemars(
@@ -16,6 +48,98 @@ pub struct CmdOverrides {
     )]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub additional_params: Option<Vec<String>>,
+    #[schemars(
+        title = "Environment Variables",
+        description = "Extra environment variables for the executor process"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, String>>,
+}
+
+impl CmdOverrides {
+    pub fn env_vars(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.env.iter().flatten()
+    }
+
+    /// Add a project's extra params and environment on top of these overrides
+    pub fn merge(&mut self, project_override: &ExecutorOverride) {
+        if !project_override.extra_params.is_empty() {
+            self.additional_params
+                .get_or_insert_with(Vec::new)
+                .extend(project_override.extra_params.iter().cloned());
+        }
+        if !project_override.env.is_empty() {
+            self.env.get_or_insert_with(HashMap::new).extend(
+                project_override
+                    .env
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone())),
+            );
+        }
+    }
+
+    /// Fill in project environment variables underneath these overrides, so a
+    /// variable set for this specific executor wins over the project-wide value
+    pub fn merge_project_env(&mut self, project_env: &HashMap<String, String>) {
+        if project_env.is_empty() {
+            return;
+        }
+        let env = self.env.get_or_insert_with(HashMap::new);
+        for (key, value) in project_env {
+            env.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// Per-project executor overrides, keyed by executor
+pub type ExecutorOverrides = HashMap<BaseCodingAgent, ExecutorOverride>;
+
+/// Per-project additions to an executor's command. Overrides can only add
+/// params and environment; they never replace the base command.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, Default)]
+pub struct ExecutorOverride {
+    /// Params appended to the executor command, e.g. `--model opus`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_params: Vec<String>,
+    /// Extra environment variables for the executor process
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+impl ExecutorOverride {
+    /// Check the override is well formed: no empty params and only valid
+    /// environment variable names
+    pub fn validate(&self) -> Result<(), String> {
+        if self.extra_params.iter().any(|p| p.trim().is_empty()) {
+            return Err("Extra params must not be empty".to_string());
+        }
+        validate_env(&self.env)
+    }
+
+    /// Check that none of the extra params sets a flag the executor already
+    /// passes. Most CLIs take the last value of a repeated flag, so this would
+    /// silently replace a required arg (e.g. `--output-format=text` breaks log
+    /// parsing).
+    pub fn validate_against(&self, builder: &CommandBuilder) -> Result<(), String> {
+        match self
+            .extra_params
+            .iter()
+            .filter_map(|p| flag_name(p))
+            .find(|flag| builder.has_flag(flag))
+        {
+            Some(flag) => Err(format!(
+                "{flag} is set by the executor and can't be overridden"
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The flag name of a param (`--model=x` -> `--model`), if it is a flag
+fn flag_name(param: &str) -> Option<&str> {
+    param
+        .starts_with('-')
+        .then(|| param.split('=').next().unwrap_or(param))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
@@ -60,6 +184,14 @@ impl CommandBuilder {
         }
         self
     }
+    /// Whether the params already include `flag`, either bare or as `flag=value`
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.params
+            .iter()
+            .flatten()
+            .any(|p| flag_name(p) == Some(flag))
+    }
+
     pub fn build_initial(&self) -> String {
         let mut parts = vec![self.base.clone()];
         if let Some(ref params) = self.params {
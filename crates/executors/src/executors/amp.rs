@@ -32,7 +32,7 @@ pub struct Amp {
 }
 
 impl Amp {
-    fn build_command_builder(&self) -> CommandBuilder {
+    pub(crate) fn build_command_builder(&self) -> CommandBuilder {
         let mut builder = CommandBuilder::new("npx -y @sourcegraph/amp@latest")
             .params(["--execute", "--stream-json"]);
         if self.dangerously_allow_all.unwrap_or(false) {
@@ -61,6 +61,7 @@ impl StandardCodingAgentExecutor for Amp {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.cmd.env_vars())
             .arg(shell_arg)
             .arg(&amp_command);
 
@@ -95,6 +96,7 @@ impl StandardCodingAgentExecutor for Amp {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.cmd.env_vars())
             .arg(shell_arg)
             .arg(&fork_cmd)
             .output()
@@ -131,6 +133,7 @@ impl StandardCodingAgentExecutor for Amp {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.cmd.env_vars())
             .arg(shell_arg)
             .arg(&continue_cmd);
 
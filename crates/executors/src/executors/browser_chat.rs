@@ -54,7 +54,7 @@ impl StandardCodingAgentExecutor for ClaudeBrowserChat {
 
         let mut child = command
             .group_spawn()
-            .map_err(|e| ExecutorError::Io(e))?;
+            .map_err(ExecutorError::Io)?;
 
         // The browser automation handles its own interaction, so we don't need to write to stdin
         // Just close stdin to let the process run independently
@@ -121,7 +121,7 @@ impl StandardCodingAgentExecutor for M365CopilotChat {
 
         let mut child = command
             .group_spawn()
-            .map_err(|e| ExecutorError::Io(e))?;
+            .map_err(ExecutorError::Io)?;
 
         // The browser automation handles its own interaction, so we don't need to write to stdin
         // Just close stdin to let the process run independently
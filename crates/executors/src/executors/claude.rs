@@ -48,7 +48,7 @@ pub struct ClaudeCode {
 }
 
 impl ClaudeCode {
-    fn build_command_builder(&self) -> CommandBuilder {
+    pub(crate) fn build_command_builder(&self) -> CommandBuilder {
         // If base_command_override is provided and claude_code_router is also set, log a warning
         if self.cmd.base_command_override.is_some() && self.claude_code_router.is_some() {
             tracing::warn!(
@@ -70,6 +70,16 @@ impl ClaudeCode {
 
         apply_overrides(builder, &self.cmd)
     }
+
+    /// Shell command for a fresh session; plan mode wraps it to stop at the plan
+    pub(crate) fn initial_command(&self) -> String {
+        let base_command = self.build_command_builder().build_initial();
+        if self.plan.unwrap_or(false) {
+            create_watchkill_script(&base_command)
+        } else {
+            base_command
+        }
+    }
 }
 
 #[async_trait]
@@ -80,13 +90,7 @@ impl StandardCodingAgentExecutor for ClaudeCode {
         prompt: &str,
     ) -> Result<AsyncGroupChild, ExecutorError> {
         let (shell_cmd, shell_arg) = get_shell_command();
-        let command_builder = self.build_command_builder();
-        let base_command = command_builder.build_initial();
-        let claude_command = if self.plan.unwrap_or(false) {
-            create_watchkill_script(&base_command)
-        } else {
-            base_command
-        };
+        let claude_command = self.initial_command();
 
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
@@ -97,6 +101,7 @@ impl StandardCodingAgentExecutor for ClaudeCode {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.cmd.env_vars())
             .arg(shell_arg)
             .arg(&claude_command);
 
@@ -137,6 +142,7 @@ impl StandardCodingAgentExecutor for ClaudeCode {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.cmd.env_vars())
             .arg(shell_arg)
             .arg(&claude_command);
 
@@ -244,7 +250,10 @@ impl ClaudeLogProcessor {
             while let Some(Ok(msg)) = stream.next().await {
                 let chunk = match msg {
                     LogMsg::Stdout(x) => x,
-                    LogMsg::JsonPatch(_) | LogMsg::SessionId(_) | LogMsg::Stderr(_) => continue,
+                    LogMsg::JsonPatch(_)
+                    | LogMsg::SessionId(_)
+                    | LogMsg::Stderr(_)
+                    | LogMsg::Error { .. } => continue,
                     LogMsg::Finished => break,
                 };
 
@@ -1086,6 +1095,10 @@ pub enum ClaudeJson {
         is_error: Option<bool>,
         duration_ms: Option<u64>,
         result: Option<serde_json::Value>,
+        #[serde(default)]
+        total_cost_usd: Option<f64>,
+        #[serde(default)]
+        usage: Option<ClaudeUsage>,
     },
     // Catch-all for unknown message types
     #[serde(untagged)]
@@ -1095,6 +1108,14 @@ pub enum ClaudeJson {
     },
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ClaudeUsage {
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub cache_creation_input_tokens: Option<u64>,
+    pub cache_read_input_tokens: Option<u64>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ClaudeMessage {
     pub id: Option<String>,
@@ -1340,7 +1361,13 @@ impl ClaudeToolData {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
+    use crate::{
+        command::{ExecutorOverride, mask_env},
+        executors::CodingAgent,
+    };
 
     #[test]
     fn test_claude_json_parsing() {
@@ -1507,6 +1534,7 @@ mod tests {
             cmd: crate::command::CmdOverrides {
                 base_command_override: None,
                 additional_params: None,
+                env: None,
             },
         };
         let msg_store = Arc::new(MsgStore::new());
@@ -1790,4 +1818,107 @@ mod tests {
 
         // ToolResult entry is ignored - no third entry
     }
+
+    fn claude_agent() -> CodingAgent {
+        CodingAgent::ClaudeCode(serde_json::from_str("{}").unwrap())
+    }
+
+    #[test]
+    fn test_executor_override_merges_into_command() {
+        let project_override = ExecutorOverride {
+            extra_params: vec!["--model".to_string(), "opus".to_string()],
+            env: HashMap::from([("ANTHROPIC_LOG".to_string(), "debug".to_string())]),
+        };
+
+        let CodingAgent::ClaudeCode(claude) =
+            claude_agent().with_override(&project_override).unwrap()
+        else {
+            panic!("override changed the executor type");
+        };
+
+        let command = claude.build_command_builder().build_initial();
+        assert!(command.contains("--output-format=stream-json"));
+        assert!(command.ends_with("--model opus"));
+        assert_eq!(
+            claude.cmd.env_vars().collect::<Vec<_>>(),
+            vec![(&"ANTHROPIC_LOG".to_string(), &"debug".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_executor_override_cannot_replace_required_args() {
+        let project_override = ExecutorOverride {
+            extra_params: vec!["--output-format=text".to_string()],
+            ..Default::default()
+        };
+
+        let err = claude_agent().with_override(&project_override).unwrap_err();
+        assert!(matches!(err, ExecutorError::InvalidOverride(_)));
+    }
+
+    #[test]
+    fn test_executor_override_rejects_invalid_env_names() {
+        let project_override = ExecutorOverride {
+            env: HashMap::from([("BAD=NAME".to_string(), "x".to_string())]),
+            ..Default::default()
+        };
+
+        let err = claude_agent().with_override(&project_override).unwrap_err();
+        assert!(matches!(err, ExecutorError::InvalidOverride(_)));
+    }
+
+    #[test]
+    fn test_executor_env_takes_precedence_over_project_env() {
+        let project_override = ExecutorOverride {
+            env: HashMap::from([("ANTHROPIC_LOG".to_string(), "debug".to_string())]),
+            ..Default::default()
+        };
+        let project_env = HashMap::from([
+            ("ANTHROPIC_LOG".to_string(), "info".to_string()),
+            ("PROJECT_TOKEN".to_string(), "secret".to_string()),
+        ]);
+
+        let CodingAgent::ClaudeCode(claude) = claude_agent()
+            .with_override(&project_override)
+            .unwrap()
+            .with_project_env(&project_env)
+        else {
+            panic!("project env changed the executor type");
+        };
+
+        let env: HashMap<_, _> = claude.cmd.env_vars().collect();
+        assert_eq!(env[&"ANTHROPIC_LOG".to_string()], "debug");
+        assert_eq!(env[&"PROJECT_TOKEN".to_string()], "secret");
+        assert_eq!(
+            mask_env(&project_env),
+            "ANTHROPIC_LOG=*** PROJECT_TOKEN=***"
+        );
+    }
+
+    #[test]
+    fn test_system_prompt_is_passed_through_the_environment() {
+        let instructions = "Always run `cargo test`; never force-push \"main\"";
+        let CodingAgent::ClaudeCode(claude) = claude_agent().with_system_prompt(instructions)
+        else {
+            panic!("system prompt changed the executor type");
+        };
+
+        let command = claude.build_command_builder().build_initial();
+        assert!(command.ends_with("--append-system-prompt \"$VK_AGENT_INSTRUCTIONS\""));
+        assert!(!command.contains("cargo test"));
+        let env: HashMap<_, _> = claude.cmd.env_vars().collect();
+        assert_eq!(env[&"VK_AGENT_INSTRUCTIONS".to_string()], instructions);
+    }
+
+    #[test]
+    fn test_plan_mode_has_no_system_prompt() {
+        let agent = CodingAgent::ClaudeCode(serde_json::from_str(r#"{"plan":true}"#).unwrap());
+        assert!(!agent.supports_system_prompt());
+
+        let CodingAgent::ClaudeCode(claude) = agent.with_system_prompt("be careful") else {
+            panic!("system prompt changed the executor type");
+        };
+        assert!(claude.cmd.additional_params.is_none());
+        assert!(claude.cmd.env.is_none());
+    }
 }
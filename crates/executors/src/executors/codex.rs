@@ -219,7 +219,7 @@ pub struct Codex {
 }
 
 impl Codex {
-    fn build_command_builder(&self) -> CommandBuilder {
+    pub(crate) fn build_command_builder(&self) -> CommandBuilder {
         let mut builder = CommandBuilder::new("npx -y @openai/codex exec")
             .params(["--json", "--skip-git-repo-check"]);
 
@@ -244,6 +244,14 @@ impl Codex {
 
         apply_overrides(builder, &self.cmd)
     }
+
+    /// Environment the agent is spawned with: its configured variables, then the fixed ones
+    pub(crate) fn spawn_env(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.cmd
+            .env_vars()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .chain([("NODE_NO_WARNINGS", "1"), ("RUST_LOG", "info")])
+    }
 }
 
 #[async_trait]
@@ -265,10 +273,9 @@ impl StandardCodingAgentExecutor for Codex {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.spawn_env())
             .arg(shell_arg)
-            .arg(&codex_command)
-            .env("NODE_NO_WARNINGS", "1")
-            .env("RUST_LOG", "info");
+            .arg(&codex_command);
 
         let mut child = command.group_spawn()?;
 
@@ -306,10 +313,9 @@ impl StandardCodingAgentExecutor for Codex {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.spawn_env())
             .arg(shell_arg)
-            .arg(&codex_command)
-            .env("NODE_NO_WARNINGS", "1")
-            .env("RUST_LOG", "info");
+            .arg(&codex_command);
 
         let mut child = command.group_spawn()?;
 
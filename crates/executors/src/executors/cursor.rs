@@ -41,7 +41,7 @@ pub struct Cursor {
 }
 
 impl Cursor {
-    fn build_command_builder(&self) -> CommandBuilder {
+    pub(crate) fn build_command_builder(&self) -> CommandBuilder {
         let mut builder =
             CommandBuilder::new("cursor-agent").params(["-p", "--output-format=stream-json"]);
 
@@ -76,6 +76,7 @@ impl StandardCodingAgentExecutor for Cursor {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.cmd.env_vars())
             .arg(shell_arg)
             .arg(&agent_cmd);
 
@@ -109,6 +110,7 @@ impl StandardCodingAgentExecutor for Cursor {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.cmd.env_vars())
             .arg(shell_arg)
             .arg(&agent_cmd);
 
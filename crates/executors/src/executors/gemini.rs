@@ -62,7 +62,7 @@ pub struct Gemini {
 }
 
 impl Gemini {
-    fn build_command_builder(&self) -> CommandBuilder {
+    pub(crate) fn build_command_builder(&self) -> CommandBuilder {
         let mut builder = self.model.build_command_builder();
 
         if self.yolo.unwrap_or(false) {
@@ -71,6 +71,14 @@ impl Gemini {
 
         apply_overrides(builder, &self.cmd)
     }
+
+    /// Environment the agent is spawned with: its configured variables, then the fixed ones
+    pub(crate) fn spawn_env(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.cmd
+            .env_vars()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .chain([("NODE_NO_WARNINGS", "1")])
+    }
 }
 
 #[async_trait]
@@ -92,9 +100,9 @@ impl StandardCodingAgentExecutor for Gemini {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.spawn_env())
             .arg(shell_arg)
-            .arg(gemini_command)
-            .env("NODE_NO_WARNINGS", "1");
+            .arg(gemini_command);
 
         let mut child = command.group_spawn()?;
 
@@ -136,9 +144,9 @@ impl StandardCodingAgentExecutor for Gemini {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.spawn_env())
             .arg(shell_arg)
-            .arg(gemini_command)
-            .env("NODE_NO_WARNINGS", "1");
+            .arg(gemini_command);
 
         let mut child = command.group_spawn()?;
 
@@ -1,4 +1,4 @@
-use std::{path::Path, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc};
 
 use async_trait::async_trait;
 use command_group::AsyncGroupChild;
@@ -13,9 +13,17 @@ use ts_rs::TS;
 use utils::msg_store::MsgStore;
 
 use crate::{
+    actions::ExecutionPreview,
+    command::{ExecutorOverride, masked_env},
     executors::{
-        amp::Amp, browser_chat::{ClaudeBrowserChat, M365CopilotChat}, claude::ClaudeCode, codex::Codex, cursor::Cursor, gemini::Gemini,
-        opencode::Opencode, qwen::QwenCode,
+        amp::Amp,
+        browser_chat::{ClaudeBrowserChat, M365CopilotChat},
+        claude::ClaudeCode,
+        codex::Codex,
+        cursor::Cursor,
+        gemini::Gemini,
+        opencode::Opencode,
+        qwen::QwenCode,
     },
     mcp_config::McpConfig,
 };
@@ -29,6 +37,18 @@ pub mod gemini;
 pub mod opencode;
 pub mod qwen;
 
+/// Environment variable carrying project instructions to agents that take a system prompt
+const AGENT_INSTRUCTIONS_ENV: &str = "VK_AGENT_INSTRUCTIONS";
+
+/// A quoted reference to an environment variable, expanded by the platform shell
+fn shell_env_ref(name: &str) -> String {
+    if cfg!(windows) {
+        format!("\"%{name}%\"")
+    } else {
+        format!("\"${name}\"")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum BaseAgentCapability {
@@ -51,6 +71,10 @@ pub enum ExecutorError {
     TomlSerialize(#[from] toml::ser::Error),
     #[error(transparent)]
     TomlDeserialize(#[from] toml::de::Error),
+    #[error("Invalid executor override: {0}")]
+    InvalidOverride(String),
+    #[error("Preview is not available for {0}")]
+    PreviewNotSupported(String),
 }
 
 #[enum_dispatch]
@@ -135,6 +159,141 @@ impl CodingAgent {
         }
     }
 
+    /// Apply a project's executor override. Fails if the override is malformed
+    /// or would replace a param the executor passes itself.
+    pub fn with_override(
+        mut self,
+        project_override: &ExecutorOverride,
+    ) -> Result<Self, ExecutorError> {
+        project_override
+            .validate()
+            .map_err(ExecutorError::InvalidOverride)?;
+        let (builder, cmd) = match &mut self {
+            Self::ClaudeCode(agent) => (agent.build_command_builder(), &mut agent.cmd),
+            Self::Amp(agent) => (agent.build_command_builder(), &mut agent.cmd),
+            Self::Gemini(agent) => (agent.build_command_builder(), &mut agent.cmd),
+            Self::Codex(agent) => (agent.build_command_builder(), &mut agent.cmd),
+            Self::Opencode(agent) => (agent.build_command_builder(), &mut agent.cmd),
+            Self::Cursor(agent) => (agent.build_command_builder(), &mut agent.cmd),
+            Self::QwenCode(agent) => (agent.build_command_builder(), &mut agent.cmd),
+            Self::ClaudeBrowserChat(_) | Self::M365CopilotChat(_) => {
+                return Err(ExecutorError::InvalidOverride(format!(
+                    "{} does not run a command",
+                    BaseCodingAgent::from(&self)
+                )));
+            }
+        };
+        project_override
+            .validate_against(&builder)
+            .map_err(ExecutorError::InvalidOverride)?;
+        cmd.merge(project_override);
+        Ok(self)
+    }
+
+    /// Apply project-level environment variables with lower precedence than any
+    /// environment already configured for this executor
+    pub fn with_project_env(mut self, project_env: &HashMap<String, String>) -> Self {
+        let cmd = match &mut self {
+            Self::ClaudeCode(agent) => &mut agent.cmd,
+            Self::Amp(agent) => &mut agent.cmd,
+            Self::Gemini(agent) => &mut agent.cmd,
+            Self::Codex(agent) => &mut agent.cmd,
+            Self::Opencode(agent) => &mut agent.cmd,
+            Self::Cursor(agent) => &mut agent.cmd,
+            Self::QwenCode(agent) => &mut agent.cmd,
+            Self::ClaudeBrowserChat(_) | Self::M365CopilotChat(_) => return self,
+        };
+        cmd.merge_project_env(project_env);
+        self
+    }
+
+    /// Resolve the command, environment and stdin prompt `spawn` would use for `prompt`
+    /// in `current_dir`, without running anything
+    pub fn preview(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+    ) -> Result<ExecutionPreview, ExecutorError> {
+        let (command, env, append_prompt) = match self {
+            Self::ClaudeCode(agent) => (
+                agent.initial_command(),
+                masked_env(agent.cmd.env_vars()),
+                &agent.append_prompt,
+            ),
+            Self::Amp(agent) => (
+                agent.build_command_builder().build_initial(),
+                masked_env(agent.cmd.env_vars()),
+                &agent.append_prompt,
+            ),
+            Self::Gemini(agent) => (
+                agent.build_command_builder().build_initial(),
+                masked_env(agent.spawn_env()),
+                &agent.append_prompt,
+            ),
+            Self::Codex(agent) => (
+                agent.build_command_builder().build_initial(),
+                masked_env(agent.spawn_env()),
+                &agent.append_prompt,
+            ),
+            Self::Opencode(agent) => (
+                agent.build_command_builder().build_initial(),
+                masked_env(agent.spawn_env()),
+                &agent.append_prompt,
+            ),
+            Self::Cursor(agent) => (
+                agent.build_command_builder().build_initial(),
+                masked_env(agent.cmd.env_vars()),
+                &agent.append_prompt,
+            ),
+            Self::QwenCode(agent) => (
+                agent.build_command_builder().build_initial(),
+                masked_env(agent.cmd.env_vars()),
+                &agent.append_prompt,
+            ),
+            Self::ClaudeBrowserChat(_) | Self::M365CopilotChat(_) => {
+                return Err(ExecutorError::PreviewNotSupported(
+                    BaseCodingAgent::from(self).to_string(),
+                ));
+            }
+        };
+        Ok(ExecutionPreview {
+            command,
+            working_dir: current_dir.to_string_lossy().to_string(),
+            env,
+            prompt: Some(append_prompt.combine_prompt(prompt)),
+        })
+    }
+
+    /// Whether the agent takes instructions through a system prompt, separate from the
+    /// user prompt. Claude's plan mode wraps the command in a script that would mangle
+    /// the extra argument, so it doesn't.
+    pub fn supports_system_prompt(&self) -> bool {
+        matches!(self, Self::ClaudeCode(agent) if !agent.plan.unwrap_or(false))
+    }
+
+    /// Pass `instructions` as the agent's system prompt, if it `supports_system_prompt`.
+    /// They reach the CLI through an environment variable so the shell never parses them.
+    pub fn with_system_prompt(mut self, instructions: &str) -> Self {
+        if !self.supports_system_prompt() {
+            return self;
+        }
+        if let Self::ClaudeCode(agent) = &mut self {
+            agent.cmd.env.get_or_insert_with(HashMap::new).insert(
+                AGENT_INSTRUCTIONS_ENV.to_string(),
+                instructions.to_string(),
+            );
+            agent
+                .cmd
+                .additional_params
+                .get_or_insert_with(Vec::new)
+                .extend([
+                    "--append-system-prompt".to_string(),
+                    shell_env_ref(AGENT_INSTRUCTIONS_ENV),
+                ]);
+        }
+        self
+    }
+
     pub fn supports_mcp(&self) -> bool {
         self.default_mcp_config_path().is_some()
     }
@@ -43,7 +43,7 @@ pub struct Opencode {
 }
 
 impl Opencode {
-    fn build_command_builder(&self) -> CommandBuilder {
+    pub(crate) fn build_command_builder(&self) -> CommandBuilder {
         let mut builder =
             CommandBuilder::new("npx -y opencode-ai@latest run").params(["--print-logs"]);
 
@@ -57,6 +57,14 @@ impl Opencode {
 
         apply_overrides(builder, &self.cmd)
     }
+
+    /// Environment the agent is spawned with: its configured variables, then the fixed ones
+    pub(crate) fn spawn_env(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.cmd
+            .env_vars()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .chain([("NODE_NO_WARNINGS", "1")])
+    }
 }
 
 #[async_trait]
@@ -78,9 +86,9 @@ impl StandardCodingAgentExecutor for Opencode {
             .stdout(Stdio::piped()) // Keep stdout but we won't use it
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.spawn_env())
             .arg(shell_arg)
-            .arg(opencode_command)
-            .env("NODE_NO_WARNINGS", "1");
+            .arg(opencode_command);
 
         let mut child = command.group_spawn()?;
 
@@ -113,9 +121,9 @@ impl StandardCodingAgentExecutor for Opencode {
             .stdout(Stdio::piped()) // Keep stdout but we won't use it
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.spawn_env())
             .arg(shell_arg)
-            .arg(&opencode_command)
-            .env("NODE_NO_WARNINGS", "1");
+            .arg(&opencode_command);
 
         let mut child = command.group_spawn()?;
 
@@ -25,7 +25,7 @@ pub struct QwenCode {
 }
 
 impl QwenCode {
-    fn build_command_builder(&self) -> CommandBuilder {
+    pub(crate) fn build_command_builder(&self) -> CommandBuilder {
         let mut builder = CommandBuilder::new("npx -y @qwen-code/qwen-code@latest");
 
         if self.yolo.unwrap_or(false) {
@@ -55,6 +55,7 @@ impl StandardCodingAgentExecutor for QwenCode {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.cmd.env_vars())
             .arg(shell_arg)
             .arg(&qwen_command);
 
@@ -89,6 +90,7 @@ impl StandardCodingAgentExecutor for QwenCode {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
+            .envs(self.cmd.env_vars())
             .arg(shell_arg)
             .arg(&qwen_command);
 
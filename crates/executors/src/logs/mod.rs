@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 pub mod plain_text_processor;
+pub mod provider_error;
 pub mod stderr_processor;
+pub mod usage;
 pub mod utils;
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -0,0 +1,106 @@
+use serde_json::Value;
+
+/// An API error a coding agent reported in its JSON output. Fields are `None`
+/// when the error doesn't carry them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProviderError {
+    /// The provider's error type, e.g. `overloaded_error` or `rate_limit_error`
+    pub error_type: Option<String>,
+    /// The HTTP status of the failed request
+    pub status: Option<u16>,
+}
+
+impl ProviderError {
+    /// Scan raw agent output for structured API errors.
+    ///
+    /// Errors come as `{"type":"error","error":{"type":...}}` objects (the provider's
+    /// own body) or objects with a `status`/`status_code` of 400 or above, either as a
+    /// whole line or embedded in a JSON string, like Claude's `"API Error: 529 {...}"`
+    /// result text.
+    pub fn find_all(output: &str) -> Vec<Self> {
+        let mut errors = Vec::new();
+        for line in output.lines() {
+            if let Some(value) = parse_embedded_json(line) {
+                collect(&value, &mut errors);
+            }
+        }
+        errors
+    }
+}
+
+/// The first JSON object in `text`, if any
+fn parse_embedded_json(text: &str) -> Option<Value> {
+    let start = text.find('{')?;
+    serde_json::Deserializer::from_str(&text[start..])
+        .into_iter::<Value>()
+        .next()?
+        .ok()
+}
+
+fn collect(value: &Value, errors: &mut Vec<ProviderError>) {
+    match value {
+        Value::Object(map) => {
+            let error_type = (map.get("type").and_then(Value::as_str) == Some("error"))
+                .then(|| map.get("error")?.get("type")?.as_str())
+                .flatten()
+                .map(str::to_string);
+            let status = ["status", "status_code", "statusCode"]
+                .iter()
+                .find_map(|key| map.get(*key)?.as_u64())
+                .and_then(|status| u16::try_from(status).ok())
+                .filter(|status| *status >= 400);
+            if error_type.is_some() || status.is_some() {
+                errors.push(ProviderError { error_type, status });
+            }
+            map.values().for_each(|child| collect(child, errors));
+        }
+        Value::Array(items) => items.iter().for_each(|child| collect(child, errors)),
+        Value::String(text) if text.contains('{') => {
+            if let Some(embedded) = parse_embedded_json(text) {
+                collect(&embedded, errors);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_provider_errors_in_lines_and_embedded_text() {
+        let output = concat!(
+            r#"{"type":"system","subtype":"init"}"#,
+            "\n",
+            r#"{"type":"result","is_error":true,"result":"API Error: 529 {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}"}"#,
+            "\n",
+            r#"Request failed: {"status":429,"message":"Too Many Requests"}"#,
+            "\n",
+            "Error: 503 mentioned in plain text\n",
+        );
+        assert_eq!(
+            ProviderError::find_all(output),
+            [
+                ProviderError {
+                    error_type: Some("overloaded_error".to_string()),
+                    status: None,
+                },
+                ProviderError {
+                    error_type: None,
+                    status: Some(429),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn successful_statuses_and_plain_errors_are_ignored() {
+        let output = concat!(
+            r#"{"status":200,"type":"error"}"#,
+            "\n",
+            r#"{"type":"assistant","message":{"content":"fix the 503 handler"}}"#,
+        );
+        assert!(ProviderError::find_all(output).is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::executors::{
+    claude::ClaudeJson,
+    codex::{CodexJson, CodexMsgContent},
+};
+
+/// Token counts and cost reported by a coding agent. Fields are `None` when
+/// the agent doesn't report them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TokenUsage {
+    pub input_tokens: Option<i64>,
+    pub output_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+}
+
+impl TokenUsage {
+    /// Scan raw agent stdout (JSON lines) for usage reports.
+    ///
+    /// Claude reports cumulative totals on its final `result` line, so the last
+    /// one wins. Codex emits a `token_count` event per turn, which are summed.
+    pub fn from_stdout(stdout: &str) -> Option<Self> {
+        let mut usage: Option<TokenUsage> = None;
+
+        for line in stdout.lines().map(str::trim).filter(|l| l.starts_with('{')) {
+            if let Ok(ClaudeJson::Result {
+                usage: Some(claude_usage),
+                total_cost_usd,
+                ..
+            }) = serde_json::from_str::<ClaudeJson>(line)
+            {
+                let input = [
+                    claude_usage.input_tokens,
+                    claude_usage.cache_creation_input_tokens,
+                    claude_usage.cache_read_input_tokens,
+                ]
+                .into_iter()
+                .flatten()
+                .reduce(|a, b| a + b);
+
+                usage = Some(TokenUsage {
+                    input_tokens: input.map(|t| t as i64),
+                    output_tokens: claude_usage.output_tokens.map(|t| t as i64),
+                    cost_usd: total_cost_usd,
+                });
+            } else if let Ok(CodexJson::StructuredMessage {
+                msg:
+                    CodexMsgContent::TokenCount {
+                        input_tokens,
+                        output_tokens,
+                        ..
+                    },
+                ..
+            }) = serde_json::from_str::<CodexJson>(line)
+            {
+                let total = usage.get_or_insert_with(TokenUsage::default);
+                total.input_tokens = add_tokens(total.input_tokens, input_tokens);
+                total.output_tokens = add_tokens(total.output_tokens, output_tokens);
+            }
+        }
+
+        usage
+    }
+}
+
+fn add_tokens(total: Option<i64>, delta: Option<u64>) -> Option<i64> {
+    match (total, delta) {
+        (Some(total), Some(delta)) => Some(total + delta as i64),
+        (None, Some(delta)) => Some(delta as i64),
+        (total, None) => total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_claude_result_usage() {
+        let stdout = r#"{"type":"system","subtype":"init","session_id":"abc","model":"claude-sonnet-4"}
+{"type":"result","subtype":"success","is_error":false,"duration_ms":6059,"result":"Done","total_cost_usd":0.0421,"usage":{"input_tokens":12,"cache_creation_input_tokens":1000,"cache_read_input_tokens":2000,"output_tokens":345}}"#;
+
+        assert_eq!(
+            TokenUsage::from_stdout(stdout),
+            Some(TokenUsage {
+                input_tokens: Some(3012),
+                output_tokens: Some(345),
+                cost_usd: Some(0.0421),
+            })
+        );
+    }
+
+    #[test]
+    fn sums_codex_token_counts() {
+        let stdout = r#"{"id":"1","msg":{"type":"token_count","input_tokens":1674,"cached_input_tokens":1627,"output_tokens":384,"reasoning_output_tokens":384,"total_tokens":2058}}
+{"id":"2","msg":{"type":"token_count","input_tokens":326,"cached_input_tokens":0,"output_tokens":16,"reasoning_output_tokens":0,"total_tokens":342}}"#;
+
+        assert_eq!(
+            TokenUsage::from_stdout(stdout),
+            Some(TokenUsage {
+                input_tokens: Some(2000),
+                output_tokens: Some(400),
+                cost_usd: None,
+            })
+        );
+    }
+
+    #[test]
+    fn no_usage_reported() {
+        let stdout = r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":6059,"result":"Done"}
+plain text output"#;
+
+        assert_eq!(TokenUsage::from_stdout(stdout), None);
+    }
+}
@@ -1,4 +1,4 @@
-use json_patch::Patch;
+use json_patch::{Patch, PatchOperation as JsonPatchOperation};
 use serde::{Deserialize, Serialize};
 use serde_json::{from_value, json};
 use ts_rs::TS;
@@ -15,7 +15,7 @@ enum PatchOperation {
 }
 
 #[allow(clippy::large_enum_variant)]
-#[derive(Serialize, TS)]
+#[derive(Serialize, Deserialize, TS)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "type", content = "content")]
 pub enum PatchType {
     NormalizedEntry(NormalizedEntry),
@@ -113,4 +113,62 @@ impl ConversationPatch {
 
         from_value(json!([patch_entry])).unwrap()
     }
+
+    /// The conversation entry carried by a patch from `add_normalized_entry` or `replace`,
+    /// or `None` for any other patch
+    pub fn extract_normalized_entry(patch: &Patch) -> Option<NormalizedEntry> {
+        patch.0.iter().find_map(|operation| {
+            let value = match operation {
+                JsonPatchOperation::Add(op) => &op.value,
+                JsonPatchOperation::Replace(op) => &op.value,
+                _ => return None,
+            };
+            match PatchType::deserialize(value) {
+                Ok(PatchType::NormalizedEntry(entry)) => Some(entry),
+                _ => None,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::NormalizedEntryType;
+
+    fn assistant_message(content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn extracts_entries_from_add_and_replace_patches() {
+        let added = ConversationPatch::add_normalized_entry(3, assistant_message("first"));
+        let entry = ConversationPatch::extract_normalized_entry(&added).unwrap();
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(entry.content, "first");
+
+        let replaced = ConversationPatch::replace(3, assistant_message("second"));
+        let entry = ConversationPatch::extract_normalized_entry(&replaced).unwrap();
+        assert_eq!(entry.content, "second");
+    }
+
+    #[test]
+    fn other_patches_carry_no_entry() {
+        let patches = [
+            ConversationPatch::add_stdout(0, "out".to_string()),
+            ConversationPatch::add_stderr(1, "err".to_string()),
+            ConversationPatch::remove_diff("src~1main.rs".to_string()),
+        ];
+        for patch in &patches {
+            assert!(ConversationPatch::extract_normalized_entry(patch).is_none());
+        }
+    }
 }
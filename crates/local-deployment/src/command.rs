@@ -1,13 +1,19 @@
 use command_group::AsyncGroupChild;
+use executors::actions::browser_chat_request::BrowserChatRequest;
 #[cfg(unix)]
 use nix::{
     sys::signal::{Signal, killpg},
     unistd::{Pid, getpgid},
 };
 use services::services::container::ContainerError;
-use tokio::time::Duration;
+use tokio::time::{Duration, Instant};
 
-pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), ContainerError> {
+/// Stop the whole process group, escalating SIGINT -> SIGTERM -> SIGKILL. Each
+/// signal gets up to `grace` to take effect before moving on to the next one.
+pub async fn kill_process_group(
+    child: &mut AsyncGroupChild,
+    grace: Duration,
+) -> Result<(), ContainerError> {
     // hit the whole process group, not just the leader
     #[cfg(unix)]
     {
@@ -16,6 +22,13 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
                 .map_err(|e| ContainerError::KillFailed(std::io::Error::other(e)))?;
 
             for sig in [Signal::SIGINT, Signal::SIGTERM, Signal::SIGKILL] {
+                if sig == Signal::SIGKILL {
+                    tracing::warn!(
+                        "Process group {} did not exit within {:?} of SIGTERM, sending SIGKILL",
+                        pgid,
+                        grace
+                    );
+                }
                 if let Err(e) = killpg(pgid, sig) {
                     tracing::warn!(
                         "Failed to send signal {:?} to process group {}: {}",
@@ -24,13 +37,7 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
                         e
                     );
                 }
-                tokio::time::sleep(Duration::from_secs(2)).await;
-                if child
-                    .inner()
-                    .try_wait()
-                    .map_err(ContainerError::Io)?
-                    .is_some()
-                {
+                if wait_for_exit(child, grace).await? {
                     break;
                 }
             }
@@ -41,3 +48,125 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
     let _ = child.wait().await;
     Ok(())
 }
+
+/// Stop a browser chat automation. It is first asked over its control channel to close
+/// the browser itself, so the session profile is saved and no window is left behind; the
+/// process group is only signalled if it is still running after `grace`.
+pub async fn stop_browser_chat(
+    child: &mut AsyncGroupChild,
+    grace: Duration,
+) -> Result<(), ContainerError> {
+    if BrowserChatRequest::request_stop(child).await && wait_for_exit(child, grace).await? {
+        let _ = child.wait().await;
+        return Ok(());
+    }
+    kill_process_group(child, grace).await
+}
+
+/// Poll until the child exits or `grace` elapses. Returns whether it exited.
+async fn wait_for_exit(
+    child: &mut AsyncGroupChild,
+    grace: Duration,
+) -> Result<bool, ContainerError> {
+    let deadline = Instant::now() + grace;
+    loop {
+        if child
+            .inner()
+            .try_wait()
+            .map_err(ContainerError::Io)?
+            .is_some()
+        {
+            return Ok(true);
+        }
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Kill the process group if it has been running longer than `timeout`.
+/// Returns whether the child was killed.
+pub async fn kill_if_timed_out(
+    child: &mut AsyncGroupChild,
+    started: Instant,
+    timeout: Option<Duration>,
+    grace: Duration,
+) -> Result<bool, ContainerError> {
+    match timeout {
+        Some(timeout) if started.elapsed() > timeout => {
+            kill_process_group(child, grace).await?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use command_group::AsyncCommandGroup;
+    use tokio::process::Command;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn over_time_child_is_killed() {
+        let mut child = Command::new("sleep").arg("30").group_spawn().unwrap();
+        let started = Instant::now();
+
+        let grace = Duration::from_secs(2);
+        let killed = kill_if_timed_out(&mut child, started, Some(Duration::from_secs(60)), grace)
+            .await
+            .unwrap();
+        assert!(!killed);
+        assert!(child.inner().try_wait().unwrap().is_none());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let killed = kill_if_timed_out(&mut child, started, Some(Duration::from_millis(10)), grace)
+            .await
+            .unwrap();
+        assert!(killed);
+        let status = child.inner().try_wait().unwrap().expect("child has exited");
+        assert!(!status.success());
+    }
+
+    #[tokio::test]
+    async fn stubborn_child_is_force_killed() {
+        // Ignores SIGINT and SIGTERM, so only SIGKILL stops it
+        let mut child = Command::new("sh")
+            .args(["-c", "trap '' INT TERM; sleep 30"])
+            .group_spawn()
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let started = Instant::now();
+        kill_process_group(&mut child, Duration::from_millis(200))
+            .await
+            .unwrap();
+
+        assert!(child.inner().try_wait().unwrap().is_some());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn browser_chat_exits_on_stop_request_before_any_signal() {
+        // Ignores signals, so it can only exit by honouring the stop line
+        let mut child = Command::new("sh")
+            .args([
+                "-c",
+                "trap '' INT TERM; read line; [ \"$line\" = stop ] || sleep 30",
+            ])
+            .stdin(std::process::Stdio::piped())
+            .group_spawn()
+            .unwrap();
+
+        let started = Instant::now();
+        stop_browser_chat(&mut child, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let status = child.inner().try_wait().unwrap().expect("child has exited");
+        assert!(status.success());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -2,12 +2,12 @@ use std::{
     collections::{HashMap, HashSet},
     io,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Weak},
     time::Duration,
 };
 
 use anyhow::anyhow;
-use async_stream::try_stream;
+use async_stream::stream;
 use async_trait::async_trait;
 use axum::response::sse::Event;
 use command_group::AsyncGroupChild;
@@ -15,47 +15,134 @@ use db::{
     DBService,
     models::{
         execution_process::{
-            ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+            ExecutionContext, ExecutionProcess, ExecutionProcessKillReason,
+            ExecutionProcessRunReason, ExecutionProcessStatus,
         },
+        execution_process_logs::ExecutionProcessLogs,
         executor_session::ExecutorSession,
         merge::Merge,
         project::Project,
         task::{Task, TaskStatus},
-        task_attempt::TaskAttempt,
+        task_attempt::{TaskAttempt, TaskAttemptError},
     },
 };
 use deployment::DeploymentError;
 use executors::{
-    actions::{Executable, ExecutorAction},
+    actions::{
+        Executable, ExecutorAction, ExecutorActionType,
+        coding_agent_initial::CodingAgentInitialRequest,
+    },
+    command::mask_env,
     logs::{
-        NormalizedEntry, NormalizedEntryType,
-        utils::{ConversationPatch, patch::escape_json_pointer_segment},
+        NormalizedEntryType,
+        provider_error::ProviderError,
+        usage::TokenUsage,
+        utils::{
+            ConversationPatch,
+            patch::{PatchType, escape_json_pointer_segment},
+        },
     },
+    profile::ExecutorProfileId,
 };
-use futures::{StreamExt, TryStreamExt, stream::select};
-use notify_debouncer_full::DebouncedEvent;
+use futures::{Stream, StreamExt, TryStreamExt, stream::select};
+use notify_debouncer_full::{DebounceEventResult, DebouncedEvent};
 use serde_json::json;
 use services::services::{
     analytics::AnalyticsContext,
-    config::Config,
-    container::{ContainerError, ContainerRef, ContainerService},
+    browser_chat_service::NodeBrowserChatService,
+    config::{Config, DirtyWorktreePolicy},
+    container::{
+        AttemptComparison, ContainerError, ContainerRef, ContainerService, DiffStatsCache,
+    },
+    execution_limiter::{ExecutionLimiter, ExecutionPermit},
     filesystem_watcher,
-    git::{DiffTarget, GitService},
+    git::{CommitMetadata, DiffTarget, GitService, GitServiceError},
     image::ImageService,
     notification::NotificationService,
+    rate_limiter::ExecutorRateLimiter,
     worktree_manager::WorktreeManager,
 };
-use tokio::{sync::RwLock, task::JoinHandle};
-use tokio_util::io::ReaderStream;
+use tokio::{
+    sync::{RwLock, watch},
+    task::JoinHandle,
+    time::Instant,
+};
+use tokio_util::{io::ReaderStream, sync::CancellationToken};
 use utils::{
+    diff::Diff,
     log_msg::LogMsg,
     msg_store::MsgStore,
-    text::{git_branch_id, short_uuid},
+    path::{get_vibe_kanban_temp_dir, normalize_path_separators},
+    text::{short_uuid, truncate_to_char_boundary},
 };
 use uuid::Uuid;
 
 use crate::command;
 
+/// First interval between exit checks of a running process; doubles up to
+/// `EXIT_POLL_MAX_INTERVAL` so quick processes are noticed promptly without long runs
+/// waking the monitor constantly
+const EXIT_POLL_INITIAL_INTERVAL: Duration = Duration::from_millis(10);
+const EXIT_POLL_MAX_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How many worktree paths are stat'ed at once when looking for externally deleted ones
+const WORKTREE_EXISTS_CONCURRENCY: usize = 16;
+
+/// Backoff between retries of a live diff update that hit a held git lock or a worktree
+/// changing under it
+const DIFF_RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_millis(50),
+    Duration::from_millis(200),
+    Duration::from_millis(500),
+];
+
+/// When to re-check a finished execution's MsgStore that is still referenced elsewhere
+const MSG_STORE_RELEASE_CHECKS: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(30),
+];
+
+fn next_exit_poll_interval(interval: Duration) -> Duration {
+    (interval * 2).min(EXIT_POLL_MAX_INTERVAL)
+}
+
+type DiffRefreshers = Arc<RwLock<HashMap<Uuid, watch::Sender<()>>>>;
+
+/// Held by a live diff stream; removes the attempt's refresher once its last stream is
+/// dropped
+struct DiffRefreshSubscription {
+    task_attempt_id: Uuid,
+    refreshers: DiffRefreshers,
+}
+
+impl Drop for DiffRefreshSubscription {
+    fn drop(&mut self) {
+        let task_attempt_id = self.task_attempt_id;
+        let refreshers = self.refreshers.clone();
+        // Runs after the stream's receiver is gone, and leaves the refresher to any stream
+        // that subscribed in the meantime
+        tokio::spawn(async move {
+            let mut refreshers = refreshers.write().await;
+            if refreshers
+                .get(&task_attempt_id)
+                .is_some_and(|refresher| refresher.receiver_count() == 0)
+            {
+                refreshers.remove(&task_attempt_id);
+            }
+        });
+    }
+}
+
+/// What a live diff stream compares: the attempt's worktree against its base branch,
+/// limited to `subpath` when set
+struct LiveDiffTarget {
+    worktree_path: PathBuf,
+    task_branch: String,
+    base_branch: String,
+    subpath: Option<String>,
+}
+
 /// Browser session metadata for tracking persistent browser processes
 #[derive(Debug, Clone)]
 pub struct BrowserSession {
@@ -66,16 +153,66 @@ pub struct BrowserSession {
     pub created_at: std::time::Instant,
 }
 
+/// Keeps a queued coding agent start in `queued_starts` while it waits. If the start is
+/// abandoned (its future dropped) before it gets a slot, the process is recorded as
+/// failed instead of staying queued forever.
+struct QueuedStartGuard {
+    container: LocalContainerService,
+    execution_process_id: Uuid,
+    armed: bool,
+}
+
+impl Drop for QueuedStartGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let container = self.container.clone();
+        let id = self.execution_process_id;
+        tokio::spawn(async move {
+            if container.queued_starts.write().await.remove(&id).is_none() {
+                return;
+            }
+            tracing::warn!("Queued start of execution process {} was abandoned", id);
+            if let Err(e) = ExecutionProcess::update_completion(
+                &container.db.pool,
+                id,
+                ExecutionProcessStatus::Failed,
+                None,
+                None,
+            )
+            .await
+            {
+                tracing::error!("Failed to mark abandoned execution process {}: {}", id, e);
+            }
+        });
+    }
+}
+
 #[derive(Clone)]
 pub struct LocalContainerService {
     db: DBService,
     child_store: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AsyncGroupChild>>>>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+    log_forwarders: Arc<RwLock<HashMap<Uuid, JoinHandle<()>>>>,
     browser_sessions: Arc<RwLock<HashMap<String, BrowserSession>>>, // session_id -> BrowserSession
     config: Arc<RwLock<Config>>,
     git: GitService,
     image_service: ImageService,
     analytics: Option<AnalyticsContext>,
+    rate_limiter: Arc<ExecutorRateLimiter>,
+    /// Refresh triggers shared by an attempt's live diff streams, see `refresh_diff`
+    diff_refreshers: DiffRefreshers,
+    diff_stats_cache: Arc<DiffStatsCache>,
+    execution_limiter: Arc<ExecutionLimiter>,
+    /// Concurrency slots held by running coding agents, released when they exit
+    execution_permits: Arc<RwLock<HashMap<Uuid, ExecutionPermit>>>,
+    /// Coding agent starts waiting for a concurrency slot, by execution process. Removing
+    /// an entry and cancelling its token cancels the start.
+    queued_starts: Arc<RwLock<HashMap<Uuid, CancellationToken>>>,
+    /// Coding agents waiting to be re-run after a retryable failure, by the failed
+    /// execution process, until the re-run has started
+    agent_retries: Arc<RwLock<HashMap<Uuid, CancellationToken>>>,
 }
 
 impl LocalContainerService {
@@ -94,11 +231,19 @@ impl LocalContainerService {
             db,
             child_store,
             msg_stores,
+            log_forwarders: Arc::new(RwLock::new(HashMap::new())),
             browser_sessions,
             config,
             git,
             image_service,
             analytics,
+            rate_limiter: Arc::new(ExecutorRateLimiter::new()),
+            diff_refreshers: Arc::new(RwLock::new(HashMap::new())),
+            diff_stats_cache: Arc::new(DiffStatsCache::default()),
+            execution_limiter: Arc::new(ExecutionLimiter::new()),
+            execution_permits: Arc::new(RwLock::new(HashMap::new())),
+            queued_starts: Arc::new(RwLock::new(HashMap::new())),
+            agent_retries: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -115,6 +260,7 @@ impl LocalContainerService {
     pub async fn remove_child_from_store(&self, id: &Uuid) {
         let mut map = self.child_store.write().await;
         map.remove(id);
+        self.execution_permits.write().await.remove(id);
     }
 
     /// Add a browser session for tracking
@@ -146,16 +292,306 @@ impl LocalContainerService {
     /// A context is finalized when
     /// - The next action is None (no follow-up actions)
     /// - The run reason is not DevServer
+    /// - It isn't a standalone setup script re-run
     fn should_finalize(ctx: &ExecutionContext) -> bool {
-        ctx.execution_process
-            .executor_action()
-            .unwrap()
-            .next_action
-            .is_none()
+        let executor_action = ctx.execution_process.executor_action().unwrap();
+        executor_action.next_action.is_none()
             && (!matches!(
                 ctx.execution_process.run_reason,
                 ExecutionProcessRunReason::DevServer
             ))
+            && !Self::is_standalone_setup(executor_action, &ctx.execution_process.run_reason)
+    }
+
+    /// Configured time limit for an execution, if any. Browser chats are limited per agent.
+    fn execution_timeout(config: &Config, process: &ExecutionProcess) -> Option<Duration> {
+        let secs = match process.run_reason {
+            ExecutionProcessRunReason::SetupScript => config.setup_script_timeout_secs,
+            ExecutionProcessRunReason::CleanupScript => config.cleanup_script_timeout_secs,
+            ExecutionProcessRunReason::CodingAgent => config.coding_agent_timeout_secs,
+            ExecutionProcessRunReason::BrowserChat => {
+                return match process.executor_action().map(|action| action.typ()) {
+                    Ok(ExecutorActionType::BrowserChatRequest(request)) => {
+                        config.browser_chat_timeouts.timeout(&request.agent_type)
+                    }
+                    _ => None,
+                };
+            }
+            _ => 0,
+        };
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+
+    /// Delay before re-running a coding agent that failed with a retryable provider error,
+    /// judged by its exit code and the errors in its output, or None if it should stay
+    /// failed
+    async fn agent_retry_delay(&self, ctx: &ExecutionContext) -> Option<Duration> {
+        let process = &ctx.execution_process;
+        if process.run_reason != ExecutionProcessRunReason::CodingAgent
+            || process.status != ExecutionProcessStatus::Failed
+        {
+            return None;
+        }
+        let errors: Vec<ProviderError> = self
+            .agent_output(process.id)
+            .await
+            .iter()
+            .flat_map(|output| ProviderError::find_all(output))
+            .collect();
+        self.config.read().await.agent_retry.retry_delay(
+            process.exit_code,
+            &errors,
+            process.retry_count,
+        )
+    }
+
+    /// The stdout and stderr of finished process `exec_id` still held in memory
+    async fn agent_output(&self, exec_id: Uuid) -> Vec<String> {
+        // The process may exit before its last output has been forwarded
+        if let Some(forwarder) = self.log_forwarders.write().await.remove(&exec_id) {
+            let _ = tokio::time::timeout(Duration::from_secs(1), forwarder).await;
+        }
+        let history = match self.msg_stores.read().await.get(&exec_id) {
+            Some(store) => store.get_history(),
+            None => Vec::new(),
+        };
+        history
+            .into_iter()
+            .filter_map(|msg| match msg {
+                LogMsg::Stdout(output) | LogMsg::Stderr(output) => Some(output),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The initial request of a coding agent that failed because the session it was told
+    /// to resume doesn't exist, with the resume dropped so it can re-run in a fresh session.
+    /// None if the process failed any other way.
+    async fn fresh_session_rerun(&self, ctx: &ExecutionContext) -> Option<ExecutorAction> {
+        let process = &ctx.execution_process;
+        if process.run_reason != ExecutionProcessRunReason::CodingAgent
+            || process.status != ExecutionProcessStatus::Failed
+        {
+            return None;
+        }
+        let mut executor_action = process.executor_action().ok()?.clone();
+        let ExecutorActionType::CodingAgentInitialRequest(request) = &mut executor_action.typ
+        else {
+            return None;
+        };
+        request.resume_session_id.as_ref()?;
+        let output = self.agent_output(process.id).await;
+        if !output
+            .iter()
+            .any(|output| CodingAgentInitialRequest::session_not_found(output))
+        {
+            return None;
+        }
+        request.resume_session_id = None;
+        Some(executor_action)
+    }
+
+    /// Watch a finished execution's MsgStore that other holders still reference, logging
+    /// who holds it (when tracked) if it hasn't been released after the last check.
+    /// Resolves to whether it was released.
+    fn spawn_msg_store_release_check(
+        exec_id: Uuid,
+        store: Weak<MsgStore>,
+        checks: &'static [Duration],
+    ) -> JoinHandle<bool> {
+        tracing::debug!(
+            "MsgStore for {} still has {} holders after finishing, waiting for release",
+            exec_id,
+            store.strong_count()
+        );
+        tokio::spawn(async move {
+            for delay in checks {
+                tokio::time::sleep(*delay).await;
+                if store.strong_count() == 0 {
+                    return true;
+                }
+            }
+            let Some(store) = store.upgrade() else {
+                return true;
+            };
+            // Less our own upgraded handle
+            let holders = Arc::strong_count(&store) - 1;
+            match store.handout_summary() {
+                Some(summary) if summary.is_empty() => tracing::error!(
+                    "There are still {} strong Arcs to MsgStore for {}, none of them through tracked handles",
+                    holders,
+                    exec_id
+                ),
+                Some(summary) => tracing::error!(
+                    "There are still {} strong Arcs to MsgStore for {}, handed out by: {}",
+                    holders,
+                    exec_id,
+                    summary
+                ),
+                None => tracing::error!(
+                    "There are still {} strong Arcs to MsgStore for {} (enable track_msg_store_holders to see who holds them)",
+                    holders,
+                    exec_id
+                ),
+            }
+            false
+        })
+    }
+
+    /// Re-run the action of failed process `exec_id` after `delay`, continuing the agent's
+    /// session if it reported one. The task is finalized if the re-run can't start. The
+    /// wait is abandoned if the retry is cancelled through `agent_retries`.
+    async fn spawn_agent_retry(&self, exec_id: Uuid, delay: Duration) {
+        let container = self.clone();
+        let cancel = CancellationToken::new();
+        self.agent_retries
+            .write()
+            .await
+            .insert(exec_id, cancel.clone());
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(delay) => {}
+            }
+            if let Ok(ctx) = ExecutionProcess::load_context(&container.db.pool, exec_id).await
+                && let Err(e) = container.retry_agent(&ctx).await
+            {
+                tracing::error!(
+                    "Failed to re-run coding agent for task attempt {}: {}",
+                    ctx.task_attempt.id,
+                    e
+                );
+                Self::finalize_task(&container.db, &container.config, &ctx).await;
+            }
+            container.agent_retries.write().await.remove(&exec_id);
+        });
+    }
+
+    async fn retry_agent(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
+        let process = &ctx.execution_process;
+        let mut executor_action = process
+            .executor_action()
+            .map_err(ContainerError::Other)?
+            .clone();
+        let session_id = ExecutorSession::find_by_execution_process_id(&self.db.pool, process.id)
+            .await?
+            .and_then(|session| session.session_id);
+        if let Some(session_id) = session_id {
+            match &mut executor_action.typ {
+                ExecutorActionType::CodingAgentInitialRequest(request) => {
+                    request.resume_session_id = Some(session_id)
+                }
+                ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                    request.session_id = session_id
+                }
+                _ => {}
+            }
+        }
+        self.start_execution_retry(
+            &ctx.task_attempt,
+            &executor_action,
+            &process.run_reason,
+            process.retry_count + 1,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Wait out `profile`'s rate limit, then take a concurrency slot for a coding agent.
+    /// While either has to wait, the process is shown as queued; the rate limit comes
+    /// first so a held slot never idles on it. Returns None if the process was stopped
+    /// while queued.
+    async fn acquire_execution_permit(
+        &self,
+        task_attempt: &TaskAttempt,
+        execution_process: &ExecutionProcess,
+        profile: &ExecutorProfileId,
+    ) -> Result<Option<ExecutionPermit>, ContainerError> {
+        let project = task_attempt
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent task not found")))?
+            .parent_project(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent project not found")))?;
+        let (global_limit, rate_limit) = {
+            let config = self.config.read().await;
+            (
+                config.max_concurrent_executions.map(|limit| limit as usize),
+                config.executor_rate_limit(profile),
+            )
+        };
+        let project_limit = project
+            .max_concurrent_executions
+            .map(|limit| limit.max(0) as usize);
+        let rate_limited = match &rate_limit {
+            Some((key, requests_per_minute)) => {
+                !self
+                    .rate_limiter
+                    .try_acquire(key, *requests_per_minute)
+                    .await
+            }
+            None => false,
+        };
+        if !rate_limited
+            && let Some(permit) =
+                self.execution_limiter
+                    .try_acquire(project.id, global_limit, project_limit)
+        {
+            return Ok(Some(permit));
+        }
+
+        let cancel = CancellationToken::new();
+        self.queued_starts
+            .write()
+            .await
+            .insert(execution_process.id, cancel.clone());
+        let mut guard = QueuedStartGuard {
+            container: self.clone(),
+            execution_process_id: execution_process.id,
+            armed: true,
+        };
+        ExecutionProcess::update_completion(
+            &self.db.pool,
+            execution_process.id,
+            ExecutionProcessStatus::Queued,
+            None,
+            None,
+        )
+        .await?;
+        let turn = async {
+            if rate_limited && let Some((key, requests_per_minute)) = &rate_limit {
+                self.rate_limiter.acquire(key, *requests_per_minute).await;
+            }
+            self.execution_limiter
+                .acquire(project.id, global_limit, project_limit)
+                .await
+        };
+        let permit = tokio::select! {
+            permit = turn => Some(permit),
+            _ = cancel.cancelled() => None,
+        };
+        guard.armed = false;
+        // Whoever takes the entry out decides: a stop that got there first wins over a
+        // slot that freed up at the same time
+        if self
+            .queued_starts
+            .write()
+            .await
+            .remove(&execution_process.id)
+            .is_none()
+        {
+            return Ok(None);
+        }
+        ExecutionProcess::update_completion(
+            &self.db.pool,
+            execution_process.id,
+            ExecutionProcessStatus::Running,
+            None,
+            None,
+        )
+        .await?;
+        Ok(permit)
     }
 
     /// Finalize task execution by updating status to InReview and sending notifications
@@ -174,23 +610,41 @@ impl LocalContainerService {
             "Checking {} active worktrees for external deletion...",
             active_attempts.len()
         );
-        for (attempt_id, worktree_path) in active_attempts {
-            // Check if worktree directory exists
-            if !std::path::Path::new(&worktree_path).exists() {
-                // Worktree was deleted externally, mark as deleted in database
-                if let Err(e) = TaskAttempt::mark_worktree_deleted(&db.pool, attempt_id).await {
-                    tracing::error!(
-                        "Failed to mark externally deleted worktree as deleted for attempt {}: {}",
-                        attempt_id,
-                        e
-                    );
-                } else {
-                    tracing::info!(
-                        "Marked externally deleted worktree as deleted for attempt {} (path: {})",
-                        attempt_id,
-                        worktree_path
-                    );
-                }
+        // Stat the paths off the runtime, a bounded number at a time
+        let deleted: Vec<(Uuid, String)> = futures::stream::iter(active_attempts)
+            .map(|(attempt_id, worktree_path)| async move {
+                let path = worktree_path.clone();
+                let exists = tokio::task::spawn_blocking(move || Path::new(&path).exists())
+                    .await
+                    // Treat a failed check as present; the next sweep will retry it
+                    .unwrap_or(true);
+                (!exists).then_some((attempt_id, worktree_path))
+            })
+            .buffer_unordered(WORKTREE_EXISTS_CONCURRENCY)
+            .filter_map(std::future::ready)
+            .collect()
+            .await;
+        if deleted.is_empty() {
+            return Ok(());
+        }
+
+        // Worktree was deleted externally, mark as deleted in database
+        let attempt_ids: Vec<Uuid> = deleted.iter().map(|(attempt_id, _)| *attempt_id).collect();
+        if let Err(e) = TaskAttempt::mark_worktrees_deleted(&db.pool, &attempt_ids).await {
+            for (attempt_id, _) in &deleted {
+                tracing::error!(
+                    "Failed to mark externally deleted worktree as deleted for attempt {}: {}",
+                    attempt_id,
+                    e
+                );
+            }
+        } else {
+            for (attempt_id, worktree_path) in &deleted {
+                tracing::info!(
+                    "Marked externally deleted worktree as deleted for attempt {} (path: {})",
+                    attempt_id,
+                    worktree_path
+                );
             }
         }
         Ok(())
@@ -205,7 +659,12 @@ impl LocalContainerService {
             );
             return;
         }
-        let worktree_base_dir = WorktreeManager::get_worktree_base_dir();
+        self.cleanup_orphaned_worktrees_in(&WorktreeManager::get_worktree_base_dir())
+            .await;
+    }
+
+    /// Delete every directory under `worktree_base_dir` that no attempt refers to
+    async fn cleanup_orphaned_worktrees_in(&self, worktree_base_dir: &Path) {
         if !worktree_base_dir.exists() {
             tracing::debug!(
                 "Worktree base directory {} does not exist, skipping orphan cleanup",
@@ -213,7 +672,7 @@ impl LocalContainerService {
             );
             return;
         }
-        let entries = match std::fs::read_dir(&worktree_base_dir) {
+        let entries = match std::fs::read_dir(worktree_base_dir) {
             Ok(entries) => entries,
             Err(e) => {
                 tracing::error!(
@@ -224,6 +683,14 @@ impl LocalContainerService {
                 return;
             }
         };
+        // One query up front rather than one per directory
+        let known_refs = match TaskAttempt::all_container_refs(&self.db().pool).await {
+            Ok(refs) => refs,
+            Err(e) => {
+                tracing::error!("Failed to load container refs for orphan cleanup: {}", e);
+                return;
+            }
+        };
         for entry in entries {
             let entry = match entry {
                 Ok(entry) => entry,
@@ -239,9 +706,7 @@ impl LocalContainerService {
             }
 
             let worktree_path_str = path.to_string_lossy().to_string();
-            if let Ok(false) =
-                TaskAttempt::container_ref_exists(&self.db().pool, &worktree_path_str).await
-            {
+            if !known_refs.contains(&worktree_path_str) {
                 // This is an orphaned worktree - delete it
                 tracing::info!("Found orphaned worktree: {}", worktree_path_str);
                 if let Err(e) = WorktreeManager::cleanup_worktree(&path, None).await {
@@ -298,8 +763,115 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Evict the least recently active idle worktrees until the total worktree
+    /// disk usage is back under `max_bytes`. Attempts with running processes
+    /// are never evicted.
+    pub async fn cleanup_over_disk_limit(
+        db: &DBService,
+        max_bytes: u64,
+    ) -> Result<(), DeploymentError> {
+        if max_bytes == 0 {
+            return Ok(());
+        }
+        let usage = WorktreeManager::disk_usage().await?;
+        let total: u64 = usage.iter().map(|u| u.bytes).sum();
+        if total <= max_bytes {
+            return Ok(());
+        }
+        let usage_by_path: HashMap<String, u64> = usage
+            .into_iter()
+            .map(|u| (u.path.to_string_lossy().to_string(), u.bytes))
+            .collect();
+        let candidates = TaskAttempt::find_evictable_for_cleanup(&db.pool).await?;
+        let evictions = Self::select_evictions(candidates, &usage_by_path, total, max_bytes);
+        tracing::info!(
+            "Worktrees use {} bytes, over the {} byte limit; evicting {} worktrees",
+            total,
+            max_bytes,
+            evictions.len()
+        );
+
+        let mut remaining = total;
+        for (attempt_id, worktree_path, git_repo_path, bytes) in evictions {
+            match Self::cleanup_expired_attempt(
+                db,
+                attempt_id,
+                PathBuf::from(&worktree_path),
+                PathBuf::from(git_repo_path),
+            )
+            .await
+            {
+                Ok(()) => {
+                    remaining = remaining.saturating_sub(bytes);
+                    tracing::info!(
+                        "Evicted worktree {} of attempt {}, reclaimed {} bytes",
+                        worktree_path,
+                        attempt_id,
+                        bytes
+                    );
+                }
+                Err(e) => tracing::error!("Failed to evict worktree of attempt {attempt_id}: {e}"),
+            }
+        }
+        if remaining > max_bytes {
+            tracing::warn!(
+                "Worktrees still use {} bytes after eviction (limit {}); remaining attempts are running",
+                remaining,
+                max_bytes
+            );
+        }
+        Ok(())
+    }
+
+    /// Pick evictions from `candidates` (oldest first) until `total` would drop
+    /// to `max_bytes` or below. Returns the candidates with their size in bytes.
+    fn select_evictions(
+        candidates: Vec<(Uuid, String, String)>,
+        usage_by_path: &HashMap<String, u64>,
+        total: u64,
+        max_bytes: u64,
+    ) -> Vec<(Uuid, String, String, u64)> {
+        let mut remaining = total;
+        let mut evictions = Vec::new();
+        for (attempt_id, worktree_path, git_repo_path) in candidates {
+            if remaining <= max_bytes {
+                break;
+            }
+            // Worktrees already gone from disk free nothing
+            let Some(&bytes) = usage_by_path.get(&worktree_path) else {
+                continue;
+            };
+            remaining = remaining.saturating_sub(bytes);
+            evictions.push((attempt_id, worktree_path, git_repo_path, bytes));
+        }
+        evictions
+    }
+
+    /// Delete persisted logs of processes that finished more than `retention_days` ago
+    /// (0 = keep forever)
+    pub async fn cleanup_expired_logs(
+        db: &DBService,
+        retention_days: u32,
+    ) -> Result<(), DeploymentError> {
+        if retention_days == 0 {
+            return Ok(());
+        }
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(retention_days.into());
+        let deleted = ExecutionProcessLogs::delete_completed_before(&db.pool, cutoff).await?;
+        if deleted > 0 {
+            tracing::info!(
+                "Deleted logs of {} execution processes older than {} days",
+                deleted,
+                retention_days
+            );
+        }
+        Ok(())
+    }
+
     pub async fn spawn_worktree_cleanup(&self) {
         let db = self.db.clone();
+        let config = self.config.clone();
+        let image_service = self.image_service.clone();
         let mut cleanup_interval = tokio::time::interval(tokio::time::Duration::from_secs(1800)); // 30 minutes
         self.cleanup_orphaned_worktrees().await;
         tokio::spawn(async move {
@@ -316,6 +888,21 @@ impl LocalContainerService {
                     .unwrap_or_else(|e| {
                         tracing::error!("Failed to clean up expired worktree attempts: {}", e)
                     });
+                let max_disk_bytes = config.read().await.worktree_max_disk_bytes;
+                Self::cleanup_over_disk_limit(&db, max_disk_bytes)
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::error!("Failed to evict worktrees over the disk limit: {}", e)
+                    });
+                let log_retention_days = config.read().await.log_retention_days;
+                Self::cleanup_expired_logs(&db, log_retention_days)
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::error!("Failed to clean up expired execution logs: {}", e)
+                    });
+                if let Err(e) = image_service.cleanup_orphaned().await {
+                    tracing::error!("Failed to clean up orphaned images: {}", e);
+                }
             }
         });
     }
@@ -332,18 +919,62 @@ impl LocalContainerService {
         let analytics = self.analytics.clone();
 
         tokio::spawn(async move {
+            let started = Instant::now();
+            let grace = Duration::from_secs(config.read().await.stop_grace_period_secs);
+            let timeout = match ExecutionProcess::find_by_id(&db.pool, exec_id).await {
+                Ok(Some(process)) => Self::execution_timeout(&*config.read().await, &process),
+                _ => None,
+            };
+            let mut timed_out = false;
+            let mut poll_interval = EXIT_POLL_INITIAL_INTERVAL;
+
             loop {
                 let status_opt = {
-                    let child_lock = {
-                        let map = child_store.read().await;
-                        map.get(&exec_id)
-                            .cloned()
-                            .unwrap_or_else(|| panic!("Child handle missing for {exec_id}"))
+                    let child_lock = child_store.read().await.get(&exec_id).cloned();
+                    let Some(child_lock) = child_lock else {
+                        // stop_execution removed it and has already recorded the outcome
+                        tracing::debug!(
+                            "Child handle for execution process {} is gone, stopping exit monitor",
+                            exec_id
+                        );
+                        break;
                     };
 
                     let mut child_handler = child_lock.write().await;
                     match child_handler.try_wait() {
                         Ok(Some(status)) => Some(Ok(status)),
+                        Ok(None) if !timed_out => {
+                            match command::kill_if_timed_out(
+                                &mut child_handler,
+                                started,
+                                timeout,
+                                grace,
+                            )
+                            .await
+                            {
+                                Ok(true) => {
+                                    timed_out = true;
+                                    let secs = timeout.unwrap_or_default().as_secs();
+                                    tracing::warn!(
+                                        "Execution process {} timed out after {}s, killed",
+                                        exec_id,
+                                        secs
+                                    );
+                                    if let Some(store) = msg_stores.read().await.get(&exec_id) {
+                                        store.push_stderr(format!(
+                                            "Timed out after {secs}s, process was killed"
+                                        ));
+                                    }
+                                }
+                                Ok(false) => {}
+                                Err(e) => tracing::error!(
+                                    "Failed to kill timed out execution process {}: {}",
+                                    exec_id,
+                                    e
+                                ),
+                            }
+                            None
+                        }
                         Ok(None) => None,
                         Err(e) => Some(Err(e)),
                     }
@@ -371,6 +1002,7 @@ impl LocalContainerService {
                             exec_id,
                             status.clone(),
                             exit_code,
+                            timed_out.then_some(ExecutionProcessKillReason::Timeout),
                         )
                         .await
                     {
@@ -390,6 +1022,16 @@ impl LocalContainerService {
                             ExecutionProcessStatus::Completed
                         ) && exit_code == Some(0)
                         {
+                            if matches!(
+                                ctx.execution_process.run_reason,
+                                ExecutionProcessRunReason::SetupScript
+                            ) && let Err(e) =
+                                TaskAttempt::mark_setup_completed(&db.pool, ctx.task_attempt.id)
+                                    .await
+                            {
+                                tracing::error!("Failed to record setup completion: {}", e);
+                            }
+
                             // Commit changes (if any) and get feedback about whether changes were made
                             let changes_committed = match container.try_commit_changes(&ctx).await {
                                 Ok(committed) => committed,
@@ -434,7 +1076,56 @@ impl LocalContainerService {
                             }
                         }
 
-                        if Self::should_finalize(&ctx) {
+                        // A session that couldn't be resumed re-runs the request in a fresh
+                        // one, and a transient provider error re-runs the agent, instead of
+                        // finishing
+                        let fresh_session = if timed_out {
+                            None
+                        } else {
+                            container.fresh_session_rerun(&ctx).await
+                        };
+                        let retry_delay = if timed_out || fresh_session.is_some() {
+                            None
+                        } else {
+                            container.agent_retry_delay(&ctx).await
+                        };
+                        if let Some(executor_action) = fresh_session {
+                            tracing::warn!(
+                                "Coding agent process {} could not resume its session, starting a fresh one",
+                                exec_id
+                            );
+                            if let Err(e) = container
+                                .start_execution_retry(
+                                    &ctx.task_attempt,
+                                    &executor_action,
+                                    &ctx.execution_process.run_reason,
+                                    ctx.execution_process.retry_count,
+                                )
+                                .await
+                            {
+                                tracing::error!(
+                                    "Failed to start a fresh session for task attempt {}: {}",
+                                    ctx.task_attempt.id,
+                                    e
+                                );
+                                Self::finalize_task(&db, &config, &ctx).await;
+                            }
+                        } else if let Some(delay) = retry_delay {
+                            tracing::warn!(
+                                "Coding agent process {} hit a retryable error, re-running in {}s",
+                                exec_id,
+                                delay.as_secs()
+                            );
+                            container.spawn_agent_retry(exec_id, delay).await;
+                        } else if Self::should_finalize(&ctx)
+                            || (timed_out
+                                && !Self::is_standalone_setup(
+                                    ctx.execution_process.executor_action().unwrap(),
+                                    &ctx.execution_process.run_reason,
+                                ))
+                        {
+                            // A timed out process won't start its next action, so finalize
+                            // here rather than leaving the task in progress
                             Self::finalize_task(&db, &config, &ctx).await;
                         }
 
@@ -458,20 +1149,15 @@ impl LocalContainerService {
 
                     // Now that commit/next-action/finalization steps for this process are complete,
                     // capture the HEAD OID as the definitive "after" state (best-effort).
-                    if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
-                        let worktree_dir = container.task_attempt_to_current_dir(&ctx.task_attempt);
-                        if let Ok(head) = container.git().get_head_info(&worktree_dir)
-                            && let Err(e) = ExecutionProcess::update_after_head_commit(
-                                &db.pool, exec_id, &head.oid,
-                            )
-                            .await
-                        {
-                            tracing::warn!(
-                                "Failed to update after_head_commit for {}: {}",
-                                exec_id,
-                                e
-                            );
-                        }
+                    // HEAD is read from the worktree root, not the attempt's subpath.
+                    if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await
+                        && let Some(worktree_dir) = &ctx.task_attempt.container_ref
+                        && let Ok(head) = container.git().get_head_info(Path::new(worktree_dir))
+                        && let Err(e) =
+                            ExecutionProcess::update_after_head_commit(&db.pool, exec_id, &head.oid)
+                                .await
+                    {
+                        tracing::warn!("Failed to update after_head_commit for {}: {}", exec_id, e);
                     }
 
                     // Cleanup msg store
@@ -480,32 +1166,38 @@ impl LocalContainerService {
                         tokio::time::sleep(Duration::from_millis(50)).await; // Wait for the finish message to propogate
                         match Arc::try_unwrap(msg_arc) {
                             Ok(inner) => drop(inner),
-                            Err(arc) => tracing::error!(
-                                "There are still {} strong Arcs to MsgStore for {}",
-                                Arc::strong_count(&arc),
-                                exec_id
-                            ),
+                            Err(arc) => {
+                                // Streams and normalizers often let go shortly after
+                                // the finish message, so only complain if they don't
+                                Self::spawn_msg_store_release_check(
+                                    exec_id,
+                                    Arc::downgrade(&arc),
+                                    &MSG_STORE_RELEASE_CHECKS,
+                                );
+                            }
                         }
                     }
 
-                    // Cleanup child handle
+                    // Cleanup child handle and log forwarder, freeing its concurrency slot
                     child_store.write().await.remove(&exec_id);
+                    container.execution_permits.write().await.remove(&exec_id);
+                    container.log_forwarders.write().await.remove(&exec_id);
                     break;
                 }
 
-                // still running, sleep and try again
-                tokio::time::sleep(Duration::from_millis(250)).await;
+                // still running, sleep and try again. Polling rather than awaiting
+                // `wait()` keeps the child lock free for stop_execution to kill it
+                tokio::time::sleep(poll_interval).await;
+                poll_interval = next_exit_poll_interval(poll_interval);
             }
         })
     }
 
-    pub fn dir_name_from_task_attempt(attempt_id: &Uuid, task_title: &str) -> String {
-        let task_title_id = git_branch_id(task_title);
-        format!("vk-{}-{}", short_uuid(attempt_id), task_title_id)
-    }
-
     async fn track_child_msgs_in_store(&self, id: Uuid, child: &mut AsyncGroupChild) {
         let store = Arc::new(MsgStore::new());
+        if self.config.read().await.track_msg_store_holders {
+            store.track_handouts();
+        }
 
         let out = child.inner().stdout.take().expect("no stdout");
         let err = child.inner().stderr.take().expect("no stderr");
@@ -522,7 +1214,8 @@ impl LocalContainerService {
 
         // Merge and forward into the store
         let merged = select(out, err); // Stream<Item = Result<LogMsg, io::Error>>
-        store.clone().spawn_forwarder(merged);
+        let forwarder = store.clone().spawn_forwarder(merged);
+        self.log_forwarders.write().await.insert(id, forwarder);
 
         let mut map = self.msg_stores().write().await;
         map.insert(id, store);
@@ -563,53 +1256,174 @@ impl LocalContainerService {
         Ok(project_repo_path)
     }
 
-    /// Create a diff stream for merged attempts (never changes)
-    fn create_merged_diff_stream(
+    /// Disposable working directory of a scratch attempt
+    fn scratch_dir(task_attempt_id: Uuid) -> PathBuf {
+        get_vibe_kanban_temp_dir()
+            .join("scratch")
+            .join(task_attempt_id.to_string())
+    }
+
+    /// Apply the configured `on_dirty_worktree` policy before a coding agent runs in
+    /// `worktree_path`, so manual edits aren't clobbered
+    async fn handle_dirty_worktree(
+        &self,
+        task_attempt: &TaskAttempt,
+        worktree_path: &Path,
+    ) -> Result<(), ContainerError> {
+        let policy = self.config.read().await.on_dirty_worktree;
+        match policy {
+            DirtyWorktreePolicy::Ignore => Ok(()),
+            DirtyWorktreePolicy::Refuse => Ok(self.git().ensure_worktree_clean(worktree_path)?),
+            DirtyWorktreePolicy::Stash => {
+                let message = format!(
+                    "vibe-kanban: uncommitted changes before agent run on attempt {}",
+                    task_attempt.id
+                );
+                let stash_ref = format!("refs/vibe-kanban/stash/{}", task_attempt.id);
+                if self
+                    .git()
+                    .stash_changes(worktree_path, &stash_ref, &message)?
+                {
+                    tracing::info!(
+                        "Stashed uncommitted changes in {} to {} before starting agent",
+                        worktree_path.display(),
+                        stash_ref
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the project environment variables to inject into an attempt's executions
+    async fn get_project_env(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<HashMap<String, String>, ContainerError> {
+        let env_vars = task_attempt
+            .parent_task(&self.db().pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent task not found")))?
+            .parent_project(&self.db().pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent project not found")))?
+            .env_vars
+            .0;
+
+        if !env_vars.is_empty() {
+            tracing::debug!(
+                "Injecting project environment for task attempt {}: {}",
+                task_attempt.id,
+                mask_env(&env_vars)
+            );
+        }
+        Ok(env_vars)
+    }
+
+    /// Create a diff stream for a single commit, e.g. a merge (never changes)
+    fn create_commit_diff_stream(
         &self,
         project_repo_path: &Path,
         merge_commit_id: &str,
+        subpath: Option<&str>,
     ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
     {
+        let commit = self.git().get_commit(project_repo_path, merge_commit_id)?;
+        let path_filter = subpath.map(|p| [p]);
         let diffs = self.git().get_diffs(
             DiffTarget::Commit {
                 repo_path: project_repo_path,
                 commit_sha: merge_commit_id,
             },
-            None,
+            path_filter.as_ref().map(|f| &f[..]),
         )?;
 
-        let stream = futures::stream::iter(diffs.into_iter().map(|diff| {
+        // Header with the commit's metadata, ahead of the file diffs
+        let header: json_patch::Patch =
+            serde_json::from_value(json!([{ "op": "add", "path": "/commit", "value": commit }]))
+                .map_err(|e| ContainerError::Other(anyhow!(e)))?;
+
+        Ok(Self::create_static_diff_stream(header, diffs))
+    }
+
+    /// The "already merged" section shown ahead of the live diff of an attempt that was
+    /// merged and then continued: one patch setting `/commit` to the merge commit and
+    /// `/merged` to its file diffs, then a `/sinceMerge` marker holding the merge commit,
+    /// after which everything under `/entries` is new since the merge
+    fn merged_section_patches(
+        &self,
+        project_repo_path: &Path,
+        merge_commit_id: &str,
+        subpath: Option<&str>,
+    ) -> Result<Vec<json_patch::Patch>, ContainerError> {
+        let commit = self.git().get_commit(project_repo_path, merge_commit_id)?;
+        let path_filter = subpath.map(|p| [p]);
+        let merged: serde_json::Map<String, serde_json::Value> = self
+            .git()
+            .get_diffs(
+                DiffTarget::Commit {
+                    repo_path: project_repo_path,
+                    commit_sha: merge_commit_id,
+                },
+                path_filter.as_ref().map(|f| &f[..]),
+            )?
+            .into_iter()
+            .map(|diff| (GitService::diff_path(&diff), json!(PatchType::Diff(diff))))
+            .collect();
+
+        [
+            json!([
+                { "op": "add", "path": "/commit", "value": commit },
+                { "op": "add", "path": "/merged", "value": merged },
+            ]),
+            json!([{ "op": "add", "path": "/sinceMerge", "value": commit.sha }]),
+        ]
+        .into_iter()
+        .map(|patch| serde_json::from_value(patch).map_err(|e| ContainerError::Other(anyhow!(e))))
+        .collect()
+    }
+
+    /// Emit a header patch, one patch per file diff, then `Finished`
+    fn create_static_diff_stream(
+        header: json_patch::Patch,
+        diffs: Vec<Diff>,
+    ) -> futures::stream::BoxStream<'static, Result<Event, std::io::Error>> {
+        futures::stream::once(async move {
+            Ok::<_, std::io::Error>(LogMsg::JsonPatch(header).to_sse_event())
+        })
+        .chain(futures::stream::iter(diffs.into_iter().map(|diff| {
             let entry_index = GitService::diff_path(&diff);
             let patch =
                 ConversationPatch::add_diff(escape_json_pointer_segment(&entry_index), diff);
             let event = LogMsg::JsonPatch(patch).to_sse_event();
             Ok::<_, std::io::Error>(event)
-        }))
+        })))
         .chain(futures::stream::once(async {
             Ok::<_, std::io::Error>(LogMsg::Finished.to_sse_event())
         }))
-        .boxed();
-
-        Ok(stream)
+        .boxed()
     }
 
     /// Create a live diff stream for ongoing attempts
     async fn create_live_diff_stream(
         &self,
+        task_attempt_id: Uuid,
         worktree_path: &Path,
         task_branch: &str,
         base_branch: &str,
+        subpath: Option<&str>,
     ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
     {
         // Get initial snapshot
         let git_service = self.git().clone();
+        let path_filter = subpath.map(|p| [p]);
         let initial_diffs = git_service.get_diffs(
             DiffTarget::Worktree {
                 worktree_path,
                 branch_name: task_branch,
                 base_branch,
             },
-            None,
+            path_filter.as_ref().map(|f| &f[..]),
         )?;
 
         let initial_stream = futures::stream::iter(initial_diffs.into_iter().map(|diff| {
@@ -622,52 +1436,194 @@ impl LocalContainerService {
         .boxed();
 
         // Create live update stream
-        let worktree_path = worktree_path.to_path_buf();
-        let task_branch = task_branch.to_string();
-        let base_branch = base_branch.to_string();
+        let watcher_config = self.config.read().await.filesystem_watcher.clone();
+        let (refresh_rx, refresh_subscription) = self.diff_refresh_receiver(task_attempt_id).await;
+        let target = LiveDiffTarget {
+            worktree_path: worktree_path.to_path_buf(),
+            task_branch: task_branch.to_string(),
+            base_branch: base_branch.to_string(),
+            subpath: subpath.map(str::to_string),
+        };
 
         let live_stream = {
             let git_service = git_service.clone();
-            try_stream! {
-                let (_debouncer, mut rx, canonical_worktree_path) =
-                    filesystem_watcher::async_watcher(worktree_path.clone())
-                        .map_err(|e| io::Error::other(e.to_string()))?;
-
-                while let Some(result) = rx.next().await {
-                    match result {
-                        Ok(events) => {
-                            let changed_paths = Self::extract_changed_paths(&events, &canonical_worktree_path, &worktree_path);
-
-                            if !changed_paths.is_empty() {
-                                for event in Self::process_file_changes(
-                                    &git_service,
-                                    &worktree_path,
-                                    &task_branch,
-                                    &base_branch,
-                                    &changed_paths,
-                                ).map_err(|e| {
+            stream! {
+                // The watcher guard lives in the stream, so it is torn down as soon as
+                // the SSE response is dropped on client disconnect. So does the refresh
+                // subscription.
+                let _refresh_subscription = refresh_subscription;
+                let (_watcher, rx, canonical_worktree_path) =
+                    match filesystem_watcher::async_watcher(target.worktree_path.clone(), &watcher_config) {
+                        Ok(components) => components,
+                        Err(e) => {
+                            tracing::error!("Failed to start filesystem watcher: {}", e);
+                            let message = format!("Live diff stopped: {e}");
+                            yield Ok::<_, io::Error>(LogMsg::Error { message }.to_sse_event());
+                            return;
+                        }
+                    };
+                let mut updates =
+                    Self::live_diff_updates(git_service, rx, canonical_worktree_path, target, refresh_rx);
+                while let Some(update) = updates.next().await {
+                    yield update;
+                }
+            }
+        }.boxed();
+
+        let combined_stream = select(initial_stream, live_stream);
+        Ok(combined_stream.boxed())
+    }
+
+    /// Diff updates for `target`'s worktree as the watcher reports `events`, and full
+    /// snapshots on refresh requests. Failures are sent to the client as error events
+    /// rather than ending the response with a bare io::Error. Only a broken watcher or a
+    /// removed worktree ends the stream; a failed git diff is reported and the next change
+    /// retries it.
+    fn live_diff_updates(
+        git_service: GitService,
+        mut rx: impl Stream<Item = DebounceEventResult> + Send + Unpin + 'static,
+        canonical_worktree_path: PathBuf,
+        target: LiveDiffTarget,
+        mut refresh_rx: watch::Receiver<()>,
+    ) -> futures::stream::BoxStream<'static, Result<Event, io::Error>> {
+        let LiveDiffTarget {
+            worktree_path,
+            task_branch,
+            base_branch,
+            subpath,
+        } = target;
+        stream! {
+            loop {
+                let result = tokio::select! {
+                    result = rx.next() => match result {
+                        Some(result) => result,
+                        None => break,
+                    },
+                    // Refreshes requested while a snapshot is in flight are marked
+                    // seen together, so they coalesce into the next snapshot
+                    Ok(()) = refresh_rx.changed() => {
+                        refresh_rx.borrow_and_update();
+                        match Self::snapshot_diff_event(
+                            &git_service,
+                            &worktree_path,
+                            &task_branch,
+                            &base_branch,
+                            subpath.as_deref(),
+                        ) {
+                            Ok(event) => yield Ok(event),
+                            Err(e) if !worktree_path.exists() => {
+                                tracing::error!("Worktree removed while refreshing diff: {}", e);
+                                let message = format!("Live diff stopped: {e}");
+                                yield Ok(LogMsg::Error { message }.to_sse_event());
+                                break;
+                            }
+                            Err(e) => {
+                                tracing::error!("Error refreshing diff: {}", e);
+                                let message = format!("Diff refresh failed: {e}");
+                                yield Ok(LogMsg::Error { message }.to_sse_event());
+                            }
+                        }
+                        continue;
+                    }
+                };
+                match result {
+                    Ok(events) => {
+                        let mut changed_paths = Self::extract_changed_paths(&events, &canonical_worktree_path, &worktree_path);
+                        if let Some(subpath) = &subpath {
+                            changed_paths.retain(|p| Self::is_within_subpath(p, subpath));
+                        }
+
+                        if !changed_paths.is_empty() {
+                            match Self::process_file_changes(
+                                &git_service,
+                                &worktree_path,
+                                &task_branch,
+                                &base_branch,
+                                &changed_paths,
+                            ).await {
+                                Ok(events) => {
+                                    for event in events {
+                                        yield Ok(event);
+                                    }
+                                }
+                                // With the worktree gone there is nothing left to diff
+                                Err(e) if !worktree_path.exists() => {
+                                    tracing::error!("Worktree removed while streaming diff: {}", e);
+                                    let message = format!("Live diff stopped: {e}");
+                                    yield Ok(LogMsg::Error { message }.to_sse_event());
+                                    break;
+                                }
+                                Err(e) => {
                                     tracing::error!("Error processing file changes: {}", e);
-                                    io::Error::other(e.to_string())
-                                })? {
-                                    yield event;
+                                    let message = format!("Failed to update diff: {e}");
+                                    yield Ok(LogMsg::Error { message }.to_sse_event());
                                 }
                             }
                         }
-                        Err(errors) => {
-                            let error_msg = errors.iter()
-                                .map(|e| e.to_string())
-                                .collect::<Vec<_>>()
-                                .join("; ");
-                            tracing::error!("Filesystem watcher error: {}", error_msg);
-                            Err(io::Error::other(error_msg))?;
-                        }
+                    }
+                    Err(errors) => {
+                        let error_msg = errors.iter()
+                            .map(|e| e.to_string())
+                            .collect::<Vec<_>>()
+                            .join("; ");
+                        tracing::error!("Filesystem watcher error: {}", error_msg);
+                        let message = format!("Live diff stopped: {error_msg}");
+                        yield Ok(LogMsg::Error { message }.to_sse_event());
+                        break;
                     }
                 }
             }
-        }.boxed();
+        }
+        .boxed()
+    }
 
-        let combined_stream = select(initial_stream, live_stream);
-        Ok(combined_stream.boxed())
+    /// Subscribe to refreshes of `task_attempt_id`'s live diffs, for as long as the
+    /// subscription is held
+    async fn diff_refresh_receiver(
+        &self,
+        task_attempt_id: Uuid,
+    ) -> (watch::Receiver<()>, DiffRefreshSubscription) {
+        let receiver = self
+            .diff_refreshers
+            .write()
+            .await
+            .entry(task_attempt_id)
+            .or_insert_with(|| watch::channel(()).0)
+            .subscribe();
+        let subscription = DiffRefreshSubscription {
+            task_attempt_id,
+            refreshers: self.diff_refreshers.clone(),
+        };
+        (receiver, subscription)
+    }
+
+    /// The full worktree diff as one patch replacing every entry, so files whose changes
+    /// the watcher missed are added and stale ones dropped in a single update
+    fn snapshot_diff_event(
+        git_service: &GitService,
+        worktree_path: &Path,
+        task_branch: &str,
+        base_branch: &str,
+        subpath: Option<&str>,
+    ) -> Result<Event, ContainerError> {
+        let path_filter = subpath.map(|p| [p]);
+        let entries: serde_json::Map<String, serde_json::Value> = git_service
+            .get_diffs(
+                DiffTarget::Worktree {
+                    worktree_path,
+                    branch_name: task_branch,
+                    base_branch,
+                },
+                path_filter.as_ref().map(|f| &f[..]),
+            )?
+            .into_iter()
+            .map(|diff| (GitService::diff_path(&diff), json!(PatchType::Diff(diff))))
+            .collect();
+        let patch = serde_json::from_value(json!([
+            { "op": "replace", "path": "/entries", "value": entries }
+        ]))
+        .map_err(|e| ContainerError::Other(anyhow!(e)))?;
+        Ok(LogMsg::JsonPatch(patch).to_sse_event())
     }
 
     /// Extract changed file paths from filesystem events
@@ -683,14 +1639,22 @@ impl LocalContainerService {
                 path.strip_prefix(canonical_worktree_path)
                     .or_else(|_| path.strip_prefix(worktree_path))
                     .ok()
-                    .map(|p| p.to_string_lossy().replace('\\', "/"))
+                    .map(|p| normalize_path_separators(&p.to_string_lossy()))
             })
             .filter(|s| !s.is_empty())
             .collect()
     }
 
-    /// Process file changes and generate diff events
-    fn process_file_changes(
+    /// Whether a worktree-relative path lies inside the attempt's subpath
+    fn is_within_subpath(path: &str, subpath: &str) -> bool {
+        path.strip_prefix(subpath)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+
+    /// Process file changes and generate diff events. Diffs that fail transiently (a held
+    /// git lock, or the worktree changing while it is staged) are retried with backoff
+    /// before giving up.
+    async fn process_file_changes(
         git_service: &GitService,
         worktree_path: &Path,
         task_branch: &str,
@@ -699,14 +1663,28 @@ impl LocalContainerService {
     ) -> Result<Vec<Event>, ContainerError> {
         let path_filter: Vec<&str> = changed_paths.iter().map(|s| s.as_str()).collect();
 
-        let current_diffs = git_service.get_diffs(
-            DiffTarget::Worktree {
-                worktree_path,
-                branch_name: task_branch,
-                base_branch,
-            },
-            Some(&path_filter),
-        )?;
+        let mut retry_delays = DIFF_RETRY_DELAYS.iter();
+        let current_diffs = loop {
+            let result = git_service.get_diffs(
+                DiffTarget::Worktree {
+                    worktree_path,
+                    branch_name: task_branch,
+                    base_branch,
+                },
+                Some(&path_filter),
+            );
+            match result {
+                Ok(diffs) => break diffs,
+                Err(e) if e.is_transient() => match retry_delays.next() {
+                    Some(delay) => {
+                        tracing::debug!("Transient git error computing diff, retrying: {}", e);
+                        tokio::time::sleep(*delay).await;
+                    }
+                    None => return Err(e.into()),
+                },
+                Err(e) => return Err(e.into()),
+            }
+        };
 
         let mut events = Vec::new();
         let mut files_with_diffs = HashSet::new();
@@ -749,18 +1727,46 @@ impl ContainerService for LocalContainerService {
         &self.git
     }
 
+    fn rate_limiter(&self) -> &ExecutorRateLimiter {
+        &self.rate_limiter
+    }
+
+    fn diff_stats_cache(&self) -> &Arc<DiffStatsCache> {
+        &self.diff_stats_cache
+    }
+
+    fn execution_limiter(&self) -> &Arc<ExecutionLimiter> {
+        &self.execution_limiter
+    }
+
     fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf {
-        PathBuf::from(task_attempt.container_ref.clone().unwrap_or_default())
+        let worktree_dir = PathBuf::from(task_attempt.container_ref.clone().unwrap_or_default());
+        match &task_attempt.subpath {
+            Some(subpath) => worktree_dir.join(subpath),
+            None => worktree_dir,
+        }
     }
     /// Create a container
     async fn create(&self, task_attempt: &TaskAttempt) -> Result<ContainerRef, ContainerError> {
+        // Refuse before touching the database or git when the worktree disk can't take it
+        let min_free_bytes = self.config.read().await.worktree_min_free_bytes;
+        WorktreeManager::check_base_dir_capacity(
+            &WorktreeManager::get_worktree_base_dir(),
+            min_free_bytes,
+        )?;
+
         let task = task_attempt
             .parent_task(&self.db.pool)
             .await?
             .ok_or(sqlx::Error::RowNotFound)?;
 
-        let task_branch_name =
-            LocalContainerService::dir_name_from_task_attempt(&task_attempt.id, &task.title);
+        let task_branch_name = self
+            .config
+            .read()
+            .await
+            .worktree_naming
+            .name(&task_attempt.id, &task.title)
+            .map_err(|e| ContainerError::Other(anyhow!(e)))?;
         let worktree_path = WorktreeManager::get_worktree_base_dir().join(&task_branch_name);
 
         let project = task
@@ -768,6 +1774,30 @@ impl ContainerService for LocalContainerService {
             .await?
             .ok_or(sqlx::Error::RowNotFound)?;
 
+        if self.config.read().await.fetch_before_create {
+            let git = self.git.clone();
+            let repo_path = project.git_repo_path.clone();
+            let base_branch = task_attempt.base_branch.clone();
+            match tokio::task::spawn_blocking(move || {
+                git.fetch_base_branch(&repo_path, &base_branch)
+            })
+            .await
+            {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    tracing::warn!(
+                        "Fetch before creating worktree for attempt {} failed, using local {}: {}",
+                        task_attempt.id,
+                        task_attempt.base_branch,
+                        e
+                    );
+                }
+                Err(e) => {
+                    tracing::warn!("Fetch task for attempt {} failed: {}", task_attempt.id, e);
+                }
+            }
+        }
+
         WorktreeManager::create_worktree(
             &project.git_repo_path,
             &task_branch_name,
@@ -810,7 +1840,91 @@ impl ContainerService for LocalContainerService {
         Ok(worktree_path.to_string_lossy().to_string())
     }
 
+    async fn create_scratch(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<ContainerRef, ContainerError> {
+        let scratch_dir = Self::scratch_dir(task_attempt.id);
+        tokio::fs::create_dir_all(&scratch_dir).await?;
+
+        TaskAttempt::update_container_ref(
+            &self.db.pool,
+            task_attempt.id,
+            &scratch_dir.to_string_lossy(),
+        )
+        .await?;
+        TaskAttempt::mark_scratch(&self.db.pool, task_attempt.id).await?;
+
+        Ok(scratch_dir.to_string_lossy().to_string())
+    }
+
+    async fn is_attempt_running(&self, task_attempt_id: Uuid) -> Result<bool, ContainerError> {
+        if self
+            .browser_sessions
+            .read()
+            .await
+            .values()
+            .any(|session| session.task_attempt_id == task_attempt_id)
+        {
+            return Ok(true);
+        }
+        let processes =
+            ExecutionProcess::find_by_task_attempt_id(&self.db.pool, task_attempt_id).await?;
+        let child_store = self.child_store.read().await;
+        let queued_starts = self.queued_starts.read().await;
+        let agent_retries = self.agent_retries.read().await;
+        Ok(processes.iter().any(|process| {
+            child_store.contains_key(&process.id)
+                || queued_starts.contains_key(&process.id)
+                || agent_retries.contains_key(&process.id)
+        }))
+    }
+
+    async fn cancel_agent_retries(&self, task_attempt: &TaskAttempt) {
+        let Ok(processes) =
+            ExecutionProcess::find_by_task_attempt_id(&self.db.pool, task_attempt.id).await
+        else {
+            return;
+        };
+        let cancelled: Vec<_> = {
+            let mut agent_retries = self.agent_retries.write().await;
+            processes
+                .iter()
+                .filter_map(|process| agent_retries.remove(&process.id))
+                .collect()
+        };
+        if cancelled.is_empty() {
+            return;
+        }
+        for cancel in cancelled {
+            cancel.cancel();
+        }
+        if let Err(e) =
+            Task::update_status(&self.db.pool, task_attempt.task_id, TaskStatus::InReview).await
+        {
+            tracing::error!("Failed to update task status to InReview: {e}");
+        }
+        tracing::debug!(
+            "Pending coding agent re-runs of task attempt {} cancelled",
+            task_attempt.id
+        );
+    }
+
     async fn delete_inner(&self, task_attempt: &TaskAttempt) -> Result<(), ContainerError> {
+        if task_attempt.scratch {
+            let scratch_dir = Self::scratch_dir(task_attempt.id);
+            if let Err(e) = tokio::fs::remove_dir_all(&scratch_dir).await
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                tracing::warn!(
+                    "Failed to remove scratch dir for task attempt {}: {}",
+                    task_attempt.id,
+                    e
+                );
+            }
+            return Ok(());
+        }
+
         // cleanup the container, here that means deleting the worktree
         let task = task_attempt
             .parent_task(&self.db.pool)
@@ -843,6 +1957,13 @@ impl ContainerService for LocalContainerService {
         &self,
         task_attempt: &TaskAttempt,
     ) -> Result<ContainerRef, ContainerError> {
+        // Scratch dirs are disposable, so one that was cleaned up just starts empty again
+        if task_attempt.scratch {
+            let scratch_dir = Self::scratch_dir(task_attempt.id);
+            tokio::fs::create_dir_all(&scratch_dir).await?;
+            return Ok(scratch_dir.to_string_lossy().to_string());
+        }
+
         // Get required context
         let task = task_attempt
             .parent_task(&self.db.pool)
@@ -854,15 +1975,16 @@ impl ContainerService for LocalContainerService {
             .await?
             .ok_or(sqlx::Error::RowNotFound)?;
 
-        let container_ref = task_attempt.container_ref.as_ref().ok_or_else(|| {
-            ContainerError::Other(anyhow!("Container ref not found for task attempt"))
-        })?;
+        let container_ref = task_attempt
+            .container_ref
+            .as_ref()
+            .ok_or(ContainerError::MissingWorktree(task_attempt.id))?;
         let worktree_path = PathBuf::from(container_ref);
 
         let branch_name = task_attempt
             .branch
             .as_ref()
-            .ok_or_else(|| ContainerError::Other(anyhow!("Branch not found for task attempt")))?;
+            .ok_or(ContainerError::MissingBranch(task_attempt.id))?;
 
         WorktreeManager::ensure_worktree_exists(
             &project.git_repo_path,
@@ -875,6 +1997,9 @@ impl ContainerService for LocalContainerService {
     }
 
     async fn is_container_clean(&self, task_attempt: &TaskAttempt) -> Result<bool, ContainerError> {
+        if task_attempt.scratch {
+            return Ok(true); // Nothing in a scratch dir is tracked
+        }
         if let Some(container_ref) = &task_attempt.container_ref {
             // If container_ref is set, check if the worktree exists
             let path = PathBuf::from(container_ref);
@@ -901,15 +2026,65 @@ impl ContainerService for LocalContainerService {
             .ok_or(ContainerError::Other(anyhow!(
                 "Container ref not found for task attempt"
             )))?;
-        let current_dir = PathBuf::from(container_ref);
+        let mut current_dir = PathBuf::from(container_ref);
+        if let Some(subpath) = &task_attempt.subpath {
+            current_dir.push(subpath);
+            if !current_dir.is_dir() {
+                return Err(ContainerError::Other(anyhow!(
+                    "Subpath '{subpath}' does not exist in the worktree"
+                )));
+            }
+        }
+
+        if !task_attempt.scratch
+            && matches!(
+                executor_action.typ(),
+                ExecutorActionType::CodingAgentInitialRequest(_)
+                    | ExecutorActionType::CodingAgentFollowUpRequest(_)
+            )
+        {
+            self.handle_dirty_worktree(task_attempt, Path::new(container_ref))
+                .await?;
+        }
+
+        let project_env = self.get_project_env(task_attempt).await?;
+
+        // Throttled starts wait here for their turn rather than failing
+        let profile = match executor_action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            _ => None,
+        };
+        // and so do agents over the global or project concurrency limit, until one finishes
+        let permit = match profile {
+            Some(profile) => match self
+                .acquire_execution_permit(task_attempt, execution_process, profile)
+                .await?
+            {
+                Some(permit) => Some(permit),
+                // Stopped while queued; stop_execution has already recorded it
+                None => return Ok(()),
+            },
+            None => None,
+        };
 
         // Create the child and stream, add to execution tracker
-        let mut child = executor_action.spawn(&current_dir).await?;
+        let mut child = executor_action.spawn(&current_dir, &project_env).await?;
 
         self.track_child_msgs_in_store(execution_process.id, &mut child)
             .await;
 
         self.add_child_to_store(execution_process.id, child).await;
+        if let Some(permit) = permit {
+            self.execution_permits
+                .write()
+                .await
+                .insert(execution_process.id, permit);
+        }
 
         // Spawn exit monitor
         let _hn = self.spawn_exit_monitor(&execution_process.id);
@@ -947,8 +2122,24 @@ impl ContainerService for LocalContainerService {
                 session_id, execution_process.task_attempt_id);
         }
 
+        let task_attempt =
+            TaskAttempt::find_by_id(&self.db.pool, execution_process.task_attempt_id)
+                .await?
+                .ok_or(ContainerError::Other(anyhow!("Task attempt not found")))?;
+        let project_env = self.get_project_env(&task_attempt).await?;
+
+        // Run the automation with the same node binary the health check resolves
+        let node = NodeBrowserChatService::from_config(&*self.config.read().await)
+            .node_runtime()
+            .await
+            .map_err(|e| ContainerError::Other(e.into()))?;
+        let mut executor_action = executor_action.clone();
+        if let ExecutorActionType::BrowserChatRequest(request) = &mut executor_action.typ {
+            request.node_path = Some(node.path);
+        }
+
         // Create the child and stream, add to execution tracker
-        let mut child = executor_action.spawn(&current_dir).await?;
+        let mut child = executor_action.spawn(&current_dir, &project_env).await?;
 
         self.track_child_msgs_in_store(execution_process.id, &mut child)
             .await;
@@ -964,7 +2155,62 @@ impl ContainerService for LocalContainerService {
     async fn stop_execution(
         &self,
         execution_process: &ExecutionProcess,
+        reason: ExecutionProcessKillReason,
     ) -> Result<(), ContainerError> {
+        // A failed agent waiting to be re-run has already exited; stopping it cancels the
+        // re-run
+        if let Some(cancel) = self
+            .agent_retries
+            .write()
+            .await
+            .remove(&execution_process.id)
+        {
+            cancel.cancel();
+            if let Ok(ctx) =
+                ExecutionProcess::load_context(&self.db.pool, execution_process.id).await
+                && let Err(e) =
+                    Task::update_status(&self.db.pool, ctx.task.id, TaskStatus::InReview).await
+            {
+                tracing::error!("Failed to update task status to InReview: {e}");
+            }
+            tracing::debug!(
+                "Re-run of execution process {} cancelled",
+                execution_process.id
+            );
+            return Ok(());
+        }
+
+        // A start still waiting for a concurrency slot has no child yet; taking it out of
+        // the queue cancels it
+        if let Some(cancel) = self
+            .queued_starts
+            .write()
+            .await
+            .remove(&execution_process.id)
+        {
+            cancel.cancel();
+            ExecutionProcess::update_completion(
+                &self.db.pool,
+                execution_process.id,
+                ExecutionProcessStatus::Killed,
+                None,
+                Some(reason),
+            )
+            .await?;
+            if let Ok(ctx) =
+                ExecutionProcess::load_context(&self.db.pool, execution_process.id).await
+                && let Err(e) =
+                    Task::update_status(&self.db.pool, ctx.task.id, TaskStatus::InReview).await
+            {
+                tracing::error!("Failed to update task status to InReview: {e}");
+            }
+            tracing::debug!(
+                "Queued execution process {} cancelled",
+                execution_process.id
+            );
+            return Ok(());
+        }
+
         let child = self
             .get_child_from_store(&execution_process.id)
             .await
@@ -976,13 +2222,22 @@ impl ContainerService for LocalContainerService {
             execution_process.id,
             ExecutionProcessStatus::Killed,
             None,
+            Some(reason),
         )
         .await?;
 
-        // Kill the child process and remove from the store
+        // Kill the child process (escalating to SIGKILL after the grace period)
+        // and only then remove it from the store
+        let grace = Duration::from_secs(self.config.read().await.stop_grace_period_secs);
         {
             let mut child_guard = child.write().await;
-            if let Err(e) = command::kill_process_group(&mut child_guard).await {
+            let stopped = if execution_process.run_reason == ExecutionProcessRunReason::BrowserChat
+            {
+                command::stop_browser_chat(&mut child_guard, grace).await
+            } else {
+                command::kill_process_group(&mut child_guard, grace).await
+            };
+            if let Err(e) = stopped {
                 tracing::error!(
                     "Failed to stop execution process {}: {}",
                     execution_process.id,
@@ -1016,16 +2271,16 @@ impl ContainerService for LocalContainerService {
         );
 
         // Record after-head commit OID (best-effort)
-        if let Ok(ctx) = ExecutionProcess::load_context(&self.db.pool, execution_process.id).await {
-            let worktree = self.task_attempt_to_current_dir(&ctx.task_attempt);
-            if let Ok(head) = self.git().get_head_info(&worktree) {
-                let _ = ExecutionProcess::update_after_head_commit(
-                    &self.db.pool,
-                    execution_process.id,
-                    &head.oid,
-                )
-                .await;
-            }
+        if let Ok(ctx) = ExecutionProcess::load_context(&self.db.pool, execution_process.id).await
+            && let Some(worktree) = &ctx.task_attempt.container_ref
+            && let Ok(head) = self.git().get_head_info(Path::new(worktree))
+        {
+            let _ = ExecutionProcess::update_after_head_commit(
+                &self.db.pool,
+                execution_process.id,
+                &head.oid,
+            )
+            .await;
         }
 
         Ok(())
@@ -1036,6 +2291,14 @@ impl ContainerService for LocalContainerService {
         task_attempt: &TaskAttempt,
     ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
     {
+        // Scratch attempts have no branch, so there's never anything to diff
+        if task_attempt.scratch {
+            return Ok(Self::create_static_diff_stream(
+                json_patch::Patch(Vec::new()),
+                Vec::new(),
+            ));
+        }
+
         let project_repo_path = self.get_project_repo_path(task_attempt).await?;
         let latest_merge =
             Merge::find_latest_by_task_attempt_id(&self.db.pool, task_attempt.id).await?;
@@ -1057,45 +2320,226 @@ impl ContainerService for LocalContainerService {
             false
         };
 
+        // The merge is ignored once it has been reverted
+        let merge_commit = latest_merge
+            .filter(|merge| merge.revert_commit().is_none())
+            .and_then(|merge| merge.merge_commit());
+
         // Show merged diff when no new work is on the branch or container
-        if let Some(merge) = &latest_merge
-            && let Some(commit) = merge.merge_commit()
+        if let Some(commit) = &merge_commit
             && self.is_container_clean(task_attempt).await?
             && !is_ahead
         {
-            return self.create_merged_diff_stream(&project_repo_path, &commit);
+            return self.create_commit_diff_stream(
+                &project_repo_path,
+                commit,
+                task_attempt.subpath.as_deref(),
+            );
         }
 
         // worktree is needed for non-merged diffs
         let container_ref = self.ensure_container_exists(task_attempt).await?;
         let worktree_path = PathBuf::from(container_ref);
 
-        // Handle ongoing attempts (live streaming diff)
-        self.create_live_diff_stream(&worktree_path, &task_branch, &task_attempt.base_branch)
-            .await
-    }
+        // Handle ongoing attempts (live streaming diff). The base branch already holds
+        // the merged work, so after a merge this is only what's new since.
+        let live_stream = self
+            .create_live_diff_stream(
+                task_attempt.id,
+                &worktree_path,
+                &task_branch,
+                &task_attempt.base_branch,
+                task_attempt.subpath.as_deref(),
+            )
+            .await?;
 
-    async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError> {
-        if !matches!(
-            ctx.execution_process.run_reason,
-            ExecutionProcessRunReason::CodingAgent | ExecutionProcessRunReason::CleanupScript,
+        // Merged then continued: keep the merged diff visible as its own section
+        let Some(commit) = merge_commit else {
+            return Ok(live_stream);
+        };
+        // A PR's merge commit may not have been fetched; the live diff still works
+        let merged_section = match self.merged_section_patches(
+            &project_repo_path,
+            &commit,
+            task_attempt.subpath.as_deref(),
         ) {
-            return Ok(false);
+            Ok(patches) => patches,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load merged diff {} for task attempt {}: {}",
+                    commit,
+                    task_attempt.id,
+                    e
+                );
+                return Ok(live_stream);
+            }
+        };
+        Ok(futures::stream::iter(
+            merged_section
+                .into_iter()
+                .map(|patch| Ok::<_, std::io::Error>(LogMsg::JsonPatch(patch).to_sse_event())),
+        )
+        .chain(live_stream)
+        .boxed())
+    }
+
+    async fn refresh_diff(&self, task_attempt: &TaskAttempt) -> bool {
+        let mut refreshers = self.diff_refreshers.write().await;
+        match refreshers.get(&task_attempt.id) {
+            Some(refresher) if refresher.send(()).is_ok() => true,
+            // Every stream of this attempt has disconnected
+            Some(_) => {
+                refreshers.remove(&task_attempt.id);
+                false
+            }
+            None => false,
         }
+    }
 
-        let message = match ctx.execution_process.run_reason {
-            ExecutionProcessRunReason::CodingAgent => {
-                // Try to retrieve the task summary from the executor session
-                // otherwise fallback to default message
-                match ExecutorSession::find_by_execution_process_id(
-                    &self.db().pool,
-                    ctx.execution_process.id,
-                )
-                .await
-                {
-                    Ok(Some(session)) if session.summary.is_some() => session.summary.unwrap(),
-                    Ok(_) => {
-                        tracing::debug!(
+    async fn attempt_commits(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<Vec<CommitMetadata>, ContainerError> {
+        if task_attempt.scratch {
+            return Ok(Vec::new());
+        }
+        let project_repo_path = self.get_project_repo_path(task_attempt).await?;
+        let task_branch = task_attempt
+            .branch
+            .clone()
+            .ok_or(ContainerError::Other(anyhow!(
+                "Task attempt {} does not have a branch",
+                task_attempt.id
+            )))?;
+        let limit = self.config.read().await.attempt_commits_limit;
+
+        Ok(self.git().list_commits(
+            &project_repo_path,
+            &task_branch,
+            &task_attempt.base_branch,
+            limit,
+        )?)
+    }
+
+    async fn get_commit_diff(
+        &self,
+        task_attempt: &TaskAttempt,
+        commit_sha: &str,
+    ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
+    {
+        let project_repo_path = self.get_project_repo_path(task_attempt).await?;
+        let task_branch = task_attempt
+            .branch
+            .clone()
+            .ok_or(ContainerError::Other(anyhow!(
+                "Task attempt {} does not have a branch",
+                task_attempt.id
+            )))?;
+
+        // Only the task branch's own commits can be viewed, not arbitrary repo history
+        if !self.git().is_commit_on_branch_only(
+            &project_repo_path,
+            &task_branch,
+            &task_attempt.base_branch,
+            commit_sha,
+        )? {
+            return Err(GitServiceError::CommitNotFound(commit_sha.to_string()).into());
+        }
+
+        self.create_commit_diff_stream(
+            &project_repo_path,
+            commit_sha,
+            task_attempt.subpath.as_deref(),
+        )
+    }
+
+    async fn compare_attempts(
+        &self,
+        from: &TaskAttempt,
+        to: &TaskAttempt,
+    ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
+    {
+        if from.task_id != to.task_id {
+            return Err(TaskAttemptError::ValidationError(
+                "Only attempts of the same task can be compared".to_string(),
+            )
+            .into());
+        }
+
+        let project_repo_path = self.get_project_repo_path(from).await?;
+        let [from_branch, to_branch] = [from, to].map(|attempt| {
+            attempt.branch.clone().ok_or(ContainerError::Other(anyhow!(
+                "Task attempt {} does not have a branch",
+                attempt.id
+            )))
+        });
+        let (from_branch, to_branch) = (from_branch?, to_branch?);
+
+        let merge_base = self
+            .git()
+            .get_merge_base(&project_repo_path, &from_branch, &to_branch)?
+            .ok_or_else(|| {
+                GitServiceError::InvalidRepository(format!(
+                    "Branches {from_branch} and {to_branch} share no history"
+                ))
+            })?;
+        let from_sha = self
+            .git()
+            .get_branch_oid(&project_repo_path, &from_branch)?;
+        let to_sha = self.git().get_branch_oid(&project_repo_path, &to_branch)?;
+        let comparison = AttemptComparison {
+            merge_base,
+            from: self.git().get_commit(&project_repo_path, &from_sha)?,
+            to: self.git().get_commit(&project_repo_path, &to_sha)?,
+        };
+
+        // Only narrow to a subpath when both attempts were scoped to the same one
+        let subpath = from
+            .subpath
+            .as_deref()
+            .filter(|_| from.subpath == to.subpath);
+        let path_filter = subpath.map(|p| [p]);
+        let diffs = self.git().get_diffs(
+            DiffTarget::Commits {
+                repo_path: &project_repo_path,
+                from_sha: &from_sha,
+                to_sha: &to_sha,
+            },
+            path_filter.as_ref().map(|f| &f[..]),
+        )?;
+
+        let header: json_patch::Patch = serde_json::from_value(
+            json!([{ "op": "add", "path": "/comparison", "value": comparison }]),
+        )
+        .map_err(|e| ContainerError::Other(anyhow!(e)))?;
+
+        Ok(Self::create_static_diff_stream(header, diffs))
+    }
+
+    async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError> {
+        if ctx.task_attempt.scratch {
+            return Ok(false);
+        }
+        if !matches!(
+            ctx.execution_process.run_reason,
+            ExecutionProcessRunReason::CodingAgent | ExecutionProcessRunReason::CleanupScript,
+        ) {
+            return Ok(false);
+        }
+
+        let message = match ctx.execution_process.run_reason {
+            ExecutionProcessRunReason::CodingAgent => {
+                // Try to retrieve the task summary from the executor session
+                // otherwise fallback to default message
+                match ExecutorSession::find_by_execution_process_id(
+                    &self.db().pool,
+                    ctx.execution_process.id,
+                )
+                .await
+                {
+                    Ok(Some(session)) if session.summary.is_some() => session.summary.unwrap(),
+                    Ok(_) => {
+                        tracing::debug!(
                             "No summary found for execution process {}, using default message",
                             ctx.execution_process.id
                         );
@@ -1139,7 +2583,24 @@ impl ContainerService for LocalContainerService {
             message
         );
 
-        let changes_committed = self.git().commit(Path::new(container_ref), &message)?;
+        let (skip_noop_cleanup, signing) = {
+            let config = self.config.read().await;
+            (
+                matches!(
+                    ctx.execution_process.run_reason,
+                    ExecutionProcessRunReason::CleanupScript
+                ) && config.skip_noop_cleanup_commits,
+                config.commit_signing(),
+            )
+        };
+        let git = self.git().with_commit_signing(signing);
+        let worktree_path = Path::new(container_ref);
+        let subpath = ctx.task_attempt.subpath.as_deref();
+        let changes_committed = if skip_noop_cleanup {
+            git.commit_tracked_changes(worktree_path, subpath, &message)?
+        } else {
+            git.commit_scoped(worktree_path, subpath, &message)?
+        };
         Ok(changes_committed)
     }
 
@@ -1165,7 +2626,7 @@ impl ContainerService for LocalContainerService {
                 && !parent.exists()
             {
                 std::fs::create_dir_all(parent).map_err(|e| {
-                    ContainerError::Other(anyhow!("Failed to create directory {:?}: {}", parent, e))
+                    ContainerError::Other(anyhow!("Failed to create directory {parent:?}: {e}"))
                 })?;
             }
 
@@ -1173,17 +2634,13 @@ impl ContainerService for LocalContainerService {
             if source_file.exists() {
                 std::fs::copy(&source_file, &target_file).map_err(|e| {
                     ContainerError::Other(anyhow!(
-                        "Failed to copy file {:?} to {:?}: {}",
-                        source_file,
-                        target_file,
-                        e
+                        "Failed to copy file {source_file:?} to {target_file:?}: {e}"
                     ))
                 })?;
                 tracing::info!("Copied file {:?} to worktree", file_path);
             } else {
                 return Err(ContainerError::Other(anyhow!(
-                    "File {:?} does not exist in the project directory",
-                    source_file
+                    "File {source_file:?} does not exist in the project directory"
                 )));
             }
         }
@@ -1192,8 +2649,8 @@ impl ContainerService for LocalContainerService {
 }
 
 impl LocalContainerService {
-    /// Extract the last assistant message from the MsgStore history
-    fn extract_last_assistant_message(&self, exec_id: &Uuid) -> Option<String> {
+    /// Extract the last assistant message from the MsgStore history, truncated to `max_bytes`
+    fn extract_last_assistant_message(&self, exec_id: &Uuid, max_bytes: usize) -> Option<String> {
         // Get the MsgStore for this execution
         let msg_stores = self.msg_stores.try_read().ok()?;
         let msg_store = msg_stores.get(exec_id)?;
@@ -1202,20 +2659,19 @@ impl LocalContainerService {
         let history = msg_store.get_history();
 
         for msg in history.iter().rev() {
-            if let LogMsg::JsonPatch(patch) = msg {
-                // Try to extract a NormalizedEntry from the patch
-                if let Some(entry) = self.extract_normalized_entry_from_patch(patch)
-                    && matches!(entry.entry_type, NormalizedEntryType::AssistantMessage)
-                {
-                    let content = entry.content.trim();
-                    if !content.is_empty() {
-                        // Truncate to reasonable size (4KB as Oracle suggested)
-                        const MAX_SUMMARY_LENGTH: usize = 4096;
-                        if content.len() > MAX_SUMMARY_LENGTH {
-                            return Some(format!("{}...", &content[..MAX_SUMMARY_LENGTH]));
-                        }
-                        return Some(content.to_string());
+            if let LogMsg::JsonPatch(patch) = msg
+                && let Some(entry) = ConversationPatch::extract_normalized_entry(patch)
+                && matches!(entry.entry_type, NormalizedEntryType::AssistantMessage)
+            {
+                let content = entry.content.trim();
+                if !content.is_empty() {
+                    if content.len() > max_bytes {
+                        return Some(format!(
+                            "{}...",
+                            truncate_to_char_boundary(content, max_bytes)
+                        ));
                     }
+                    return Some(content.to_string());
                 }
             }
         }
@@ -1223,30 +2679,21 @@ impl LocalContainerService {
         None
     }
 
-    /// Extract a NormalizedEntry from a JsonPatch if it contains one
-    fn extract_normalized_entry_from_patch(
-        &self,
-        patch: &json_patch::Patch,
-    ) -> Option<NormalizedEntry> {
-        // Convert the patch to JSON to examine its structure
-        if let Ok(patch_json) = serde_json::to_value(patch)
-            && let Some(operations) = patch_json.as_array()
-        {
-            for operation in operations {
-                if let Some(value) = operation.get("value") {
-                    // Try to extract a NormalizedEntry from the value
-                    if let Some(patch_type) = value.get("type").and_then(|t| t.as_str())
-                        && patch_type == "NORMALIZED_ENTRY"
-                        && let Some(content) = value.get("content")
-                        && let Ok(entry) =
-                            serde_json::from_value::<NormalizedEntry>(content.clone())
-                    {
-                        return Some(entry);
-                    }
-                }
-            }
-        }
-        None
+    /// Parse token usage and cost from the raw stdout of an execution
+    fn extract_token_usage(&self, exec_id: &Uuid) -> Option<TokenUsage> {
+        let msg_stores = self.msg_stores.try_read().ok()?;
+        let msg_store = msg_stores.get(exec_id)?;
+
+        let stdout: String = msg_store
+            .get_history()
+            .iter()
+            .filter_map(|msg| match msg {
+                LogMsg::Stdout(chunk) => Some(chunk.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        TokenUsage::from_stdout(&stdout)
     }
 
     /// Update the executor session summary with the final assistant message
@@ -1258,14 +2705,2702 @@ impl LocalContainerService {
         if let Some(session) = session {
             // Only update if summary is not already set
             if session.summary.is_none() {
-                if let Some(summary) = self.extract_last_assistant_message(exec_id) {
+                let max_bytes = self.config.read().await.execution_summary_max_bytes;
+                if let Some(summary) = self.extract_last_assistant_message(exec_id, max_bytes) {
                     ExecutorSession::update_summary(&self.db.pool, *exec_id, &summary).await?;
                 } else {
                     tracing::debug!("No assistant message found for execution {}", exec_id);
                 }
             }
+
+            // Agents that don't report usage leave the columns null
+            if let Some(usage) = self.extract_token_usage(exec_id) {
+                ExecutorSession::update_usage(&self.db.pool, *exec_id, &usage).await?;
+            }
         }
 
         Ok(())
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use command_group::AsyncCommandGroup;
+    use db::models::{
+        execution_process::CreateExecutionProcess,
+        executor_session::{CreateExecutorSession, ExecutorSession},
+        merge::MergeStatus,
+        project::{CreateProject, validate_prompt_template},
+        task::CreateTask,
+        task_attempt::{CreateTaskAttempt, TaskAttemptError},
+    };
+    use executors::{
+        actions::{
+            coding_agent_initial::CodingAgentInitialRequest,
+            script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        },
+        executors::BaseCodingAgent,
+        profile::ExecutorProfileId,
+    };
+    use services::services::{
+        container::{AttemptDiffStats, PullRequestOptions},
+        git::{CloneOptions, DiffStats},
+        git_cli::GitCli,
+        github_service::GitHubServiceError,
+        gitlab_service::GitLabServiceError,
+    };
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tokio::process::Command;
+    use utils::msg_store::MsgStoreHandle;
+
+    use super::*;
+
+    async fn test_container(config: Config) -> LocalContainerService {
+        // A single connection so every query sees the same in-memory database
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+        let image_service = ImageService::new(pool.clone(), config.image_max_size_bytes).unwrap();
+        LocalContainerService::new(
+            DBService { pool },
+            Arc::new(RwLock::new(HashMap::new())),
+            Arc::new(RwLock::new(config)),
+            GitService::new(),
+            image_service,
+            None,
+        )
+    }
+
+    fn quiet_config() -> Config {
+        let mut config = Config::default();
+        config.notifications.sound_enabled = false;
+        config.notifications.push_enabled = false;
+        config
+    }
+
+    /// Insert a project with one task (optionally continuing `parent_task_attempt`) and an
+    /// attempt on it
+    async fn create_task_attempt(
+        container: &LocalContainerService,
+        executor: BaseCodingAgent,
+        parent_task_attempt: Option<Uuid>,
+    ) -> (Task, TaskAttempt) {
+        let pool = &container.db.pool;
+        let project = Project::create(
+            pool,
+            &CreateProject {
+                name: "project".to_string(),
+                // Repo paths are unique per project
+                git_repo_path: format!("/tmp/project-{}", Uuid::new_v4()),
+                use_existing_repo: true,
+                setup_script: None,
+                dev_script: None,
+                cleanup_script: None,
+                copy_files: None,
+                dev_server_url_pattern: None,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let task = Task::create(
+            pool,
+            &CreateTask {
+                project_id: project.id,
+                title: "task".to_string(),
+                description: None,
+                parent_task_attempt,
+                image_ids: None,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let task_attempt = TaskAttempt::create(
+            pool,
+            &CreateTaskAttempt {
+                executor,
+                base_branch: "main".to_string(),
+                subpath: None,
+            },
+            task.id,
+        )
+        .await
+        .unwrap();
+        (task, task_attempt)
+    }
+
+    /// Insert a running setup script process and spawn `script` as its child
+    async fn spawn_running_process(
+        container: &LocalContainerService,
+        script: &str,
+    ) -> ExecutionProcess {
+        let pool = &container.db.pool;
+        let (_, task_attempt) =
+            create_task_attempt(container, BaseCodingAgent::ClaudeCode, None).await;
+        let execution_process = ExecutionProcess::create(
+            pool,
+            &CreateExecutionProcess {
+                task_attempt_id: task_attempt.id,
+                executor_action: ExecutorAction::new(
+                    ExecutorActionType::ScriptRequest(ScriptRequest {
+                        script: script.to_string(),
+                        language: ScriptRequestLanguage::Bash,
+                        context: ScriptContext::SetupScript,
+                    }),
+                    None,
+                ),
+                run_reason: ExecutionProcessRunReason::SetupScript,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+
+        let child = Command::new("sh")
+            .args(["-c", script])
+            .group_spawn()
+            .unwrap();
+        container
+            .add_child_to_store(execution_process.id, child)
+            .await;
+        execution_process
+    }
+
+    #[test]
+    fn exit_poll_interval_backs_off_to_the_cap() {
+        let mut interval = EXIT_POLL_INITIAL_INTERVAL;
+        let mut waited = Duration::ZERO;
+        // A process finishing within the first few checks is noticed quickly
+        for _ in 0..4 {
+            waited += interval;
+            interval = next_exit_poll_interval(interval);
+        }
+        assert_eq!(waited, Duration::from_millis(150));
+
+        for _ in 0..10 {
+            interval = next_exit_poll_interval(interval);
+        }
+        assert_eq!(interval, EXIT_POLL_MAX_INTERVAL);
+    }
+
+    /// How long after `since` the exit monitor recorded `execution_process` as finished
+    async fn exit_noticed_after(
+        container: &LocalContainerService,
+        execution_process: &ExecutionProcess,
+        since: Instant,
+    ) -> Duration {
+        let _monitor = container.spawn_exit_monitor(&execution_process.id);
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while ExecutionProcess::find_by_id(&container.db.pool, execution_process.id)
+                .await
+                .unwrap()
+                .is_some_and(|p| p.status == ExecutionProcessStatus::Running)
+            {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        since.elapsed()
+    }
+
+    #[tokio::test]
+    async fn exit_monitor_notices_real_exits_within_the_poll_cap() {
+        let container = test_container(quiet_config()).await;
+
+        let quick = spawn_running_process(&container, "true").await;
+        let elapsed = exit_noticed_after(&container, &quick, Instant::now()).await;
+        assert!(elapsed < Duration::from_millis(200), "{elapsed:?}");
+
+        // Long enough for polling to have backed off to the cap
+        let slow = spawn_running_process(&container, "sleep 1.2").await;
+        let elapsed = exit_noticed_after(&container, &slow, Instant::now()).await;
+        let latest =
+            Duration::from_millis(1200) + EXIT_POLL_MAX_INTERVAL + Duration::from_millis(150);
+        assert!(elapsed < latest, "{elapsed:?}");
+    }
+
+    #[test]
+    fn evicts_oldest_worktrees_until_under_limit() {
+        let candidate = |name: &str| (Uuid::new_v4(), name.to_string(), "/repo".to_string());
+        let candidates = vec![
+            candidate("/wt/oldest"),
+            candidate("/wt/gone"),
+            candidate("/wt/middle"),
+            candidate("/wt/newest"),
+        ];
+        let usage_by_path = HashMap::from([
+            ("/wt/oldest".to_string(), 300),
+            ("/wt/middle".to_string(), 500),
+            ("/wt/newest".to_string(), 1000),
+            ("/wt/running".to_string(), 200),
+        ]);
+
+        let evicted: Vec<_> =
+            LocalContainerService::select_evictions(candidates, &usage_by_path, 2000, 1500)
+                .into_iter()
+                .map(|(_, path, _, bytes)| (path, bytes))
+                .collect();
+        assert_eq!(
+            evicted,
+            [
+                ("/wt/oldest".to_string(), 300),
+                ("/wt/middle".to_string(), 500)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn running_attempts_are_not_evictable() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let running = spawn_running_process(&container, "sleep 30").await;
+        let idle = spawn_running_process(&container, "true").await;
+        ExecutionProcess::update_completion(
+            pool,
+            idle.id,
+            ExecutionProcessStatus::Completed,
+            Some(0),
+            None,
+        )
+        .await
+        .unwrap();
+        for (process, path) in [(&running, "/tmp/vk-running"), (&idle, "/tmp/vk-idle")] {
+            TaskAttempt::update_container_ref(pool, process.task_attempt_id, path)
+                .await
+                .unwrap();
+        }
+
+        let candidates = TaskAttempt::find_evictable_for_cleanup(pool).await.unwrap();
+        let attempt_ids: Vec<_> = candidates.iter().map(|(id, _, _)| *id).collect();
+        assert_eq!(attempt_ids, [idle.task_attempt_id]);
+
+        container
+            .stop_execution(&running, ExecutionProcessKillReason::User)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn user_stop_records_user_reason() {
+        let container = test_container(quiet_config()).await;
+        let execution_process = spawn_running_process(&container, "sleep 30").await;
+
+        container
+            .stop_execution(&execution_process, ExecutionProcessKillReason::User)
+            .await
+            .unwrap();
+
+        let stopped = ExecutionProcess::find_by_id(&container.db.pool, execution_process.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stopped.status, ExecutionProcessStatus::Killed);
+        assert_eq!(stopped.kill_reason, Some(ExecutionProcessKillReason::User));
+    }
+
+    #[tokio::test]
+    async fn timed_out_process_records_timeout_reason() {
+        let mut config = quiet_config();
+        config.setup_script_timeout_secs = 1;
+        let container = test_container(config).await;
+        let execution_process = spawn_running_process(&container, "sleep 30").await;
+
+        container
+            .spawn_exit_monitor(&execution_process.id)
+            .await
+            .unwrap();
+
+        let stopped = ExecutionProcess::find_by_id(&container.db.pool, execution_process.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stopped.status, ExecutionProcessStatus::Failed);
+        assert_eq!(
+            stopped.kill_reason,
+            Some(ExecutionProcessKillReason::Timeout)
+        );
+    }
+
+    #[tokio::test]
+    async fn stopping_while_the_exit_monitor_runs_does_not_panic_it() {
+        let container = test_container(quiet_config()).await;
+        for delay_ms in [0, 5, 20, 60] {
+            let execution_process = spawn_running_process(&container, "sleep 30").await;
+            let monitor = container.spawn_exit_monitor(&execution_process.id);
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+
+            container
+                .stop_execution(&execution_process, ExecutionProcessKillReason::User)
+                .await
+                .unwrap();
+            tokio::time::timeout(Duration::from_secs(5), monitor)
+                .await
+                .unwrap()
+                .unwrap();
+
+            let stopped = ExecutionProcess::find_by_id(&container.db.pool, execution_process.id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(stopped.status, ExecutionProcessStatus::Killed);
+        }
+    }
+
+    #[tokio::test]
+    async fn exit_monitor_for_an_already_removed_child_finishes_quietly() {
+        let container = test_container(quiet_config()).await;
+        let execution_process = spawn_running_process(&container, "true").await;
+        container
+            .remove_child_from_store(&execution_process.id)
+            .await;
+
+        container
+            .spawn_exit_monitor(&execution_process.id)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn msg_store_release_check_waits_for_lingering_holders() {
+        const CHECKS: [Duration; 2] = [Duration::from_millis(20), Duration::from_millis(20)];
+
+        let store = Arc::new(MsgStore::new());
+        store.track_handouts();
+        let handle = MsgStoreHandle::new(store.clone());
+        let released = LocalContainerService::spawn_msg_store_release_check(
+            Uuid::new_v4(),
+            Arc::downgrade(&store),
+            &CHECKS,
+        );
+        assert!(!released.await.unwrap());
+
+        let released = LocalContainerService::spawn_msg_store_release_check(
+            Uuid::new_v4(),
+            Arc::downgrade(&store),
+            &CHECKS,
+        );
+        drop(handle);
+        drop(store);
+        assert!(released.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn finished_or_stopped_processes_free_their_project_slot() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        for script in ["true", "sleep 30"] {
+            let execution_process = spawn_running_process(&container, script).await;
+            let project_id = ExecutionProcess::load_context(pool, execution_process.id)
+                .await
+                .unwrap()
+                .task
+                .project_id;
+            let permit = container
+                .execution_limiter
+                .acquire(project_id, None, Some(1))
+                .await;
+            container
+                .execution_permits
+                .write()
+                .await
+                .insert(execution_process.id, permit);
+            let next_start = container
+                .execution_limiter
+                .acquire(project_id, None, Some(1));
+            assert!(
+                tokio::time::timeout(Duration::from_millis(50), next_start)
+                    .await
+                    .is_err()
+            );
+
+            let monitor = container.spawn_exit_monitor(&execution_process.id);
+            if script == "sleep 30" {
+                container
+                    .stop_execution(&execution_process, ExecutionProcessKillReason::User)
+                    .await
+                    .unwrap();
+            }
+            monitor.await.unwrap();
+            assert!(container.execution_limiter.status().is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn process_exiting_on_its_own_has_no_kill_reason() {
+        let container = test_container(quiet_config()).await;
+        let execution_process = spawn_running_process(&container, "true").await;
+
+        container
+            .spawn_exit_monitor(&execution_process.id)
+            .await
+            .unwrap();
+
+        let finished = ExecutionProcess::find_by_id(&container.db.pool, execution_process.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(finished.status, ExecutionProcessStatus::Completed);
+        assert_eq!(finished.kill_reason, None);
+    }
+
+    #[tokio::test]
+    async fn externally_deleted_worktrees_are_marked_and_live_ones_kept() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let base_dir = std::env::temp_dir().join(format!("worktrees-{}", Uuid::new_v4()));
+        let mut attempts = Vec::new();
+        for i in 0..6 {
+            let worktree_path = base_dir.join(format!("wt-{i}"));
+            // Even-numbered worktrees stay on disk
+            if i % 2 == 0 {
+                std::fs::create_dir_all(&worktree_path).unwrap();
+            }
+            let (_, task_attempt) =
+                create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+            TaskAttempt::update_container_ref(
+                pool,
+                task_attempt.id,
+                &worktree_path.to_string_lossy(),
+            )
+            .await
+            .unwrap();
+            attempts.push((i, task_attempt.id));
+        }
+
+        LocalContainerService::check_externally_deleted_worktrees(&container.db)
+            .await
+            .unwrap();
+
+        for (i, attempt_id) in attempts {
+            let task_attempt = TaskAttempt::find_by_id(pool, attempt_id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(task_attempt.worktree_deleted, i % 2 == 1, "worktree {i}");
+        }
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn orphan_cleanup_keeps_only_referenced_worktrees() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let base_dir = std::env::temp_dir().join(format!("worktrees-{}", Uuid::new_v4()));
+        let referenced = base_dir.join("referenced");
+        let orphaned = base_dir.join("orphaned");
+        std::fs::create_dir_all(&referenced).unwrap();
+        std::fs::create_dir_all(&orphaned).unwrap();
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        TaskAttempt::update_container_ref(pool, task_attempt.id, &referenced.to_string_lossy())
+            .await
+            .unwrap();
+
+        container.cleanup_orphaned_worktrees_in(&base_dir).await;
+
+        assert!(referenced.exists());
+        assert!(!orphaned.exists());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ensure_container_exists_names_the_missing_field() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+
+        let err = container
+            .ensure_container_exists(&task_attempt)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ContainerError::MissingWorktree(id) if id == task_attempt.id));
+
+        TaskAttempt::update_container_ref(pool, task_attempt.id, "/tmp/unused-worktree")
+            .await
+            .unwrap();
+        let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let err = container
+            .ensure_container_exists(&task_attempt)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ContainerError::MissingBranch(id) if id == task_attempt.id));
+    }
+
+    #[tokio::test]
+    async fn attempt_is_running_while_its_child_is_tracked() {
+        let container = test_container(quiet_config()).await;
+        let execution_process = spawn_running_process(&container, "sleep 30").await;
+        let attempt_id = execution_process.task_attempt_id;
+        let task_attempt = TaskAttempt::find_by_id(&container.db.pool, attempt_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let (_, idle_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+
+        assert!(container.is_attempt_running(attempt_id).await.unwrap());
+        assert!(
+            container
+                .has_running_processes(task_attempt.task_id)
+                .await
+                .unwrap()
+        );
+        assert!(!container.is_attempt_running(idle_attempt.id).await.unwrap());
+
+        container
+            .stop_execution(&execution_process, ExecutionProcessKillReason::User)
+            .await
+            .unwrap();
+        assert!(!container.is_attempt_running(attempt_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn successful_setup_script_records_completion_without_chaining() {
+        let container = test_container(quiet_config()).await;
+        let execution_process = spawn_running_process(&container, "true").await;
+
+        container
+            .spawn_exit_monitor(&execution_process.id)
+            .await
+            .unwrap();
+
+        let pool = &container.db.pool;
+        let task_attempt = TaskAttempt::find_by_id(pool, execution_process.task_attempt_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(task_attempt.setup_completed_at.is_some());
+        // A standalone setup script has no next action to start
+        let processes = ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id)
+            .await
+            .unwrap();
+        assert_eq!(processes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn standalone_setup_re_run_leaves_the_task_status_alone() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+        Task::update_status(pool, task.id, TaskStatus::Done)
+            .await
+            .unwrap();
+
+        let executor_action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "true".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+            }),
+            None,
+        );
+        let execution_process = container
+            .start_execution(
+                &task_attempt,
+                &executor_action,
+                &ExecutionProcessRunReason::SetupScript,
+            )
+            .await
+            .unwrap();
+        let status = || async {
+            Task::find_by_id(pool, task.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .status
+        };
+        assert_eq!(status().await, TaskStatus::Done);
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while ExecutionProcess::find_by_id(pool, execution_process.id)
+                .await
+                .unwrap()
+                .is_some_and(|p| p.status == ExecutionProcessStatus::Running)
+            {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+        // Give the exit monitor time to finish its bookkeeping
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(status().await, TaskStatus::Done);
+
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_setup_script_does_not_record_completion() {
+        let container = test_container(quiet_config()).await;
+        let execution_process = spawn_running_process(&container, "exit 1").await;
+
+        container
+            .spawn_exit_monitor(&execution_process.id)
+            .await
+            .unwrap();
+
+        let task_attempt =
+            TaskAttempt::find_by_id(&container.db.pool, execution_process.task_attempt_id)
+                .await
+                .unwrap()
+                .unwrap();
+        assert!(task_attempt.setup_completed_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn spawned_script_receives_project_env_vars() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        Project::update_env_vars(
+            pool,
+            task.project_id,
+            &HashMap::from([("VK_PROJECT_VAR".to_string(), "from-project".to_string())]),
+        )
+        .await
+        .unwrap();
+        let worktree = std::env::temp_dir();
+        TaskAttempt::update_container_ref(pool, task_attempt.id, &worktree.to_string_lossy())
+            .await
+            .unwrap();
+        let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let executor_action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "echo \"$VK_PROJECT_VAR\"".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+            }),
+            None,
+        );
+        let execution_process = container
+            .start_execution(
+                &task_attempt,
+                &executor_action,
+                &ExecutionProcessRunReason::SetupScript,
+            )
+            .await
+            .unwrap();
+        // Wait for the exit monitor to record completion
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while ExecutionProcess::find_by_id(pool, execution_process.id)
+                .await
+                .unwrap()
+                .is_some_and(|p| p.status == ExecutionProcessStatus::Running)
+            {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let jsonl = container
+            .export_logs_jsonl(&execution_process.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let stdout: String = jsonl
+            .lines()
+            .filter_map(|line| {
+                let line: serde_json::Value = serde_json::from_str(line).unwrap();
+                line["Stdout"].as_str().map(str::to_string)
+            })
+            .collect();
+        assert_eq!(stdout, "from-project\n");
+    }
+
+    #[tokio::test]
+    async fn preview_execution_resolves_command_without_spawning() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        Project::update_env_vars(
+            pool,
+            task.project_id,
+            &HashMap::from([("VK_PROJECT_SECRET".to_string(), "hunter2".to_string())]),
+        )
+        .await
+        .unwrap();
+        let worktree = std::env::temp_dir().join(format!("preview-{}", task_attempt.id));
+        TaskAttempt::update_container_ref(pool, task_attempt.id, &worktree.to_string_lossy())
+            .await
+            .unwrap();
+        let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let agent_action = ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: "fix the bug".to_string(),
+                executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+                executor_override: None,
+                resume_session_id: None,
+                agent_instructions: None,
+            }),
+            None,
+        );
+        let preview = container
+            .preview_execution(&task_attempt, &agent_action)
+            .await
+            .unwrap();
+        assert!(preview.command.contains("--output-format=stream-json"));
+        assert_eq!(preview.working_dir, worktree.to_string_lossy());
+        assert!(preview.prompt.unwrap().starts_with("fix the bug"));
+        assert_eq!(preview.env["VK_PROJECT_SECRET"], "***");
+
+        let script_action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "npm install".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+            }),
+            None,
+        );
+        let preview = container
+            .preview_execution(&task_attempt, &script_action)
+            .await
+            .unwrap();
+        assert_eq!(preview.command, "npm install");
+        assert_eq!(preview.prompt, None);
+        assert!(!serde_json::to_string(&preview).unwrap().contains("hunter2"));
+
+        // Nothing was recorded or started, and the worktree was never created
+        assert!(
+            ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!worktree.exists());
+    }
+
+    #[tokio::test]
+    async fn agent_instructions_use_system_prompt_or_lead_the_prompt() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        TaskAttempt::update_container_ref(pool, task_attempt.id, "/tmp/instructions-worktree")
+            .await
+            .unwrap();
+        let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let action_for = |executor| {
+            ExecutorAction::new(
+                ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                    prompt: "fix the bug".to_string(),
+                    executor_profile_id: ExecutorProfileId::new(executor),
+                    executor_override: None,
+                    resume_session_id: None,
+                    agent_instructions: Some("Always run the tests.".to_string()),
+                }),
+                None,
+            )
+        };
+
+        let claude = container
+            .preview_execution(&task_attempt, &action_for(BaseCodingAgent::ClaudeCode))
+            .await
+            .unwrap();
+        assert!(claude.command.contains("--append-system-prompt"));
+        assert!(claude.env.contains_key("VK_AGENT_INSTRUCTIONS"));
+        assert!(!claude.env.contains_key("NODE_NO_WARNINGS"));
+        assert!(claude.prompt.unwrap().starts_with("fix the bug"));
+
+        let gemini = container
+            .preview_execution(&task_attempt, &action_for(BaseCodingAgent::Gemini))
+            .await
+            .unwrap();
+        assert!(!gemini.command.contains("--append-system-prompt"));
+        // The fixed environment Gemini is spawned with
+        assert!(gemini.env.contains_key("NODE_NO_WARNINGS"));
+        assert!(
+            gemini
+                .prompt
+                .unwrap()
+                .starts_with("Always run the tests.\n\nfix the bug")
+        );
+    }
+
+    #[tokio::test]
+    async fn prompt_template_is_filled_in_and_unset_falls_back_to_default() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        let project = Project::update_prompt_template(
+            pool,
+            task.project_id,
+            Some("Follow the style guide.\n{title} ({base_branch}, {repo_path}){description}"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            task.to_prompt_with_context(pool, &task_attempt)
+                .await
+                .unwrap(),
+            format!(
+                "Follow the style guide.\ntask (main, {})",
+                project.git_repo_path.display()
+            )
+        );
+
+        Project::update_prompt_template(pool, task.project_id, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            task.to_prompt_with_context(pool, &task_attempt)
+                .await
+                .unwrap(),
+            task.to_prompt()
+        );
+
+        assert!(validate_prompt_template("{title}: {{literal}}").is_ok());
+        assert_eq!(
+            validate_prompt_template("{title} by {author}"),
+            Err("Unknown placeholder '{author}'".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn retryable_agent_failure_is_re_run_and_others_finalize() {
+        let mut config = quiet_config();
+        config.agent_retry.max_retries = 2;
+        config.agent_retry.backoff_secs = 0;
+        let container = test_container(config).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, _) = create_mergeable_attempt(&container, false).await;
+        let marker = std::env::temp_dir().join(format!("vk-retry-{}", Uuid::new_v4()));
+        let run_agent = |script: String| {
+            let executor_action = ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script,
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::SetupScript,
+                }),
+                None,
+            );
+            let container = &container;
+            let task_attempt = &task_attempt;
+            async move {
+                container
+                    .start_execution(
+                        task_attempt,
+                        &executor_action,
+                        &ExecutionProcessRunReason::CodingAgent,
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
+        let wait_for_finished = |count: usize| async move {
+            tokio::time::timeout(Duration::from_secs(10), async {
+                loop {
+                    let processes =
+                        ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id)
+                            .await
+                            .unwrap();
+                    if processes.len() == count
+                        && processes
+                            .iter()
+                            .all(|p| p.status != ExecutionProcessStatus::Running)
+                    {
+                        return processes;
+                    }
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+            })
+            .await
+            .unwrap()
+        };
+
+        // Fails like an overloaded provider the first time, then succeeds
+        let first = run_agent(format!(
+            "if [ -e '{0}' ]; then echo done; else touch '{0}'; \
+             echo '{{\"type\":\"error\",\"error\":{{\"type\":\"overloaded_error\"}}}}'; \
+             exit 1; fi",
+            marker.display()
+        ))
+        .await;
+        let processes = wait_for_finished(2).await;
+        std::fs::remove_file(&marker).unwrap();
+        assert_eq!(processes[0].id, first.id);
+        assert_eq!(processes[0].status, ExecutionProcessStatus::Failed);
+        assert_eq!(processes[0].retry_count, 0);
+        assert_eq!(processes[1].status, ExecutionProcessStatus::Completed);
+        assert_eq!(processes[1].retry_count, 1);
+
+        // Other failures finalize as before, even when they mention a status code
+        run_agent("echo 'Error: 503 in the handler' >&2; exit 1".to_string()).await;
+        let processes = wait_for_finished(3).await;
+        assert_eq!(processes[2].status, ExecutionProcessStatus::Failed);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(
+            ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+        let task = Task::find_by_id(pool, task.id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::InReview);
+    }
+
+    /// Start a coding agent on `task_attempt` in the background and wait for it to queue
+    async fn start_queued_agent(
+        container: &LocalContainerService,
+        task_attempt: &TaskAttempt,
+    ) -> (
+        ExecutionProcess,
+        JoinHandle<Result<ExecutionProcess, ContainerError>>,
+    ) {
+        let executor_action = ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: "fix the bug".to_string(),
+                executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+                executor_override: None,
+                resume_session_id: None,
+                agent_instructions: None,
+            }),
+            None,
+        );
+        let start = {
+            let container = container.clone();
+            let task_attempt = task_attempt.clone();
+            tokio::spawn(async move {
+                container
+                    .start_execution(
+                        &task_attempt,
+                        &executor_action,
+                        &ExecutionProcessRunReason::CodingAgent,
+                    )
+                    .await
+            })
+        };
+        let queued = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let processes =
+                    ExecutionProcess::find_by_task_attempt_id(&container.db.pool, task_attempt.id)
+                        .await
+                        .unwrap();
+                if let Some(process) = processes
+                    .into_iter()
+                    .find(|p| p.status == ExecutionProcessStatus::Queued)
+                {
+                    return process;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .unwrap();
+        (queued, start)
+    }
+
+    #[tokio::test]
+    async fn stopping_a_queued_start_cancels_it_before_it_spawns() {
+        let mut config = quiet_config();
+        config.max_concurrent_executions = Some(1);
+        let container = test_container(config).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, _) = create_mergeable_attempt(&container, false).await;
+        // Another agent holds the only slot
+        let held = container
+            .execution_limiter
+            .acquire(task.project_id, Some(1), None)
+            .await;
+
+        let (queued, start) = start_queued_agent(&container, &task_attempt).await;
+        container
+            .stop_execution(&queued, ExecutionProcessKillReason::User)
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), start)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let stopped = ExecutionProcess::find_by_id(pool, queued.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stopped.status, ExecutionProcessStatus::Killed);
+        assert_eq!(stopped.kill_reason, Some(ExecutionProcessKillReason::User));
+        let task = Task::find_by_id(pool, task.id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::InReview);
+
+        // Freeing the slot afterwards doesn't start it after all
+        drop(held);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(container.get_child_from_store(&queued.id).await.is_none());
+        assert!(container.execution_limiter.status().is_empty());
+    }
+
+    #[tokio::test]
+    async fn queued_and_retrying_agents_count_as_running() {
+        let mut config = quiet_config();
+        config.max_concurrent_executions = Some(1);
+        let container = test_container(config).await;
+        let (task, task_attempt, _) = create_mergeable_attempt(&container, false).await;
+        let _held = container
+            .execution_limiter
+            .acquire(task.project_id, Some(1), None)
+            .await;
+
+        let (queued, start) = start_queued_agent(&container, &task_attempt).await;
+        assert!(container.is_attempt_running(task_attempt.id).await.unwrap());
+        assert!(container.has_running_processes(task.id).await.unwrap());
+
+        container
+            .stop_execution(&queued, ExecutionProcessKillReason::User)
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), start)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(!container.is_attempt_running(task_attempt.id).await.unwrap());
+
+        // Between a retryable failure and its re-run nothing is spawned or queued
+        container
+            .spawn_agent_retry(queued.id, Duration::from_secs(60))
+            .await;
+        assert!(container.is_attempt_running(task_attempt.id).await.unwrap());
+
+        // Stopping the attempt, as deleting it does, cancels the pending re-run
+        container.try_stop(&task_attempt).await;
+        assert!(!container.is_attempt_running(task_attempt.id).await.unwrap());
+        container
+            .spawn_agent_retry(queued.id, Duration::from_millis(50))
+            .await;
+        container
+            .stop_execution(&queued, ExecutionProcessKillReason::User)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(
+            ExecutionProcess::find_by_task_attempt_id(&container.db.pool, task_attempt.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limited_start_waits_queued_without_holding_a_slot() {
+        let mut config = quiet_config();
+        config
+            .executor_rate_limits
+            .insert("CLAUDE_CODE".to_string(), 1);
+        let container = test_container(config).await;
+        let (_, task_attempt, _) = create_mergeable_attempt(&container, false).await;
+        assert!(container.rate_limiter.try_acquire("CLAUDE_CODE", 1).await);
+
+        let (queued, start) = start_queued_agent(&container, &task_attempt).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(container.execution_limiter.status().is_empty());
+        assert_eq!(container.rate_limiter.status().await[0].waiting, 1);
+
+        container
+            .stop_execution(&queued, ExecutionProcessKillReason::User)
+            .await
+            .unwrap();
+        start.await.unwrap().unwrap();
+        assert_eq!(container.rate_limiter.status().await[0].waiting, 0);
+    }
+
+    #[tokio::test]
+    async fn browser_chat_runs_with_the_configured_node() {
+        use std::os::unix::fs::PermissionsExt;
+
+        use executors::actions::browser_chat_request::{BrowserChatAgentType, BrowserChatRequest};
+
+        let dir = std::env::temp_dir().join(format!("vk-node-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let marker = dir.join("ran");
+        let node = dir.join("node");
+        std::fs::write(
+            &node,
+            format!(
+                "#!/bin/sh\n[ \"$1\" = --version ] && {{ echo v22.0.0; exit 0; }}\necho \"$@\" > '{}'\n",
+                marker.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&node, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mut config = quiet_config();
+        config.browser_chat_node_path = Some(node.to_string_lossy().to_string());
+        let container = test_container(config).await;
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeBrowserChat, None).await;
+
+        let executor_action = ExecutorAction::new(
+            ExecutorActionType::BrowserChatRequest(BrowserChatRequest {
+                message: "hello".to_string(),
+                agent_type: BrowserChatAgentType::Claude,
+                executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeBrowserChat),
+                session_id: None,
+                node_path: None,
+            }),
+            None,
+        );
+        container
+            .start_execution(
+                &task_attempt,
+                &executor_action,
+                &ExecutionProcessRunReason::BrowserChat,
+            )
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !marker.exists() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn execution_timeline_lists_processes_in_order() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        let mut processes = Vec::new();
+        for run_reason in [
+            ExecutionProcessRunReason::SetupScript,
+            ExecutionProcessRunReason::CodingAgent,
+        ] {
+            let execution_process = ExecutionProcess::create(
+                pool,
+                &CreateExecutionProcess {
+                    task_attempt_id: task_attempt.id,
+                    executor_action: ExecutorAction::new(
+                        ExecutorActionType::ScriptRequest(ScriptRequest {
+                            script: "true".to_string(),
+                            language: ScriptRequestLanguage::Bash,
+                            context: ScriptContext::SetupScript,
+                        }),
+                        None,
+                    ),
+                    run_reason,
+                },
+                Uuid::new_v4(),
+            )
+            .await
+            .unwrap();
+            processes.push(execution_process);
+        }
+        ExecutionProcess::update_completion(
+            pool,
+            processes[0].id,
+            ExecutionProcessStatus::Completed,
+            Some(0),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let timeline = TaskAttempt::execution_timeline(pool, task_attempt.id)
+            .await
+            .unwrap();
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].execution_process_id, processes[0].id);
+        assert_eq!(
+            timeline[0].run_reason,
+            ExecutionProcessRunReason::SetupScript
+        );
+        assert_eq!(timeline[0].status, ExecutionProcessStatus::Completed);
+        assert_eq!(timeline[0].exit_code, Some(0));
+        assert!(timeline[0].completed_at.is_some());
+        assert!(timeline[0].duration_ms >= 0);
+        assert_eq!(timeline[1].execution_process_id, processes[1].id);
+        assert_eq!(
+            timeline[1].run_reason,
+            ExecutionProcessRunReason::CodingAgent
+        );
+        assert_eq!(timeline[1].status, ExecutionProcessStatus::Running);
+        assert_eq!(timeline[1].completed_at, None);
+    }
+
+    /// Clone a fresh upstream into the project's repo path, then commit `upstream.txt`
+    /// upstream so the project's `main` is one commit behind its remote
+    async fn create_attempt_behind_upstream(
+        container: &LocalContainerService,
+    ) -> (TaskAttempt, PathBuf) {
+        let pool = &container.db.pool;
+        let (task, task_attempt) =
+            create_task_attempt(container, BaseCodingAgent::ClaudeCode, None).await;
+        let repo_path = Project::find_by_id(pool, task.project_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .git_repo_path;
+        let upstream_path = PathBuf::from(format!("{}-upstream", repo_path.display()));
+        let git = GitService::new();
+        git.initialize_repo_with_main_branch(&upstream_path)
+            .unwrap();
+        GitService::clone_repository(
+            &format!("file://{}", upstream_path.display()),
+            &repo_path,
+            &CloneOptions::default(),
+            |_| {},
+        )
+        .unwrap();
+        std::fs::write(upstream_path.join("upstream.txt"), "new\n").unwrap();
+        git.commit(&upstream_path, "upstream change").unwrap();
+        (task_attempt, repo_path)
+    }
+
+    #[tokio::test]
+    async fn create_fetches_the_base_branch_when_enabled() {
+        let mut config = quiet_config();
+        config.fetch_before_create = true;
+        let container = test_container(config).await;
+        let (task_attempt, repo_path) = create_attempt_behind_upstream(&container).await;
+
+        let worktree_path = PathBuf::from(container.create(&task_attempt).await.unwrap());
+
+        assert!(worktree_path.join("upstream.txt").exists());
+        // The local base branch itself is brought up to date, so diffs and merges
+        // against it see the same base the attempt started from
+        let git = GitService::new();
+        assert_eq!(
+            git.get_branch_oid(&repo_path, "main").unwrap(),
+            git.get_branch_oid(&repo_path, "origin/main").unwrap()
+        );
+        assert!(repo_path.join("upstream.txt").exists());
+        WorktreeManager::cleanup_worktree(&worktree_path, None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn create_uses_the_local_base_branch_when_fetch_is_disabled_or_fails() {
+        let container = test_container(quiet_config()).await;
+        let (task_attempt, _) = create_attempt_behind_upstream(&container).await;
+        let worktree_path = PathBuf::from(container.create(&task_attempt).await.unwrap());
+        assert!(!worktree_path.join("upstream.txt").exists());
+        WorktreeManager::cleanup_worktree(&worktree_path, None)
+            .await
+            .unwrap();
+
+        // An unreachable remote falls back to the local ref instead of failing
+        let mut config = quiet_config();
+        config.fetch_before_create = true;
+        let container = test_container(config).await;
+        let (task_attempt, repo_path) = create_attempt_behind_upstream(&container).await;
+        let unreachable = ["remote", "set-url", "origin", "file:///nonexistent"];
+        GitCli::new().git(&repo_path, unreachable).unwrap();
+        let worktree_path = PathBuf::from(container.create(&task_attempt).await.unwrap());
+        assert!(!worktree_path.join("upstream.txt").exists());
+        WorktreeManager::cleanup_worktree(&worktree_path, None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn retry_attempt_starts_a_fresh_attempt_with_the_initial_profile() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+        // A slow setup script keeps the retried agent itself from being spawned
+        let project = Project::find_by_id(pool, task.project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        Project::update(
+            pool,
+            project.id,
+            project.name,
+            project.git_repo_path.to_string_lossy().into_owned(),
+            Some("sleep 5".to_string()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let profile = ExecutorProfileId {
+            executor: BaseCodingAgent::ClaudeCode,
+            variant: Some("PLAN".to_string()),
+        };
+        ExecutionProcess::create(
+            pool,
+            &CreateExecutionProcess {
+                task_attempt_id: task_attempt.id,
+                executor_action: ExecutorAction::new(
+                    ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                        prompt: "task".to_string(),
+                        executor_profile_id: profile.clone(),
+                        executor_override: None,
+                        resume_session_id: None,
+                        agent_instructions: None,
+                    }),
+                    None,
+                ),
+                run_reason: ExecutionProcessRunReason::CodingAgent,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+
+        let retried = container.retry_attempt(&task_attempt, None).await.unwrap();
+
+        assert_ne!(retried.id, task_attempt.id);
+        assert_eq!(retried.task_id, task.id);
+        assert_eq!(retried.base_branch, task_attempt.base_branch);
+        let task = Task::find_by_id(pool, task.id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::InProgress);
+        let processes = ExecutionProcess::find_by_task_attempt_id(pool, retried.id)
+            .await
+            .unwrap();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(
+            processes[0].run_reason,
+            ExecutionProcessRunReason::SetupScript
+        );
+        let agent = processes[0]
+            .executor_action()
+            .unwrap()
+            .next_action()
+            .unwrap();
+        assert!(matches!(
+            &agent.typ,
+            ExecutorActionType::CodingAgentInitialRequest(request)
+                if request.executor_profile_id == profile
+        ));
+
+        // The original and the retry are both live, so a limit of two refuses another
+        let err = container
+            .retry_attempt(&task_attempt, Some(2))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerError::TaskAttemptError(TaskAttemptError::AttemptLimitReached(2))
+        ));
+
+        container.try_stop(&retried).await;
+        let retried = TaskAttempt::find_by_id(pool, retried.id)
+            .await
+            .unwrap()
+            .unwrap();
+        WorktreeManager::cleanup_worktree(&PathBuf::from(retried.container_ref.unwrap()), None)
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn merged_section_separates_merged_diff_from_new_work() {
+        let container = test_container(quiet_config()).await;
+        let (_, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+        let merge_commit = container
+            .merge_attempt(&task_attempt, &quiet_config())
+            .await
+            .unwrap();
+
+        let patches = container
+            .merged_section_patches(&repo_path, &merge_commit, None)
+            .unwrap();
+
+        let mut state = json!({ "entries": {} });
+        for patch in &patches {
+            json_patch::patch(&mut state, patch).unwrap();
+        }
+        assert_eq!(state["commit"]["sha"], merge_commit);
+        assert_eq!(state["merged"]["shared.txt"]["type"], "DIFF");
+        assert_eq!(state["sinceMerge"], merge_commit);
+        assert_eq!(state["entries"], json!({}));
+        // The boundary marker comes last, after the merged diff
+        assert_eq!(patches.len(), 2);
+        let marker = serde_json::to_string(&patches[1]).unwrap();
+        assert!(marker.contains("sinceMerge"));
+    }
+
+    #[tokio::test]
+    async fn refresh_diff_resends_the_diff_once_per_burst() {
+        let container = test_container(quiet_config()).await;
+        let (_, task_attempt, _) = create_mergeable_attempt(&container, false).await;
+        assert!(!container.refresh_diff(&task_attempt).await);
+
+        let mut stream = container.get_diff(&task_attempt).await.unwrap();
+        // Initial snapshot: the single changed file
+        let _ = stream.next().await.unwrap().unwrap();
+
+        // Two refreshes before the stream gets to them produce one snapshot
+        assert!(container.refresh_diff(&task_attempt).await);
+        assert!(container.refresh_diff(&task_attempt).await);
+        let snapshot = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("refresh produced no snapshot")
+            .unwrap()
+            .unwrap();
+        assert!(format!("{snapshot:?}").contains(r#"\"op\":\"replace\",\"path\":\"/entries\""#));
+        assert!(
+            tokio::time::timeout(Duration::from_millis(1000), stream.next())
+                .await
+                .is_err()
+        );
+
+        drop(stream);
+        // The refresher goes with the attempt's last stream
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(
+            !container
+                .diff_refreshers
+                .read()
+                .await
+                .contains_key(&task_attempt.id)
+        );
+        assert!(!container.refresh_diff(&task_attempt).await);
+    }
+
+    #[tokio::test]
+    async fn broken_watcher_sends_an_error_event_before_the_diff_stream_ends() {
+        let container = test_container(quiet_config()).await;
+        let (_, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+
+        let mut stream = container.get_diff(&task_attempt).await.unwrap();
+        // The initial snapshot is taken up front; the watcher only starts once polled
+        let worktree_path = PathBuf::from(task_attempt.container_ref.clone().unwrap());
+        std::fs::remove_dir_all(&worktree_path).unwrap();
+        let _ = stream.next().await.unwrap().unwrap();
+
+        let error = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("no error event")
+            .unwrap()
+            .unwrap();
+        let error = format!("{error:?}");
+        assert!(error.contains("event: stream_error"), "{error}");
+        assert!(error.contains("Live diff stopped"), "{error}");
+        assert!(
+            tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn watcher_error_while_streaming_ends_the_live_diff_with_an_error_event() {
+        let container = test_container(quiet_config()).await;
+        let (_, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+        let worktree_path = PathBuf::from(task_attempt.container_ref.clone().unwrap());
+
+        // The watcher started fine and fails later, e.g. when the OS watch limit is hit
+        let events = futures::stream::iter(vec![Err(vec![notify::Error::generic(
+            "watch limit reached",
+        )])]);
+        let (_refresh_tx, refresh_rx) = watch::channel(());
+        let updates = LocalContainerService::live_diff_updates(
+            container.git().clone(),
+            events,
+            worktree_path.clone(),
+            LiveDiffTarget {
+                worktree_path,
+                task_branch: task_attempt.branch.clone().unwrap(),
+                base_branch: task_attempt.base_branch.clone(),
+                subpath: None,
+            },
+            refresh_rx,
+        );
+        let updates: Vec<_> = tokio::time::timeout(Duration::from_secs(5), updates.collect())
+            .await
+            .expect("live diff did not end");
+
+        assert_eq!(updates.len(), 1);
+        let error = format!("{:?}", updates[0].as_ref().unwrap());
+        assert!(error.contains("event: stream_error"), "{error}");
+        assert!(
+            error.contains("Live diff stopped: watch limit reached"),
+            "{error}"
+        );
+
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn task_diff_stats_covers_every_attempt_and_follows_the_branch_tip() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+        let unstarted = TaskAttempt::create(
+            pool,
+            &CreateTaskAttempt {
+                executor: BaseCodingAgent::ClaudeCode,
+                base_branch: "main".to_string(),
+                subpath: None,
+            },
+            task.id,
+        )
+        .await
+        .unwrap();
+
+        let stats_of = |all: &[AttemptDiffStats], id: Uuid| {
+            all.iter().find(|s| s.task_attempt_id == id).unwrap().stats
+        };
+        let first = container.task_diff_stats(task.id).await.unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(
+            stats_of(&first, task_attempt.id),
+            Some(DiffStats {
+                files_changed: 1,
+                insertions: 1,
+                deletions: 1,
+                commits: 1,
+            })
+        );
+        assert_eq!(stats_of(&first, unstarted.id), None);
+
+        let again = container.task_diff_stats(task.id).await.unwrap();
+        assert_eq!(
+            stats_of(&again, task_attempt.id),
+            stats_of(&first, task_attempt.id)
+        );
+
+        let worktree_path = PathBuf::from(format!("{}-worktree", repo_path.display()));
+        std::fs::write(worktree_path.join("new.txt"), "one\ntwo\n").unwrap();
+        GitService::new().commit(&worktree_path, "more").unwrap();
+        let moved = container.task_diff_stats(task.id).await.unwrap();
+        assert_eq!(
+            stats_of(&moved, task_attempt.id),
+            Some(DiffStats {
+                files_changed: 2,
+                insertions: 3,
+                deletions: 1,
+                commits: 2,
+            })
+        );
+
+        // Deleting the attempt drops its cached stats
+        container.delete(&task_attempt).await.unwrap();
+        assert!(
+            !container
+                .diff_stats_cache
+                .read()
+                .unwrap()
+                .contains_key(&task_attempt.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn dirty_worktree_is_refused_or_stashed_per_config() {
+        let worktree = std::env::temp_dir().join(format!("vk-dirty-{}", Uuid::new_v4()));
+        let git = GitService::new();
+        git.initialize_repo_with_main_branch(&worktree).unwrap();
+        std::fs::write(worktree.join("notes.txt"), "original\n").unwrap();
+        git.commit(&worktree, "add notes").unwrap();
+        std::fs::write(worktree.join("notes.txt"), "manual edit\n").unwrap();
+
+        let mut config = quiet_config();
+        config.on_dirty_worktree = DirtyWorktreePolicy::Refuse;
+        let container = test_container(config).await;
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        let err = container
+            .handle_dirty_worktree(&task_attempt, &worktree)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerError::GitServiceError(GitServiceError::WorktreeDirty(_, ref files))
+                if files == "notes.txt"
+        ));
+
+        container.config.write().await.on_dirty_worktree = DirtyWorktreePolicy::Stash;
+        container
+            .handle_dirty_worktree(&task_attempt, &worktree)
+            .await
+            .unwrap();
+        assert!(git.is_worktree_clean(&worktree).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(worktree.join("notes.txt")).unwrap(),
+            "original\n"
+        );
+        // The edits are kept under the attempt's own ref, not the shared stash
+        let cli = GitCli::new();
+        let stash_ref = format!("refs/vibe-kanban/stash/{}", task_attempt.id);
+        assert_eq!(cli.git(&worktree, ["stash", "list"]).unwrap(), "");
+        cli.git(&worktree, ["stash", "apply", &stash_ref]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(worktree.join("notes.txt")).unwrap(),
+            "manual edit\n"
+        );
+
+        std::fs::remove_dir_all(&worktree).unwrap();
+    }
+
+    #[tokio::test]
+    async fn branch_status_counts_commits_ahead_and_behind_base() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        let status = container
+            .attempt_branch_status(&task_attempt)
+            .await
+            .unwrap();
+        assert!(!status.branches_exist, "attempt has no branch yet");
+
+        let repo_path = Project::find_by_id(pool, task.project_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .git_repo_path;
+        let git = GitService::new();
+        git.initialize_repo_with_main_branch(&repo_path).unwrap();
+        git.create_branch(&repo_path, "feature").unwrap();
+        git.checkout_branch(&repo_path, "feature").unwrap();
+        for file in ["a.txt", "b.txt"] {
+            std::fs::write(repo_path.join(file), file).unwrap();
+            git.commit(&repo_path, file).unwrap();
+        }
+        git.checkout_branch(&repo_path, "main").unwrap();
+        std::fs::write(repo_path.join("c.txt"), "c").unwrap();
+        git.commit(&repo_path, "c.txt").unwrap();
+        TaskAttempt::update_branch(pool, task_attempt.id, "feature")
+            .await
+            .unwrap();
+        let mut task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let status = container
+            .attempt_branch_status(&task_attempt)
+            .await
+            .unwrap();
+        assert!(status.branches_exist);
+        assert_eq!((status.commits_ahead, status.commits_behind), (2, 1));
+
+        task_attempt.base_branch = "gone".to_string();
+        let status = container
+            .attempt_branch_status(&task_attempt)
+            .await
+            .unwrap();
+        assert!(!status.branches_exist);
+        assert_eq!((status.commits_ahead, status.commits_behind), (0, 0));
+
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    /// Give the attempt a real repo with a `feature` worktree one commit ahead of main. With
+    /// `conflicting`, main and feature both change the same file.
+    async fn create_mergeable_attempt(
+        container: &LocalContainerService,
+        conflicting: bool,
+    ) -> (Task, TaskAttempt, PathBuf) {
+        let pool = &container.db.pool;
+        let (task, task_attempt) =
+            create_task_attempt(container, BaseCodingAgent::ClaudeCode, None).await;
+        let repo_path = Project::find_by_id(pool, task.project_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .git_repo_path;
+        let worktree_path = PathBuf::from(format!("{}-worktree", repo_path.display()));
+        let git = GitService::new();
+        git.initialize_repo_with_main_branch(&repo_path).unwrap();
+        std::fs::write(repo_path.join("shared.txt"), "base\n").unwrap();
+        git.commit(&repo_path, "base").unwrap();
+        git.create_branch(&repo_path, "feature").unwrap();
+        git.add_worktree(&repo_path, &worktree_path, "feature", false)
+            .unwrap();
+        std::fs::write(worktree_path.join("shared.txt"), "feature\n").unwrap();
+        git.commit(&worktree_path, "feature").unwrap();
+        if conflicting {
+            std::fs::write(repo_path.join("shared.txt"), "main\n").unwrap();
+            git.commit(&repo_path, "main").unwrap();
+        }
+
+        TaskAttempt::update_container_ref(pool, task_attempt.id, &worktree_path.to_string_lossy())
+            .await
+            .unwrap();
+        TaskAttempt::update_branch(pool, task_attempt.id, "feature")
+            .await
+            .unwrap();
+        let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        (task, task_attempt, repo_path)
+    }
+
+    #[tokio::test]
+    async fn file_change_diff_is_retried_until_the_worktree_settles() {
+        let container = test_container(quiet_config()).await;
+        let (_, task_attempt, _) = create_mergeable_attempt(&container, false).await;
+        let worktree_path = PathBuf::from(task_attempt.container_ref.clone().unwrap());
+        std::fs::write(worktree_path.join("new.txt"), "new\n").unwrap();
+        // A nested repository without commits can't be staged until it goes away
+        let nested = worktree_path.join("nested");
+        let init = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .arg(&nested)
+            .status()
+            .unwrap();
+        assert!(init.success());
+        let settle = {
+            let nested = nested.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                std::fs::remove_dir_all(nested).unwrap();
+            })
+        };
+
+        let events = LocalContainerService::process_file_changes(
+            &GitService::new(),
+            &worktree_path,
+            "feature",
+            "main",
+            &["new.txt".to_string()],
+        )
+        .await
+        .unwrap();
+        settle.await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(!nested.exists());
+    }
+
+    #[tokio::test]
+    async fn stop_records_head_of_a_subpath_attempt_from_the_worktree_root() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (_, task_attempt, _) = create_mergeable_attempt(&container, false).await;
+        let worktree_path = PathBuf::from(task_attempt.container_ref.clone().unwrap());
+        std::fs::create_dir(worktree_path.join("app")).unwrap();
+        sqlx::query("UPDATE task_attempts SET subpath = 'app' WHERE id = ?")
+            .bind(task_attempt.id)
+            .execute(pool)
+            .await
+            .unwrap();
+        let execution_process = ExecutionProcess::create(
+            pool,
+            &CreateExecutionProcess {
+                task_attempt_id: task_attempt.id,
+                executor_action: ExecutorAction::new(
+                    ExecutorActionType::ScriptRequest(ScriptRequest {
+                        script: "sleep 30".to_string(),
+                        language: ScriptRequestLanguage::Bash,
+                        context: ScriptContext::SetupScript,
+                    }),
+                    None,
+                ),
+                run_reason: ExecutionProcessRunReason::SetupScript,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let child = Command::new("sh")
+            .args(["-c", "sleep 30"])
+            .group_spawn()
+            .unwrap();
+        container
+            .add_child_to_store(execution_process.id, child)
+            .await;
+
+        container
+            .stop_execution(&execution_process, ExecutionProcessKillReason::User)
+            .await
+            .unwrap();
+
+        let head = GitService::new().get_head_info(&worktree_path).unwrap();
+        let execution_process = ExecutionProcess::find_by_id(pool, execution_process.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(execution_process.after_head_commit, Some(head.oid));
+    }
+
+    #[tokio::test]
+    async fn merge_attempt_records_merge_and_completes_task() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+
+        let merge_commit = container
+            .merge_attempt(&task_attempt, &quiet_config())
+            .await
+            .unwrap();
+
+        let git = GitService::new();
+        assert_eq!(
+            git.get_branch_oid(&repo_path, "main").unwrap(),
+            merge_commit
+        );
+        let merges = Merge::find_by_task_attempt_id(pool, task_attempt.id)
+            .await
+            .unwrap();
+        assert!(matches!(
+            merges.as_slice(),
+            [Merge::Direct(merge)] if merge.merge_commit == merge_commit
+        ));
+        let task = Task::find_by_id(pool, task.id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Done);
+        let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(task_attempt.worktree_deleted);
+        assert!(!Path::new(task_attempt.container_ref.as_deref().unwrap()).exists());
+
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn scratch_attempt_has_no_branch_diff_or_merge() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+
+        let container_ref = container.create_scratch(&task_attempt).await.unwrap();
+        let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(task_attempt.scratch);
+        assert!(task_attempt.branch.is_none());
+        assert_eq!(
+            task_attempt.container_ref.as_deref(),
+            Some(container_ref.as_str())
+        );
+        assert!(Path::new(&container_ref).is_dir());
+
+        let events: Vec<_> = container
+            .get_diff(&task_attempt)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        // Just the empty header and `Finished`
+        assert_eq!(events.len(), 2);
+        assert!(
+            container
+                .attempt_commits(&task_attempt)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            container
+                .merge_attempt(&task_attempt, &quiet_config())
+                .await,
+            Err(ContainerError::TaskAttemptError(
+                TaskAttemptError::ScratchAttempt
+            ))
+        ));
+        let branch_status = container
+            .attempt_branch_status(&task_attempt)
+            .await
+            .unwrap();
+        assert!(!branch_status.branches_exist);
+
+        container.delete_inner(&task_attempt).await.unwrap();
+        assert!(!Path::new(&container_ref).exists());
+    }
+
+    #[tokio::test]
+    async fn merged_pr_worktrees_are_pruned_once_idle() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+        let worktree_path = PathBuf::from(task_attempt.container_ref.clone().unwrap());
+        let pr = Merge::create_pr(
+            pool,
+            task_attempt.id,
+            "main",
+            1,
+            "https://github.com/owner/repo/pull/1",
+        )
+        .await
+        .unwrap();
+        Task::update_status(pool, task.id, TaskStatus::Done)
+            .await
+            .unwrap();
+
+        // An open PR isn't merged yet
+        assert!(container.prune_merged_worktrees().await.unwrap().is_empty());
+
+        Merge::update_status(pool, pr.id, MergeStatus::Merged, None)
+            .await
+            .unwrap();
+        let process = ExecutionProcess::create(
+            pool,
+            &CreateExecutionProcess {
+                task_attempt_id: task_attempt.id,
+                executor_action: ExecutorAction::new(
+                    ExecutorActionType::ScriptRequest(ScriptRequest {
+                        script: "true".to_string(),
+                        language: ScriptRequestLanguage::Bash,
+                        context: ScriptContext::CleanupScript,
+                    }),
+                    None,
+                ),
+                run_reason: ExecutionProcessRunReason::CleanupScript,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        assert!(container.prune_merged_worktrees().await.unwrap().is_empty());
+        assert!(worktree_path.exists());
+
+        ExecutionProcess::update_completion(
+            pool,
+            process.id,
+            ExecutionProcessStatus::Completed,
+            Some(0),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            container.prune_merged_worktrees().await.unwrap(),
+            [task_attempt.id]
+        );
+        assert!(!worktree_path.exists());
+        let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(task_attempt.worktree_deleted);
+        assert!(container.prune_merged_worktrees().await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn conflicting_merge_attempt_changes_nothing() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, repo_path) = create_mergeable_attempt(&container, true).await;
+        let git = GitService::new();
+        let main_before = git.get_branch_oid(&repo_path, "main").unwrap();
+
+        let err = container
+            .merge_attempt(&task_attempt, &quiet_config())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerError::GitServiceError(GitServiceError::MergeConflicts(ref paths))
+                if paths == "shared.txt"
+        ));
+
+        assert_eq!(git.get_branch_oid(&repo_path, "main").unwrap(), main_before);
+        assert!(git.is_worktree_clean(&repo_path).unwrap());
+        assert!(
+            Merge::find_by_task_attempt_id(pool, task_attempt.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let task = Task::find_by_id(pool, task.id).await.unwrap().unwrap();
+        assert_ne!(task.status, TaskStatus::Done);
+        assert!(Path::new(task_attempt.container_ref.as_deref().unwrap()).exists());
+
+        container.delete(&task_attempt).await.unwrap();
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn pull_request_requires_a_known_host_and_token() {
+        let container = test_container(quiet_config()).await;
+        let (_, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+        let git = GitService::new();
+        let mut config = quiet_config();
+        config.github.pat = None;
+        config.github.oauth_token = None;
+
+        git.set_remote(&repo_path, "origin", "https://example.com/team/project.git")
+            .unwrap();
+        let err = container
+            .create_pull_request(&task_attempt, &config, PullRequestOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerError::GitServiceError(GitServiceError::InvalidRepository(_))
+        ));
+
+        git.set_remote(&repo_path, "origin", "git@github.com:team/project.git")
+            .unwrap();
+        let err = container
+            .create_pull_request(&task_attempt, &config, PullRequestOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerError::GitHub(GitHubServiceError::TokenInvalid)
+        ));
+
+        config.gitlab.base_url = Some("https://git.example.com/".to_string());
+        git.set_remote(&repo_path, "origin", "git@git.example.com:team/project.git")
+            .unwrap();
+        let err = container
+            .create_pull_request(&task_attempt, &config, PullRequestOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerError::GitLab(GitLabServiceError::TokenInvalid)
+        ));
+        assert!(
+            Merge::find_by_task_attempt_id(&container.db.pool, task_attempt.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        container.delete(&task_attempt).await.unwrap();
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn compare_attempts_diffs_branch_tips_of_the_same_task() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, feature_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+
+        let git = GitService::new();
+        let other_worktree = PathBuf::from(format!("{}-other", repo_path.display()));
+        git.create_branch(&repo_path, "other").unwrap();
+        git.add_worktree(&repo_path, &other_worktree, "other", false)
+            .unwrap();
+        std::fs::write(other_worktree.join("other.txt"), "other\n").unwrap();
+        git.commit(&other_worktree, "other").unwrap();
+        let other_attempt = TaskAttempt::create(
+            pool,
+            &CreateTaskAttempt {
+                executor: BaseCodingAgent::ClaudeCode,
+                base_branch: "main".to_string(),
+                subpath: None,
+            },
+            task.id,
+        )
+        .await
+        .unwrap();
+        TaskAttempt::update_branch(pool, other_attempt.id, "other")
+            .await
+            .unwrap();
+        let other_attempt = TaskAttempt::find_by_id(pool, other_attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let events = container
+            .compare_attempts(&feature_attempt, &other_attempt)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        // Header, one event per changed file, then Finished
+        assert_eq!(events.len(), 4);
+        let rendered = format!("{events:?}");
+        assert!(rendered.contains("/comparison"));
+        assert!(rendered.contains(&git.get_branch_oid(&repo_path, "main").unwrap()));
+        assert!(rendered.contains("shared.txt"));
+        assert!(rendered.contains("other.txt"));
+
+        let (_, unrelated_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        let err = container
+            .compare_attempts(&feature_attempt, &unrelated_attempt)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err,
+            ContainerError::TaskAttemptError(TaskAttemptError::ValidationError(_))
+        ));
+
+        container.delete(&feature_attempt).await.unwrap();
+        std::fs::remove_dir_all(&other_worktree).unwrap();
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn revert_merge_restores_base_and_reopens_task() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+        let merge_commit = container
+            .merge_attempt(&task_attempt, &quiet_config())
+            .await
+            .unwrap();
+        let merge_id = Merge::find_latest_by_task_attempt_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .id();
+
+        let revert_commit = container
+            .revert_merge(merge_id, &quiet_config())
+            .await
+            .unwrap();
+
+        let git = GitService::new();
+        assert_eq!(
+            git.get_branch_oid(&repo_path, "main").unwrap(),
+            revert_commit
+        );
+        assert!(
+            git.is_commit_on_branch(&repo_path, "main", &merge_commit)
+                .unwrap()
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo_path.join("shared.txt")).unwrap(),
+            "base\n"
+        );
+        let merge = Merge::find_by_id(pool, merge_id).await.unwrap().unwrap();
+        assert_eq!(merge.revert_commit(), Some(revert_commit.as_str()));
+        let task = Task::find_by_id(pool, task.id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::InReview);
+
+        // A merge can only be reverted once
+        let err = container
+            .revert_merge(merge_id, &quiet_config())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerError::TaskAttemptError(TaskAttemptError::ValidationError(_))
+        ));
+
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn revert_merge_reports_conflicts_with_later_base_changes() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, repo_path) = create_mergeable_attempt(&container, false).await;
+        container
+            .merge_attempt(&task_attempt, &quiet_config())
+            .await
+            .unwrap();
+        let merge_id = Merge::find_latest_by_task_attempt_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .id();
+
+        // The base branch moves on and touches the merged lines
+        let git = GitService::new();
+        std::fs::write(repo_path.join("shared.txt"), "later\n").unwrap();
+        git.commit(&repo_path, "later").unwrap();
+        let main_before = git.get_branch_oid(&repo_path, "main").unwrap();
+
+        let err = container
+            .revert_merge(merge_id, &quiet_config())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerError::GitServiceError(GitServiceError::MergeConflicts(ref paths))
+                if paths == "shared.txt"
+        ));
+
+        assert_eq!(git.get_branch_oid(&repo_path, "main").unwrap(), main_before);
+        assert!(git.is_worktree_clean(&repo_path).unwrap());
+        let merge = Merge::find_by_id(pool, merge_id).await.unwrap().unwrap();
+        assert!(merge.revert_commit().is_none());
+        let task = Task::find_by_id(pool, task.id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Done);
+
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn promote_attempt_changes_active_attempt_and_fast_forwards_branch() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, promoted, repo_path) = create_mergeable_attempt(&container, false).await;
+        let newer = TaskAttempt::create(
+            pool,
+            &CreateTaskAttempt {
+                executor: BaseCodingAgent::Codex,
+                base_branch: "main".to_string(),
+                subpath: None,
+            },
+            task.id,
+        )
+        .await
+        .unwrap();
+
+        // Without a promotion the listing follows the newest attempt
+        let listed = Task::find_by_project_id_with_attempt_status(pool, task.project_id)
+            .await
+            .unwrap();
+        assert_eq!(listed[0].preferred_attempt_id, None);
+        assert_eq!(listed[0].executor, newer.executor);
+
+        container
+            .promote_attempt(&promoted, Some("main"))
+            .await
+            .unwrap();
+
+        let listed = Task::find_by_project_id_with_attempt_status(pool, task.project_id)
+            .await
+            .unwrap();
+        assert_eq!(listed[0].preferred_attempt_id, Some(promoted.id));
+        assert_eq!(listed[0].executor, promoted.executor);
+
+        // main was checked out, so its working tree follows the fast-forward
+        let git = GitService::new();
+        assert_eq!(
+            git.get_branch_oid(&repo_path, "main").unwrap(),
+            git.get_branch_oid(&repo_path, "feature").unwrap()
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo_path.join("shared.txt")).unwrap(),
+            "feature\n"
+        );
+        // Other attempts are kept
+        assert_eq!(
+            TaskAttempt::fetch_all(pool, Some(task.id))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        container.delete(&promoted).await.unwrap();
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn promote_attempt_refuses_to_rewind_a_diverged_branch() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (task, task_attempt, repo_path) = create_mergeable_attempt(&container, true).await;
+        let git = GitService::new();
+        let main_before = git.get_branch_oid(&repo_path, "main").unwrap();
+
+        let err = container
+            .promote_attempt(&task_attempt, Some("main"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContainerError::GitServiceError(GitServiceError::BranchesDiverged(_))
+        ));
+
+        assert_eq!(git.get_branch_oid(&repo_path, "main").unwrap(), main_before);
+        let task = Task::find_by_id(pool, task.id).await.unwrap().unwrap();
+        assert_eq!(task.preferred_attempt_id, None);
+
+        container.delete(&task_attempt).await.unwrap();
+        std::fs::remove_dir_all(&repo_path).unwrap();
+    }
+
+    /// Record a finished coding agent run on `task_attempt` whose agent reported `session_id`
+    async fn record_agent_session(
+        container: &LocalContainerService,
+        task_attempt: &TaskAttempt,
+        session_id: &str,
+    ) {
+        let pool = &container.db.pool;
+        let execution_process = ExecutionProcess::create(
+            pool,
+            &CreateExecutionProcess {
+                task_attempt_id: task_attempt.id,
+                executor_action: ExecutorAction::new(
+                    ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                        prompt: "parent".to_string(),
+                        executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+                        executor_override: None,
+                        resume_session_id: None,
+                        agent_instructions: None,
+                    }),
+                    None,
+                ),
+                run_reason: ExecutionProcessRunReason::CodingAgent,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        ExecutorSession::create(
+            pool,
+            &CreateExecutorSession {
+                task_attempt_id: task_attempt.id,
+                execution_process_id: execution_process.id,
+                prompt: Some("parent".to_string()),
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        ExecutorSession::update_session_id(pool, execution_process.id, session_id)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn attempt_sessions_are_listed_in_order_with_their_summaries() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        record_agent_session(&container, &task_attempt, "initial").await;
+        record_agent_session(&container, &task_attempt, "follow-up").await;
+        for session in ExecutorSession::find_by_task_attempt_id(pool, task_attempt.id)
+            .await
+            .unwrap()
+        {
+            let summary = format!("summary of {}", session.session_id.unwrap());
+            ExecutorSession::update_summary(pool, session.execution_process_id, &summary)
+                .await
+                .unwrap();
+        }
+
+        let sessions = ExecutorSession::find_by_task_attempt_id(pool, task_attempt.id)
+            .await
+            .unwrap();
+        let summaries: Vec<_> = sessions.iter().map(|s| s.summary.as_deref()).collect();
+        assert_eq!(
+            summaries,
+            [Some("summary of initial"), Some("summary of follow-up")]
+        );
+        let processes = ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id)
+            .await
+            .unwrap();
+        let process_ids: Vec<_> = processes.iter().map(|p| p.id).collect();
+        let session_process_ids: Vec<_> = sessions.iter().map(|s| s.execution_process_id).collect();
+        assert_eq!(session_process_ids, process_ids);
+    }
+
+    #[tokio::test]
+    async fn summary_override_survives_summary_extraction() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        record_agent_session(&container, &task_attempt, "session").await;
+        let exec_id = ExecutorSession::find_by_task_attempt_id(pool, task_attempt.id)
+            .await
+            .unwrap()[0]
+            .execution_process_id;
+        let store = Arc::new(MsgStore::new());
+        store.push_patch(ConversationPatch::add_normalized_entry(
+            0,
+            executors::logs::NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::AssistantMessage,
+                content: "Extracted summary".to_string(),
+                metadata: None,
+            },
+        ));
+        container.msg_stores.write().await.insert(exec_id, store);
+
+        let session = ExecutorSession::set_summary_override(pool, exec_id, "Fix the login flow")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(session.summary_overridden);
+
+        container
+            .update_executor_session_summary(&exec_id)
+            .await
+            .unwrap();
+        ExecutorSession::update_summary(pool, exec_id, "Extracted summary")
+            .await
+            .unwrap();
+
+        let session = ExecutorSession::find_by_execution_process_id(pool, exec_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.summary.as_deref(), Some("Fix the login flow"));
+        assert!(
+            ExecutorSession::set_summary_override(pool, Uuid::new_v4(), "none")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn continuation_task_resumes_parent_session() {
+        let container = test_container(quiet_config()).await;
+        let (_, parent_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        record_agent_session(&container, &parent_attempt, "parent-session").await;
+
+        let (child_task, _) = create_task_attempt(
+            &container,
+            BaseCodingAgent::ClaudeCode,
+            Some(parent_attempt.id),
+        )
+        .await;
+
+        assert_eq!(
+            container
+                .parent_session_id(&child_task, BaseCodingAgent::ClaudeCode)
+                .await
+                .unwrap(),
+            Some("parent-session".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn parent_session_is_not_resumed_by_other_executors_or_unrelated_tasks() {
+        let container = test_container(quiet_config()).await;
+        let (unrelated_task, parent_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        record_agent_session(&container, &parent_attempt, "parent-session").await;
+
+        let (child_task, _) =
+            create_task_attempt(&container, BaseCodingAgent::Amp, Some(parent_attempt.id)).await;
+
+        assert_eq!(
+            container
+                .parent_session_id(&child_task, BaseCodingAgent::Amp)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            container
+                .parent_session_id(&unrelated_task, BaseCodingAgent::ClaudeCode)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn resume_of_a_missing_session_re_runs_in_a_fresh_one() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let (_, task_attempt) =
+            create_task_attempt(&container, BaseCodingAgent::ClaudeCode, None).await;
+        let request = CodingAgentInitialRequest {
+            prompt: "continue".to_string(),
+            executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            executor_override: None,
+            resume_session_id: Some("gone-session".to_string()),
+            agent_instructions: None,
+        };
+        // The resume spawned, then the agent exited because the session doesn't exist
+        let failed_resume = |stderr: &'static str| {
+            let container = &container;
+            let task_attempt = &task_attempt;
+            let request = request.clone();
+            async move {
+                let execution_process = ExecutionProcess::create(
+                    pool,
+                    &CreateExecutionProcess {
+                        task_attempt_id: task_attempt.id,
+                        executor_action: ExecutorAction::new(
+                            ExecutorActionType::CodingAgentInitialRequest(request),
+                            None,
+                        ),
+                        run_reason: ExecutionProcessRunReason::CodingAgent,
+                    },
+                    Uuid::new_v4(),
+                )
+                .await
+                .unwrap();
+                ExecutionProcess::update_completion(
+                    pool,
+                    execution_process.id,
+                    ExecutionProcessStatus::Failed,
+                    Some(1),
+                    None,
+                )
+                .await
+                .unwrap();
+                let store = Arc::new(MsgStore::new());
+                store.push_stderr(stderr);
+                container
+                    .msg_stores
+                    .write()
+                    .await
+                    .insert(execution_process.id, store);
+                ExecutionProcess::load_context(pool, execution_process.id)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let ctx = failed_resume("No conversation found with session ID: gone-session\n").await;
+        let executor_action = container.fresh_session_rerun(&ctx).await.unwrap();
+        assert_eq!(
+            executor_action.typ,
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                resume_session_id: None,
+                ..request.clone()
+            })
+        );
+
+        let ctx = failed_resume("API Error: 500 Internal server error\n").await;
+        assert!(container.fresh_session_rerun(&ctx).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn exports_in_memory_logs_with_timestamps() {
+        let container = test_container(quiet_config()).await;
+        let id = Uuid::new_v4();
+        let store = Arc::new(MsgStore::new());
+        store.push_stdout("hello\n");
+        store.push_stderr("oops\n");
+        store.push_finished();
+        container.msg_stores.write().await.insert(id, store);
+
+        let jsonl = container.export_logs_jsonl(&id).await.unwrap().unwrap();
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2, "Finished is not exported");
+        assert_eq!(lines[0]["Stdout"], "hello\n");
+        assert_eq!(lines[1]["Stderr"], "oops\n");
+        assert!(lines.iter().all(|line| line["timestamp"].is_string()));
+    }
+
+    /// Insert a setup script process whose persisted logs hold `stdout`
+    async fn create_process_with_logs(
+        container: &LocalContainerService,
+        stdout: &str,
+    ) -> ExecutionProcess {
+        let pool = &container.db.pool;
+        let (_, task_attempt) =
+            create_task_attempt(container, BaseCodingAgent::ClaudeCode, None).await;
+        let execution_process = ExecutionProcess::create(
+            pool,
+            &CreateExecutionProcess {
+                task_attempt_id: task_attempt.id,
+                executor_action: ExecutorAction::new(
+                    ExecutorActionType::ScriptRequest(ScriptRequest {
+                        script: "echo persisted".to_string(),
+                        language: ScriptRequestLanguage::Bash,
+                        context: ScriptContext::SetupScript,
+                    }),
+                    None,
+                ),
+                run_reason: ExecutionProcessRunReason::SetupScript,
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let line = serde_json::to_string(&LogMsg::Stdout(stdout.to_string())).unwrap();
+        ExecutionProcessLogs::append_log_line(pool, execution_process.id, &format!("{line}\n"))
+            .await
+            .unwrap();
+        execution_process
+    }
+
+    #[tokio::test]
+    async fn exports_persisted_logs_once_store_is_gone() {
+        let container = test_container(quiet_config()).await;
+        let execution_process = create_process_with_logs(&container, "persisted\n").await;
+
+        let jsonl = container
+            .export_logs_jsonl(&execution_process.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let exported: serde_json::Value = serde_json::from_str(jsonl.trim_end()).unwrap();
+        assert_eq!(exported["Stdout"], "persisted\n");
+        assert!(exported["timestamp"].is_null());
+
+        assert!(
+            container
+                .export_logs_jsonl(&Uuid::new_v4())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_logs_are_deleted_and_recent_or_running_logs_kept() {
+        let container = test_container(quiet_config()).await;
+        let pool = &container.db.pool;
+        let old = create_process_with_logs(&container, "old\n").await;
+        let recent = create_process_with_logs(&container, "recent\n").await;
+        let running = create_process_with_logs(&container, "running\n").await;
+        for process in [&old, &recent] {
+            ExecutionProcess::update_completion(
+                pool,
+                process.id,
+                ExecutionProcessStatus::Completed,
+                Some(0),
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        sqlx::query("UPDATE execution_processes SET completed_at = ? WHERE id = ?")
+            .bind(chrono::Utc::now() - chrono::Duration::days(40))
+            .bind(old.id)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        // Retention disabled keeps everything
+        LocalContainerService::cleanup_expired_logs(&container.db, 0)
+            .await
+            .unwrap();
+        assert!(
+            ExecutionProcessLogs::find_by_execution_id(pool, old.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        LocalContainerService::cleanup_expired_logs(&container.db, 30)
+            .await
+            .unwrap();
+        assert!(
+            ExecutionProcessLogs::find_by_execution_id(pool, old.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        for kept in [&recent, &running] {
+            assert!(
+                ExecutionProcessLogs::find_by_execution_id(pool, kept.id)
+                    .await
+                    .unwrap()
+                    .is_some()
+            );
+        }
+    }
+}
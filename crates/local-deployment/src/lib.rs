@@ -92,12 +92,13 @@ impl Deployment for LocalDeployment {
             DBService::new_with_after_connect(hook).await?
         };
 
-        let image = ImageService::new(db.clone().pool)?;
+        let image_max_size_bytes = config.read().await.image_max_size_bytes;
+        let image = ImageService::new(db.clone().pool, image_max_size_bytes)?;
         {
             let image_service = image.clone();
             tokio::spawn(async move {
                 tracing::info!("Starting orphaned image cleanup...");
-                if let Err(e) = image_service.delete_orphaned_images().await {
+                if let Err(e) = image_service.cleanup_orphaned().await {
                     tracing::error!("Failed to clean up orphaned images: {}", e);
                 }
             });
@@ -163,7 +164,7 @@ impl Deployment for LocalDeployment {
         &self.analytics
     }
 
-    fn container(&self) -> &impl ContainerService {
+    fn container(&self) -> &(impl ContainerService + Clone + Send + Sync + 'static) {
         &self.container
     }
     fn auth(&self) -> &AuthService {
@@ -16,10 +16,14 @@ fn generate_types_content() -> String {
         db::models::project::ProjectWithBranch::decl(),
         db::models::project::CreateProject::decl(),
         db::models::project::UpdateProject::decl(),
+        server::routes::projects::UpdatePromptTemplate::decl(),
+        server::routes::projects::UpdateAgentInstructions::decl(),
+        server::routes::projects::UpdateConcurrencyLimit::decl(),
         db::models::project::SearchResult::decl(),
         db::models::project::SearchMatchType::decl(),
         services::services::file_search_cache::SearchMode::decl(),
         executors::actions::ExecutorAction::decl(),
+        executors::actions::ExecutionPreview::decl(),
         executors::mcp_config::McpConfig::decl(),
         executors::actions::ExecutorActionType::decl(),
         executors::actions::script::ScriptContext::decl(),
@@ -33,6 +37,7 @@ fn generate_types_content() -> String {
         db::models::task::TaskStatus::decl(),
         db::models::task::Task::decl(),
         db::models::task::TaskWithAttemptStatus::decl(),
+        db::models::task::TaskEvent::decl(),
         db::models::task::CreateTask::decl(),
         db::models::task::UpdateTask::decl(),
         db::models::image::Image::decl(),
@@ -53,16 +58,30 @@ fn generate_types_content() -> String {
         services::services::config::EditorConfig::decl(),
         services::services::config::EditorType::decl(),
         services::services::config::GitHubConfig::decl(),
+        services::services::config::GitLabConfig::decl(),
         services::services::config::SoundFile::decl(),
+        services::services::config::DirtyWorktreePolicy::decl(),
+        services::services::config::CommitSigningFormat::decl(),
+        services::services::config::AgentRetryConfig::decl(),
+        services::services::config::WorktreeNamingConfig::decl(),
+        services::services::config::WatcherBackend::decl(),
+        services::services::config::FilesystemWatcherConfig::decl(),
+        services::services::config::BrowserChatTimeoutConfig::decl(),
+        services::services::rate_limiter::RateLimitStatus::decl(),
+        services::services::execution_limiter::ProjectExecutionCount::decl(),
         services::services::auth::DeviceFlowStartResponse::decl(),
         server::routes::auth::DevicePollStatus::decl(),
         server::routes::auth::CheckTokenResponse::decl(),
         services::services::git::GitBranch::decl(),
+        services::services::git::CommitMetadata::decl(),
+        services::services::git::AbortedOperation::decl(),
+        services::services::git::DiffStats::decl(),
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
         utils::diff::FileDiffDetails::decl(),
         services::services::github_service::RepositoryInfo::decl(),
         executors::command::CommandBuilder::decl(),
+        executors::command::ExecutorOverride::decl(),
         executors::profile::ExecutorProfileId::decl(),
         executors::profile::ExecutorConfig::decl(),
         executors::executors::BaseAgentCapability::decl(),
@@ -85,15 +104,30 @@ fn generate_types_content() -> String {
         executors::actions::browser_chat_request::BrowserChatAgentType::decl(),
         server::routes::task_attempts::CreateTaskAttemptBody::decl(),
         server::routes::task_attempts::RebaseTaskAttemptRequest::decl(),
+        server::routes::task_attempts::PromoteAttemptRequest::decl(),
+        server::routes::task_attempts::RevertMergeRequest::decl(),
         server::routes::task_attempts::RestoreAttemptRequest::decl(),
         server::routes::task_attempts::RestoreAttemptResult::decl(),
         server::routes::task_attempts::CommitInfo::decl(),
         server::routes::task_attempts::CommitCompareResult::decl(),
         server::routes::task_attempts::BranchStatus::decl(),
+        server::routes::task_attempts::TaskAttemptWithBranchStatus::decl(),
+        server::routes::worktrees::WorktreeUsage::decl(),
+        server::routes::worktrees::AttemptWorktreeUsage::decl(),
+        server::routes::tasks::CreateTaskRequest::decl(),
+        server::routes::tasks::CreatedTask::decl(),
+        server::routes::execution_processes::SetSummaryRequest::decl(),
+        services::services::container::DevServerStatus::decl(),
+        services::services::container::AttemptBranchStatus::decl(),
+        services::services::container::AttemptDiffStats::decl(),
+        services::services::container::AttemptComparison::decl(),
         db::models::task_attempt::TaskAttempt::decl(),
+        db::models::task_attempt::ExecutionTimelineEntry::decl(),
         db::models::execution_process::ExecutionProcess::decl(),
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
+        db::models::execution_process::ExecutionProcessKillReason::decl(),
+        db::models::executor_session::ExecutorSession::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
@@ -102,6 +136,7 @@ fn generate_types_content() -> String {
         services::services::events::EventPatch::decl(),
         services::services::events::EventPatchInner::decl(),
         services::services::events::RecordTypes::decl(),
+        services::services::events::BoardEvent::decl(),
         executors::logs::CommandExitStatus::decl(),
         executors::logs::CommandRunResult::decl(),
         executors::logs::NormalizedConversation::decl(),
@@ -112,6 +147,7 @@ fn generate_types_content() -> String {
         executors::logs::TodoItem::decl(),
         executors::logs::ToolResult::decl(),
         executors::logs::ToolResultValueType::decl(),
+        executors::logs::usage::TokenUsage::decl(),
         executors::logs::utils::patch::PatchType::decl(),
         serde_json::Value::decl(),
     ];
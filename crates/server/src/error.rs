@@ -9,8 +9,9 @@ use deployment::DeploymentError;
 use executors::executors::ExecutorError;
 use git2::Error as Git2Error;
 use services::services::{
-    auth::AuthError, config::ConfigError, container::ContainerError, git::GitServiceError,
-    github_service::GitHubServiceError, image::ImageError, worktree_manager::WorktreeError,
+    auth::AuthError, browser_chat_service::BrowserChatError, config::ConfigError,
+    container::ContainerError, git::GitServiceError, github_service::GitHubServiceError,
+    image::ImageError, worktree_manager::WorktreeError,
 };
 use thiserror::Error;
 use utils::response::ApiResponse;
@@ -46,6 +47,10 @@ pub enum ApiError {
     Multipart(#[from] MultipartError),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    BrowserChat(#[from] BrowserChatError),
+    #[error("Not found: {0}")]
+    NotFound(String),
     #[error("Conflict: {0}")]
     Conflict(String),
 }
@@ -59,13 +64,75 @@ impl From<Git2Error> for ApiError {
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status_code, error_type) = match &self {
+            ApiError::Project(ProjectError::InvalidEnvVars(_)) => {
+                (StatusCode::BAD_REQUEST, "InvalidProjectEnvVars")
+            }
+            ApiError::Project(ProjectError::InvalidPromptTemplate(_)) => {
+                (StatusCode::BAD_REQUEST, "InvalidPromptTemplate")
+            }
+            ApiError::Project(ProjectError::InvalidDevServerUrlPattern(_)) => {
+                (StatusCode::BAD_REQUEST, "InvalidDevServerUrlPattern")
+            }
             ApiError::Project(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ProjectError"),
+            ApiError::TaskAttempt(TaskAttemptError::AttemptLimitReached(_))
+            | ApiError::Container(ContainerError::TaskAttemptError(
+                TaskAttemptError::AttemptLimitReached(_),
+            )) => (StatusCode::CONFLICT, "AttemptLimitReached"),
+            ApiError::TaskAttempt(TaskAttemptError::ScratchAttempt)
+            | ApiError::Container(ContainerError::TaskAttemptError(
+                TaskAttemptError::ScratchAttempt,
+            )) => (StatusCode::BAD_REQUEST, "ScratchAttempt"),
             ApiError::TaskAttempt(_) => (StatusCode::INTERNAL_SERVER_ERROR, "TaskAttemptError"),
+            ApiError::GitService(GitServiceError::MergeConflicts(_))
+            | ApiError::Container(ContainerError::GitServiceError(
+                GitServiceError::MergeConflicts(_),
+            )) => (StatusCode::CONFLICT, "MergeConflicts"),
+            ApiError::GitService(GitServiceError::WorktreeDirty(_, _))
+            | ApiError::Container(ContainerError::GitServiceError(
+                GitServiceError::WorktreeDirty(_, _),
+            )) => (StatusCode::CONFLICT, "WorktreeDirty"),
+            ApiError::GitService(GitServiceError::BranchesDiverged(_))
+            | ApiError::Container(ContainerError::GitServiceError(
+                GitServiceError::BranchesDiverged(_),
+            )) => (StatusCode::CONFLICT, "BranchesDiverged"),
+            ApiError::GitService(GitServiceError::BranchCheckedOut(_, _))
+            | ApiError::Container(ContainerError::GitServiceError(
+                GitServiceError::BranchCheckedOut(_, _),
+            )) => (StatusCode::CONFLICT, "BranchCheckedOut"),
+            ApiError::GitService(GitServiceError::CommitNotFound(_))
+            | ApiError::Container(ContainerError::GitServiceError(
+                GitServiceError::CommitNotFound(_),
+            )) => (StatusCode::NOT_FOUND, "CommitNotFound"),
+            ApiError::GitService(GitServiceError::CommitSigningFailed(_))
+            | ApiError::Container(ContainerError::GitServiceError(
+                GitServiceError::CommitSigningFailed(_),
+            )) => (StatusCode::INTERNAL_SERVER_ERROR, "CommitSigningFailed"),
             ApiError::GitService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitServiceError"),
             ApiError::GitHubService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHubServiceError"),
             ApiError::Auth(_) => (StatusCode::INTERNAL_SERVER_ERROR, "AuthError"),
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
+            ApiError::Executor(ExecutorError::PreviewNotSupported(_))
+            | ApiError::Container(ContainerError::ExecutorError(
+                ExecutorError::PreviewNotSupported(_),
+            )) => (StatusCode::BAD_REQUEST, "PreviewNotSupported"),
+            ApiError::Worktree(WorktreeError::InsufficientDiskSpace { .. })
+            | ApiError::Container(ContainerError::Worktree(
+                WorktreeError::InsufficientDiskSpace { .. },
+            )) => (StatusCode::INSUFFICIENT_STORAGE, "InsufficientDiskSpace"),
+            ApiError::Worktree(WorktreeError::BaseDirNotWritable { .. })
+            | ApiError::Container(ContainerError::Worktree(WorktreeError::BaseDirNotWritable {
+                ..
+            })) => (StatusCode::INSUFFICIENT_STORAGE, "WorktreeDirNotWritable"),
+            ApiError::Container(ContainerError::MissingWorktree(_)) => {
+                (StatusCode::CONFLICT, "AttemptWorktreeMissing")
+            }
+            ApiError::Container(ContainerError::MissingBranch(_)) => {
+                (StatusCode::CONFLICT, "AttemptBranchMissing")
+            }
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
+            ApiError::Executor(ExecutorError::InvalidOverride(_)) => {
+                (StatusCode::BAD_REQUEST, "InvalidExecutorOverride")
+            }
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
             ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DatabaseError"),
             ApiError::Worktree(_) => (StatusCode::INTERNAL_SERVER_ERROR, "WorktreeError"),
@@ -78,12 +145,21 @@ impl IntoResponse for ApiError {
             },
             ApiError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "IoError"),
             ApiError::Multipart(_) => (StatusCode::BAD_REQUEST, "MultipartError"),
+            ApiError::BrowserChat(
+                BrowserChatError::EnvironmentUnavailable(_) | BrowserChatError::NodeNotFound(_),
+            ) => (StatusCode::SERVICE_UNAVAILABLE, "BrowserChatUnavailable"),
+            ApiError::BrowserChat(BrowserChatError::ProtocolMismatch { .. }) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "BrowserChatProtocolMismatch",
+            ),
+            ApiError::BrowserChat(_) => (StatusCode::INTERNAL_SERVER_ERROR, "BrowserChatError"),
+            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "NotFound"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
         };
 
         let error_message = match &self {
             ApiError::Image(img_err) => match img_err {
-                ImageError::InvalidFormat => "This file type is not supported. Please upload an image file (PNG, JPG, GIF, WebP, or BMP).".to_string(),
+                ImageError::InvalidFormat => "This file type is not supported. Please upload an image file (PNG, JPG, GIF, or WebP).".to_string(),
                 ImageError::TooLarge(size, max) => format!(
                     "This image is too large ({:.1} MB). Maximum file size is {:.1} MB.",
                     *size as f64 / 1_048_576.0,
@@ -95,10 +171,81 @@ impl IntoResponse for ApiError {
                 }
             },
             ApiError::Multipart(_) => "Failed to upload file. Please ensure the file is valid and try again.".to_string(),
-            ApiError::Conflict(msg) => msg.clone(),
+            ApiError::BrowserChat(
+                BrowserChatError::EnvironmentUnavailable(_)
+                | BrowserChatError::NodeNotFound(_)
+                | BrowserChatError::ProtocolMismatch { .. },
+            ) => self.to_string(),
+            ApiError::Container(
+                ContainerError::MissingWorktree(_) | ContainerError::MissingBranch(_),
+            ) => self.to_string(),
+            ApiError::NotFound(msg) | ApiError::Conflict(msg) => msg.clone(),
             _ => format!("{}: {}", error_type, self),
         };
         let response = ApiResponse::<()>::error(&error_message);
         (status_code, Json(response)).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_resource_is_not_found() {
+        let response = ApiError::NotFound("Task attempt not found".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn unavailable_browser_environment_is_service_unavailable() {
+        let response = ApiError::BrowserChat(BrowserChatError::EnvironmentUnavailable(
+            "Node.js not found".to_string(),
+        ))
+        .into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response =
+            ApiError::BrowserChat(BrowserChatError::AutomationFailed("boom".to_string()))
+                .into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn attempt_limit_is_conflict() {
+        let response = ApiError::Container(ContainerError::TaskAttemptError(
+            TaskAttemptError::AttemptLimitReached(3),
+        ))
+        .into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn partially_set_up_attempt_is_conflict() {
+        let response =
+            ApiError::Container(ContainerError::MissingBranch(uuid::Uuid::nil())).into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn invalid_dev_server_url_pattern_is_bad_request() {
+        let response = ApiError::Project(ProjectError::InvalidDevServerUrlPattern(
+            "unclosed group".to_string(),
+        ))
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn full_worktree_disk_is_insufficient_storage() {
+        let response = ApiError::Container(ContainerError::Worktree(
+            WorktreeError::InsufficientDiskSpace {
+                path: "/tmp/worktrees".to_string(),
+                available: 1,
+                required: 2,
+            },
+        ))
+        .into_response();
+        assert_eq!(response.status(), StatusCode::INSUFFICIENT_STORAGE);
+    }
+}
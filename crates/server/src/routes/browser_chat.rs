@@ -5,13 +5,12 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
-    execution_process::ExecutionProcessRunReason,
-    task_attempt::{TaskAttempt, TaskAttemptError},
+    execution_process::{ExecutionProcess, ExecutionProcessKillReason, ExecutionProcessRunReason},
+    task_attempt::TaskAttempt,
 };
 use deployment::Deployment;
 use executors::actions::{
-    ExecutorAction, ExecutorActionType,
-    browser_chat_request::BrowserChatRequest,
+    ExecutorAction, ExecutorActionType, browser_chat_request::BrowserChatRequest,
 };
 use serde::{Deserialize, Serialize};
 use services::services::{
@@ -44,6 +43,10 @@ pub struct BrowserChatHealthResponse {
     pub message: String,
 }
 
+async fn browser_chat_service(deployment: &DeploymentImpl) -> NodeBrowserChatService {
+    NodeBrowserChatService::from_config(&*deployment.config().read().await)
+}
+
 pub async fn send_browser_chat_message(
     State(deployment): State<DeploymentImpl>,
     Path(task_attempt_id): Path<Uuid>,
@@ -52,7 +55,13 @@ pub async fn send_browser_chat_message(
     // Get the task attempt
     let task_attempt = TaskAttempt::find_by_id(&deployment.db().pool, task_attempt_id)
         .await?
-        .ok_or(ApiError::TaskAttempt(TaskAttemptError::ValidationError("Task attempt not found".to_string())))?;
+        .ok_or_else(|| ApiError::NotFound("Task attempt not found".to_string()))?;
+
+    // Fail fast with 503 rather than starting an execution that can't run
+    browser_chat_service(&deployment)
+        .await
+        .ensure_agent_ready(&request.agent_type)
+        .await?;
 
     // Create browser chat request action
     let browser_chat_request = BrowserChatRequest {
@@ -60,6 +69,7 @@ pub async fn send_browser_chat_message(
         agent_type: request.agent_type,
         executor_profile_id: request.executor_profile_id,
         session_id: None, // Initial request has no session ID
+        node_path: None,
     };
 
     let executor_action = ExecutorAction::new(
@@ -86,13 +96,34 @@ pub async fn send_browser_chat_message(
     Ok(ResponseJson(ApiResponse::success(response)))
 }
 
+/// Stop the attempt's running browser chat. The automation is asked to close its browser
+/// before the process is killed.
+pub async fn stop_browser_chat(
+    State(deployment): State<DeploymentImpl>,
+    Path(task_attempt_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let execution_process = ExecutionProcess::find_running_by_task_attempt(
+        &deployment.db().pool,
+        task_attempt_id,
+        Some(&ExecutionProcessRunReason::BrowserChat),
+    )
+    .await?
+    .ok_or_else(|| {
+        ApiError::NotFound("No browser chat is running for this task attempt".to_string())
+    })?;
+
+    deployment
+        .container()
+        .stop_execution(&execution_process, ExecutionProcessKillReason::User)
+        .await?;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 pub async fn get_browser_chat_health(
-    State(_deployment): State<DeploymentImpl>,
+    State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<BrowserChatHealthResponse>>, ApiError> {
-    // TODO: Make script path configurable
-    let browser_chat_service = NodeBrowserChatService::new("./browser-automation".to_string());
-    
-    match browser_chat_service.health_check().await {
+    match browser_chat_service(&deployment).await.health_check().await {
         Ok(healthy) => {
             let response = BrowserChatHealthResponse {
                 healthy,
@@ -118,4 +149,5 @@ pub fn router(_deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     Router::new()
         .route("/health", get(get_browser_chat_health))
         .route("/task-attempts/{task_attempt_id}/send", post(send_browser_chat_message))
+        .route("/task-attempts/{task_attempt_id}/stop", post(stop_browser_chat))
 }
\ No newline at end of file
@@ -16,7 +16,11 @@ use executors::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use services::services::config::{Config, ConfigError, SoundFile, save_config_to_file};
+use services::services::{
+    config::{Config, ConfigError, SoundFile, save_config_to_file},
+    container::ContainerService,
+    rate_limiter::RateLimitStatus,
+};
 use tokio::fs;
 use ts_rs::TS;
 use utils::{assets::config_path, response::ApiResponse};
@@ -30,6 +34,7 @@ pub fn router() -> Router<DeploymentImpl> {
         .route("/sounds/{sound}", get(get_sound))
         .route("/mcp-config", get(get_mcp_servers).post(update_mcp_servers))
         .route("/profiles", get(get_profiles).put(update_profiles))
+        .route("/rate-limits", get(get_rate_limits))
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -100,6 +105,10 @@ async fn update_config(
 ) -> ResponseJson<ApiResponse<Config>> {
     let config_path = config_path();
 
+    if let Err(e) = new_config.worktree_naming.validate() {
+        return ResponseJson(ApiResponse::error(&format!("Invalid worktree naming: {e}")));
+    }
+
     // Get the current analytics_enabled state before updating
     let old_analytics_enabled = {
         let config = deployment.config().read().await;
@@ -138,6 +147,13 @@ async fn get_sound(Path(sound): Path<SoundFile>) -> Result<Response, ApiError> {
     Ok(response)
 }
 
+async fn get_rate_limits(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<RateLimitStatus>>> {
+    let statuses = deployment.container().rate_limiter().status().await;
+    ResponseJson(ApiResponse::success(statuses))
+}
+
 #[derive(TS, Debug, Deserialize)]
 pub struct McpServerQuery {
     executor: BaseCodingAgent,
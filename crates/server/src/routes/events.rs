@@ -1,16 +1,13 @@
 use axum::{
     BoxError, Router,
     extract::State,
-    response::{
-        Sse,
-        sse::{Event, KeepAlive},
-    },
+    response::{Sse, sse::Event},
     routing::get,
 };
 use deployment::Deployment;
 use futures_util::TryStreamExt;
 
-use crate::DeploymentImpl;
+use crate::{DeploymentImpl, routes::sse_keep_alive};
 
 pub async fn events(
     State(deployment): State<DeploymentImpl>,
@@ -18,7 +15,8 @@ pub async fn events(
 {
     // Ask the container service for a combined "history + live" stream
     let stream = deployment.stream_events().await;
-    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
+    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() }))
+        .keep_alive(sse_keep_alive(&deployment).await))
 }
 
 pub fn router(_: &DeploymentImpl) -> Router<DeploymentImpl> {
@@ -1,22 +1,32 @@
 use axum::{
-    BoxError, Extension, Router,
+    BoxError, Extension, Json, Router,
     extract::{Path, Query, State},
+    http::header,
     middleware::from_fn_with_state,
-    response::{
-        Json as ResponseJson, Sse,
-        sse::{Event, KeepAlive},
+    response::{IntoResponse, Json as ResponseJson, Response, Sse, sse::Event},
+    routing::{get, post, put},
+};
+use db::models::{
+    execution_process::{
+        ExecutionProcess, ExecutionProcessKillReason, ExecutionProcessRunReason,
+        ExecutionProcessStatus,
     },
-    routing::{get, post},
+    executor_session::ExecutorSession,
+    task_attempt::{ExecutionTimelineEntry, TaskAttempt, TaskAttemptError},
 };
-use db::models::execution_process::ExecutionProcess;
 use deployment::Deployment;
+use executors::logs::usage::TokenUsage;
 use futures_util::TryStreamExt;
 use serde::Deserialize;
 use services::services::container::ContainerService;
+use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_execution_process_middleware};
+use crate::{
+    DeploymentImpl, error::ApiError, middleware::load_execution_process_middleware,
+    routes::sse_keep_alive,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct ExecutionProcessQuery {
@@ -34,6 +44,50 @@ pub async fn get_execution_processes(
     Ok(ResponseJson(ApiResponse::success(execution_processes)))
 }
 
+/// Page size for the execution history when the client doesn't ask for one
+const DEFAULT_HISTORY_LIMIT: u32 = 50;
+const MAX_HISTORY_LIMIT: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct ExecutionHistoryQuery {
+    pub task_attempt_id: Uuid,
+    pub status: Option<ExecutionProcessStatus>,
+    pub run_reason: Option<ExecutionProcessRunReason>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// The attempt's execution processes, newest first, with exit codes and durations
+pub async fn get_execution_history(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ExecutionHistoryQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionTimelineEntry>>>, ApiError> {
+    // An unknown attempt is an error rather than an empty history
+    TaskAttempt::find_by_id(&deployment.db().pool, query.task_attempt_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Task attempt not found".to_string()))?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .min(MAX_HISTORY_LIMIT);
+    let processes = ExecutionProcess::find_by_task_attempt_filtered(
+        &deployment.db().pool,
+        query.task_attempt_id,
+        query.status,
+        query.run_reason,
+        i64::from(limit),
+        i64::from(query.offset.unwrap_or(0)),
+    )
+    .await?;
+
+    let now = chrono::Utc::now();
+    let history = processes
+        .into_iter()
+        .map(|process| ExecutionTimelineEntry::new(process, now))
+        .collect();
+    Ok(ResponseJson(ApiResponse::success(history)))
+}
+
 pub async fn get_execution_process_by_id(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(_deployment): State<DeploymentImpl>,
@@ -53,7 +107,8 @@ pub async fn stream_raw_logs(
         .await
         .ok_or(axum::http::StatusCode::NOT_FOUND)?;
 
-    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
+    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() }))
+        .keep_alive(sse_keep_alive(&deployment).await))
 }
 
 pub async fn stream_normalized_logs(
@@ -68,7 +123,34 @@ pub async fn stream_normalized_logs(
         .await
         .ok_or(axum::http::StatusCode::NOT_FOUND)?;
 
-    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
+    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() }))
+        .keep_alive(sse_keep_alive(&deployment).await))
+}
+
+pub async fn download_logs_jsonl(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<Response, ApiError> {
+    let jsonl = deployment
+        .container()
+        .export_logs_jsonl(&execution_process.id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("No logs found for execution process".to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"execution-{}.jsonl\"",
+                    execution_process.id
+                ),
+            ),
+        ],
+        jsonl,
+    )
+        .into_response())
 }
 
 pub async fn stop_execution_process(
@@ -77,18 +159,56 @@ pub async fn stop_execution_process(
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
     deployment
         .container()
-        .stop_execution(&execution_process)
+        .stop_execution(&execution_process, ExecutionProcessKillReason::User)
         .await?;
 
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+pub async fn get_execution_process_usage(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<TokenUsage>>>, ApiError> {
+    let usage = ExecutorSession::usage(&deployment.db().pool, execution_process.id).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetSummaryRequest {
+    pub summary: String,
+}
+
+/// Replace the process's summary with the user's; it becomes the agent's commit message
+/// if the process hasn't committed yet, and automatic extraction won't overwrite it
+pub async fn set_execution_process_summary(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetSummaryRequest>,
+) -> Result<ResponseJson<ApiResponse<ExecutorSession>>, ApiError> {
+    let summary = payload.summary.trim();
+    if summary.is_empty() {
+        return Err(
+            TaskAttemptError::ValidationError("Summary cannot be empty".to_string()).into(),
+        );
+    }
+    let session =
+        ExecutorSession::set_summary_override(&deployment.db().pool, execution_process.id, summary)
+            .await?
+            .ok_or_else(|| {
+                ApiError::NotFound("Execution process has no executor session".to_string())
+            })?;
+    Ok(ResponseJson(ApiResponse::success(session)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempt_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
         .route("/stop", post(stop_execution_process))
         .route("/raw-logs", get(stream_raw_logs))
         .route("/normalized-logs", get(stream_normalized_logs))
+        .route("/logs.jsonl", get(download_logs_jsonl))
+        .route("/usage", get(get_execution_process_usage))
+        .route("/summary", put(set_execution_process_summary))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_execution_process_middleware,
@@ -96,6 +216,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let task_attempts_router = Router::new()
         .route("/", get(get_execution_processes))
+        .route("/history", get(get_execution_history))
         .nest("/{id}", task_attempt_id_router);
 
     Router::new().nest("/execution-processes", task_attempts_router)
@@ -134,11 +134,17 @@ pub async fn get_task_images(
     Ok(ResponseJson(ApiResponse::success(image_responses)))
 }
 
-pub fn routes() -> Router<DeploymentImpl> {
+/// Headroom on top of the image size limit for multipart framing
+const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+pub fn routes(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    // Let oversized images through to ImageService so they get a descriptive error. Like
+    // the service's limit, this is fixed at startup.
+    let body_limit = deployment.image().max_size_bytes() as usize + MULTIPART_OVERHEAD_BYTES;
     Router::new()
         .route(
             "/upload",
-            post(upload_image).layer(DefaultBodyLimit::max(20 * 1024 * 1024)), // 20MB limit
+            post(upload_image).layer(DefaultBodyLimit::max(body_limit)),
         )
         .route("/{id}/file", get(serve_image))
         .route("/{id}", delete(delete_image))
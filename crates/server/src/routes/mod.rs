@@ -1,7 +1,11 @@
+use std::time::Duration;
+
 use axum::{
     Router,
+    response::sse::KeepAlive,
     routing::{IntoMakeService, get},
 };
+use deployment::Deployment;
 
 use crate::DeploymentImpl;
 
@@ -20,6 +24,14 @@ pub mod projects;
 pub mod task_attempts;
 pub mod task_templates;
 pub mod tasks;
+pub mod worktrees;
+
+/// Keep-alive for SSE responses using the configured interval. It is sent as an
+/// SSE comment only after the stream has been idle, so clients never see it as data.
+pub async fn sse_keep_alive(deployment: &DeploymentImpl) -> KeepAlive {
+    let secs = deployment.config().read().await.sse_keepalive_interval_secs;
+    KeepAlive::new().interval(Duration::from_secs(secs.max(1)))
+}
 
 pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
     // Create routers with different middleware layers
@@ -35,7 +47,8 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(auth::router(&deployment))
         .merge(filesystem::router())
         .merge(events::router(&deployment))
-        .nest("/images", images::routes())
+        .merge(worktrees::router(&deployment))
+        .nest("/images", images::routes(&deployment))
         .nest("/browser-chat", browser_chat::router(&deployment))
         .with_state(deployment);
 
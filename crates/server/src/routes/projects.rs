@@ -1,27 +1,41 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
 use axum::{
-    Extension, Json, Router,
+    BoxError, Extension, Json, Router,
     extract::{Query, State},
     http::StatusCode,
     middleware::from_fn_with_state,
-    response::Json as ResponseJson,
-    routing::{get, post},
+    response::{Json as ResponseJson, Sse, sse::Event},
+    routing::{get, post, put},
 };
 use db::models::project::{
     CreateProject, Project, ProjectError, SearchMatchType, SearchResult, UpdateProject,
+    validate_prompt_template,
 };
 use deployment::Deployment;
+use executors::{
+    command::{ExecutorOverrides, validate_env},
+    profile::{ExecutorConfigs, ExecutorProfileId},
+};
+use futures_util::{Stream, TryStreamExt};
 use ignore::WalkBuilder;
+use serde::Deserialize;
 use services::services::{
+    config::Config,
+    container::ContainerService,
+    dev_server::DevServerUrlDetector,
+    execution_limiter::ProjectExecutionCount,
     file_ranker::FileRanker,
     file_search_cache::{CacheError, SearchMode, SearchQuery},
-    git::GitBranch,
+    git::{CloneOptions, GitBranch, GitService, GitServiceError},
 };
-use utils::{path::expand_tilde, response::ApiResponse};
+use ts_rs::TS;
+use utils::{assets::asset_dir, path::expand_tilde, response::ApiResponse};
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_project_middleware};
+use crate::{
+    DeploymentImpl, error::ApiError, middleware::load_project_middleware, routes::sse_keep_alive,
+};
 
 pub async fn get_projects(
     State(deployment): State<DeploymentImpl>,
@@ -36,6 +50,21 @@ pub async fn get_project(
     Ok(ResponseJson(ApiResponse::success(project)))
 }
 
+/// Live board events for the project; see `EventService::stream_board_events`
+pub async fn stream_project_events(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<Sse<impl Stream<Item = Result<Event, BoxError>>>, StatusCode> {
+    let stream = deployment
+        .events()
+        .stream_board_events(project.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() }))
+        .keep_alive(sse_keep_alive(&deployment).await))
+}
+
 pub async fn get_project_branches(
     Extension(project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
@@ -44,6 +73,57 @@ pub async fn get_project_branches(
     Ok(ResponseJson(ApiResponse::success(branches)))
 }
 
+/// Credentials for cloning `url`: the GitHub token for github.com, the GitLab token for
+/// the configured GitLab host and none for any other host. SSH remotes use the SSH agent
+/// or default key.
+fn clone_options(config: &Config, url: &str) -> CloneOptions {
+    let gitlab_host = reqwest::Url::parse(config.gitlab.base_url())
+        .ok()
+        .and_then(|base_url| base_url.host_str().map(str::to_lowercase));
+    let token = match GitService::parse_remote_url(url) {
+        Some((host, _)) if host == "github.com" => config.github.token(),
+        Some((host, _)) if Some(&host) == gitlab_host.as_ref() => config.gitlab.token.clone(),
+        _ => None,
+    };
+    CloneOptions {
+        token,
+        ssh_key_path: None,
+    }
+}
+
+/// Clone `url` into `target_path` off the async runtime, logging progress every 10%
+async fn clone_project_repo(
+    url: &str,
+    target_path: &Path,
+    options: CloneOptions,
+) -> Result<(), GitServiceError> {
+    let url = url.to_string();
+    let target_path = target_path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut reported = 0;
+        GitService::clone_repository(&url, &target_path, &options, |progress| {
+            if progress.total_objects == 0 {
+                return;
+            }
+            let percent = progress.received_objects * 100 / progress.total_objects;
+            if percent >= reported + 10 {
+                reported = percent - percent % 10;
+                tracing::info!(
+                    "Cloning {}: {}% ({}/{} objects, {} bytes)",
+                    url,
+                    percent,
+                    progress.received_objects,
+                    progress.total_objects,
+                    progress.received_bytes
+                );
+            }
+        })
+        .map(|_| ())
+    })
+    .await
+    .map_err(|e| GitServiceError::InvalidRepository(format!("Clone task failed: {e}")))?
+}
+
 pub async fn create_project(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateProject>,
@@ -56,13 +136,43 @@ pub async fn create_project(
         dev_script,
         cleanup_script,
         copy_files,
+        dev_server_url_pattern,
         use_existing_repo,
     } = payload;
     tracing::debug!("Creating project '{}'", name);
+    DevServerUrlDetector::new(dev_server_url_pattern.as_deref())
+        .map_err(|e| ProjectError::InvalidDevServerUrlPattern(e.to_string()))?;
+
+    // A remote URL is cloned into the managed repos directory and used from there
+    let (path, use_existing_repo) = if GitService::is_remote_url(&git_repo_path) {
+        let Some(clone_dir) = GitService::clone_dir_from_url(&git_repo_path) else {
+            return Ok(ResponseJson(ApiResponse::error(
+                "Could not tell the repository name from the URL",
+            )));
+        };
+        let (base_dir, options) = {
+            let config = deployment.config().read().await;
+            let base_dir = match &config.workspace_dir {
+                Some(dir) if !dir.trim().is_empty() => expand_tilde(dir),
+                _ => asset_dir().join("repos"),
+            };
+            (base_dir, clone_options(&config, &git_repo_path))
+        };
+        let target_path = base_dir.join(clone_dir);
+        if let Err(e) = clone_project_repo(&git_repo_path, &target_path, options).await {
+            tracing::error!("Failed to clone {}: {}", git_repo_path, e);
+            return Ok(ResponseJson(ApiResponse::error(&format!(
+                "Failed to clone repository: {}",
+                e
+            ))));
+        }
+        (target_path, true)
+    } else {
+        // Expand tilde in git repo path if present
+        (expand_tilde(&git_repo_path), use_existing_repo)
+    };
 
     // Validate and setup git repository
-    // Expand tilde in git repo path if present
-    let path = expand_tilde(&git_repo_path);
     // Check if git repo path is already used by another project
     match Project::find_by_git_repo_path(&deployment.db().pool, path.to_string_lossy().as_ref())
         .await
@@ -144,6 +254,7 @@ pub async fn create_project(
             dev_script,
             cleanup_script,
             copy_files,
+            dev_server_url_pattern,
         },
         id,
     )
@@ -173,7 +284,7 @@ pub async fn update_project(
     Extension(existing_project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<UpdateProject>,
-) -> Result<ResponseJson<ApiResponse<Project>>, StatusCode> {
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
     // Destructure payload to handle field updates.
     // This allows us to treat `None` from the payload as an explicit `null` to clear a field,
     // as the frontend currently sends all fields on update.
@@ -184,7 +295,10 @@ pub async fn update_project(
         dev_script,
         cleanup_script,
         copy_files,
+        dev_server_url_pattern,
     } = payload;
+    DevServerUrlDetector::new(dev_server_url_pattern.as_deref())
+        .map_err(|e| ProjectError::InvalidDevServerUrlPattern(e.to_string()))?;
     // If git_repo_path is being changed, check if the new path is already used by another project
     let git_repo_path = if let Some(new_git_repo_path) = git_repo_path.map(|s| expand_tilde(&s))
         && new_git_repo_path != existing_project.git_repo_path
@@ -204,7 +318,7 @@ pub async fn update_project(
             Ok(None) => new_git_repo_path,
             Err(e) => {
                 tracing::error!("Failed to check for existing git repo path: {}", e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                return Err(e.into());
             }
         }
     } else {
@@ -220,13 +334,14 @@ pub async fn update_project(
         dev_script,
         cleanup_script,
         copy_files,
+        dev_server_url_pattern,
     )
     .await
     {
         Ok(project) => Ok(ResponseJson(ApiResponse::success(project))),
         Err(e) => {
             tracing::error!("Failed to update project: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
@@ -250,6 +365,115 @@ pub async fn delete_project(
     }
 }
 
+pub async fn update_executor_overrides(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(executor_overrides): Json<ExecutorOverrides>,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    // Reject overrides that wouldn't apply cleanly to the executor's default profile
+    let configs = ExecutorConfigs::get_cached();
+    for (executor, executor_override) in &executor_overrides {
+        if let Some(agent) = configs.get_coding_agent(&ExecutorProfileId::new(*executor)) {
+            agent.with_override(executor_override)?;
+        }
+    }
+
+    let project =
+        Project::update_executor_overrides(&deployment.db().pool, project.id, &executor_overrides)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(project)))
+}
+
+pub async fn update_env_vars(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(env_vars): Json<HashMap<String, String>>,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    validate_env(&env_vars).map_err(ProjectError::InvalidEnvVars)?;
+
+    let project = Project::update_env_vars(&deployment.db().pool, project.id, &env_vars).await?;
+    Ok(ResponseJson(ApiResponse::success(project)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdatePromptTemplate {
+    /// `None` or an empty template restores the default prompt
+    pub prompt_template: Option<String>,
+}
+
+pub async fn update_prompt_template(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdatePromptTemplate>,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    let prompt_template = payload
+        .prompt_template
+        .as_deref()
+        .filter(|template| !template.trim().is_empty());
+    if let Some(template) = prompt_template {
+        validate_prompt_template(template).map_err(ProjectError::InvalidPromptTemplate)?;
+    }
+
+    let project =
+        Project::update_prompt_template(&deployment.db().pool, project.id, prompt_template).await?;
+    Ok(ResponseJson(ApiResponse::success(project)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateAgentInstructions {
+    /// `None` or empty text removes the instructions
+    pub agent_instructions: Option<String>,
+}
+
+pub async fn update_agent_instructions(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateAgentInstructions>,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    let agent_instructions = payload
+        .agent_instructions
+        .as_deref()
+        .filter(|instructions| !instructions.trim().is_empty());
+
+    let project =
+        Project::update_agent_instructions(&deployment.db().pool, project.id, agent_instructions)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(project)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateConcurrencyLimit {
+    /// `None` leaves the project under the global limit only
+    pub max_concurrent_executions: Option<u32>,
+}
+
+pub async fn update_concurrency_limit(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateConcurrencyLimit>,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    if payload.max_concurrent_executions == Some(0) {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Concurrency limit must be at least 1",
+        )));
+    }
+    let project = Project::update_max_concurrent_executions(
+        &deployment.db().pool,
+        project.id,
+        payload.max_concurrent_executions.map(i64::from),
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(project)))
+}
+
+/// Running and queued coding agents of each project with any
+pub async fn get_execution_counts(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<ProjectExecutionCount>>> {
+    let counts = deployment.container().execution_limiter().status();
+    ResponseJson(ApiResponse::success(counts))
+}
+
 #[derive(serde::Deserialize)]
 pub struct OpenEditorRequest {
     editor_type: Option<String>,
@@ -477,7 +701,13 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             "/",
             get(get_project).put(update_project).delete(delete_project),
         )
+        .route("/stream", get(stream_project_events))
         .route("/branches", get(get_project_branches))
+        .route("/executor-overrides", put(update_executor_overrides))
+        .route("/env-vars", put(update_env_vars))
+        .route("/prompt-template", put(update_prompt_template))
+        .route("/agent-instructions", put(update_agent_instructions))
+        .route("/concurrency-limit", put(update_concurrency_limit))
         .route("/search", get(search_project_files))
         .route("/open-editor", post(open_project_in_editor))
         .layer(from_fn_with_state(
@@ -487,7 +717,38 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let projects_router = Router::new()
         .route("/", get(get_projects).post(create_project))
+        .route("/execution-counts", get(get_execution_counts))
         .nest("/{id}", project_id_router);
 
     Router::new().nest("/projects", projects_router)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clone_tokens_only_go_to_their_own_host() {
+        let mut config = Config::default();
+        config.github.pat = Some("github-token".to_string());
+        config.gitlab.token = Some("gitlab-token".to_string());
+        config.gitlab.base_url = Some("https://git.example.com".to_string());
+        let token = |url: &str| clone_options(&config, url).token;
+
+        assert_eq!(
+            token("https://github.com/owner/repo.git").as_deref(),
+            Some("github-token")
+        );
+        assert_eq!(
+            token("https://git.example.com/group/repo").as_deref(),
+            Some("gitlab-token")
+        );
+        // Neither a host merely containing a known one nor an unknown host gets a token
+        assert_eq!(
+            token("https://git.example.com.evil.example/group/repo"),
+            None
+        );
+        assert_eq!(token("https://evil.example/github.com/owner/repo"), None);
+        assert_eq!(token("https://bitbucket.org/owner/repo"), None);
+    }
+}
@@ -5,43 +5,49 @@ use axum::{
     extract::{Query, State},
     http::StatusCode,
     middleware::from_fn_with_state,
-    response::{
-        Json as ResponseJson, Sse,
-        sse::{Event, KeepAlive},
-    },
+    response::{Json as ResponseJson, Sse, sse::Event},
     routing::{get, post},
 };
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    executor_session::ExecutorSession,
     image::TaskImage,
     merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
-    project::{Project, ProjectError},
-    task::{Task, TaskStatus},
-    task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
+    task::Task,
+    task_attempt::{CreateTaskAttempt, ExecutionTimelineEntry, TaskAttempt, TaskAttemptError},
 };
 use deployment::Deployment;
 use executors::{
     actions::{
-        ExecutorAction, ExecutorActionType,
+        ExecutionPreview, ExecutorAction, ExecutorActionType,
         coding_agent_follow_up::CodingAgentFollowUpRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
+    logs::usage::TokenUsage,
     profile::ExecutorProfileId,
 };
-use futures_util::TryStreamExt;
+use futures_util::{TryStreamExt, future};
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
-    container::ContainerService,
-    github_service::{CreatePrRequest, GitHubService, GitHubServiceError},
+    container::{
+        AttemptBranchStatus, AttemptDiffStats, ContainerError, ContainerService, DevServerStatus,
+        PullRequestOptions,
+    },
+    git::{AbortedOperation, CommitMetadata},
+    github_service::{GitHubService, GitHubServiceError},
     image::ImageService,
+    notification::NotificationService,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::{path::normalize_repo_subpath, response::ApiResponse};
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_task_attempt_middleware};
+use crate::{
+    DeploymentImpl, error::ApiError, middleware::load_task_attempt_middleware,
+    routes::sse_keep_alive,
+};
 
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct RebaseTaskAttemptRequest {
@@ -68,7 +74,9 @@ pub struct RestoreAttemptResult {
 
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct CreateGitHubPrRequest {
-    pub title: String,
+    /// Defaults to the task title
+    pub title: Option<String>,
+    /// Defaults to the latest agent summary, then the task description
     pub body: Option<String>,
     pub base_branch: Option<String>,
 }
@@ -85,13 +93,71 @@ pub struct TaskAttemptQuery {
     pub task_id: Option<Uuid>,
 }
 
+/// A task attempt together with how far its branch is ahead of/behind its base branch
+#[derive(Debug, Serialize, TS)]
+pub struct TaskAttemptWithBranchStatus {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub attempt: TaskAttempt,
+    pub branch_status: AttemptBranchStatus,
+    /// Whether any of the attempt's executions is still live, dev servers and queued starts
+    /// included
+    pub is_running: bool,
+}
+
 pub async fn get_task_attempts(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskAttemptQuery>,
-) -> Result<ResponseJson<ApiResponse<Vec<TaskAttempt>>>, ApiError> {
+) -> Result<ResponseJson<ApiResponse<Vec<TaskAttemptWithBranchStatus>>>, ApiError> {
     let pool = &deployment.db().pool;
     let attempts = TaskAttempt::fetch_all(pool, query.task_id).await?;
-    Ok(ResponseJson(ApiResponse::success(attempts)))
+
+    // Branch statuses are looked up concurrently, each off the async runtime
+    let lookups = attempts.into_iter().map(|attempt| {
+        let deployment = &deployment;
+        async move {
+            // A broken repo shouldn't hide the attempts themselves
+            let branch_status = deployment
+                .container()
+                .attempt_branch_status(&attempt)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!(
+                        "Failed to get branch status for task attempt {}: {}",
+                        attempt.id,
+                        e
+                    );
+                    AttemptBranchStatus::default()
+                });
+            let is_running = deployment
+                .container()
+                .is_attempt_running(attempt.id)
+                .await?;
+            Ok::<_, ApiError>(TaskAttemptWithBranchStatus {
+                attempt,
+                branch_status,
+                is_running,
+            })
+        }
+    });
+    let attempts_with_status = future::try_join_all(lookups).await?;
+    Ok(ResponseJson(ApiResponse::success(attempts_with_status)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffStatsQuery {
+    pub task_id: Uuid,
+}
+
+pub async fn get_task_diff_stats(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DiffStatsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AttemptDiffStats>>>, ApiError> {
+    let stats = deployment
+        .container()
+        .task_diff_stats(query.task_id)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(stats)))
 }
 
 pub async fn get_task_attempt(
@@ -107,6 +173,10 @@ pub struct CreateTaskAttemptBody {
     /// Executor profile specification
     pub executor_profile_id: ExecutorProfileId,
     pub base_branch: String,
+    /// Directory within the project repo to scope the attempt to (monorepos)
+    pub subpath: Option<String>,
+    /// Run in a disposable temp dir without a worktree or branch
+    pub scratch: Option<bool>,
 }
 
 impl CreateTaskAttemptBody {
@@ -122,21 +192,45 @@ pub async fn create_task_attempt(
     Json(payload): Json<CreateTaskAttemptBody>,
 ) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
     let executor_profile_id = payload.get_executor_profile_id();
+    let scratch = payload.scratch.unwrap_or(false);
+
+    // A scratch dir starts empty, so there's no repo directory to scope to
+    let subpath = match payload.subpath.as_deref().map(str::trim) {
+        _ if scratch => None,
+        Some(subpath) if !subpath.is_empty() => {
+            Some(normalize_repo_subpath(subpath).ok_or_else(|| {
+                TaskAttemptError::ValidationError(format!(
+                    "Invalid subpath '{subpath}': must be a directory inside the repository"
+                ))
+            })?)
+        }
+        _ => None,
+    };
 
-    let task_attempt = TaskAttempt::create(
+    let max_attempts = deployment.config().read().await.max_attempts_per_task;
+    let task_attempt = TaskAttempt::create_within_limit(
         &deployment.db().pool,
         &CreateTaskAttempt {
             executor: executor_profile_id.executor,
             base_branch: payload.base_branch.clone(),
+            subpath,
         },
         payload.task_id,
+        max_attempts,
     )
     .await?;
 
-    let execution_process = deployment
-        .container()
-        .start_attempt(&task_attempt, executor_profile_id.clone())
-        .await?;
+    let execution_process = if scratch {
+        deployment
+            .container()
+            .start_scratch_execution(&task_attempt, executor_profile_id.clone())
+            .await?
+    } else {
+        deployment
+            .container()
+            .start_attempt(&task_attempt, executor_profile_id.clone())
+            .await?
+    };
 
     deployment
         .track_if_analytics_allowed(
@@ -146,6 +240,7 @@ pub async fn create_task_attempt(
                 "variant": &executor_profile_id.variant,
                 "executor": &executor_profile_id.executor,
                 "attempt_id": task_attempt.id.to_string(),
+                "scratch": scratch,
             }),
         )
         .await;
@@ -276,7 +371,9 @@ pub async fn follow_up(
         }
     }
 
-    let cleanup_action = project.cleanup_script.map(|script| {
+    // The project's cleanup script expects a worktree, which scratch attempts don't have
+    let cleanup_script = project.cleanup_script.filter(|_| !task_attempt.scratch);
+    let cleanup_action = cleanup_script.map(|script| {
         Box::new(ExecutorAction::new(
             ExecutorActionType::ScriptRequest(ScriptRequest {
                 script,
@@ -305,6 +402,7 @@ pub async fn follow_up(
                 },
                 executor_profile_id: executor_profile_id.clone(),
                 session_id: Some(session_id),
+                node_path: None,
             };
             let action = ExecutorAction::new(
                 ExecutorActionType::BrowserChatRequest(browser_chat_request),
@@ -317,7 +415,12 @@ pub async fn follow_up(
             let follow_up_request = CodingAgentFollowUpRequest {
                 prompt,
                 session_id,
+                executor_override: project
+                    .executor_overrides
+                    .get(&executor_profile_id.executor)
+                    .cloned(),
                 executor_profile_id,
+                agent_instructions: project.agent_instructions.clone(),
             };
             let action = ExecutorAction::new(
                 ExecutorActionType::CodingAgentFollowUpRequest(follow_up_request),
@@ -443,7 +546,75 @@ pub async fn get_task_attempt_diff(
 ) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, ApiError> {
     let stream = deployment.container().get_diff(&task_attempt).await?;
 
-    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
+    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() }))
+        .keep_alive(sse_keep_alive(&deployment).await))
+}
+
+/// Re-send the full diff on the attempt's open diff streams. Returns whether any was open.
+pub async fn refresh_task_attempt_diff(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<bool>>, ApiError> {
+    let refreshed = deployment.container().refresh_diff(&task_attempt).await;
+    Ok(ResponseJson(ApiResponse::success(refreshed)))
+}
+
+pub async fn get_task_attempt_commits(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<CommitMetadata>>>, ApiError> {
+    let commits = deployment
+        .container()
+        .attempt_commits(&task_attempt)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(commits)))
+}
+
+/// Diff of a single commit on the attempt's branch. Commits that aren't
+/// reachable from the branch are rejected with 404.
+pub async fn get_task_attempt_commit_diff(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, ApiError> {
+    task_attempt.ensure_not_scratch()?;
+    let Some(sha) = params.get("sha") else {
+        return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "Missing sha param".to_string(),
+        )));
+    };
+    let stream = deployment
+        .container()
+        .get_commit_diff(&task_attempt, sha)
+        .await?;
+
+    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() }))
+        .keep_alive(sse_keep_alive(&deployment).await))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareAttemptsQuery {
+    pub with: Uuid,
+}
+
+/// Diff from this attempt's branch tip to the tip of another attempt of the same task
+pub async fn compare_task_attempts(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<CompareAttemptsQuery>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, ApiError> {
+    task_attempt.ensure_not_scratch()?;
+    let other = TaskAttempt::find_by_id(&deployment.db().pool, query.with)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Task attempt not found".to_string()))?;
+    other.ensure_not_scratch()?;
+    let stream = deployment
+        .container()
+        .compare_attempts(&task_attempt, &other)
+        .await?;
+
+    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() }))
+        .keep_alive(sse_keep_alive(&deployment).await))
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -518,76 +689,119 @@ pub async fn merge_task_attempt(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
-    let pool = &deployment.db().pool;
-
     let task = task_attempt
-        .parent_task(pool)
+        .parent_task(&deployment.db().pool)
         .await?
         .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
-    let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
 
-    let container_ref = deployment
+    let config = deployment.config().read().await.clone();
+    deployment
         .container()
-        .ensure_container_exists(&task_attempt)
+        .merge_attempt(&task_attempt, &config)
         .await?;
-    let worktree_path = std::path::Path::new(&container_ref);
 
-    let task_uuid_str = task.id.to_string();
-    let first_uuid_section = task_uuid_str.split('-').next().unwrap_or(&task_uuid_str);
+    NotificationService::notify_attempt_merged(config.notifications, &task, &task_attempt).await;
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_merged",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": task.project_id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
 
-    // Create commit message with task title and description
-    let mut commit_message = format!("{} (vibe-kanban {})", ctx.task.title, first_uuid_section);
+    Ok(ResponseJson(ApiResponse::success(())))
+}
 
-    // Add description on next line if it exists
-    if let Some(description) = &ctx.task.description
-        && !description.trim().is_empty()
-    {
-        commit_message.push_str("\n\n");
-        commit_message.push_str(description);
-    }
+#[derive(Debug, Deserialize, TS)]
+pub struct PromoteAttemptRequest {
+    /// Also fast-forward this branch to the attempt's branch tip
+    pub fast_forward_branch: Option<String>,
+}
 
-    // Get branch name from task attempt
-    let branch_name = ctx.task_attempt.branch.as_ref().ok_or_else(|| {
-        ApiError::TaskAttempt(TaskAttemptError::ValidationError(
-            "No branch found for task attempt".to_string(),
-        ))
-    })?;
+/// Make this attempt the task's active attempt
+pub async fn promote_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<PromoteAttemptRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    deployment
+        .container()
+        .promote_attempt(&task_attempt, payload.fast_forward_branch.as_deref())
+        .await?;
 
-    let merge_commit_id = deployment.git().merge_changes(
-        &ctx.project.git_repo_path,
-        worktree_path,
-        branch_name,
-        &ctx.task_attempt.base_branch,
-        &commit_message,
-    )?;
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_promoted",
+            serde_json::json!({
+                "task_id": task_attempt.task_id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+                "fast_forward": payload.fast_forward_branch.is_some(),
+            }),
+        )
+        .await;
 
-    Merge::create_direct(
-        pool,
-        task_attempt.id,
-        &ctx.task_attempt.base_branch,
-        &merge_commit_id,
-    )
-    .await?;
-    Task::update_status(pool, ctx.task.id, TaskStatus::Done).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Show what running `executor_action` on this attempt would execute, without running it
+pub async fn preview_task_attempt_execution(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(executor_action): Json<ExecutorAction>,
+) -> Result<ResponseJson<ApiResponse<ExecutionPreview>>, ApiError> {
+    let preview = deployment
+        .container()
+        .preview_execution(&task_attempt, &executor_action)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(preview)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct RevertMergeRequest {
+    pub merge_id: Uuid,
+}
+
+/// Revert one of this attempt's merges on its target branch. Conflicts with later
+/// changes on the target branch are reported as 409 with the conflicting files.
+pub async fn revert_task_attempt_merge(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<RevertMergeRequest>,
+) -> Result<ResponseJson<ApiResponse<String>>, ApiError> {
+    let belongs_to_attempt = Merge::find_by_id(&deployment.db().pool, payload.merge_id)
+        .await?
+        .is_some_and(|merge| merge.task_attempt_id() == task_attempt.id);
+    if !belongs_to_attempt {
+        return Err(ApiError::NotFound("Merge not found".to_string()));
+    }
+
+    let config = deployment.config().read().await.clone();
+    let revert_commit = deployment
+        .container()
+        .revert_merge(payload.merge_id, &config)
+        .await?;
 
     deployment
         .track_if_analytics_allowed(
-            "task_attempt_merged",
+            "task_attempt_merge_reverted",
             serde_json::json!({
-                "task_id": ctx.task.id.to_string(),
-                "project_id": ctx.project.id.to_string(),
+                "task_id": task_attempt.task_id.to_string(),
                 "attempt_id": task_attempt.id.to_string(),
             }),
         )
         .await;
 
-    Ok(ResponseJson(ApiResponse::success(())))
+    Ok(ResponseJson(ApiResponse::success(revert_commit)))
 }
 
 pub async fn push_task_attempt_branch(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    task_attempt.ensure_not_scratch()?;
     let github_config = deployment.config().read().await.github.clone();
     let Some(github_token) = github_config.token() else {
         return Err(GitHubServiceError::TokenInvalid.into());
@@ -619,147 +833,47 @@ pub async fn create_github_pr(
     State(deployment): State<DeploymentImpl>,
     Json(request): Json<CreateGitHubPrRequest>,
 ) -> Result<ResponseJson<ApiResponse<String, GitHubServiceError>>, ApiError> {
-    let github_config = deployment.config().read().await.github.clone();
-    let Some(github_token) = github_config.token() else {
-        return Ok(ResponseJson(ApiResponse::error_with_data(
-            GitHubServiceError::TokenInvalid,
-        )));
+    task_attempt.ensure_not_scratch()?;
+    let config = deployment.config().read().await.clone();
+    let options = PullRequestOptions {
+        title: request.title,
+        body: request.body,
+        base_branch: request.base_branch,
     };
-    // Create GitHub service instance
-    let github_service = GitHubService::new(&github_token)?;
-    if let Err(e) = github_service.check_token().await {
-        if e.is_api_data() {
-            return Ok(ResponseJson(ApiResponse::error_with_data(e)));
-        } else {
-            return Err(ApiError::GitHubService(e));
-        }
-    }
-    // Get the task attempt to access the stored base branch
-    let base_branch = request.base_branch.unwrap_or_else(|| {
-        // Use the stored base branch from the task attempt as the default
-        // Fall back to config default or "main" only if stored base branch is somehow invalid
-        if !task_attempt.base_branch.trim().is_empty() {
-            task_attempt.base_branch.clone()
-        } else {
-            github_config
-                .default_pr_base
-                .as_ref()
-                .map_or_else(|| "main".to_string(), |b| b.to_string())
-        }
-    });
 
-    let pool = &deployment.db().pool;
-    let task = task_attempt
-        .parent_task(pool)
-        .await?
-        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
-    let project = Project::find_by_id(pool, task.project_id)
-        .await?
-        .ok_or(ApiError::Project(ProjectError::ProjectNotFound))?;
-
-    // Use GitService to get the remote URL, then create GitHubRepoInfo
-    let repo_info = deployment
-        .git()
-        .get_github_repo_info(&project.git_repo_path)?;
-
-    // Get branch name from task attempt
-    let branch_name = task_attempt.branch.as_ref().ok_or_else(|| {
-        ApiError::TaskAttempt(TaskAttemptError::ValidationError(
-            "No branch found for task attempt".to_string(),
-        ))
-    })?;
-    let workspace_path = PathBuf::from(
-        deployment
-            .container()
-            .ensure_container_exists(&task_attempt)
-            .await?,
-    );
-
-    // Push the branch to GitHub first
-    if let Err(e) = deployment
-        .git()
-        .push_to_github(&workspace_path, branch_name, &github_token)
+    match deployment
+        .container()
+        .create_pull_request(&task_attempt, &config, options)
+        .await
     {
-        tracing::error!("Failed to push branch to GitHub: {}", e);
-        let gh_e = GitHubServiceError::from(e);
-        if gh_e.is_api_data() {
-            return Ok(ResponseJson(ApiResponse::error_with_data(gh_e)));
-        } else {
-            return Ok(ResponseJson(ApiResponse::error(
-                format!("Failed to push branch to GitHub: {}", gh_e).as_str(),
-            )));
-        }
-    }
-
-    let norm_base_branch_name = if matches!(
-        deployment
-            .git()
-            .find_branch_type(&project.git_repo_path, &base_branch)?,
-        BranchType::Remote
-    ) {
-        // Remote branches are formatted as {remote}/{branch} locally.
-        // For PR APIs, we must provide just the branch name.
-        let remote = deployment
-            .git()
-            .get_remote_name_from_branch_name(&workspace_path, &base_branch)?;
-        let remote_prefix = format!("{}/", remote);
-        base_branch
-            .strip_prefix(&remote_prefix)
-            .unwrap_or(&base_branch)
-            .to_string()
-    } else {
-        base_branch
-    };
-    // Create the PR using GitHub service
-    let pr_request = CreatePrRequest {
-        title: request.title.clone(),
-        body: request.body.clone(),
-        head_branch: branch_name.clone(),
-        base_branch: norm_base_branch_name.clone(),
-    };
-
-    match github_service.create_pr(&repo_info, &pr_request).await {
-        Ok(pr_info) => {
-            // Update the task attempt with PR information
-            if let Err(e) = Merge::create_pr(
-                pool,
-                task_attempt.id,
-                &norm_base_branch_name,
-                pr_info.number,
-                &pr_info.url,
-            )
-            .await
-            {
-                tracing::error!("Failed to update task attempt PR status: {}", e);
-            }
-
+        Ok(pr_url) => {
+            let task = task_attempt
+                .parent_task(&deployment.db().pool)
+                .await?
+                .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
             deployment
                 .track_if_analytics_allowed(
                     "github_pr_created",
                     serde_json::json!({
                         "task_id": task.id.to_string(),
-                        "project_id": project.id.to_string(),
+                        "project_id": task.project_id.to_string(),
                         "attempt_id": task_attempt.id.to_string(),
                     }),
                 )
                 .await;
 
-            Ok(ResponseJson(ApiResponse::success(pr_info.url)))
+            Ok(ResponseJson(ApiResponse::success(pr_url)))
         }
-        Err(e) => {
-            tracing::error!(
-                "Failed to create GitHub PR for attempt {}: {}",
-                task_attempt.id,
-                e
-            );
-            if e.is_api_data() {
-                Ok(ResponseJson(ApiResponse::error_with_data(e)))
-            } else {
-                Ok(ResponseJson(ApiResponse::error(
-                    format!("Failed to create PR: {}", e).as_str(),
-                )))
-            }
+        Err(ContainerError::GitHub(e)) if e.is_api_data() => {
+            Ok(ResponseJson(ApiResponse::error_with_data(e)))
+        }
+        Err(e @ (ContainerError::GitHub(_) | ContainerError::GitLab(_))) => {
+            tracing::error!("Failed to create PR for attempt {}: {}", task_attempt.id, e);
+            Ok(ResponseJson(ApiResponse::error(
+                format!("Failed to create PR: {}", e).as_str(),
+            )))
         }
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -833,6 +947,20 @@ pub struct BranchStatus {
     pub remote_commits_behind: Option<usize>,
     pub remote_commits_ahead: Option<usize>,
     pub merges: Vec<Merge>,
+    /// Set for scratch attempts, which have no branch or worktree; every count is then
+    /// empty
+    pub scratch: bool,
+}
+
+pub async fn get_task_attempt_ahead_behind(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttemptBranchStatus>>, ApiError> {
+    let branch_status = deployment
+        .container()
+        .attempt_branch_status(&task_attempt)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(branch_status)))
 }
 
 pub async fn get_task_attempt_branch_status(
@@ -841,6 +969,22 @@ pub async fn get_task_attempt_branch_status(
 ) -> Result<ResponseJson<ApiResponse<BranchStatus>>, ApiError> {
     let pool = &deployment.db().pool;
 
+    if task_attempt.scratch {
+        return Ok(ResponseJson(ApiResponse::success(BranchStatus {
+            commits_behind: None,
+            commits_ahead: None,
+            has_uncommitted_changes: None,
+            head_oid: None,
+            uncommitted_count: None,
+            untracked_count: None,
+            base_branch_name: task_attempt.base_branch.clone(),
+            remote_commits_behind: None,
+            remote_commits_ahead: None,
+            merges: Vec::new(),
+            scratch: true,
+        })));
+    }
+
     let task = task_attempt
         .parent_task(pool)
         .await?
@@ -905,6 +1049,7 @@ pub async fn get_task_attempt_branch_status(
         remote_commits_behind: None,
         merges,
         base_branch_name: task_attempt.base_branch.clone(),
+        scratch: false,
     };
     let has_open_pr = branch_status.merges.first().is_some_and(|m| {
         matches!(
@@ -952,10 +1097,14 @@ pub async fn rebase_task_attempt(
     State(deployment): State<DeploymentImpl>,
     request_body: Option<Json<RebaseTaskAttemptRequest>>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    task_attempt.ensure_not_scratch()?;
     // Extract new base branch from request body if provided
     let new_base_branch = request_body.and_then(|body| body.new_base_branch.clone());
 
-    let github_config = deployment.config().read().await.github.clone();
+    let (github_config, signing) = {
+        let config = deployment.config().read().await;
+        (config.github.clone(), config.commit_signing())
+    };
 
     let pool = &deployment.db().pool;
 
@@ -975,24 +1124,41 @@ pub async fn rebase_task_attempt(
         .await?;
     let worktree_path = std::path::Path::new(&container_ref);
 
-    let _new_base_commit = deployment.git().rebase_branch(
-        &ctx.project.git_repo_path,
-        worktree_path,
-        effective_base_branch.clone().as_deref(),
-        &ctx.task_attempt.base_branch.clone(),
-        github_config.token(),
-    )?;
+    let _new_base_commit = deployment
+        .git()
+        .with_commit_signing(signing)
+        .rebase_branch(
+            &ctx.project.git_repo_path,
+            worktree_path,
+            effective_base_branch.clone().as_deref(),
+            &ctx.task_attempt.base_branch.clone(),
+            github_config.token(),
+        )?;
 
     if let Some(new_base_branch) = &effective_base_branch
         && new_base_branch != &ctx.task_attempt.base_branch
     {
         TaskAttempt::update_base_branch(&deployment.db().pool, task_attempt.id, new_base_branch)
             .await?;
+        deployment.container().evict_diff_stats(task_attempt.id);
     }
 
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// Abort a merge or rebase left half-finished in the attempt's worktree
+pub async fn abort_task_attempt_operation(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AbortedOperation>>, ApiError> {
+    task_attempt.ensure_not_scratch()?;
+    let aborted = deployment
+        .container()
+        .abort_attempt_operation(&task_attempt)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(aborted)))
+}
+
 #[derive(serde::Deserialize)]
 pub struct DeleteFileQuery {
     file_path: String,
@@ -1027,79 +1193,104 @@ pub async fn delete_task_attempt_file(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+#[axum::debug_handler]
+pub async fn run_setup_script(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let execution_process = deployment
+        .container()
+        .run_setup_script(&task_attempt)
+        .await?;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
 #[axum::debug_handler]
 pub async fn start_dev_server(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let execution_process = deployment
+        .container()
+        .start_dev_server(&task_attempt)
+        .await?;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
+pub async fn stop_dev_server(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
-    let pool = &deployment.db().pool;
+    deployment
+        .container()
+        .stop_dev_server(&task_attempt)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
 
-    // Get parent task
-    let task = task_attempt
-        .parent_task(&deployment.db().pool)
-        .await?
-        .ok_or(SqlxError::RowNotFound)?;
+pub async fn get_dev_server_status(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DevServerStatus>>, ApiError> {
+    let status = deployment
+        .container()
+        .dev_server_status(&task_attempt)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
 
-    // Get parent project
-    let project = task
-        .parent_project(&deployment.db().pool)
-        .await?
-        .ok_or(SqlxError::RowNotFound)?;
+pub async fn get_task_attempt_usage(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TokenUsage>>, ApiError> {
+    let usage =
+        ExecutorSession::usage_for_task_attempt(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
 
-    // Stop any existing dev servers for this project
-    let existing_dev_servers =
-        match ExecutionProcess::find_running_dev_servers_by_project(pool, project.id).await {
-            Ok(servers) => servers,
-            Err(e) => {
-                tracing::error!(
-                    "Failed to find running dev servers for project {}: {}",
-                    project.id,
-                    e
-                );
-                return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
-                    e.to_string(),
-                )));
-            }
-        };
+/// The attempt's executor sessions, initial run then follow-ups, with their summaries
+pub async fn get_task_attempt_sessions(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutorSession>>>, ApiError> {
+    let sessions =
+        ExecutorSession::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(sessions)))
+}
 
-    for dev_server in existing_dev_servers {
-        tracing::info!(
-            "Stopping existing dev server {} for project {}",
-            dev_server.id,
-            project.id
-        );
+pub async fn get_task_attempt_timeline(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionTimelineEntry>>>, ApiError> {
+    let timeline = TaskAttempt::execution_timeline(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(timeline)))
+}
 
-        if let Err(e) = deployment.container().stop_execution(&dev_server).await {
-            tracing::error!("Failed to stop dev server {}: {}", dev_server.id, e);
-        }
-    }
+pub async fn retry_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
+    let max_attempts = deployment.config().read().await.max_attempts_per_task;
+    let new_attempt = deployment
+        .container()
+        .retry_attempt(&task_attempt, max_attempts)
+        .await?;
 
-    if let Some(dev_server) = project.dev_script {
-        // TODO: Derive script language from system config
-        let executor_action = ExecutorAction::new(
-            ExecutorActionType::ScriptRequest(ScriptRequest {
-                script: dev_server,
-                language: ScriptRequestLanguage::Bash,
-                context: ScriptContext::DevServer,
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_retried",
+            serde_json::json!({
+                "task_id": new_attempt.task_id.to_string(),
+                "executor": &new_attempt.executor,
+                "attempt_id": new_attempt.id.to_string(),
+                "retried_attempt_id": task_attempt.id.to_string(),
             }),
-            None,
-        );
-
-        deployment
-            .container()
-            .start_execution(
-                &task_attempt,
-                &executor_action,
-                &ExecutionProcessRunReason::DevServer,
-            )
-            .await?
-    } else {
-        return Ok(ResponseJson(ApiResponse::error(
-            "No dev server script configured for this project",
-        )));
-    };
+        )
+        .await;
 
-    Ok(ResponseJson(ApiResponse::success(())))
+    Ok(ResponseJson(ApiResponse::success(new_attempt)))
 }
 
 pub async fn get_task_attempt_children(
@@ -1132,14 +1323,30 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/", get(get_task_attempt))
         .route("/follow-up", post(follow_up))
         .route("/restore", post(restore_task_attempt))
+        .route("/retry", post(retry_task_attempt))
         .route("/commit-info", get(get_commit_info))
         .route("/commit-compare", get(compare_commit_to_head))
+        .route("/setup-script", post(run_setup_script))
         .route("/start-dev-server", post(start_dev_server))
+        .route("/stop-dev-server", post(stop_dev_server))
+        .route("/dev-server", get(get_dev_server_status))
+        .route("/usage", get(get_task_attempt_usage))
+        .route("/sessions", get(get_task_attempt_sessions))
+        .route("/timeline", get(get_task_attempt_timeline))
         .route("/branch-status", get(get_task_attempt_branch_status))
+        .route("/ahead-behind", get(get_task_attempt_ahead_behind))
         .route("/diff", get(get_task_attempt_diff))
+        .route("/diff/refresh", post(refresh_task_attempt_diff))
+        .route("/commits", get(get_task_attempt_commits))
+        .route("/commit-diff", get(get_task_attempt_commit_diff))
+        .route("/compare", get(compare_task_attempts))
         .route("/merge", post(merge_task_attempt))
+        .route("/revert-merge", post(revert_task_attempt_merge))
+        .route("/promote", post(promote_task_attempt))
+        .route("/preview-execution", post(preview_task_attempt_execution))
         .route("/push", post(push_task_attempt_branch))
         .route("/rebase", post(rebase_task_attempt))
+        .route("/abort-operation", post(abort_task_attempt_operation))
         .route("/pr", post(create_github_pr))
         .route("/open-editor", post(open_task_attempt_in_editor))
         .route("/delete-file", post(delete_task_attempt_file))
@@ -1152,6 +1359,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let task_attempts_router = Router::new()
         .route("/", get(get_task_attempts).post(create_task_attempt))
+        .route("/diff-stats", get(get_task_diff_stats))
         .nest("/{id}", task_attempt_id_router);
 
     Router::new().nest("/task-attempts", task_attempts_router)
@@ -5,7 +5,7 @@ use axum::{
     extract::{Query, State},
     http::StatusCode,
     middleware::from_fn_with_state,
-    response::{Json as ResponseJson, Sse, sse::KeepAlive},
+    response::{Json as ResponseJson, Sse},
     routing::{get, post},
 };
 use db::models::{
@@ -15,16 +15,20 @@ use db::models::{
     task_attempt::{CreateTaskAttempt, TaskAttempt},
 };
 use deployment::Deployment;
+use executors::profile::ExecutorProfileId;
 use futures_util::TryStreamExt;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use services::services::container::{
     ContainerService, WorktreeCleanupData, cleanup_worktrees_direct,
 };
 use sqlx::Error as SqlxError;
+use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_task_middleware};
+use crate::{
+    DeploymentImpl, error::ApiError, middleware::load_task_middleware, routes::sse_keep_alive,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct TaskQuery {
@@ -55,7 +59,8 @@ pub async fn stream_tasks(
         .await
         .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
+    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() }))
+        .keep_alive(sse_keep_alive(&deployment).await))
 }
 
 pub async fn get_task(
@@ -65,10 +70,81 @@ pub async fn get_task(
     Ok(ResponseJson(ApiResponse::success(task)))
 }
 
+/// Body of the task creation routes: the task, plus how to start its first attempt
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskRequest {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub task: CreateTask,
+    /// Start a first attempt right away. Only applies when `executor_profile_id` is set.
+    #[serde(default)]
+    #[ts(as = "Option<bool>", optional)]
+    pub auto_start: bool,
+    /// Executor profile for the auto-started attempt
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+/// A newly created task, with the attempt started for it when `auto_start` was requested
+#[derive(Debug, Serialize, TS)]
+pub struct CreatedTask {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub task: Task,
+    pub attempt_id: Option<Uuid>,
+}
+
+/// Create a first attempt for `task` on the project's current branch and start it
+async fn start_first_attempt(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    executor_profile_id: ExecutorProfileId,
+) -> Result<TaskAttempt, ApiError> {
+    let project = Project::find_by_id(&deployment.db().pool, task.project_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    let branch = deployment
+        .git()
+        .get_current_branch(&project.git_repo_path)?;
+
+    let max_attempts = deployment.config().read().await.max_attempts_per_task;
+    let task_attempt = TaskAttempt::create_within_limit(
+        &deployment.db().pool,
+        &CreateTaskAttempt {
+            executor: executor_profile_id.executor,
+            base_branch: branch,
+            subpath: None,
+        },
+        task.id,
+        max_attempts,
+    )
+    .await?;
+    let execution_process = deployment
+        .container()
+        .start_attempt(&task_attempt, executor_profile_id.clone())
+        .await?;
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_started",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "executor": &executor_profile_id.executor,
+                "variant": &executor_profile_id.variant,
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    tracing::info!("Started execution process {}", execution_process.id);
+    Ok(task_attempt)
+}
+
 pub async fn create_task(
     State(deployment): State<DeploymentImpl>,
-    Json(payload): Json<CreateTask>,
-) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    Json(request): Json<CreateTaskRequest>,
+) -> Result<ResponseJson<ApiResponse<CreatedTask>>, ApiError> {
+    let payload = &request.task;
     let id = Uuid::new_v4();
 
     tracing::debug!(
@@ -77,7 +153,7 @@ pub async fn create_task(
         payload.project_id
     );
 
-    let task = Task::create(&deployment.db().pool, &payload, id).await?;
+    let task = Task::create(&deployment.db().pool, payload, id).await?;
 
     if let Some(image_ids) = &payload.image_ids {
         TaskImage::associate_many(&deployment.db().pool, task.id, image_ids).await?;
@@ -95,15 +171,35 @@ pub async fn create_task(
         )
         .await;
 
-    Ok(ResponseJson(ApiResponse::success(task)))
+    let attempt_id = match request.executor_profile_id {
+        Some(executor_profile_id) if request.auto_start => Some(
+            start_first_attempt(&deployment, &task, executor_profile_id)
+                .await?
+                .id,
+        ),
+        _ => None,
+    };
+    // Starting an attempt moves the task along, so return its latest state
+    let task = match attempt_id {
+        Some(_) => Task::find_by_id(&deployment.db().pool, task.id)
+            .await?
+            .ok_or(ApiError::Database(SqlxError::RowNotFound))?,
+        None => task,
+    };
+
+    Ok(ResponseJson(ApiResponse::success(CreatedTask {
+        task,
+        attempt_id,
+    })))
 }
 
 pub async fn create_task_and_start(
     State(deployment): State<DeploymentImpl>,
-    Json(payload): Json<CreateTask>,
+    Json(request): Json<CreateTaskRequest>,
 ) -> Result<ResponseJson<ApiResponse<TaskWithAttemptStatus>>, ApiError> {
+    let payload = &request.task;
     let task_id = Uuid::new_v4();
-    let task = Task::create(&deployment.db().pool, &payload, task_id).await?;
+    let task = Task::create(&deployment.db().pool, payload, task_id).await?;
 
     if let Some(image_ids) = &payload.image_ids {
         TaskImage::associate_many(&deployment.db().pool, task.id, image_ids).await?;
@@ -121,45 +217,18 @@ pub async fn create_task_and_start(
         )
         .await;
 
-    // use the default executor profile and the current branch for the task attempt
-    let executor_profile_id = deployment.config().read().await.executor_profile.clone();
-    let project = Project::find_by_id(&deployment.db().pool, payload.project_id)
-        .await?
-        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
-    let branch = deployment
-        .git()
-        .get_current_branch(&project.git_repo_path)?;
-
-    let task_attempt = TaskAttempt::create(
-        &deployment.db().pool,
-        &CreateTaskAttempt {
-            executor: executor_profile_id.executor,
-            base_branch: branch,
-        },
-        task.id,
-    )
-    .await?;
-    let execution_process = deployment
-        .container()
-        .start_attempt(&task_attempt, executor_profile_id.clone())
-        .await?;
-    deployment
-        .track_if_analytics_allowed(
-            "task_attempt_started",
-            serde_json::json!({
-                "task_id": task.id.to_string(),
-                "executor": &executor_profile_id.executor,
-                "variant": &executor_profile_id.variant,
-                "attempt_id": task_attempt.id.to_string(),
-            }),
-        )
-        .await;
+    // use the requested executor profile (or the default) and the current branch for the task
+    // attempt
+    let executor_profile_id = match request.executor_profile_id {
+        Some(executor_profile_id) => executor_profile_id,
+        None => deployment.config().read().await.executor_profile.clone(),
+    };
+    let task_attempt = start_first_attempt(&deployment, &task, executor_profile_id).await?;
 
     let task = Task::find_by_id(&deployment.db().pool, task.id)
         .await?
         .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
 
-    tracing::info!("Started execution process {}", execution_process.id);
     Ok(ResponseJson(ApiResponse::success(TaskWithAttemptStatus {
         id: task.id,
         title: task.title,
@@ -167,6 +236,7 @@ pub async fn create_task_and_start(
         project_id: task.project_id,
         status: task.status,
         parent_task_attempt: task.parent_task_attempt,
+        preferred_attempt_id: task.preferred_attempt_id,
         created_at: task.created_at,
         updated_at: task.updated_at,
         has_in_progress_attempt: true,
@@ -282,6 +352,9 @@ pub async fn delete_task(
     if rows_affected == 0 {
         return Err(ApiError::Database(SqlxError::RowNotFound));
     }
+    for attempt in &attempts {
+        deployment.container().evict_diff_stats(attempt.id);
+    }
 
     // Spawn background worktree cleanup task
     let task_id = task.id;
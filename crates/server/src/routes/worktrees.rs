@@ -0,0 +1,72 @@
+use axum::{
+    Router,
+    extract::State,
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::task_attempt::TaskAttempt;
+use deployment::Deployment;
+use serde::Serialize;
+use services::services::{container::ContainerService, worktree_manager::WorktreeManager};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptWorktreeUsage {
+    /// None when no task attempt owns the worktree directory
+    pub attempt_id: Option<Uuid>,
+    pub path: String,
+    #[ts(type = "number")]
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct WorktreeUsage {
+    #[ts(type = "number")]
+    pub total_bytes: u64,
+    /// Largest first
+    pub worktrees: Vec<AttemptWorktreeUsage>,
+}
+
+pub async fn get_worktree_usage(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<WorktreeUsage>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let usage = WorktreeManager::disk_usage().await?;
+
+    let mut worktrees = Vec::with_capacity(usage.len());
+    for entry in usage {
+        let path = entry.path.to_string_lossy().to_string();
+        let attempt_id = TaskAttempt::resolve_container_ref(pool, &path)
+            .await
+            .ok()
+            .map(|(attempt_id, _, _)| attempt_id);
+        worktrees.push(AttemptWorktreeUsage {
+            attempt_id,
+            path,
+            bytes: entry.bytes,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(WorktreeUsage {
+        total_bytes: worktrees.iter().map(|w| w.bytes).sum(),
+        worktrees,
+    })))
+}
+
+/// Remove the worktrees of merged attempts of done tasks now; returns the pruned attempt ids
+pub async fn prune_merged_worktrees(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Uuid>>>, ApiError> {
+    let pruned = deployment.container().prune_merged_worktrees().await?;
+    Ok(ResponseJson(ApiResponse::success(pruned)))
+}
+
+pub fn router(_deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/worktrees/usage", get(get_worktree_usage))
+        .route("/worktrees/prune-merged", post(prune_merged_worktrees))
+}
@@ -1,38 +1,89 @@
-use std::{
-    path::Path,
-    process::Stdio,
-};
+use std::{collections::HashMap, path::Path, process::Stdio, sync::LazyLock};
 
 use anyhow::Error as AnyhowError;
 use async_trait::async_trait;
+use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tokio::process::Command;
+use tokio::{io::AsyncReadExt, process::Command, sync::Mutex};
 use ts_rs::TS;
+use utils::shell::resolve_executable_path;
 use uuid::Uuid;
 
 use executors::actions::browser_chat_request::{BrowserChatAgentType, BrowserChatRequest};
 
+use super::config::{BrowserChatTimeoutConfig, Config};
+
+/// Version of the JSON contract between this service and the automation scripts. Scripts
+/// that don't send one speak this version, which is the shape they have always had.
+pub const BROWSER_CHAT_PROTOCOL_VERSION: u32 = 1;
+
+/// Node.js installations already probed, keyed by the configured binary. Only found
+/// installations are kept, so installing Node.js later doesn't need a restart.
+static NODE_RUNTIMES: LazyLock<Mutex<HashMap<String, NodeRuntime>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 #[derive(Debug, Error)]
 pub enum BrowserChatError {
     #[error("Node.js process spawn failed: {0}")]
     SpawnFailed(#[from] std::io::Error),
+    #[error(
+        "Node.js not found at '{0}'. Install Node.js or set `browser_chat_node_path` to its binary (e.g. under nvm or volta)"
+    )]
+    NodeNotFound(String),
     #[error("Browser automation script not found: {0}")]
     ScriptNotFound(String),
     #[error("Browser automation failed: {0}")]
     AutomationFailed(String),
+    #[error(
+        "Browser automation environment is not available ({0}). Install Node.js and build the scripts with `npm install && npm run build` in browser-automation/"
+    )]
+    EnvironmentUnavailable(String),
+    #[error(
+        "Browser automation scripts speak protocol version {found} but version {supported} is required. Rebuild them with `npm install && npm run build` in browser-automation/"
+    )]
+    ProtocolMismatch { found: u32, supported: u32 },
     #[error(transparent)]
     Other(#[from] AnyhowError),
 }
 
+fn unversioned_protocol() -> u32 {
+    BROWSER_CHAT_PROTOCOL_VERSION
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct BrowserChatResponse {
+    #[serde(default = "unversioned_protocol")]
+    pub protocol_version: u32,
     pub success: bool,
     pub message: String,
     pub error: Option<String>,
     pub session_id: Option<String>,
 }
 
+/// A working Node.js installation
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRuntime {
+    /// Resolved path of the node binary
+    pub path: String,
+    /// Output of `node --version`, e.g. "v22.4.0"
+    pub version: String,
+}
+
+/// A running automation's process group (node and the browser it launched). Killed when
+/// dropped before it exits, so a cancelled `send_message` doesn't leave them running.
+struct AutomationProcess(AsyncGroupChild);
+
+impl Drop for AutomationProcess {
+    fn drop(&mut self) {
+        if matches!(self.0.try_wait(), Ok(None))
+            && let Err(e) = self.0.start_kill()
+        {
+            tracing::error!("Failed to kill abandoned browser automation: {}", e);
+        }
+    }
+}
+
 #[async_trait]
 pub trait BrowserChatService {
     /// Send a message to a browser-based chat agent
@@ -44,15 +95,54 @@ pub trait BrowserChatService {
 
     /// Check if the browser automation environment is ready
     async fn health_check(&self) -> Result<bool, BrowserChatError>;
+
+    /// Fail with `NodeNotFound` or `EnvironmentUnavailable` unless `agent_type` can run. Only
+    /// that agent's script is required, so a missing script for another agent doesn't block it.
+    async fn ensure_agent_ready(
+        &self,
+        agent_type: &BrowserChatAgentType,
+    ) -> Result<(), BrowserChatError>;
 }
 
 pub struct NodeBrowserChatService {
     script_path: String,
+    node_path: String,
+    timeouts: BrowserChatTimeoutConfig,
 }
 
 impl NodeBrowserChatService {
     pub fn new(script_path: String) -> Self {
-        Self { script_path }
+        Self {
+            script_path,
+            node_path: "node".to_string(),
+            timeouts: BrowserChatTimeoutConfig::default(),
+        }
+    }
+
+    /// The service with the node binary and time limits from `config`
+    pub fn from_config(config: &Config) -> Self {
+        // TODO: Make script path configurable
+        let service = Self::new("./browser-automation".to_string())
+            .with_timeouts(config.browser_chat_timeouts.clone());
+        match &config.browser_chat_node_path {
+            Some(node_path) if !node_path.trim().is_empty() => {
+                service.with_node_path(node_path.trim().to_string())
+            }
+            _ => service,
+        }
+    }
+
+    /// Run the scripts with this node binary, a name on PATH or a full path, instead of
+    /// `node` from PATH
+    pub fn with_node_path(mut self, node_path: String) -> Self {
+        self.node_path = node_path;
+        self
+    }
+
+    /// Kill automations that outlive the time limit configured for their agent
+    pub fn with_timeouts(mut self, timeouts: BrowserChatTimeoutConfig) -> Self {
+        self.timeouts = timeouts;
+        self
     }
 
     /// Get the script path for the given agent type
@@ -67,6 +157,78 @@ impl NodeBrowserChatService {
         }
     }
 
+    /// The Node.js installation the scripts run with, probed once per node binary
+    pub async fn node_runtime(&self) -> Result<NodeRuntime, BrowserChatError> {
+        let mut runtimes = NODE_RUNTIMES.lock().await;
+        if let Some(runtime) = runtimes.get(&self.node_path) {
+            return Ok(runtime.clone());
+        }
+
+        let not_found = || BrowserChatError::NodeNotFound(self.node_path.clone());
+        let path = resolve_executable_path(&self.node_path).ok_or_else(not_found)?;
+        let output = Command::new(&path)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .map_err(|_| not_found())?;
+        if !output.status.success() {
+            return Err(not_found());
+        }
+        let runtime = NodeRuntime {
+            path,
+            version: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        };
+        tracing::debug!(
+            "Using Node.js {} at {} for browser automation",
+            runtime.version,
+            runtime.path
+        );
+        runtimes.insert(self.node_path.clone(), runtime.clone());
+        Ok(runtime)
+    }
+
+    /// Parse the script's result from its stdout. Scripts may log freely, so only the last
+    /// non-empty line is read, and its protocol version, if it states one, is checked
+    /// before the rest.
+    fn parse_response(stdout: &str) -> Result<BrowserChatResponse, BrowserChatError> {
+        let last_line = stdout
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .unwrap_or_default();
+        let parsed = serde_json::from_str::<serde_json::Value>(last_line).and_then(|value| {
+            // A script on another protocol may have changed the rest of the shape too
+            let found = value
+                .get("protocol_version")
+                .and_then(serde_json::Value::as_u64)
+                .map_or(BROWSER_CHAT_PROTOCOL_VERSION, |version| {
+                    u32::try_from(version).unwrap_or(u32::MAX)
+                });
+            if found != BROWSER_CHAT_PROTOCOL_VERSION {
+                return Ok(Err(BrowserChatError::ProtocolMismatch {
+                    found,
+                    supported: BROWSER_CHAT_PROTOCOL_VERSION,
+                }));
+            }
+            serde_json::from_value::<BrowserChatResponse>(value).map(Ok)
+        });
+
+        match parsed {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::error!("Failed to parse browser chat response: {}", e);
+                Ok(BrowserChatResponse {
+                    protocol_version: BROWSER_CHAT_PROTOCOL_VERSION,
+                    success: false,
+                    message: "Failed to parse automation response".to_string(),
+                    error: Some(format!("JSON parse error: {e}")),
+                    session_id: None,
+                })
+            }
+        }
+    }
+
     /// Validate that required scripts exist
     async fn validate_script_exists(&self, script_path: &str) -> Result<(), BrowserChatError> {
         if !Path::new(script_path).exists() {
@@ -87,71 +249,305 @@ impl BrowserChatService for NodeBrowserChatService {
         
         // Validate script exists
         self.validate_script_exists(&script_path).await?;
+        let node = self.node_runtime().await?;
 
         // Prepare the command to run the Node.js script
-        let mut cmd = Command::new("node");
+        let mut cmd = Command::new(&node.path);
         cmd.arg(&script_path)
             .arg("--message")
             .arg(&request.message)
             .arg("--execution-id")
-            .arg(&execution_id.to_string())
+            .arg(execution_id.to_string())
             .arg("--agent-type")
-            .arg(&format!("{:?}", request.agent_type))
+            .arg(format!("{:?}", request.agent_type))
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        // Execute the command
-        let output = cmd.output().await?;
-
-        if output.status.success() {
-            // Parse the JSON response from stdout
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            match serde_json::from_str::<BrowserChatResponse>(&stdout) {
-                Ok(response) => Ok(response),
-                Err(e) => {
-                    tracing::error!("Failed to parse browser chat response: {}", e);
-                    Ok(BrowserChatResponse {
-                        success: false,
-                        message: "Failed to parse automation response".to_string(),
-                        error: Some(format!("JSON parse error: {}", e)),
-                        session_id: None,
-                    })
+        // In its own process group, so the browser it launches can be killed with it
+        let mut child = AutomationProcess(cmd.group_spawn()?);
+        let mut stdout = child
+            .0
+            .inner()
+            .stdout
+            .take()
+            .ok_or_else(|| AnyhowError::msg("Browser automation stdout was not captured"))?;
+        let mut stderr = child
+            .0
+            .inner()
+            .stderr
+            .take()
+            .ok_or_else(|| AnyhowError::msg("Browser automation stderr was not captured"))?;
+
+        // Drain stderr alongside stdout so a chatty script can't block on a full pipe
+        let stderr_reader = tokio::spawn(async move {
+            let mut buf = Vec::new();
+            let _ = stderr.read_to_end(&mut buf).await;
+            String::from_utf8_lossy(&buf).into_owned()
+        });
+        let run = async {
+            let mut output = String::new();
+            stdout.read_to_string(&mut output).await?;
+            let status = child.0.wait().await?;
+            Ok::<_, std::io::Error>((output, status))
+        };
+        let outcome = match self.timeouts.timeout(&request.agent_type) {
+            Some(limit) => tokio::time::timeout(limit, run).await.map_err(|_| limit),
+            None => Ok(run.await),
+        };
+        let (stdout, status) = match outcome {
+            Ok(result) => result?,
+            Err(limit) => {
+                tracing::warn!(
+                    "Browser automation for {:?} timed out after {:?}, killing it",
+                    request.agent_type,
+                    limit
+                );
+                if let Err(e) = child.0.kill().await {
+                    tracing::error!("Failed to kill browser automation: {}", e);
                 }
+                stderr_reader.abort();
+                return Err(BrowserChatError::AutomationFailed(format!(
+                    "Timed out after {}s waiting for the {:?} automation, which may be stuck on a login page",
+                    limit.as_secs(),
+                    request.agent_type
+                )));
             }
+        };
+        let stderr = stderr_reader.await.unwrap_or_default();
+
+        if status.success() {
+            Self::parse_response(&stdout)
         } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
             tracing::error!("Browser automation script failed: {}", stderr);
-            
+
             Err(BrowserChatError::AutomationFailed(format!(
                 "Script execution failed with exit code {}: {}",
-                output.status.code().unwrap_or(-1),
+                status.code().unwrap_or(-1),
                 stderr
             )))
         }
     }
 
     async fn health_check(&self) -> Result<bool, BrowserChatError> {
-        // Check if Node.js is available
-        let node_check = Command::new("node")
-            .arg("--version")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-            .await?;
+        for agent_type in [
+            BrowserChatAgentType::Claude,
+            BrowserChatAgentType::M365Copilot,
+        ] {
+            match self.ensure_agent_ready(&agent_type).await {
+                Ok(()) => {}
+                Err(
+                    e @ (BrowserChatError::EnvironmentUnavailable(_)
+                    | BrowserChatError::NodeNotFound(_)),
+                ) => {
+                    tracing::warn!("Browser automation for {:?} unavailable: {}", agent_type, e);
+                    return Ok(false);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(true)
+    }
 
-        if !node_check.success() {
-            return Ok(false);
+    async fn ensure_agent_ready(
+        &self,
+        agent_type: &BrowserChatAgentType,
+    ) -> Result<(), BrowserChatError> {
+        self.node_runtime().await?;
+
+        let script_path = self.get_agent_script_path(agent_type);
+        if !Path::new(&script_path).exists() {
+            return Err(BrowserChatError::EnvironmentUnavailable(format!(
+                "script not found: {script_path}"
+            )));
         }
 
-        // Check if required scripts exist
-        for agent_type in [BrowserChatAgentType::Claude, BrowserChatAgentType::M365Copilot] {
-            let script_path = self.get_agent_script_path(&agent_type);
-            if !Path::new(&script_path).exists() {
-                tracing::warn!("Browser automation script not found: {}", script_path);
-                return Ok(false);
-            }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claude_request() -> BrowserChatRequest {
+        BrowserChatRequest {
+            message: "hello".to_string(),
+            agent_type: BrowserChatAgentType::Claude,
+            executor_profile_id: executors::profile::ExecutorProfileId::new(
+                executors::executors::BaseCodingAgent::ClaudeBrowserChat,
+            ),
+            session_id: None,
+            node_path: None,
         }
+    }
 
-        Ok(true)
+    /// Write a Claude script that records its pid and never exits, returning the pid file
+    fn write_hanging_script(scripts: &Path) -> std::path::PathBuf {
+        let pid_file = scripts.join("pid");
+        std::fs::write(
+            scripts.join("claude-automation.js"),
+            format!(
+                "require('fs').writeFileSync({:?}, String(process.pid));\nsetInterval(() => {{}}, 1000);\n",
+                pid_file.to_string_lossy()
+            ),
+        )
+        .unwrap();
+        pid_file
+    }
+
+    #[tokio::test]
+    #[ignore = "needs node on PATH"]
+    async fn missing_script_only_blocks_its_own_agent() {
+        let scripts = tempfile::tempdir().unwrap();
+        std::fs::write(scripts.path().join("claude-automation.js"), "").unwrap();
+        let service = NodeBrowserChatService::new(scripts.path().to_string_lossy().to_string());
+
+        assert!(
+            service
+                .ensure_agent_ready(&BrowserChatAgentType::Claude)
+                .await
+                .is_ok()
+        );
+        assert!(matches!(
+            service
+                .ensure_agent_ready(&BrowserChatAgentType::M365Copilot)
+                .await,
+            Err(BrowserChatError::EnvironmentUnavailable(_))
+        ));
+        assert!(!service.health_check().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn missing_node_is_reported_before_spawning() {
+        let scripts = tempfile::tempdir().unwrap();
+        std::fs::write(scripts.path().join("claude-automation.js"), "").unwrap();
+        let missing_node = scripts.path().join("no-such-node");
+        let service = NodeBrowserChatService::new(scripts.path().to_string_lossy().to_string())
+            .with_node_path(missing_node.to_string_lossy().to_string());
+        let request = claude_request();
+
+        assert!(matches!(
+            service.send_message(&request, Uuid::new_v4()).await,
+            Err(BrowserChatError::NodeNotFound(path)) if path == missing_node.to_string_lossy()
+        ));
+        assert!(matches!(
+            service
+                .ensure_agent_ready(&BrowserChatAgentType::Claude)
+                .await,
+            Err(BrowserChatError::NodeNotFound(_))
+        ));
+        assert!(!service.health_check().await.unwrap());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    #[ignore = "needs node on PATH"]
+    async fn hung_automation_is_killed_at_the_deadline() {
+        let scripts = tempfile::tempdir().unwrap();
+        let pid_file = write_hanging_script(scripts.path());
+        let service = NodeBrowserChatService::new(scripts.path().to_string_lossy().to_string())
+            .with_timeouts(BrowserChatTimeoutConfig {
+                claude_secs: 1,
+                m365_copilot_secs: 0,
+            });
+        let request = claude_request();
+
+        let started = std::time::Instant::now();
+        let result = service.send_message(&request, Uuid::new_v4()).await;
+
+        assert!(
+            matches!(&result, Err(BrowserChatError::AutomationFailed(msg)) if msg.contains("Timed out"))
+        );
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let alive = std::process::Command::new("kill")
+            .args(["-0", &pid])
+            .status()
+            .unwrap()
+            .success();
+        assert!(!alive);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs node on PATH"]
+    async fn cancelled_send_kills_the_automation() {
+        let scripts = tempfile::tempdir().unwrap();
+        let pid_file = write_hanging_script(scripts.path());
+        let service = NodeBrowserChatService::new(scripts.path().to_string_lossy().to_string())
+            .with_timeouts(BrowserChatTimeoutConfig {
+                claude_secs: 0,
+                m365_copilot_secs: 0,
+            });
+        let request = claude_request();
+        let send =
+            tokio::spawn(async move { service.send_message(&request, Uuid::new_v4()).await });
+        let pid = tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            loop {
+                if let Ok(pid) = std::fs::read_to_string(&pid_file)
+                    && !pid.is_empty()
+                {
+                    return pid;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        send.abort();
+        let alive = || {
+            std::process::Command::new("kill")
+                .args(["-0", &pid])
+                .status()
+                .unwrap()
+                .success()
+        };
+        let gone = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while alive() {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        })
+        .await;
+        assert!(gone.is_ok(), "automation {pid} outlived its cancelled send");
+    }
+
+    #[test]
+    fn result_is_read_from_the_last_line_after_logs() {
+        let stdout = "Starting browser automation for Claude\n\
+            {\"level\": \"info\"}\n\
+            {\"protocol_version\": 1, \"success\": true, \"message\": \"hi\", \"error\": null, \"session_id\": \"s1\"}\n\n";
+
+        let response = NodeBrowserChatService::parse_response(stdout).unwrap();
+        assert!(response.success);
+        assert_eq!(response.message, "hi");
+        assert_eq!(response.session_id.as_deref(), Some("s1"));
+    }
+
+    #[test]
+    fn unversioned_output_is_accepted_and_other_versions_are_rejected() {
+        // The scripts don't state a version, and their output is the current shape
+        let unversioned =
+            r#"{"success": true, "message": "hi", "error": null, "session_id": null}"#;
+        let response = NodeBrowserChatService::parse_response(unversioned).unwrap();
+        assert!(response.success);
+        assert_eq!(response.protocol_version, BROWSER_CHAT_PROTOCOL_VERSION);
+
+        let older = r#"{"protocol_version": 0, "success": true, "message": "hi"}"#;
+        assert!(matches!(
+            NodeBrowserChatService::parse_response(older),
+            Err(BrowserChatError::ProtocolMismatch {
+                found: 0,
+                supported: BROWSER_CHAT_PROTOCOL_VERSION
+            })
+        ));
+
+        let newer = r#"{"protocol_version": 2, "reply": {"text": "hi"}}"#;
+        assert!(matches!(
+            NodeBrowserChatService::parse_response(newer),
+            Err(BrowserChatError::ProtocolMismatch { found: 2, .. })
+        ));
+
+        let response = NodeBrowserChatService::parse_response("not json").unwrap();
+        assert!(!response.success);
+    }
+}
@@ -14,13 +14,21 @@ pub enum ConfigError {
     ValidationError(String),
 }
 
-pub type Config = versions::v6::Config;
-pub type NotificationConfig = versions::v6::NotificationConfig;
-pub type EditorConfig = versions::v6::EditorConfig;
-pub type ThemeMode = versions::v6::ThemeMode;
-pub type SoundFile = versions::v6::SoundFile;
-pub type EditorType = versions::v6::EditorType;
-pub type GitHubConfig = versions::v6::GitHubConfig;
+pub type Config = versions::v7::Config;
+pub type NotificationConfig = versions::v7::NotificationConfig;
+pub type EditorConfig = versions::v7::EditorConfig;
+pub type ThemeMode = versions::v7::ThemeMode;
+pub type SoundFile = versions::v7::SoundFile;
+pub type EditorType = versions::v7::EditorType;
+pub type GitHubConfig = versions::v7::GitHubConfig;
+pub type GitLabConfig = versions::v7::GitLabConfig;
+pub type DirtyWorktreePolicy = versions::v7::DirtyWorktreePolicy;
+pub type CommitSigningFormat = versions::v7::CommitSigningFormat;
+pub type AgentRetryConfig = versions::v7::AgentRetryConfig;
+pub type WorktreeNamingConfig = versions::v7::WorktreeNamingConfig;
+pub type WatcherBackend = versions::v7::WatcherBackend;
+pub type FilesystemWatcherConfig = versions::v7::FilesystemWatcherConfig;
+pub type BrowserChatTimeoutConfig = versions::v7::BrowserChatTimeoutConfig;
 
 /// Will always return config, trying old schemas or eventually returning default
 pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
@@ -0,0 +1,701 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::Error;
+use executors::{
+    actions::browser_chat_request::BrowserChatAgentType, executors::BaseCodingAgent,
+    logs::provider_error::ProviderError, profile::ExecutorProfileId,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ts_rs::TS;
+use utils::text::{expand_placeholders, git_branch_slug, short_uuid};
+use uuid::Uuid;
+pub use v6::{EditorConfig, EditorType, GitHubConfig, NotificationConfig, SoundFile, ThemeMode};
+
+use crate::services::{
+    config::versions::v6, git::CommitSigning, worktree_manager::WorktreeManager,
+};
+
+/// Default upper bound for uploaded task images (20MB)
+pub const DEFAULT_IMAGE_MAX_SIZE_BYTES: u64 = 20 * 1024 * 1024;
+
+fn default_image_max_size_bytes() -> u64 {
+    DEFAULT_IMAGE_MAX_SIZE_BYTES
+}
+
+/// Default cap on the executor summary used for commit messages
+pub const DEFAULT_EXECUTION_SUMMARY_MAX_BYTES: usize = 4096;
+
+fn default_execution_summary_max_bytes() -> usize {
+    DEFAULT_EXECUTION_SUMMARY_MAX_BYTES
+}
+
+/// Default seconds of silence before an SSE keep-alive comment is sent
+pub const DEFAULT_SSE_KEEPALIVE_INTERVAL_SECS: u64 = 15;
+
+fn default_sse_keepalive_interval_secs() -> u64 {
+    DEFAULT_SSE_KEEPALIVE_INTERVAL_SECS
+}
+
+/// Default seconds a stopped process gets per signal before escalating
+pub const DEFAULT_STOP_GRACE_PERIOD_SECS: u64 = 2;
+
+fn default_stop_grace_period_secs() -> u64 {
+    DEFAULT_STOP_GRACE_PERIOD_SECS
+}
+
+/// Default cap on the number of commits listed for an attempt
+pub const DEFAULT_ATTEMPT_COMMITS_LIMIT: usize = 200;
+
+fn default_attempt_commits_limit() -> usize {
+    DEFAULT_ATTEMPT_COMMITS_LIMIT
+}
+
+pub const DEFAULT_WORKTREE_MIN_FREE_BYTES: u64 = 512 * 1024 * 1024;
+
+fn default_worktree_min_free_bytes() -> u64 {
+    DEFAULT_WORKTREE_MIN_FREE_BYTES
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// What to do with uncommitted changes in an attempt's worktree before a coding agent runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(use_ts_enum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DirtyWorktreePolicy {
+    /// Don't start the agent; report the modified files instead
+    Refuse,
+    /// Stash the changes under `refs/vibe-kanban/stash/<attempt id>`, so they can be
+    /// restored with `git stash apply <ref>`
+    Stash,
+    /// Start the agent on top of the changes
+    #[default]
+    Ignore,
+}
+
+/// Which backend git uses to sign commits (git's `gpg.format`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(use_ts_enum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CommitSigningFormat {
+    /// GPG keys
+    #[default]
+    Openpgp,
+    /// SSH keys
+    Ssh,
+    /// X.509 certificates, e.g. via gitsign
+    X509,
+}
+
+impl CommitSigningFormat {
+    pub fn as_git_format(&self) -> &'static str {
+        match self {
+            CommitSigningFormat::Openpgp => "openpgp",
+            CommitSigningFormat::Ssh => "ssh",
+            CommitSigningFormat::X509 => "x509",
+        }
+    }
+}
+
+/// Credentials for opening merge requests on GitLab
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct GitLabConfig {
+    /// Personal or project access token with the `api` scope
+    pub token: Option<String>,
+    /// Base URL of a self-hosted instance (defaults to https://gitlab.com)
+    pub base_url: Option<String>,
+}
+
+impl GitLabConfig {
+    pub fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/'))
+            .filter(|url| !url.is_empty())
+            .unwrap_or("https://gitlab.com")
+    }
+}
+
+/// How attempt worktree directories (and their branches) are named
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct WorktreeNamingConfig {
+    /// Name with `{prefix}`, `{short_id}` (start of the attempt id) and `{slug}` (from the
+    /// task title) placeholders
+    pub template: String,
+    pub prefix: String,
+    /// Longest slug taken from the task title, in characters
+    pub max_slug_length: usize,
+    /// Longest name, in characters. Longer names get their slug shortened and suffixed
+    /// with a hash of the full title so they stay unique.
+    pub max_name_length: usize,
+}
+
+impl Default for WorktreeNamingConfig {
+    fn default() -> Self {
+        Self {
+            template: "{prefix}-{short_id}-{slug}".to_string(),
+            prefix: "vk".to_string(),
+            max_slug_length: 10,
+            max_name_length: 60,
+        }
+    }
+}
+
+impl WorktreeNamingConfig {
+    /// Worktree directory and branch name for attempt `attempt_id` of a task titled
+    /// `task_title`
+    pub fn name(&self, attempt_id: &Uuid, task_title: &str) -> Result<String, String> {
+        let slug = git_branch_slug(task_title, self.max_slug_length);
+        let mut name = self.render(attempt_id, &slug)?;
+        let length = name.chars().count();
+        if length > self.max_name_length {
+            let hash = format!("{:x}", Sha256::digest(task_title.as_bytes()));
+            let hash = &hash[..8];
+            // Room for the slug once the hash and its separator are added
+            let keep = slug
+                .chars()
+                .count()
+                .saturating_sub(length - self.max_name_length + hash.len() + 1);
+            let short: String = slug.chars().take(keep).collect();
+            let short = short.trim_end_matches('-');
+            let slug = if short.is_empty() {
+                hash.to_string()
+            } else {
+                format!("{short}-{hash}")
+            };
+            name = self.render(attempt_id, &slug)?;
+            if name.chars().count() > self.max_name_length {
+                return Err(format!(
+                    "'{name}' is longer than {} characters",
+                    self.max_name_length
+                ));
+            }
+        }
+        if !WorktreeManager::is_valid_worktree_name(&name) {
+            return Err(format!(
+                "'{name}' is not a valid worktree directory and branch name"
+            ));
+        }
+        Ok(name)
+    }
+
+    fn render(&self, attempt_id: &Uuid, slug: &str) -> Result<String, String> {
+        expand_placeholders(&self.template, |var| match var {
+            "prefix" => Some(self.prefix.clone()),
+            "short_id" => Some(short_uuid(attempt_id)),
+            "slug" => Some(slug.to_string()),
+            _ => None,
+        })
+    }
+
+    /// Check the template produces a legal name, e.g. before saving it. `{short_id}` is
+    /// required, since it is what keeps attempts of the same task apart.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.template.contains("{short_id}") {
+            return Err(format!(
+                "'{}' must include the {{short_id}} placeholder",
+                self.template
+            ));
+        }
+        self.name(&Uuid::nil(), "Example task").map(|_| ())
+    }
+}
+
+/// Re-running coding agents that failed with a transient provider error. Off unless
+/// `max_retries` is raised.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct AgentRetryConfig {
+    /// Re-runs allowed per action (0 = never retry)
+    pub max_retries: u32,
+    /// Seconds before the first re-run; doubles with every further one
+    pub backoff_secs: u64,
+    /// Provider error types reported in the agent's JSON output that mark a failure as
+    /// retryable, e.g. `overloaded_error`
+    pub error_types: Vec<String>,
+    /// HTTP statuses of provider errors in the agent's JSON output that mark a failure
+    /// as retryable
+    pub status_codes: Vec<u16>,
+    /// Exit codes that mark a failure as retryable
+    pub exit_codes: Vec<i64>,
+}
+
+impl Default for AgentRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            backoff_secs: 10,
+            error_types: ["rate_limit_error", "overloaded_error"]
+                .map(String::from)
+                .to_vec(),
+            status_codes: vec![429, 503, 529],
+            exit_codes: Vec::new(),
+        }
+    }
+}
+
+impl AgentRetryConfig {
+    /// How long to wait before re-running an action that failed with `exit_code` and
+    /// reported `errors` after `retry_count` earlier re-runs, or None if it shouldn't be
+    /// retried
+    pub fn retry_delay(
+        &self,
+        exit_code: Option<i64>,
+        errors: &[ProviderError],
+        retry_count: i64,
+    ) -> Option<Duration> {
+        if retry_count >= i64::from(self.max_retries) {
+            return None;
+        }
+        let retryable = exit_code.is_some_and(|code| self.exit_codes.contains(&code))
+            || errors.iter().any(|error| {
+                error
+                    .error_type
+                    .as_ref()
+                    .is_some_and(|error_type| self.error_types.contains(error_type))
+                    || error
+                        .status
+                        .is_some_and(|status| self.status_codes.contains(&status))
+            });
+        let backoff = self
+            .backoff_secs
+            .saturating_mul(1 << retry_count.clamp(0, 16));
+        retryable.then(|| Duration::from_secs(backoff))
+    }
+}
+
+/// How live diffs notice changes to an attempt's worktree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(use_ts_enum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WatcherBackend {
+    /// Polling on network and container filesystems, native events everywhere else
+    #[default]
+    Auto,
+    /// The OS's file change events (inotify, FSEvents, ReadDirectoryChangesW)
+    Native,
+    /// Rescan the worktree periodically; slower but works on any filesystem
+    Polling,
+}
+
+/// Filesystem watching for live diffs
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct FilesystemWatcherConfig {
+    pub backend: WatcherBackend,
+    /// How often the polling backend rescans, in milliseconds
+    pub poll_interval_ms: u64,
+}
+
+impl Default for FilesystemWatcherConfig {
+    fn default() -> Self {
+        Self {
+            backend: WatcherBackend::Auto,
+            poll_interval_ms: 1000,
+        }
+    }
+}
+
+/// Time limits for a single browser chat automation run, per agent
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct BrowserChatTimeoutConfig {
+    /// Seconds before a Claude automation is killed (0 = no limit)
+    pub claude_secs: u64,
+    /// Seconds before an M365 Copilot automation is killed (0 = no limit). Its
+    /// sign-in flow usually takes longer.
+    pub m365_copilot_secs: u64,
+}
+
+impl Default for BrowserChatTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            claude_secs: 300,
+            m365_copilot_secs: 600,
+        }
+    }
+}
+
+impl BrowserChatTimeoutConfig {
+    /// Configured time limit for `agent_type`, if any
+    pub fn timeout(&self, agent_type: &BrowserChatAgentType) -> Option<Duration> {
+        let secs = match agent_type {
+            BrowserChatAgentType::Claude => self.claude_secs,
+            BrowserChatAgentType::M365Copilot => self.m365_copilot_secs,
+        };
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct Config {
+    pub config_version: String,
+    pub theme: ThemeMode,
+    pub executor_profile: ExecutorProfileId,
+    pub disclaimer_acknowledged: bool,
+    pub onboarding_acknowledged: bool,
+    pub github_login_acknowledged: bool,
+    pub telemetry_acknowledged: bool,
+    pub notifications: NotificationConfig,
+    pub editor: EditorConfig,
+    pub github: GitHubConfig,
+    #[serde(default)]
+    pub gitlab: GitLabConfig,
+    pub analytics_enabled: Option<bool>,
+    pub workspace_dir: Option<String>,
+    pub last_app_version: Option<String>,
+    pub show_release_notes: bool,
+    /// Largest image, in bytes, accepted for task attachments. Read at startup, so a
+    /// change applies after the server restarts.
+    #[serde(default = "default_image_max_size_bytes")]
+    pub image_max_size_bytes: u64,
+    /// Don't commit after a cleanup script that left tracked files unchanged
+    #[serde(default = "default_true")]
+    pub skip_noop_cleanup_commits: bool,
+    /// Longest final agent message, in bytes, kept as the summary/commit message
+    #[serde(default = "default_execution_summary_max_bytes")]
+    pub execution_summary_max_bytes: usize,
+    /// Kill setup scripts that run longer than this many seconds (0 = no limit)
+    #[serde(default)]
+    pub setup_script_timeout_secs: u64,
+    /// Kill cleanup scripts that run longer than this many seconds (0 = no limit)
+    #[serde(default)]
+    pub cleanup_script_timeout_secs: u64,
+    /// Stop coding agents that run longer than this many seconds (0 = no limit)
+    #[serde(default)]
+    pub coding_agent_timeout_secs: u64,
+    /// Seconds an SSE stream may stay idle before a keep-alive is sent
+    #[serde(default = "default_sse_keepalive_interval_secs")]
+    pub sse_keepalive_interval_secs: u64,
+    /// Seconds to wait after SIGINT/SIGTERM before escalating when stopping a process
+    #[serde(default = "default_stop_grace_period_secs")]
+    pub stop_grace_period_secs: u64,
+    /// Maximum number of commits returned when listing an attempt's commits
+    #[serde(default = "default_attempt_commits_limit")]
+    pub attempt_commits_limit: usize,
+    /// Evict the oldest idle worktrees once all worktrees together use more
+    /// than this many bytes (0 = no limit)
+    #[serde(default)]
+    pub worktree_max_disk_bytes: u64,
+    /// Delete persisted logs of processes that finished more than this many
+    /// days ago (0 = keep forever)
+    #[serde(default)]
+    pub log_retention_days: u32,
+    /// Refuse to create more attempts for a task once it has this many whose
+    /// worktree hasn't been deleted (unset = unlimited)
+    #[serde(default)]
+    pub max_attempts_per_task: Option<u32>,
+    /// How to handle uncommitted changes (e.g. manual edits) in a worktree
+    /// when a coding agent is about to run in it
+    #[serde(default)]
+    pub on_dirty_worktree: DirtyWorktreePolicy,
+    /// Sign agent and merge commits; fail instead of committing unsigned
+    #[serde(default)]
+    pub commit_sign: bool,
+    /// Key to sign with (GPG key id, SSH key path, ...); falls back to git's
+    /// `user.signingkey` when unset
+    #[serde(default)]
+    pub commit_signing_key: Option<String>,
+    #[serde(default)]
+    pub commit_signing_format: CommitSigningFormat,
+    /// URL that receives a JSON POST for every task event, e.g. `task.status_changed`
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Coding agent starts allowed per minute, keyed by executor (`CLAUDE_CODE`) or
+    /// profile variant (`CLAUDE_CODE:PLAN`). Starts over the limit wait for their turn.
+    #[serde(default)]
+    pub executor_rate_limits: HashMap<String, u32>,
+    /// Automatic re-runs of coding agents that hit a transient provider error
+    #[serde(default)]
+    pub agent_retry: AgentRetryConfig,
+    /// Naming scheme for attempt worktrees and branches
+    #[serde(default)]
+    pub worktree_naming: WorktreeNamingConfig,
+    /// Fetch the project's remote before creating an attempt worktree, so attempts start
+    /// from the latest base branch. Falls back to the local ref when the fetch fails.
+    #[serde(default)]
+    pub fetch_before_create: bool,
+    /// How live diffs watch worktrees for changes
+    #[serde(default)]
+    pub filesystem_watcher: FilesystemWatcherConfig,
+    /// Record where each execution's log store is handed out, so a store still held
+    /// after its process finished can be traced to its holders. Debugging aid, off by default.
+    #[serde(default)]
+    pub track_msg_store_holders: bool,
+    /// Most coding agents that may run at once across all projects; further starts
+    /// queue until one finishes. Unset or zero means no limit.
+    #[serde(default)]
+    pub max_concurrent_executions: Option<u32>,
+    /// Free disk space required in the worktree directory before creating a worktree,
+    /// so a nearly full disk fails up front instead of partway through. Zero disables.
+    #[serde(default = "default_worktree_min_free_bytes")]
+    pub worktree_min_free_bytes: u64,
+    /// Remove an attempt's worktree as soon as its PR is merged instead of waiting for
+    /// it to expire. Direct merges always remove the worktree.
+    #[serde(default)]
+    pub prune_worktrees_after_merge: bool,
+    /// Kill browser chat automations that run longer than this, e.g. stuck on a login wall
+    #[serde(default)]
+    pub browser_chat_timeouts: BrowserChatTimeoutConfig,
+    /// Node.js binary for browser chat automation, a name on PATH or a full path (e.g.
+    /// under nvm or volta). Unset uses `node` from PATH.
+    #[serde(default)]
+    pub browser_chat_node_path: Option<String>,
+}
+
+impl Config {
+    /// The configured rate limit for `profile` and the key it is configured under. A
+    /// variant-specific limit wins over its executor's, which all its variants share.
+    pub fn executor_rate_limit(&self, profile: &ExecutorProfileId) -> Option<(String, u32)> {
+        [profile.to_string(), profile.executor.to_string()]
+            .into_iter()
+            .find_map(|key| {
+                let limit = *self.executor_rate_limits.get(&key)?;
+                Some((key, limit))
+            })
+    }
+
+    /// Signing to apply to commits, if `commit_sign` is enabled
+    pub fn commit_signing(&self) -> Option<CommitSigning> {
+        self.commit_sign.then(|| CommitSigning {
+            format: self.commit_signing_format,
+            key: self
+                .commit_signing_key
+                .clone()
+                .filter(|key| !key.trim().is_empty()),
+        })
+    }
+
+    pub fn from_previous_version(raw_config: &str) -> Result<Self, Error> {
+        let old_config = match serde_json::from_str::<v6::Config>(raw_config) {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                tracing::error!("❌ Failed to parse config: {}", e);
+                tracing::error!("   at line {}, column {}", e.line(), e.column());
+                return Err(e.into());
+            }
+        };
+
+        Ok(Self {
+            config_version: "v7".to_string(),
+            theme: old_config.theme,
+            executor_profile: old_config.executor_profile,
+            disclaimer_acknowledged: old_config.disclaimer_acknowledged,
+            onboarding_acknowledged: old_config.onboarding_acknowledged,
+            github_login_acknowledged: old_config.github_login_acknowledged,
+            telemetry_acknowledged: old_config.telemetry_acknowledged,
+            notifications: old_config.notifications,
+            editor: old_config.editor,
+            github: old_config.github,
+            gitlab: GitLabConfig::default(),
+            analytics_enabled: old_config.analytics_enabled,
+            workspace_dir: old_config.workspace_dir,
+            last_app_version: old_config.last_app_version,
+            show_release_notes: old_config.show_release_notes,
+            image_max_size_bytes: DEFAULT_IMAGE_MAX_SIZE_BYTES,
+            skip_noop_cleanup_commits: true,
+            execution_summary_max_bytes: DEFAULT_EXECUTION_SUMMARY_MAX_BYTES,
+            setup_script_timeout_secs: 0,
+            cleanup_script_timeout_secs: 0,
+            coding_agent_timeout_secs: 0,
+            sse_keepalive_interval_secs: DEFAULT_SSE_KEEPALIVE_INTERVAL_SECS,
+            stop_grace_period_secs: DEFAULT_STOP_GRACE_PERIOD_SECS,
+            attempt_commits_limit: DEFAULT_ATTEMPT_COMMITS_LIMIT,
+            worktree_max_disk_bytes: 0,
+            log_retention_days: 0,
+            max_attempts_per_task: None,
+            on_dirty_worktree: DirtyWorktreePolicy::Ignore,
+            commit_sign: false,
+            commit_signing_key: None,
+            commit_signing_format: CommitSigningFormat::Openpgp,
+            webhook_url: None,
+            executor_rate_limits: HashMap::new(),
+            agent_retry: AgentRetryConfig::default(),
+            worktree_naming: WorktreeNamingConfig::default(),
+            fetch_before_create: false,
+            filesystem_watcher: FilesystemWatcherConfig::default(),
+            track_msg_store_holders: false,
+            max_concurrent_executions: None,
+            worktree_min_free_bytes: DEFAULT_WORKTREE_MIN_FREE_BYTES,
+            prune_worktrees_after_merge: false,
+            browser_chat_timeouts: BrowserChatTimeoutConfig::default(),
+            browser_chat_node_path: None,
+        })
+    }
+}
+
+impl From<String> for Config {
+    fn from(raw_config: String) -> Self {
+        if let Ok(config) = serde_json::from_str::<Config>(&raw_config)
+            && config.config_version == "v7"
+        {
+            return config;
+        }
+
+        match Self::from_previous_version(&raw_config) {
+            Ok(config) => {
+                tracing::info!("Config upgraded to v7");
+                config
+            }
+            Err(e) => {
+                tracing::warn!("Config migration failed: {}, using default", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            config_version: "v7".to_string(),
+            theme: ThemeMode::System,
+            executor_profile: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            disclaimer_acknowledged: false,
+            onboarding_acknowledged: false,
+            github_login_acknowledged: false,
+            telemetry_acknowledged: false,
+            notifications: NotificationConfig::default(),
+            editor: EditorConfig::default(),
+            github: GitHubConfig::default(),
+            gitlab: GitLabConfig::default(),
+            analytics_enabled: None,
+            workspace_dir: None,
+            last_app_version: None,
+            show_release_notes: false,
+            image_max_size_bytes: DEFAULT_IMAGE_MAX_SIZE_BYTES,
+            skip_noop_cleanup_commits: true,
+            execution_summary_max_bytes: DEFAULT_EXECUTION_SUMMARY_MAX_BYTES,
+            setup_script_timeout_secs: 0,
+            cleanup_script_timeout_secs: 0,
+            coding_agent_timeout_secs: 0,
+            sse_keepalive_interval_secs: DEFAULT_SSE_KEEPALIVE_INTERVAL_SECS,
+            stop_grace_period_secs: DEFAULT_STOP_GRACE_PERIOD_SECS,
+            attempt_commits_limit: DEFAULT_ATTEMPT_COMMITS_LIMIT,
+            worktree_max_disk_bytes: 0,
+            log_retention_days: 0,
+            max_attempts_per_task: None,
+            on_dirty_worktree: DirtyWorktreePolicy::Ignore,
+            commit_sign: false,
+            commit_signing_key: None,
+            commit_signing_format: CommitSigningFormat::Openpgp,
+            webhook_url: None,
+            executor_rate_limits: HashMap::new(),
+            agent_retry: AgentRetryConfig::default(),
+            worktree_naming: WorktreeNamingConfig::default(),
+            fetch_before_create: false,
+            filesystem_watcher: FilesystemWatcherConfig::default(),
+            track_msg_store_holders: false,
+            max_concurrent_executions: None,
+            worktree_min_free_bytes: DEFAULT_WORKTREE_MIN_FREE_BYTES,
+            prune_worktrees_after_merge: false,
+            browser_chat_timeouts: BrowserChatTimeoutConfig::default(),
+            browser_chat_node_path: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_worktree_naming_keeps_the_original_scheme() {
+        let attempt_id = Uuid::parse_str("1a2b3c4d-0000-0000-0000-000000000000").unwrap();
+        assert_eq!(
+            WorktreeNamingConfig::default().name(&attempt_id, "Fix the login page"),
+            Ok("vk-1a2b-fix-the-lo".to_string())
+        );
+    }
+
+    #[test]
+    fn worktree_naming_follows_template_and_rejects_illegal_names() {
+        let attempt_id = Uuid::parse_str("1a2b3c4d-0000-0000-0000-000000000000").unwrap();
+        let naming = WorktreeNamingConfig {
+            template: "{prefix}_{slug}_{short_id}".to_string(),
+            prefix: "team".to_string(),
+            max_slug_length: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            naming.name(&attempt_id, "Fix the login page"),
+            Ok("team_fix-t_1a2b".to_string())
+        );
+        assert!(naming.validate().is_ok());
+
+        let nested = WorktreeNamingConfig {
+            template: "{prefix}/{short_id}".to_string(),
+            ..Default::default()
+        };
+        assert!(nested.validate().is_err());
+        let unknown = WorktreeNamingConfig {
+            template: "{prefix}-{short_id}-{user}".to_string(),
+            ..Default::default()
+        };
+        assert!(unknown.validate().is_err());
+        let without_id = WorktreeNamingConfig {
+            template: "{prefix}-{slug}".to_string(),
+            ..Default::default()
+        };
+        assert!(without_id.validate().is_err());
+    }
+
+    #[test]
+    fn long_titles_are_capped_with_a_hash_of_the_title() {
+        let attempt_id = Uuid::parse_str("1a2b3c4d-0000-0000-0000-000000000000").unwrap();
+        let naming = WorktreeNamingConfig {
+            max_slug_length: 500,
+            ..Default::default()
+        };
+        let title = "word ".repeat(60);
+        let name = naming.name(&attempt_id, &title).unwrap();
+        assert!(name.chars().count() <= 60, "{name}");
+        assert!(name.starts_with("vk-1a2b-word-word"), "{name}");
+        assert_eq!(naming.name(&attempt_id, &title).unwrap(), name);
+
+        // Titles that only differ past the cut still get different names
+        let other = naming.name(&attempt_id, &format!("{title}x")).unwrap();
+        assert_ne!(other, name);
+        assert!(other.chars().count() <= 60, "{other}");
+
+        // Short titles are left alone
+        assert_eq!(
+            naming.name(&attempt_id, "Fix login"),
+            Ok("vk-1a2b-fix-login".to_string())
+        );
+    }
+
+    #[test]
+    fn agent_retries_are_opt_in_and_match_structured_errors() {
+        let overloaded = [ProviderError {
+            error_type: Some("overloaded_error".to_string()),
+            status: None,
+        }];
+        let config = AgentRetryConfig::default();
+        assert_eq!(config.retry_delay(Some(1), &overloaded, 0), None);
+
+        let config = AgentRetryConfig {
+            max_retries: 2,
+            ..AgentRetryConfig::default()
+        };
+        assert_eq!(
+            config.retry_delay(Some(1), &overloaded, 1),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(config.retry_delay(Some(1), &overloaded, 2), None);
+        let bad_request = [ProviderError {
+            error_type: Some("invalid_request_error".to_string()),
+            status: Some(400),
+        }];
+        assert_eq!(config.retry_delay(Some(1), &bad_request, 0), None);
+        let throttled = [ProviderError {
+            error_type: None,
+            status: Some(429),
+        }];
+        assert!(config.retry_delay(Some(1), &throttled, 0).is_some());
+    }
+}
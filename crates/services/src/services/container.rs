@@ -1,8 +1,9 @@
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
+    str::FromStr,
     sync::{
-        Arc,
+        Arc, RwLock as StdRwLock,
         atomic::{AtomicUsize, Ordering},
     },
 };
@@ -10,44 +11,126 @@ use std::{
 use anyhow::{Error as AnyhowError, anyhow};
 use async_trait::async_trait;
 use axum::response::sse::Event;
+use chrono::{DateTime, Utc};
 use db::{
     DBService,
     models::{
         execution_process::{
-            CreateExecutionProcess, ExecutionContext, ExecutionProcess, ExecutionProcessRunReason,
-            ExecutionProcessStatus,
+            CreateExecutionProcess, ExecutionContext, ExecutionProcess, ExecutionProcessKillReason,
+            ExecutionProcessRunReason, ExecutionProcessStatus,
         },
         execution_process_logs::ExecutionProcessLogs,
         executor_session::{CreateExecutorSession, ExecutorSession},
+        merge::Merge,
         task::{Task, TaskStatus},
-        task_attempt::{TaskAttempt, TaskAttemptError},
+        task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
     },
 };
 use executors::{
     actions::{
-        ExecutorAction, ExecutorActionType,
+        Executable, ExecutionPreview, ExecutorAction, ExecutorActionType,
         browser_chat_request::{BrowserChatRequest, BrowserChatAgentType},
         coding_agent_initial::CodingAgentInitialRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
+    command::ExecutorOverride,
     executors::{BaseCodingAgent, ExecutorError, StandardCodingAgentExecutor},
     logs::{NormalizedEntry, NormalizedEntryType, utils::patch::ConversationPatch},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use futures::{StreamExt, TryStreamExt, future};
+use git2::BranchType;
+use serde::Serialize;
 use sqlx::Error as SqlxError;
 use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinHandle};
-use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use ts_rs::TS;
+use utils::{
+    log_msg::LogMsg,
+    msg_store::{MsgStore, MsgStoreHandle},
+};
 use uuid::Uuid;
 
 use crate::services::{
-    git::{GitService, GitServiceError},
+    config::Config,
+    dev_server::{DevServerUrlDetector, port_from_url},
+    execution_limiter::ExecutionLimiter,
+    git::{AbortedOperation, CommitMetadata, DiffStats, GitService, GitServiceError},
+    github_service::{CreatePrRequest, GitHubService, GitHubServiceError},
+    gitlab_service::{GitLabRepoInfo, GitLabService, GitLabServiceError},
     image::ImageService,
+    rate_limiter::ExecutorRateLimiter,
     worktree_manager::{WorktreeError, WorktreeManager},
 };
 pub type ContainerRef = String;
 
+/// Current state of the dev server for a task attempt
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DevServerStatus {
+    pub running: bool,
+    /// Latest dev server execution process for the attempt, if one was ever started
+    pub execution_process_id: Option<Uuid>,
+    /// URL the dev server announced in its output, when it could be detected
+    pub url: Option<String>,
+    pub port: Option<u16>,
+}
+
+/// How far an attempt's branch has moved relative to its base branch
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct AttemptBranchStatus {
+    pub commits_ahead: usize,
+    pub commits_behind: usize,
+    /// False when the attempt's branch or its base branch doesn't exist (yet); the
+    /// counts are then zero
+    pub branches_exist: bool,
+}
+
+/// Header of an attempt comparison stream: the two branch tips and where they diverged
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AttemptComparison {
+    pub merge_base: String,
+    pub from: CommitMetadata,
+    pub to: CommitMetadata,
+}
+
+/// Diff stats of one attempt of a task; `None` when the attempt has no branch yet or
+/// its stats couldn't be computed
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AttemptDiffStats {
+    pub task_attempt_id: Uuid,
+    pub stats: Option<DiffStats>,
+}
+
+/// Last diff stats computed for each attempt, keyed by task attempt id. Each entry holds
+/// the branch and base tip oids the stats are for, so a moved tip recomputes them; entries
+/// are evicted when their attempt is deleted or moved to another base branch.
+pub type DiffStatsCache = StdRwLock<HashMap<Uuid, (String, String, DiffStats)>>;
+
+/// `branch`'s diff stats against `base_branch`, reused from `cache` while neither tip moved
+fn cached_diff_stats(
+    git: &GitService,
+    cache: &DiffStatsCache,
+    task_attempt_id: Uuid,
+    repo_path: &Path,
+    branch: &str,
+    base_branch: &str,
+) -> Result<DiffStats, GitServiceError> {
+    let tip = git.get_branch_oid(repo_path, branch)?;
+    let base = git.get_branch_oid(repo_path, base_branch)?;
+    if let Some((cached_tip, cached_base, stats)) = cache.read().unwrap().get(&task_attempt_id)
+        && *cached_tip == tip
+        && *cached_base == base
+    {
+        return Ok(*stats);
+    }
+    let stats = git.diff_stats(repo_path, &base, &tip)?;
+    cache
+        .write()
+        .unwrap()
+        .insert(task_attempt_id, (tip, base, stats));
+    Ok(stats)
+}
+
 /// Data needed for background worktree cleanup (doesn't require DB access)
 #[derive(Debug, Clone)]
 pub struct WorktreeCleanupData {
@@ -82,6 +165,28 @@ pub async fn cleanup_worktrees_direct(data: &[WorktreeCleanupData]) -> Result<()
     Ok(())
 }
 
+/// Remove one merged attempt's worktree and mark it deleted; false if the removal failed
+async fn prune_merged_worktree(
+    db: &DBService,
+    attempt_id: Uuid,
+    worktree_path: &str,
+    git_repo_path: &str,
+) -> Result<bool, ContainerError> {
+    if let Err(e) =
+        WorktreeManager::cleanup_worktree(Path::new(worktree_path), Some(Path::new(git_repo_path)))
+            .await
+    {
+        tracing::error!(
+            "Failed to prune worktree of merged task attempt {}: {}",
+            attempt_id,
+            e
+        );
+        return Ok(false);
+    }
+    TaskAttempt::mark_worktree_deleted(&db.pool, attempt_id).await?;
+    Ok(true)
+}
+
 #[derive(Debug, Error)]
 pub enum ContainerError {
     #[error(transparent)]
@@ -99,9 +204,33 @@ pub enum ContainerError {
     #[error(transparent)]
     TaskAttemptError(#[from] TaskAttemptError),
     #[error(transparent)]
+    GitHub(#[from] GitHubServiceError),
+    #[error(transparent)]
+    GitLab(#[from] GitLabServiceError),
+    #[error("Task attempt {0} has no worktree recorded; it was never set up or setup failed")]
+    MissingWorktree(Uuid),
+    #[error("Task attempt {0} has no branch recorded; it was never set up or setup failed")]
+    MissingBranch(Uuid),
+    #[error(transparent)]
     Other(#[from] AnyhowError), // Catches any unclassified errors
 }
 
+/// Overrides for `create_pull_request`; unset fields fall back to the task and attempt
+#[derive(Debug, Clone, Default)]
+pub struct PullRequestOptions {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub base_branch: Option<String>,
+}
+
+/// A line of an exported execution log: the stored `LogMsg` plus when it was received
+#[derive(Serialize)]
+struct ExportedLogLine<'a> {
+    timestamp: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    msg: &'a LogMsg,
+}
+
 #[async_trait]
 pub trait ContainerService {
     fn msg_stores(&self) -> &Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>;
@@ -110,28 +239,69 @@ pub trait ContainerService {
 
     fn git(&self) -> &GitService;
 
+    fn rate_limiter(&self) -> &ExecutorRateLimiter;
+
+    fn diff_stats_cache(&self) -> &Arc<DiffStatsCache>;
+
+    /// Drop `task_attempt_id`'s cached diff stats
+    fn evict_diff_stats(&self, task_attempt_id: Uuid) {
+        self.diff_stats_cache()
+            .write()
+            .unwrap()
+            .remove(&task_attempt_id);
+    }
+
+    fn execution_limiter(&self) -> &Arc<ExecutionLimiter>;
+
     fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf;
 
     async fn create(&self, task_attempt: &TaskAttempt) -> Result<ContainerRef, ContainerError>;
 
+    /// Create a disposable directory for a scratch attempt, with no worktree or branch,
+    /// and mark the attempt as scratch
+    async fn create_scratch(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<ContainerRef, ContainerError>;
+
     async fn delete(&self, task_attempt: &TaskAttempt) -> Result<(), ContainerError> {
         self.try_stop(task_attempt).await;
+        self.stop_dev_server(task_attempt).await?;
+        self.evict_diff_stats(task_attempt.id);
         self.delete_inner(task_attempt).await
     }
 
+    /// Remove the worktrees of merged attempts of done tasks now rather than on expiry
+    async fn prune_merged_worktrees(&self) -> Result<Vec<Uuid>, ContainerError> {
+        let merged = TaskAttempt::find_merged_for_cleanup(&self.db().pool).await?;
+        let mut pruned = Vec::with_capacity(merged.len());
+        for (attempt_id, worktree_path, git_repo_path) in merged {
+            if self.is_attempt_running(attempt_id).await? {
+                continue;
+            }
+            if prune_merged_worktree(self.db(), attempt_id, &worktree_path, &git_repo_path).await? {
+                pruned.push(attempt_id);
+            }
+        }
+        if !pruned.is_empty() {
+            tracing::info!("Pruned {} worktrees of merged task attempts", pruned.len());
+        }
+        Ok(pruned)
+    }
+
+    /// Whether any execution of this attempt is still live in this container, dev
+    /// servers, starts queued for a slot and agents waiting to be re-run included. Guard
+    /// anything that removes or rewrites the attempt's worktree with this rather than the
+    /// recorded process status, which can be stale.
+    async fn is_attempt_running(&self, task_attempt_id: Uuid) -> Result<bool, ContainerError>;
+
     /// Check if a task has any running execution processes
     async fn has_running_processes(&self, task_id: Uuid) -> Result<bool, ContainerError> {
         let attempts = TaskAttempt::fetch_all(&self.db().pool, Some(task_id)).await?;
 
         for attempt in attempts {
-            if let Ok(processes) =
-                ExecutionProcess::find_by_task_attempt_id(&self.db().pool, attempt.id).await
-            {
-                for process in processes {
-                    if process.status == ExecutionProcessStatus::Running {
-                        return Ok(true);
-                    }
-                }
+            if self.is_attempt_running(attempt.id).await? {
+                return Ok(true);
             }
         }
 
@@ -145,25 +315,39 @@ pub trait ContainerService {
     ) -> Result<(), ContainerError> {
         for attempt in task_attempts {
             self.try_stop(attempt).await;
+            self.stop_dev_server(attempt).await?;
         }
         Ok(())
     }
 
+    /// Cancel re-runs of this attempt's failed coding agents that are still waiting out
+    /// their backoff
+    async fn cancel_agent_retries(&self, task_attempt: &TaskAttempt);
+
+    /// Stop the running and queued execution processes for this attempt, and any pending
+    /// agent re-runs. Dev servers are left running; they only stop through
+    /// `stop_dev_server`.
     async fn try_stop(&self, task_attempt: &TaskAttempt) {
-        // stop all execution processes for this attempt
+        self.cancel_agent_retries(task_attempt).await;
         if let Ok(processes) =
             ExecutionProcess::find_by_task_attempt_id(&self.db().pool, task_attempt.id).await
         {
             for process in processes {
-                if process.status == ExecutionProcessStatus::Running {
-                    self.stop_execution(&process).await.unwrap_or_else(|e| {
-                        tracing::debug!(
-                            "Failed to stop execution process {} for task attempt {}: {}",
-                            process.id,
-                            task_attempt.id,
-                            e
-                        );
-                    });
+                if matches!(
+                    process.status,
+                    ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+                ) && process.run_reason != ExecutionProcessRunReason::DevServer
+                {
+                    self.stop_execution(&process, ExecutionProcessKillReason::User)
+                        .await
+                        .unwrap_or_else(|e| {
+                            tracing::debug!(
+                                "Failed to stop execution process {} for task attempt {}: {}",
+                                process.id,
+                                task_attempt.id,
+                                e
+                            );
+                        });
                 }
             }
         }
@@ -193,6 +377,7 @@ pub trait ContainerService {
     async fn stop_execution(
         &self,
         execution_process: &ExecutionProcess,
+        reason: ExecutionProcessKillReason,
     ) -> Result<(), ContainerError>;
 
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError>;
@@ -209,6 +394,42 @@ pub trait ContainerService {
         task_attempt: &TaskAttempt,
     ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>;
 
+    /// Make the attempt's open live diff streams recompute and re-send the whole diff,
+    /// for changes the filesystem watcher missed. Requests arriving while a refresh is
+    /// pending are coalesced into it. Returns false when no diff stream is open.
+    async fn refresh_diff(&self, task_attempt: &TaskAttempt) -> bool;
+
+    /// Commits on the attempt's branch ahead of its base branch, newest first
+    async fn attempt_commits(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<Vec<CommitMetadata>, ContainerError>;
+
+    /// Stream the diff of a single commit on the attempt's branch
+    async fn get_commit_diff(
+        &self,
+        task_attempt: &TaskAttempt,
+        commit_sha: &str,
+    ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>;
+
+    /// Stream the diff from the tip of `from`'s branch to the tip of `to`'s branch. Both
+    /// attempts must belong to the same task.
+    async fn compare_attempts(
+        &self,
+        from: &TaskAttempt,
+        to: &TaskAttempt,
+    ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>;
+
+    /// A setup script re-run on its own in an existing worktree, which doesn't chain into the
+    /// coding agent. It leaves the task's status alone and sends no notifications.
+    fn is_standalone_setup(
+        executor_action: &ExecutorAction,
+        run_reason: &ExecutionProcessRunReason,
+    ) -> bool {
+        run_reason == &ExecutionProcessRunReason::SetupScript
+            && executor_action.next_action.is_none()
+    }
+
     /// Helper function to determine if an executor profile represents a browser chat agent
     fn is_browser_chat_agent(executor_profile_id: &ExecutorProfileId) -> Option<BrowserChatAgentType> {
         match executor_profile_id.executor {
@@ -236,10 +457,47 @@ pub trait ContainerService {
         }
     }
 
+    /// The agent session of the parent attempt's last coding agent run, for a task continuing
+    /// that attempt. Sessions only carry over between attempts using the same executor.
+    async fn parent_session_id(
+        &self,
+        task: &Task,
+        executor: BaseCodingAgent,
+    ) -> Result<Option<String>, ContainerError> {
+        let pool = &self.db().pool;
+        let Some(parent_attempt_id) = task.parent_task_attempt else {
+            return Ok(None);
+        };
+        let Some(parent_attempt) = TaskAttempt::find_by_id(pool, parent_attempt_id).await? else {
+            return Ok(None);
+        };
+        if parent_attempt.executor != executor.to_string() {
+            return Ok(None);
+        }
+        let Some(process) = ExecutionProcess::find_latest_by_task_attempt_and_run_reason(
+            pool,
+            parent_attempt_id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(
+            ExecutorSession::find_by_execution_process_id(pool, process.id)
+                .await?
+                .and_then(|session| session.session_id),
+        )
+    }
+
     /// Create the appropriate executor action based on the executor type
     fn create_executor_action(
         prompt: String,
         executor_profile_id: ExecutorProfileId,
+        executor_override: Option<ExecutorOverride>,
+        resume_session_id: Option<String>,
+        agent_instructions: Option<String>,
         cleanup_action: Option<Box<ExecutorAction>>,
     ) -> ExecutorAction {
         if let Some(agent_type) = Self::is_browser_chat_agent(&executor_profile_id) {
@@ -254,6 +512,7 @@ pub trait ContainerService {
                     agent_type,
                     executor_profile_id,
                     session_id: None, // Initial request, no session yet
+                    node_path: None,
                 }),
                 cleanup_action,
             )
@@ -265,16 +524,59 @@ pub trait ContainerService {
                 ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
                     prompt,
                     executor_profile_id,
+                    executor_override,
+                    resume_session_id,
+                    agent_instructions,
                 }),
                 cleanup_action,
             )
         }
     }
 
-    /// Fetch the MsgStore for a given execution ID, panicking if missing.
-    async fn get_msg_store_by_id(&self, uuid: &Uuid) -> Option<Arc<MsgStore>> {
+    /// Fetch the MsgStore for a given execution ID, panicking if missing. The handle counts
+    /// as a holder of the store until dropped.
+    async fn get_msg_store_by_id(&self, uuid: &Uuid) -> Option<MsgStoreHandle> {
         let map = self.msg_stores().read().await;
-        map.get(uuid).cloned()
+        map.get(uuid).cloned().map(MsgStoreHandle::new)
+    }
+
+    /// Dump an execution's logs as JSONL for download, one `LogMsg` per line with the time it
+    /// was received. While the in-memory store exists this includes normalized entries;
+    /// afterwards only the persisted raw output is available, without timestamps.
+    async fn export_logs_jsonl(&self, id: &Uuid) -> Result<Option<String>, ContainerError> {
+        let messages: Vec<(Option<DateTime<Utc>>, LogMsg)> =
+            if let Some(store) = self.get_msg_store_by_id(id).await {
+                store
+                    .get_timestamped_history()
+                    .into_iter()
+                    .map(|(received_at, msg)| (Some(received_at), msg))
+                    .collect()
+            } else {
+                match ExecutionProcessLogs::find_by_execution_id(&self.db().pool, *id).await? {
+                    Some(record) => record
+                        .parse_logs()
+                        .map_err(|e| ContainerError::Other(e.into()))?
+                        .into_iter()
+                        .map(|msg| (None, msg))
+                        .collect(),
+                    None => return Ok(None),
+                }
+            };
+
+        let mut jsonl = String::new();
+        for (timestamp, msg) in &messages {
+            if matches!(msg, LogMsg::Finished) {
+                continue;
+            }
+            let line = serde_json::to_string(&ExportedLogLine {
+                timestamp: *timestamp,
+                msg,
+            })
+            .map_err(|e| ContainerError::Other(e.into()))?;
+            jsonl.push_str(&line);
+            jsonl.push('\n');
+        }
+        Ok(Some(jsonl))
     }
 
     async fn stream_raw_logs(
@@ -568,13 +870,620 @@ pub trait ContainerService {
                         LogMsg::Finished => {
                             break;
                         }
-                        LogMsg::JsonPatch(_) => continue,
+                        LogMsg::JsonPatch(_) | LogMsg::Error { .. } => continue,
+                    }
+                }
+            }
+        })
+    }
+
+    /// Re-run the project's setup script in the attempt's existing worktree, as a standalone
+    /// step that doesn't chain into the coding agent
+    async fn run_setup_script(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        let task = task_attempt
+            .parent_task(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let project = task
+            .parent_project(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+
+        let Some(setup_script) = project.setup_script else {
+            return Err(ContainerError::Other(anyhow!(
+                "No setup script configured for this project"
+            )));
+        };
+
+        self.ensure_container_exists(task_attempt).await?;
+
+        let executor_action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: setup_script,
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+            }),
+            None,
+        );
+
+        self.start_execution(
+            task_attempt,
+            &executor_action,
+            &ExecutionProcessRunReason::SetupScript,
+        )
+        .await
+    }
+
+    /// Squash-merge the attempt's branch into its base branch, record the merge, mark the
+    /// task done and remove the attempt's worktree. Conflicts are detected before anything
+    /// is written, so a failed merge leaves the repo untouched. The merge commit is signed
+    /// when `config` enables commit signing. Returns the merge commit.
+    async fn merge_attempt(
+        &self,
+        task_attempt: &TaskAttempt,
+        config: &Config,
+    ) -> Result<String, ContainerError> {
+        let pool = &self.db().pool;
+        let task = task_attempt
+            .parent_task(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let project = task
+            .parent_project(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        task_attempt.ensure_not_scratch()?;
+        let branch_name = task_attempt.branch.as_ref().ok_or_else(|| {
+            TaskAttemptError::ValidationError("No branch found for task attempt".to_string())
+        })?;
+        let container_ref = self.ensure_container_exists(task_attempt).await?;
+
+        let task_uuid_str = task.id.to_string();
+        let first_uuid_section = task_uuid_str.split('-').next().unwrap_or(&task_uuid_str);
+        // Create commit message with task title and description
+        let mut commit_message = format!("{} (vibe-kanban {})", task.title, first_uuid_section);
+        if let Some(description) = &task.description
+            && !description.trim().is_empty()
+        {
+            commit_message.push_str("\n\n");
+            commit_message.push_str(description);
+        }
+
+        let merge_commit_id = self
+            .git()
+            .with_commit_signing(config.commit_signing())
+            .merge_changes(
+                &project.git_repo_path,
+                Path::new(&container_ref),
+                branch_name,
+                &task_attempt.base_branch,
+                &commit_message,
+            )?;
+
+        Merge::create_direct(
+            pool,
+            task_attempt.id,
+            &task_attempt.base_branch,
+            &merge_commit_id,
+        )
+        .await?;
+        Task::update_status(pool, task.id, TaskStatus::Done).await?;
+
+        // The merge already succeeded, so a failed cleanup is only worth a warning
+        match self.delete(task_attempt).await {
+            Ok(()) => TaskAttempt::mark_worktree_deleted(pool, task_attempt.id).await?,
+            Err(e) => tracing::warn!(
+                "Failed to clean up worktree for merged task attempt {}: {}",
+                task_attempt.id,
+                e
+            ),
+        }
+
+        Ok(merge_commit_id)
+    }
+
+    /// Make `task_attempt` the task's active attempt, the one the board and new work
+    /// default to. When `fast_forward_branch` is given that branch is also moved to the
+    /// attempt's branch tip, which must be a fast-forward. Other attempts and their
+    /// worktrees are left as they are.
+    async fn promote_attempt(
+        &self,
+        task_attempt: &TaskAttempt,
+        fast_forward_branch: Option<&str>,
+    ) -> Result<(), ContainerError> {
+        let pool = &self.db().pool;
+        if let Some(shared_branch) = fast_forward_branch {
+            let branch_name = task_attempt.branch.as_ref().ok_or_else(|| {
+                TaskAttemptError::ValidationError("No branch found for task attempt".to_string())
+            })?;
+            let project = task_attempt
+                .parent_task(pool)
+                .await?
+                .ok_or(SqlxError::RowNotFound)?
+                .parent_project(pool)
+                .await?
+                .ok_or(SqlxError::RowNotFound)?;
+            self.git()
+                .fast_forward_branch(&project.git_repo_path, shared_branch, branch_name)?;
+        }
+
+        Task::set_preferred_attempt(pool, task_attempt.task_id, task_attempt.id).await?;
+        Ok(())
+    }
+
+    /// Back out of a merge or rebase left half-finished in the attempt's worktree, e.g.
+    /// after a conflicted rebase the user gave up on. Returns what was aborted, which is
+    /// `AbortedOperation::None` when the worktree wasn't stuck.
+    async fn abort_attempt_operation(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<AbortedOperation, ContainerError> {
+        let container_ref = self.ensure_container_exists(task_attempt).await?;
+        Ok(self.git().abort_operation(Path::new(&container_ref))?)
+    }
+
+    /// Undo a recorded merge with a revert commit on its target branch, record the
+    /// revert and move the task back to `InReview`. If the target branch has since
+    /// changed the same lines this fails with `MergeConflicts` listing the conflicted
+    /// files and nothing is changed. Returns the revert commit.
+    async fn revert_merge(
+        &self,
+        merge_id: Uuid,
+        config: &Config,
+    ) -> Result<String, ContainerError> {
+        let pool = &self.db().pool;
+        let merge = Merge::find_by_id(pool, merge_id)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        if merge.revert_commit().is_some() {
+            return Err(TaskAttemptError::ValidationError(
+                "Merge has already been reverted".to_string(),
+            )
+            .into());
+        }
+        let merge_commit = merge.merge_commit().ok_or_else(|| {
+            TaskAttemptError::ValidationError("Pull request has not been merged yet".to_string())
+        })?;
+        let task_attempt = TaskAttempt::find_by_id(pool, merge.task_attempt_id())
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let task = task_attempt
+            .parent_task(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let project = task
+            .parent_project(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+
+        let revert_commit = self
+            .git()
+            .with_commit_signing(config.commit_signing())
+            .revert_commit(
+                &project.git_repo_path,
+                merge.target_branch_name(),
+                &merge_commit,
+            )?;
+
+        Merge::mark_reverted(pool, merge_id, &revert_commit).await?;
+        Task::update_status(pool, task.id, TaskStatus::InReview).await?;
+
+        Ok(revert_commit)
+    }
+
+    /// Push the attempt's branch and open a pull request against its base branch on
+    /// GitHub, or a merge request on GitLab, depending on where the project's default
+    /// remote points. The title defaults to the task title and the body to the latest
+    /// agent summary (or the task description). The PR is recorded as a `Merge` so the
+    /// PR monitor can track it. Returns the PR URL.
+    async fn create_pull_request(
+        &self,
+        task_attempt: &TaskAttempt,
+        config: &Config,
+        options: PullRequestOptions,
+    ) -> Result<String, ContainerError> {
+        let pool = &self.db().pool;
+        let task = task_attempt
+            .parent_task(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let project = task
+            .parent_project(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let branch_name = task_attempt.branch.as_ref().ok_or_else(|| {
+            TaskAttemptError::ValidationError("No branch found for task attempt".to_string())
+        })?;
+        let (remote_name, remote_url) = self.git().get_default_remote(&project.git_repo_path)?;
+        let github_repo = self.git().get_github_repo_info(&project.git_repo_path).ok();
+        let gitlab_repo = GitLabRepoInfo::from_remote_url(&remote_url, config.gitlab.base_url());
+        let worktree_path = PathBuf::from(self.ensure_container_exists(task_attempt).await?);
+
+        let base_branch = options
+            .base_branch
+            .filter(|b| !b.trim().is_empty())
+            .unwrap_or_else(|| {
+                if !task_attempt.base_branch.trim().is_empty() {
+                    task_attempt.base_branch.clone()
+                } else {
+                    config
+                        .github
+                        .default_pr_base
+                        .clone()
+                        .unwrap_or_else(|| "main".to_string())
+                }
+            });
+        // Remote branches are formatted as {remote}/{branch} locally, but PR APIs
+        // expect just the branch name
+        let base_branch = if matches!(
+            self.git()
+                .find_branch_type(&project.git_repo_path, &base_branch)?,
+            BranchType::Remote
+        ) {
+            let remote = self
+                .git()
+                .get_remote_name_from_branch_name(&worktree_path, &base_branch)?;
+            base_branch
+                .strip_prefix(&format!("{remote}/"))
+                .unwrap_or(&base_branch)
+                .to_string()
+        } else {
+            base_branch
+        };
+
+        let body = match options.body {
+            Some(body) => Some(body),
+            None => ExecutorSession::find_by_task_attempt_id(pool, task_attempt.id)
+                .await?
+                .into_iter()
+                .rev()
+                .find_map(|session| session.summary.filter(|s| !s.trim().is_empty()))
+                .or(task.description.clone()),
+        };
+        let request = CreatePrRequest {
+            title: options
+                .title
+                .filter(|t| !t.trim().is_empty())
+                .unwrap_or_else(|| format!("{} (vibe-kanban)", task.title)),
+            body,
+            head_branch: branch_name.clone(),
+            base_branch: base_branch.clone(),
+        };
+
+        let pr_info = match (github_repo, gitlab_repo) {
+            (Some(repo_info), _) => {
+                let token = config
+                    .github
+                    .token()
+                    .ok_or(GitHubServiceError::TokenInvalid)?;
+                let github_service = GitHubService::new(&token)?;
+                github_service.check_token().await?;
+                self.git()
+                    .push_branch(&worktree_path, branch_name, &remote_name, &token)
+                    .map_err(GitHubServiceError::from)?;
+                github_service.create_pr(&repo_info, &request).await?
+            }
+            (None, Some(repo_info)) => {
+                let token = config
+                    .gitlab
+                    .token
+                    .clone()
+                    .filter(|t| !t.is_empty())
+                    .ok_or(GitLabServiceError::TokenInvalid)?;
+                self.git()
+                    .push_branch(&worktree_path, branch_name, &remote_name, &token)
+                    .map_err(GitLabServiceError::from)?;
+                GitLabService::new(&token, config.gitlab.base_url())
+                    .create_merge_request(&repo_info, &request)
+                    .await?
+            }
+            (None, None) => {
+                return Err(GitServiceError::InvalidRepository(format!(
+                    "Remote '{remote_name}' ({remote_url}) is neither a GitHub nor a configured GitLab repository"
+                ))
+                .into());
+            }
+        };
+
+        if let Err(e) = Merge::create_pr(
+            pool,
+            task_attempt.id,
+            &base_branch,
+            pr_info.number,
+            &pr_info.url,
+        )
+        .await
+        {
+            tracing::error!("Failed to update task attempt PR status: {}", e);
+        }
+
+        Ok(pr_info.url)
+    }
+
+    /// Count the commits the attempt's branch is ahead of and behind its base branch
+    async fn attempt_branch_status(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<AttemptBranchStatus, ContainerError> {
+        // Scratch attempts have no branch to compare
+        if task_attempt.scratch {
+            return Ok(AttemptBranchStatus::default());
+        }
+        let Some(branch) = task_attempt.branch.clone() else {
+            return Ok(AttemptBranchStatus::default());
+        };
+        let project = task_attempt
+            .parent_task(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?
+            .parent_project(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+
+        // Walking the commit graph blocks, and listings look up many attempts at once
+        let git = self.git().clone();
+        let base_branch = task_attempt.base_branch.clone();
+        let status = tokio::task::spawn_blocking(move || {
+            git.get_branch_status(&project.git_repo_path, &branch, &base_branch)
+        })
+        .await
+        .map_err(AnyhowError::from)?;
+        match status {
+            Ok((commits_ahead, commits_behind)) => Ok(AttemptBranchStatus {
+                commits_ahead,
+                commits_behind,
+                branches_exist: true,
+            }),
+            Err(GitServiceError::BranchNotFound(_)) => Ok(AttemptBranchStatus::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Committed diff stats of every attempt of `task_id` against its base branch, for
+    /// ranking competing attempts. Attempts are computed concurrently and cached by
+    /// branch tip, so repeated calls only recompute attempts that moved.
+    async fn task_diff_stats(
+        &self,
+        task_id: Uuid,
+    ) -> Result<Vec<AttemptDiffStats>, ContainerError> {
+        let pool = &self.db().pool;
+        let project = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?
+            .parent_project(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let attempts = TaskAttempt::fetch_all(pool, Some(task_id)).await?;
+
+        let lookups = attempts.into_iter().map(|attempt| {
+            let git = self.git().clone();
+            let cache = self.diff_stats_cache().clone();
+            let repo_path = project.git_repo_path.clone();
+            async move {
+                let Some(branch) = attempt.branch else {
+                    return AttemptDiffStats {
+                        task_attempt_id: attempt.id,
+                        stats: None,
+                    };
+                };
+                let stats = tokio::task::spawn_blocking(move || {
+                    cached_diff_stats(
+                        &git,
+                        &cache,
+                        attempt.id,
+                        &repo_path,
+                        &branch,
+                        &attempt.base_branch,
+                    )
+                })
+                .await;
+                // One broken attempt shouldn't hide the others' stats
+                let stats = match stats {
+                    Ok(Ok(stats)) => Some(stats),
+                    Ok(Err(GitServiceError::BranchNotFound(_))) => None,
+                    Ok(Err(e)) => {
+                        tracing::warn!(
+                            "Failed to compute diff stats for task attempt {}: {}",
+                            attempt.id,
+                            e
+                        );
+                        None
+                    }
+                    Err(e) => {
+                        tracing::warn!("Diff stats task for attempt {} failed: {}", attempt.id, e);
+                        None
                     }
+                };
+                AttemptDiffStats {
+                    task_attempt_id: attempt.id,
+                    stats,
+                }
+            }
+        });
+        Ok(future::join_all(lookups).await)
+    }
+
+    /// Start the project's dev server for this attempt. Any dev server already
+    /// running for the same project is stopped first.
+    async fn start_dev_server(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        let task = task_attempt
+            .parent_task(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let project = task
+            .parent_project(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+
+        let Some(dev_script) = project.dev_script else {
+            return Err(ContainerError::Other(anyhow!(
+                "No dev server script configured for this project"
+            )));
+        };
+
+        let existing_dev_servers =
+            ExecutionProcess::find_running_dev_servers_by_project(&self.db().pool, project.id)
+                .await?;
+        for dev_server in existing_dev_servers {
+            tracing::info!(
+                "Stopping existing dev server {} for project {}",
+                dev_server.id,
+                project.id
+            );
+            if let Err(e) = self
+                .stop_execution(&dev_server, ExecutionProcessKillReason::User)
+                .await
+            {
+                tracing::error!("Failed to stop dev server {}: {}", dev_server.id, e);
+            }
+        }
+
+        // TODO: Derive script language from system config
+        let executor_action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: dev_script,
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::DevServer,
+            }),
+            None,
+        );
+
+        let execution_process = self
+            .start_execution(
+                task_attempt,
+                &executor_action,
+                &ExecutionProcessRunReason::DevServer,
+            )
+            .await?;
+
+        match DevServerUrlDetector::new(project.dev_server_url_pattern.as_deref()) {
+            Ok(detector) => {
+                self.spawn_dev_server_url_detection(&execution_process.id, detector);
+            }
+            Err(e) => tracing::warn!(
+                "Invalid dev server URL pattern for project {}: {}",
+                project.id,
+                e
+            ),
+        }
+
+        Ok(execution_process)
+    }
+
+    /// Watch a dev server's output and store the first URL it announces
+    fn spawn_dev_server_url_detection(
+        &self,
+        execution_id: &Uuid,
+        detector: DevServerUrlDetector,
+    ) -> JoinHandle<()> {
+        let execution_id = *execution_id;
+        let msg_stores = self.msg_stores().clone();
+        let db = self.db().clone();
+
+        tokio::spawn(async move {
+            let store = {
+                let map = msg_stores.read().await;
+                map.get(&execution_id).cloned()
+            };
+            let Some(store) = store else {
+                return;
+            };
+
+            let mut stream = store.history_plus_stream();
+            while let Some(Ok(msg)) = stream.next().await {
+                let line = match &msg {
+                    LogMsg::Stdout(line) | LogMsg::Stderr(line) => line,
+                    LogMsg::Finished => break,
+                    _ => continue,
+                };
+                if let Some(url) = detector.detect(line) {
+                    tracing::info!("Dev server {} listening on {}", execution_id, url);
+                    if let Err(e) =
+                        ExecutionProcess::update_dev_server_url(&db.pool, execution_id, &url).await
+                    {
+                        tracing::error!(
+                            "Failed to store dev server URL for execution {}: {}",
+                            execution_id,
+                            e
+                        );
+                    }
+                    break;
                 }
             }
         })
     }
 
+    /// Stop every running dev server for this attempt. Fails with the first error once
+    /// the rest have been stopped.
+    async fn stop_dev_server(&self, task_attempt: &TaskAttempt) -> Result<(), ContainerError> {
+        let processes =
+            ExecutionProcess::find_by_task_attempt_id(&self.db().pool, task_attempt.id).await?;
+        // One dev server failing to stop shouldn't leave the others running
+        let mut first_error = None;
+        for process in processes {
+            if process.run_reason == ExecutionProcessRunReason::DevServer
+                && process.status == ExecutionProcessStatus::Running
+                && let Err(e) = self
+                    .stop_execution(&process, ExecutionProcessKillReason::User)
+                    .await
+            {
+                tracing::error!(
+                    "Failed to stop dev server {} for task attempt {}: {}",
+                    process.id,
+                    task_attempt.id,
+                    e
+                );
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    async fn dev_server_status(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<DevServerStatus, ContainerError> {
+        // A dev server that is still up wins over a newer one that already exited
+        let pool = &self.db().pool;
+        let dev_server = Some(&ExecutionProcessRunReason::DevServer);
+        let process =
+            match ExecutionProcess::find_running_by_task_attempt(pool, task_attempt.id, dev_server)
+                .await?
+            {
+                Some(process) => Some(process),
+                None => {
+                    ExecutionProcess::find_latest_by_task_attempt_and_run_reason(
+                        pool,
+                        task_attempt.id,
+                        &ExecutionProcessRunReason::DevServer,
+                    )
+                    .await?
+                }
+            };
+        let Some(process) = process else {
+            return Ok(DevServerStatus {
+                running: false,
+                execution_process_id: None,
+                url: None,
+                port: None,
+            });
+        };
+
+        let port = process.dev_server_url.as_deref().and_then(port_from_url);
+        Ok(DevServerStatus {
+            running: process.status == ExecutionProcessStatus::Running,
+            execution_process_id: Some(process.id),
+            url: process.dev_server_url,
+            port,
+        })
+    }
+
     async fn start_attempt(
         &self,
         task_attempt: &TaskAttempt,
@@ -605,19 +1514,33 @@ pub trait ContainerService {
             .await?
             .ok_or(SqlxError::RowNotFound)?;
 
+        let task_prompt = task
+            .to_prompt_with_context(&self.db().pool, &task_attempt)
+            .await?;
+
         // Handle prompt creation differently for browser agents vs coding agents
-        let prompt = if Self::is_browser_chat_agent(&executor_profile_id).is_some() {
-            // Browser agents don't need worktree paths, use task prompt directly
-            task.to_prompt()
+        let prompt =
+            if Self::is_browser_chat_agent(&executor_profile_id).is_some() {
+                // Browser agents don't need worktree paths, use task prompt directly
+                task_prompt
+            } else {
+                // Coding agents need worktree paths for image canonicalization
+                let worktree_path =
+                    PathBuf::from(task_attempt.container_ref.as_ref().ok_or_else(|| {
+                        ContainerError::Other(anyhow!("Container ref not found"))
+                    })?);
+                ImageService::canonicalise_image_paths(&task_prompt, &worktree_path)
+            };
+
+        let executor_override = project
+            .executor_overrides
+            .get(&executor_profile_id.executor)
+            .cloned();
+        let resume_session_id = if Self::is_browser_chat_agent(&executor_profile_id).is_some() {
+            None
         } else {
-            // Coding agents need worktree paths for image canonicalization
-            let worktree_path = PathBuf::from(
-                task_attempt
-                    .container_ref
-                    .as_ref()
-                    .ok_or_else(|| ContainerError::Other(anyhow!("Container ref not found")))?,
-            );
-            ImageService::canonicalise_image_paths(&task.to_prompt(), &worktree_path)
+            self.parent_session_id(&task, executor_profile_id.executor)
+                .await?
         };
 
         let cleanup_action = project.cleanup_script.map(|script| {
@@ -643,6 +1566,9 @@ pub trait ContainerService {
                 Some(Box::new(Self::create_executor_action(
                     prompt,
                     executor_profile_id.clone(),
+                    executor_override,
+                    resume_session_id,
+                    project.agent_instructions.clone(),
                     cleanup_action,
                 ))),
             );
@@ -657,9 +1583,12 @@ pub trait ContainerService {
             let executor_action = Self::create_executor_action(
                 prompt,
                 executor_profile_id.clone(),
+                executor_override,
+                resume_session_id,
+                project.agent_instructions.clone(),
                 cleanup_action,
             );
-            
+
             // Use appropriate run reason based on executor type
             let run_reason = if Self::is_browser_chat_agent(&executor_profile_id).is_some() {
                 &ExecutionProcessRunReason::BrowserChat
@@ -677,11 +1606,161 @@ pub trait ContainerService {
         Ok(execution_process)
     }
 
+    /// Start the coding agent on the task in a throwaway directory instead of a worktree.
+    /// No branch is created and the project's setup and cleanup scripts don't run, so
+    /// nothing is committed, diffed or merged. Browser chat agents never need a worktree
+    /// and start as usual.
+    async fn start_scratch_execution(
+        &self,
+        task_attempt: &TaskAttempt,
+        executor_profile_id: ExecutorProfileId,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        if Self::is_browser_chat_agent(&executor_profile_id).is_some() {
+            return self.start_attempt(task_attempt, executor_profile_id).await;
+        }
+
+        self.create_scratch(task_attempt).await?;
+
+        let task = task_attempt
+            .parent_task(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let project = task
+            .parent_project(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let task_attempt = TaskAttempt::find_by_id(&self.db().pool, task_attempt.id)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+
+        let prompt = task
+            .to_prompt_with_context(&self.db().pool, &task_attempt)
+            .await?;
+        let executor_override = project
+            .executor_overrides
+            .get(&executor_profile_id.executor)
+            .cloned();
+        let executor_action = Self::create_executor_action(
+            prompt,
+            executor_profile_id,
+            executor_override,
+            None,
+            project.agent_instructions.clone(),
+            None,
+        );
+
+        self.start_execution(
+            &task_attempt,
+            &executor_action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await
+    }
+
+    /// Start a fresh attempt for the same task, reusing the base branch and the
+    /// executor profile of the attempt's initial request. The new attempt gets
+    /// its own worktree and runs the same setup → agent → cleanup chain.
+    /// Fails if the task already has `max_attempts` live attempts.
+    async fn retry_attempt(
+        &self,
+        task_attempt: &TaskAttempt,
+        max_attempts: Option<u32>,
+    ) -> Result<TaskAttempt, ContainerError> {
+        let processes =
+            ExecutionProcess::find_by_task_attempt_id(&self.db().pool, task_attempt.id).await?;
+        let initial_profile =
+            processes
+                .iter()
+                .find_map(|process| match &process.executor_action().ok()?.typ {
+                    ExecutorActionType::CodingAgentInitialRequest(request) => {
+                        Some(request.executor_profile_id.clone())
+                    }
+                    ExecutorActionType::BrowserChatRequest(request)
+                        if request.session_id.is_none() =>
+                    {
+                        Some(request.executor_profile_id.clone())
+                    }
+                    _ => None,
+                });
+        let executor_profile_id = match initial_profile {
+            Some(profile) => profile,
+            None => {
+                let executor = BaseCodingAgent::from_str(&task_attempt.executor).map_err(|_| {
+                    TaskAttemptError::ValidationError(format!(
+                        "Unknown executor '{}' on task attempt {}",
+                        task_attempt.executor, task_attempt.id
+                    ))
+                })?;
+                ExecutorProfileId::new(executor)
+            }
+        };
+
+        let new_attempt = TaskAttempt::create_within_limit(
+            &self.db().pool,
+            &CreateTaskAttempt {
+                executor: executor_profile_id.executor,
+                base_branch: task_attempt.base_branch.clone(),
+                subpath: task_attempt.subpath.clone(),
+            },
+            task_attempt.task_id,
+            max_attempts,
+        )
+        .await?;
+
+        Task::update_status(
+            &self.db().pool,
+            task_attempt.task_id,
+            TaskStatus::InProgress,
+        )
+        .await?;
+        self.start_attempt(&new_attempt, executor_profile_id)
+            .await?;
+
+        Ok(new_attempt)
+    }
+
+    /// Resolve what `start_execution` would run for `executor_action` on this attempt:
+    /// command line, working directory, masked environment and prompt. Nothing is spawned
+    /// or recorded. Only the action itself is previewed, not its `next_action` chain.
+    async fn preview_execution(
+        &self,
+        task_attempt: &TaskAttempt,
+        executor_action: &ExecutorAction,
+    ) -> Result<ExecutionPreview, ContainerError> {
+        if task_attempt.container_ref.is_none() {
+            return Err(ContainerError::Other(anyhow!(
+                "Container ref not found for task attempt"
+            )));
+        }
+        let project = task_attempt
+            .parent_task(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?
+            .parent_project(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let current_dir = self.task_attempt_to_current_dir(task_attempt);
+
+        Ok(executor_action.preview(&current_dir, &project.env_vars.0)?)
+    }
+
     async fn start_execution(
         &self,
         task_attempt: &TaskAttempt,
         executor_action: &ExecutorAction,
         run_reason: &ExecutionProcessRunReason,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        self.start_execution_retry(task_attempt, executor_action, run_reason, 0)
+            .await
+    }
+
+    /// Start `executor_action` as its `retry_count`th re-run after retryable failures
+    async fn start_execution_retry(
+        &self,
+        task_attempt: &TaskAttempt,
+        executor_action: &ExecutorAction,
+        run_reason: &ExecutionProcessRunReason,
+        retry_count: i64,
     ) -> Result<ExecutionProcess, ContainerError> {
         // Update task status to InProgress when starting an attempt
         let task = task_attempt
@@ -690,6 +1769,7 @@ pub trait ContainerService {
             .ok_or(SqlxError::RowNotFound)?;
         if task.status != TaskStatus::InProgress
             && run_reason != &ExecutionProcessRunReason::DevServer
+            && !Self::is_standalone_setup(executor_action, run_reason)
         {
             Task::update_status(&self.db().pool, task.id, TaskStatus::InProgress).await?;
         }
@@ -700,9 +1780,18 @@ pub trait ContainerService {
             run_reason: run_reason.clone(),
         };
 
-        let execution_process =
+        let mut execution_process =
             ExecutionProcess::create(&self.db().pool, &create_execution_process, Uuid::new_v4())
                 .await?;
+        if retry_count > 0 {
+            ExecutionProcess::update_retry_count(
+                &self.db().pool,
+                execution_process.id,
+                retry_count,
+            )
+            .await?;
+            execution_process.retry_count = retry_count;
+        }
 
         if let Some(prompt) = match executor_action.typ() {
             ExecutorActionType::CodingAgentInitialRequest(coding_agent_request) => {
@@ -760,7 +1849,7 @@ pub trait ContainerService {
                             .push_patch(ConversationPatch::add_normalized_entry(0, user_entry));
 
                         executor.normalize_logs(
-                            msg_store,
+                            Arc::clone(&msg_store),
                             &self.task_attempt_to_current_dir(task_attempt),
                         );
                     } else {
@@ -782,7 +1871,7 @@ pub trait ContainerService {
                             .push_patch(ConversationPatch::add_normalized_entry(0, user_entry));
 
                         executor.normalize_logs(
-                            msg_store,
+                            Arc::clone(&msg_store),
                             &self.task_attempt_to_current_dir(task_attempt),
                         );
                     } else {
@@ -0,0 +1,97 @@
+use regex::Regex;
+
+/// Patterns tried, in order, when a project doesn't configure its own.
+/// The first capture group is taken as the URL.
+pub const DEFAULT_DEV_SERVER_URL_PATTERNS: &[&str] = &[
+    // Vite ("➜  Local:   http://localhost:5173/"), Next.js ("- Local:        http://localhost:3000")
+    r"Local:\s+(https?://\S+)",
+    // Anything else that announces a loopback URL
+    r"(https?://(?:localhost|127\.0\.0\.1|0\.0\.0\.0|\[::1?\]):\d+\S*)",
+];
+
+/// Extracts the URL a dev server reports it is listening on from its output
+#[derive(Debug, Clone)]
+pub struct DevServerUrlDetector {
+    patterns: Vec<Regex>,
+}
+
+impl DevServerUrlDetector {
+    /// Build a detector using the project's pattern if set, otherwise the defaults
+    pub fn new(custom_pattern: Option<&str>) -> Result<Self, regex::Error> {
+        let patterns = match custom_pattern.map(str::trim).filter(|p| !p.is_empty()) {
+            Some(pattern) => vec![Regex::new(pattern)?],
+            None => DEFAULT_DEV_SERVER_URL_PATTERNS
+                .iter()
+                .map(|p| Regex::new(p))
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Self { patterns })
+    }
+
+    /// Return the first URL found in `output`, ignoring ANSI colour codes
+    pub fn detect(&self, output: &str) -> Option<String> {
+        let output = strip_ansi_escapes::strip_str(output);
+        self.patterns.iter().find_map(|re| {
+            re.captures(&output).map(|caps| {
+                caps.get(1)
+                    .or_else(|| caps.get(0))
+                    .map(|m| m.as_str().trim_end_matches(['/', ',', '.']).to_string())
+                    .unwrap_or_default()
+            })
+        })
+    }
+}
+
+/// Port component of a dev server URL, e.g. 5173 for `http://localhost:5173/`
+pub fn port_from_url(url: &str) -> Option<u16> {
+    let after_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = after_scheme.split('/').next()?;
+    let (_, port) = authority.rsplit_once(':')?;
+    port.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_vite_url() {
+        let detector = DevServerUrlDetector::new(None).unwrap();
+        let output = "\n  \u{1b}[32m\u{1b}[1mVITE\u{1b}[22m v5.4.2\u{1b}[39m  ready in 312 ms\n\n  \u{1b}[32m➜\u{1b}[39m  \u{1b}[1mLocal\u{1b}[22m:   \u{1b}[36mhttp://localhost:\u{1b}[1m5173\u{1b}[22m/\u{1b}[39m\n";
+        let url = detector.detect(output).unwrap();
+        assert_eq!(url, "http://localhost:5173");
+        assert_eq!(port_from_url(&url), Some(5173));
+    }
+
+    #[test]
+    fn detects_nextjs_url() {
+        let detector = DevServerUrlDetector::new(None).unwrap();
+        let output = "   ▲ Next.js 14.2.5\n   - Local:        http://localhost:3000\n   - Environments: .env\n";
+        let url = detector.detect(output).unwrap();
+        assert_eq!(url, "http://localhost:3000");
+        assert_eq!(port_from_url(&url), Some(3000));
+    }
+
+    #[test]
+    fn detects_with_custom_pattern() {
+        let detector = DevServerUrlDetector::new(Some(r"Serving on (\S+)")).unwrap();
+        let output = "Serving on http://192.168.1.20:8080/app";
+        assert_eq!(
+            detector.detect(output).as_deref(),
+            Some("http://192.168.1.20:8080/app")
+        );
+        // Defaults are not consulted once a custom pattern is configured
+        assert_eq!(detector.detect("Local: http://localhost:5173"), None);
+    }
+
+    #[test]
+    fn invalid_custom_pattern_is_rejected() {
+        assert!(DevServerUrlDetector::new(Some("(unclosed")).is_err());
+    }
+
+    #[test]
+    fn ignores_output_without_url() {
+        let detector = DevServerUrlDetector::new(None).unwrap();
+        assert_eq!(detector.detect("compiling..."), None);
+    }
+}
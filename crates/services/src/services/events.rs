@@ -1,11 +1,15 @@
-use std::{str::FromStr, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+    sync::Arc,
+};
 
 use anyhow::Error as AnyhowError;
 use axum::response::sse::Event;
 use db::{
     DBService,
     models::{
-        execution_process::ExecutionProcess,
+        execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
         task::{Task, TaskWithAttemptStatus},
         task_attempt::TaskAttempt,
     },
@@ -14,11 +18,11 @@ use futures::{StreamExt, TryStreamExt};
 use json_patch::{AddOperation, Patch, PatchOperation, RemoveOperation, ReplaceOperation};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use sqlx::{Error as SqlxError, sqlite::SqliteOperation};
+use sqlx::{Error as SqlxError, SqlitePool, sqlite::SqliteOperation};
 use strum_macros::{Display, EnumString};
 use thiserror::Error;
 use tokio::sync::RwLock;
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
 use ts_rs::TS;
 use utils::{log_msg::LogMsg, msg_store::MsgStore};
 use uuid::Uuid;
@@ -129,6 +133,163 @@ pub struct EventPatch {
     value: EventPatchInner,
 }
 
+/// Change to a project's board, sent as a named SSE event on its board stream
+#[derive(Debug, Serialize, TS)]
+#[serde(tag = "event")]
+pub enum BoardEvent {
+    /// Every task of the project. Sent first, and again whenever the stream fell behind
+    /// and may have missed changes.
+    #[serde(rename = "snapshot")]
+    Snapshot { tasks: Vec<TaskWithAttemptStatus> },
+    #[serde(rename = "task.created")]
+    TaskCreated { task: TaskWithAttemptStatus },
+    #[serde(rename = "task.updated")]
+    TaskUpdated { task: TaskWithAttemptStatus },
+    #[serde(rename = "task.deleted")]
+    TaskDeleted { task_id: Uuid },
+    /// An execution of one of the task's attempts started or changed status
+    #[serde(rename = "attempt.status_changed")]
+    AttemptStatusChanged {
+        task_id: Uuid,
+        task_attempt_id: Uuid,
+        execution_process_id: Uuid,
+        run_reason: ExecutionProcessRunReason,
+        status: ExecutionProcessStatus,
+    },
+}
+
+impl BoardEvent {
+    /// Name of the SSE event, matching the `event` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoardEvent::Snapshot { .. } => "snapshot",
+            BoardEvent::TaskCreated { .. } => "task.created",
+            BoardEvent::TaskUpdated { .. } => "task.updated",
+            BoardEvent::TaskDeleted { .. } => "task.deleted",
+            BoardEvent::AttemptStatusChanged { .. } => "attempt.status_changed",
+        }
+    }
+
+    fn to_sse_event(&self) -> Result<Event, std::io::Error> {
+        Event::default()
+            .event(self.name())
+            .json_data(self)
+            .map_err(std::io::Error::other)
+    }
+}
+
+/// What one board stream has sent so far, used to filter the shared patches down to
+/// its project
+struct BoardState {
+    pool: SqlitePool,
+    project_id: Uuid,
+    /// Tasks of the project; removal patches carry only the task id
+    task_ids: HashSet<Uuid>,
+    /// Last status sent per execution, so updates that don't change it are skipped
+    process_statuses: HashMap<Uuid, ExecutionProcessStatus>,
+    /// Events worked out but not sent yet
+    pending: VecDeque<BoardEvent>,
+}
+
+impl BoardState {
+    async fn snapshot(&mut self) -> Result<BoardEvent, EventError> {
+        let tasks =
+            Task::find_by_project_id_with_attempt_status(&self.pool, self.project_id).await?;
+        self.task_ids = tasks.iter().map(|task| task.id).collect();
+        Ok(BoardEvent::Snapshot { tasks })
+    }
+
+    /// Queue the board events a patch from the DB hook amounts to for this project
+    async fn handle_patch(&mut self, patch: &Patch) {
+        match self.event_for(patch).await {
+            Ok(Some(event)) => self.pending.push_back(event),
+            Ok(None) => {}
+            Err(e) => tracing::error!(
+                "Board stream for project {} failed to handle a change: {}",
+                self.project_id,
+                e
+            ),
+        }
+    }
+
+    async fn event_for(&mut self, patch: &Patch) -> Result<Option<BoardEvent>, EventError> {
+        let Some(op) = patch.0.first() else {
+            return Ok(None);
+        };
+        if let Some(task_id) = op.path().strip_prefix("/tasks/") {
+            let (value, created) = match op {
+                PatchOperation::Add(op) => (&op.value, true),
+                PatchOperation::Replace(op) => (&op.value, false),
+                PatchOperation::Remove(_) => {
+                    let Ok(task_id) = Uuid::parse_str(task_id) else {
+                        return Ok(None);
+                    };
+                    return Ok(self
+                        .task_ids
+                        .remove(&task_id)
+                        .then_some(BoardEvent::TaskDeleted { task_id }));
+                }
+                _ => return Ok(None),
+            };
+            let task = serde_json::from_value::<TaskWithAttemptStatus>(value.clone())?;
+            if task.project_id != self.project_id {
+                return Ok(None);
+            }
+            self.task_ids.insert(task.id);
+            return Ok(Some(if created {
+                BoardEvent::TaskCreated { task }
+            } else {
+                BoardEvent::TaskUpdated { task }
+            }));
+        }
+
+        let Ok(event_patch) = serde_json::from_value::<EventPatch>(serde_json::to_value(op)?)
+        else {
+            return Ok(None);
+        };
+        match event_patch.value.record {
+            RecordTypes::ExecutionProcess(process) => {
+                let Some(task_attempt) =
+                    TaskAttempt::find_by_id(&self.pool, process.task_attempt_id).await?
+                else {
+                    return Ok(None);
+                };
+                if !self.task_ids.contains(&task_attempt.task_id)
+                    || self.process_statuses.get(&process.id) == Some(&process.status)
+                {
+                    return Ok(None);
+                }
+                self.process_statuses
+                    .insert(process.id, process.status.clone());
+                Ok(Some(BoardEvent::AttemptStatusChanged {
+                    task_id: task_attempt.task_id,
+                    task_attempt_id: process.task_attempt_id,
+                    execution_process_id: process.id,
+                    run_reason: process.run_reason,
+                    status: process.status,
+                }))
+            }
+            // The hook couldn't tell which task was deleted once the row was gone, so
+            // find out which of this project's tasks are missing
+            RecordTypes::DeletedTask { task_id: None, .. } => {
+                let remaining: HashSet<_> =
+                    Task::find_by_project_id_with_attempt_status(&self.pool, self.project_id)
+                        .await?
+                        .into_iter()
+                        .map(|task| task.id)
+                        .collect();
+                let deleted: Vec<_> = self.task_ids.difference(&remaining).copied().collect();
+                for task_id in deleted {
+                    self.task_ids.remove(&task_id);
+                    self.pending.push_back(BoardEvent::TaskDeleted { task_id });
+                }
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
 impl EventService {
     /// Creates a new EventService that will work with a DBService configured with hooks
     pub fn new(db: DBService, msg_store: Arc<MsgStore>, entry_count: Arc<RwLock<usize>>) -> Self {
@@ -559,4 +720,200 @@ impl EventService {
 
         Ok(combined_stream)
     }
+
+    /// Stream board events (task created/updated/deleted, attempt status changes) for a
+    /// project, derived from the same DB hook patches as the task stream. Starts with a
+    /// snapshot of the project's tasks and resends one if the stream lags behind the hook.
+    pub async fn stream_board_events(
+        &self,
+        project_id: Uuid,
+    ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, EventError>
+    {
+        Ok(self
+            .board_events(project_id)
+            .await?
+            .map(|event| event.and_then(|event| event.to_sse_event()))
+            .boxed())
+    }
+
+    async fn board_events(
+        &self,
+        project_id: Uuid,
+    ) -> Result<futures::stream::BoxStream<'static, Result<BoardEvent, std::io::Error>>, EventError>
+    {
+        // Subscribe before taking the snapshot so no change falls in between
+        let messages = BroadcastStream::new(self.msg_store.get_receiver());
+        let mut state = BoardState {
+            pool: self.db.pool.clone(),
+            project_id,
+            task_ids: HashSet::new(),
+            process_statuses: HashMap::new(),
+            pending: VecDeque::new(),
+        };
+        let snapshot = state.snapshot().await?;
+
+        let live =
+            futures::stream::unfold((messages, state), |(mut messages, mut state)| async move {
+                loop {
+                    if let Some(event) = state.pending.pop_front() {
+                        return Some((Ok(event), (messages, state)));
+                    }
+                    match messages.next().await? {
+                        Ok(LogMsg::JsonPatch(patch)) => state.handle_patch(&patch).await,
+                        Ok(_) => {}
+                        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                            tracing::warn!(
+                                "Board stream for project {} missed {} events, resending snapshot",
+                                state.project_id,
+                                skipped
+                            );
+                            // Anything still queued is superseded by the snapshot
+                            state.pending.clear();
+                            let snapshot = state.snapshot().await.map_err(std::io::Error::other);
+                            return Some((snapshot, (messages, state)));
+                        }
+                    }
+                }
+            });
+        Ok(futures::stream::once(async move { Ok(snapshot) })
+            .chain(live)
+            .boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use db::models::{
+        task::TaskStatus,
+        test_support::{create_attempt, create_execution_process, create_project, create_task},
+    };
+    use futures::stream::BoxStream;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// An event service whose database reports writes made through the returned pool,
+    /// like the app's. Writes through the service's own pool go unreported.
+    async fn hooked_service() -> (EventService, SqlitePool, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let options = SqliteConnectOptions::new()
+            .filename(dir.path().join("db.sqlite"))
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options.clone()).await.unwrap();
+        sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+        let db = DBService { pool };
+        let msg_store = Arc::new(MsgStore::new());
+        let entry_count = Arc::new(RwLock::new(0));
+        let hook = EventService::create_hook(msg_store.clone(), entry_count.clone(), db.clone());
+        let hooked_pool = SqlitePoolOptions::new()
+            .after_connect(move |conn, _| hook(conn))
+            .connect_with(options)
+            .await
+            .unwrap();
+        (
+            EventService::new(db, msg_store, entry_count),
+            hooked_pool,
+            dir,
+        )
+    }
+
+    async fn next_event(
+        events: &mut BoxStream<'static, Result<BoardEvent, std::io::Error>>,
+    ) -> BoardEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .expect("no board event within 5s")
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn board_events_follow_the_projects_tasks_and_executions() {
+        let (service, pool, _dir) = hooked_service().await;
+        let project = create_project(&service.db.pool).await;
+        let other_project = create_project(&service.db.pool).await;
+        let task = create_task(&service.db.pool, project.id, "existing").await;
+        let mut events = service.board_events(project.id).await.unwrap();
+        assert!(matches!(
+            next_event(&mut events).await,
+            BoardEvent::Snapshot { tasks } if tasks.len() == 1 && tasks[0].id == task.id
+        ));
+
+        let created = create_task(&pool, project.id, "new").await;
+        assert!(matches!(
+            next_event(&mut events).await,
+            BoardEvent::TaskCreated { task } if task.id == created.id
+        ));
+
+        // Another project's task must not show up before this project's next change
+        create_task(&pool, other_project.id, "elsewhere").await;
+        Task::update_status(&pool, task.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+        assert!(matches!(
+            next_event(&mut events).await,
+            BoardEvent::TaskUpdated { task: updated }
+                if updated.id == task.id && updated.status == TaskStatus::InProgress
+        ));
+
+        let task_attempt = create_attempt(&service.db.pool, task.id).await;
+        let execution_process =
+            create_execution_process(&pool, &task_attempt, ExecutionProcessRunReason::SetupScript)
+                .await;
+        assert!(matches!(
+            next_event(&mut events).await,
+            BoardEvent::AttemptStatusChanged {
+                task_attempt_id,
+                status: ExecutionProcessStatus::Running,
+                ..
+            } if task_attempt_id == task_attempt.id
+        ));
+        ExecutionProcess::update_completion(
+            &pool,
+            execution_process.id,
+            ExecutionProcessStatus::Completed,
+            Some(0),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            next_event(&mut events).await,
+            BoardEvent::AttemptStatusChanged {
+                execution_process_id,
+                status: ExecutionProcessStatus::Completed,
+                ..
+            } if execution_process_id == execution_process.id
+        ));
+
+        Task::delete(&pool, created.id).await.unwrap();
+        assert!(matches!(
+            next_event(&mut events).await,
+            BoardEvent::TaskDeleted { task_id } if task_id == created.id
+        ));
+    }
+
+    #[tokio::test]
+    async fn lagging_board_stream_resends_the_snapshot() {
+        let (service, _pool, _dir) = hooked_service().await;
+        let project = create_project(&service.db.pool).await;
+        let mut events = service.board_events(project.id).await.unwrap();
+        assert!(matches!(
+            next_event(&mut events).await,
+            BoardEvent::Snapshot { tasks } if tasks.is_empty()
+        ));
+
+        // Created without the hook, so only a fresh snapshot can report it
+        let task = create_task(&service.db.pool, project.id, "missed").await;
+        for _ in 0..=10_000 {
+            service.msg_store.push_stdout("noise");
+        }
+        assert!(matches!(
+            next_event(&mut events).await,
+            BoardEvent::Snapshot { tasks } if tasks.len() == 1 && tasks[0].id == task.id
+        ));
+    }
 }
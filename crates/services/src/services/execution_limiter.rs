@@ -0,0 +1,287 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+use tokio::sync::Notify;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Caps how many coding agents run at once, across all projects and within each project.
+/// A start waits until it is under both limits; limits are passed on every call so config
+/// and project changes apply to the next start.
+#[derive(Default)]
+pub struct ExecutionLimiter {
+    state: Mutex<LimiterState>,
+    released: Notify,
+}
+
+#[derive(Default)]
+struct LimiterState {
+    running: usize,
+    projects: HashMap<Uuid, ProjectSlots>,
+}
+
+#[derive(Default)]
+struct ProjectSlots {
+    running: usize,
+    waiting: usize,
+}
+
+/// Running and queued coding agents of one project, for observability
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct ProjectExecutionCount {
+    pub project_id: Uuid,
+    pub running: usize,
+    /// Starts waiting for the global or project limit
+    pub waiting: usize,
+}
+
+/// A running slot, given back when dropped
+pub struct ExecutionPermit {
+    limiter: Arc<ExecutionLimiter>,
+    project_id: Uuid,
+}
+
+impl Drop for ExecutionPermit {
+    fn drop(&mut self) {
+        {
+            let mut state = self.limiter.state.lock().unwrap();
+            state.running = state.running.saturating_sub(1);
+            if let Some(project) = state.projects.get_mut(&self.project_id) {
+                project.running = project.running.saturating_sub(1);
+                if project.running == 0 && project.waiting == 0 {
+                    state.projects.remove(&self.project_id);
+                }
+            }
+        }
+        self.limiter.released.notify_waiters();
+    }
+}
+
+/// Undoes a start's place in the queue if it is cancelled while waiting
+struct QueuedStart<'a> {
+    limiter: &'a ExecutionLimiter,
+    project_id: Uuid,
+    queued: bool,
+}
+
+impl Drop for QueuedStart<'_> {
+    fn drop(&mut self) {
+        if !self.queued {
+            return;
+        }
+        let mut state = self.limiter.state.lock().unwrap();
+        if let Some(project) = state.projects.get_mut(&self.project_id) {
+            project.waiting = project.waiting.saturating_sub(1);
+            if project.running == 0 && project.waiting == 0 {
+                state.projects.remove(&self.project_id);
+            }
+        }
+    }
+}
+
+fn under(limit: Option<usize>, running: usize) -> bool {
+    // Zero means unlimited, like the executor rate limits
+    limit.is_none_or(|limit| limit == 0 || running < limit)
+}
+
+impl ExecutionLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait until a coding agent of `project_id` may start under `global_limit` and
+    /// `project_limit` (`None` or zero for no limit) and take a slot under both
+    pub async fn acquire(
+        self: &Arc<Self>,
+        project_id: Uuid,
+        global_limit: Option<usize>,
+        project_limit: Option<usize>,
+    ) -> ExecutionPermit {
+        let mut start = QueuedStart {
+            limiter: self,
+            project_id,
+            queued: false,
+        };
+        loop {
+            // Registered before checking so a release in between isn't missed
+            let released = self.released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+            {
+                let mut state = self.state.lock().unwrap();
+                let global_running = state.running;
+                let project = state.projects.entry(project_id).or_default();
+                if under(global_limit, global_running) && under(project_limit, project.running) {
+                    project.running += 1;
+                    if start.queued {
+                        project.waiting -= 1;
+                        start.queued = false;
+                    }
+                    state.running += 1;
+                    return ExecutionPermit {
+                        limiter: self.clone(),
+                        project_id,
+                    };
+                }
+                if !start.queued {
+                    start.queued = true;
+                    project.waiting += 1;
+                    tracing::info!(
+                        "Concurrency limit reached, queueing coding agent start for project {}",
+                        project_id
+                    );
+                }
+            }
+            released.await;
+        }
+    }
+
+    /// Take a slot under both limits if one is free right now, without queueing
+    pub fn try_acquire(
+        self: &Arc<Self>,
+        project_id: Uuid,
+        global_limit: Option<usize>,
+        project_limit: Option<usize>,
+    ) -> Option<ExecutionPermit> {
+        let mut state = self.state.lock().unwrap();
+        let global_running = state.running;
+        let project = state.projects.entry(project_id).or_default();
+        if !(under(global_limit, global_running) && under(project_limit, project.running)) {
+            if project.running == 0 && project.waiting == 0 {
+                state.projects.remove(&project_id);
+            }
+            return None;
+        }
+        project.running += 1;
+        state.running += 1;
+        Some(ExecutionPermit {
+            limiter: self.clone(),
+            project_id,
+        })
+    }
+
+    /// Running and waiting counts of every project with either, sorted by project
+    pub fn status(&self) -> Vec<ProjectExecutionCount> {
+        let state = self.state.lock().unwrap();
+        let mut counts: Vec<_> = state
+            .projects
+            .iter()
+            .map(|(project_id, slots)| ProjectExecutionCount {
+                project_id: *project_id,
+                running: slots.running,
+                waiting: slots.waiting,
+            })
+            .collect();
+        counts.sort_by_key(|count| count.project_id);
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    async fn is_blocked(
+        limiter: &Arc<ExecutionLimiter>,
+        project_id: Uuid,
+        global_limit: Option<usize>,
+        project_limit: Option<usize>,
+    ) -> bool {
+        tokio::time::timeout(
+            Duration::from_millis(50),
+            limiter.acquire(project_id, global_limit, project_limit),
+        )
+        .await
+        .is_err()
+    }
+
+    #[tokio::test]
+    async fn project_limit_queues_only_that_project() {
+        let limiter = Arc::new(ExecutionLimiter::new());
+        let (low, busy) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let _low_permit = limiter.acquire(low, Some(4), Some(1)).await;
+        assert!(is_blocked(&limiter, low, Some(4), Some(1)).await);
+
+        let _busy_permits = [
+            limiter.acquire(busy, Some(4), None).await,
+            limiter.acquire(busy, Some(4), None).await,
+            limiter.acquire(busy, Some(4), None).await,
+        ];
+        // The global limit is now reached, so even an uncapped project waits
+        assert!(is_blocked(&limiter, Uuid::new_v4(), Some(4), None).await);
+    }
+
+    #[tokio::test]
+    async fn waiting_start_runs_once_a_slot_is_released() {
+        let limiter = Arc::new(ExecutionLimiter::new());
+        let project_id = Uuid::new_v4();
+        let permit = limiter.acquire(project_id, None, Some(1)).await;
+
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire(project_id, None, Some(1)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(
+            limiter.status(),
+            [ProjectExecutionCount {
+                project_id,
+                running: 1,
+                waiting: 1,
+            }]
+        );
+
+        drop(permit);
+        let permit = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(limiter.status()[0].running, 1);
+        assert_eq!(limiter.status()[0].waiting, 0);
+
+        drop(permit);
+        assert!(limiter.status().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelled_start_leaves_the_queue() {
+        let limiter = Arc::new(ExecutionLimiter::new());
+        let project_id = Uuid::new_v4();
+        let permit = limiter.acquire(project_id, Some(1), None).await;
+        assert!(is_blocked(&limiter, project_id, Some(1), None).await);
+        assert_eq!(limiter.status()[0].waiting, 0);
+
+        drop(permit);
+        assert!(limiter.status().is_empty());
+    }
+
+    #[tokio::test]
+    async fn try_acquire_takes_a_free_slot_or_none_without_queueing() {
+        let limiter = Arc::new(ExecutionLimiter::new());
+        let project_id = Uuid::new_v4();
+        let permit = limiter.try_acquire(project_id, Some(1), None).unwrap();
+        assert!(limiter.try_acquire(project_id, Some(1), None).is_none());
+        assert_eq!(limiter.status()[0].waiting, 0);
+
+        drop(permit);
+        assert!(limiter.status().is_empty());
+        assert!(limiter.try_acquire(project_id, Some(1), None).is_some());
+    }
+
+    #[tokio::test]
+    async fn zero_or_none_means_unlimited() {
+        let limiter = Arc::new(ExecutionLimiter::new());
+        let project_id = Uuid::new_v4();
+        let mut permits = Vec::new();
+        for _ in 0..10 {
+            permits.push(limiter.acquire(project_id, Some(0), None).await);
+        }
+        assert_eq!(limiter.status()[0].running, 10);
+    }
+}
@@ -1,6 +1,9 @@
 use std::{
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
     time::Duration,
 };
 
@@ -12,17 +15,85 @@ use ignore::{
     WalkBuilder,
     gitignore::{Gitignore, GitignoreBuilder},
 };
-use notify::{RecommendedWatcher, RecursiveMode};
+use notify::{EventKind, PollWatcher, RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{
     DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache, new_debouncer,
+    new_debouncer_opt,
 };
 use thiserror::Error;
 
-pub type WatcherComponents = (
-    Debouncer<RecommendedWatcher, RecommendedCache>,
-    Receiver<DebounceEventResult>,
-    PathBuf,
-);
+use super::config::{FilesystemWatcherConfig, WatcherBackend};
+
+pub type WatcherComponents = (WatcherGuard, Receiver<DebounceEventResult>, PathBuf);
+
+static ACTIVE_WATCHERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Filesystems that native change events miss writes on, e.g. writes made by another
+/// host or from outside the container
+const UNRELIABLE_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "9p",
+    "virtiofs",
+    "overlay",
+    "fuse.sshfs",
+    "fuse.grpcfuse",
+    "fakeowner",
+];
+
+/// Shortest interval the polling backend rescans at
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Number of filesystem watchers currently alive
+pub fn active_watcher_count() -> usize {
+    ACTIVE_WATCHERS.load(Ordering::Relaxed)
+}
+
+/// Owns a debounced watcher. Dropping it stops the watcher and releases its
+/// OS watch handles, so hold it for exactly as long as events are consumed
+/// (e.g. inside the SSE stream so a client disconnect tears it down).
+pub struct WatcherGuard {
+    _debouncer: WatcherDebouncer,
+    root: PathBuf,
+}
+
+enum WatcherDebouncer {
+    Native(Debouncer<RecommendedWatcher, RecommendedCache>),
+    Polling(Debouncer<PollWatcher, RecommendedCache>),
+}
+
+impl WatcherDebouncer {
+    fn watch(&mut self, path: &Path) -> notify::Result<()> {
+        match self {
+            WatcherDebouncer::Native(debouncer) => debouncer.watch(path, RecursiveMode::Recursive),
+            WatcherDebouncer::Polling(debouncer) => debouncer.watch(path, RecursiveMode::Recursive),
+        }
+    }
+}
+
+impl WatcherGuard {
+    fn new(debouncer: WatcherDebouncer, root: PathBuf) -> Self {
+        ACTIVE_WATCHERS.fetch_add(1, Ordering::Relaxed);
+        Self {
+            _debouncer: debouncer,
+            root,
+        }
+    }
+}
+
+impl Drop for WatcherGuard {
+    fn drop(&mut self) {
+        let remaining = ACTIVE_WATCHERS.fetch_sub(1, Ordering::Relaxed) - 1;
+        tracing::debug!(
+            "Filesystem watcher for {} torn down ({} still active)",
+            self.root.display(),
+            remaining
+        );
+    }
+}
 
 #[derive(Debug, Error)]
 pub enum FilesystemWatcherError {
@@ -98,6 +169,11 @@ fn path_allowed(path: &Path, gi: &Gitignore, canonical_root: &Path) -> bool {
 }
 
 fn debounced_should_forward(event: &DebouncedEvent, gi: &Gitignore, canonical_root: &Path) -> bool {
+    // Reads change nothing, and forwarding them would loop forever: recomputing the
+    // diff opens the changed files again
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
     // DebouncedEvent is a struct that wraps the underlying notify::Event
     // We can check its paths field to determine if the event should be forwarded
     event
@@ -106,7 +182,42 @@ fn debounced_should_forward(event: &DebouncedEvent, gi: &Gitignore, canonical_ro
         .all(|path| path_allowed(path, gi, canonical_root))
 }
 
-pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatcherError> {
+/// Whether native change events can't be trusted for `path`, because it lives on a
+/// network or container filesystem. Only detected on Linux.
+pub fn is_unreliable_filesystem(path: &Path) -> bool {
+    #[cfg(target_os = "linux")]
+    if let Ok(mounts) = std::fs::read_to_string("/proc/self/mounts") {
+        return mount_fs_type(&mounts, path).is_some_and(|fs| UNRELIABLE_FILESYSTEMS.contains(&fs));
+    }
+    let _ = path;
+    false
+}
+
+/// Type of the filesystem mounted closest above `path`, from a mount table in
+/// `/proc/self/mounts` format
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn mount_fs_type<'a>(mounts: &'a str, path: &Path) -> Option<&'a str> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            // Spaces in mount points are escaped as \040
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((mount_point, fs_type))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        // Later mounts on the same point shadow earlier ones
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// Watch `root` recursively, using native events or polling as `config` says
+pub fn async_watcher(
+    root: PathBuf,
+    config: &FilesystemWatcherConfig,
+) -> Result<WatcherComponents, FilesystemWatcherError> {
     let canonical_root = canonicalize_lossy(&root);
     let gi_set = Arc::new(build_gitignore_set(&canonical_root)?);
     let (mut tx, rx) = channel(64); // Increased capacity for error bursts
@@ -114,37 +225,161 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
     let gi_clone = gi_set.clone();
     let root_clone = canonical_root.clone();
 
-    let mut debouncer = new_debouncer(
-        Duration::from_millis(200),
-        None, // Use default config
-        move |res: DebounceEventResult| {
-            match res {
-                Ok(events) => {
-                    // Filter events and only send allowed ones
-                    let filtered_events: Vec<DebouncedEvent> = events
-                        .into_iter()
-                        .filter(|ev| debounced_should_forward(ev, &gi_clone, &root_clone))
-                        .collect();
-
-                    if !filtered_events.is_empty() {
-                        let filtered_result = Ok(filtered_events);
-                        futures::executor::block_on(async {
-                            tx.send(filtered_result).await.ok();
-                        });
-                    }
-                }
-                Err(errors) => {
-                    // Always forward errors
+    let event_handler = move |res: DebounceEventResult| {
+        match res {
+            Ok(events) => {
+                // Filter events and only send allowed ones
+                let filtered_events: Vec<DebouncedEvent> = events
+                    .into_iter()
+                    .filter(|ev| debounced_should_forward(ev, &gi_clone, &root_clone))
+                    .collect();
+
+                if !filtered_events.is_empty() {
+                    let filtered_result = Ok(filtered_events);
                     futures::executor::block_on(async {
-                        tx.send(Err(errors)).await.ok();
+                        tx.send(filtered_result).await.ok();
                     });
                 }
             }
-        },
-    )?;
+            Err(errors) => {
+                // Always forward errors
+                futures::executor::block_on(async {
+                    tx.send(Err(errors)).await.ok();
+                });
+            }
+        }
+    };
+
+    let polling = match config.backend {
+        WatcherBackend::Native => false,
+        WatcherBackend::Polling => true,
+        WatcherBackend::Auto => is_unreliable_filesystem(&canonical_root),
+    };
+    let mut debouncer = if polling {
+        let poll_interval = Duration::from_millis(config.poll_interval_ms).max(MIN_POLL_INTERVAL);
+        tracing::debug!(
+            "Polling {} for changes every {:?}",
+            canonical_root.display(),
+            poll_interval
+        );
+        WatcherDebouncer::Polling(new_debouncer_opt(
+            Duration::from_millis(200),
+            None,
+            event_handler,
+            RecommendedCache::new(),
+            notify::Config::default().with_poll_interval(poll_interval),
+        )?)
+    } else {
+        WatcherDebouncer::Native(new_debouncer(
+            Duration::from_millis(200),
+            None, // Use default config
+            event_handler,
+        )?)
+    };
 
     // Start watching the root directory
-    debouncer.watch(&canonical_root, RecursiveMode::Recursive)?;
+    debouncer.watch(&canonical_root)?;
+
+    Ok((
+        WatcherGuard::new(debouncer, canonical_root.clone()),
+        rx,
+        canonical_root,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use futures::StreamExt;
+
+    use super::*;
 
-    Ok((debouncer, rx, canonical_root))
+    /// The watcher count is process-wide, so tests that start watchers take turns
+    static WATCHER_TESTS: LazyLock<tokio::sync::Mutex<()>> = LazyLock::new(Default::default);
+
+    #[tokio::test]
+    async fn dropping_stream_releases_watcher() {
+        let _serial = WATCHER_TESTS.lock().await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let before = active_watcher_count();
+
+        let (guard, rx, _) = async_watcher(
+            dir.path().to_path_buf(),
+            &FilesystemWatcherConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(active_watcher_count(), before + 1);
+
+        // Mirror how diff streams hold the guard for the lifetime of the subscriber
+        let mut stream = futures::stream::unfold((guard, rx), |(guard, mut rx)| async move {
+            rx.next().await.map(|events| (events, (guard, rx)))
+        })
+        .boxed();
+        std::fs::write(dir.path().join("file.txt"), "hello").unwrap();
+        assert!(stream.next().await.is_some());
+        assert_eq!(active_watcher_count(), before + 1);
+
+        // Client disconnect: the SSE response drops the stream
+        drop(stream);
+        assert_eq!(active_watcher_count(), before);
+    }
+
+    #[tokio::test]
+    async fn reading_files_is_not_a_change() {
+        let _serial = WATCHER_TESTS.lock().await;
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("file.txt"), "hello").unwrap();
+        let config = FilesystemWatcherConfig {
+            backend: WatcherBackend::Native,
+            ..Default::default()
+        };
+        let (_guard, mut rx, _) = async_watcher(dir.path().to_path_buf(), &config).unwrap();
+
+        std::fs::read_to_string(dir.path().join("file.txt")).unwrap();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(1000), rx.next())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn polling_backend_sees_changes() {
+        let _serial = WATCHER_TESTS.lock().await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = FilesystemWatcherConfig {
+            backend: WatcherBackend::Polling,
+            poll_interval_ms: 100,
+        };
+        let (_guard, mut rx, _) = async_watcher(dir.path().to_path_buf(), &config).unwrap();
+
+        std::fs::write(dir.path().join("file.txt"), "hello").unwrap();
+        let events = tokio::time::timeout(Duration::from_secs(10), rx.next())
+            .await
+            .expect("polling watcher reported no change")
+            .unwrap()
+            .unwrap();
+        assert!(
+            events
+                .iter()
+                .any(|event| event.paths.iter().any(|p| p.ends_with("file.txt")))
+        );
+    }
+
+    #[test]
+    fn mount_fs_type_picks_the_closest_mount() {
+        let mounts = "\
+overlay / overlay rw,relatime 0 0
+proc /proc proc rw 0 0
+server:/export /home/dev/my\\040repos nfs4 rw 0 0
+tmpfs /tmp tmpfs rw 0 0
+";
+        let fs = |path: &str| mount_fs_type(mounts, Path::new(path));
+        assert_eq!(fs("/home/dev/my repos/project"), Some("nfs4"));
+        assert_eq!(fs("/tmp/vibe-kanban/worktrees/vk-1"), Some("tmpfs"));
+        assert_eq!(fs("/home/dev/other"), Some("overlay"));
+        // Path components are compared whole, not as string prefixes
+        assert_eq!(fs("/tmpfoo"), Some("overlay"));
+    }
 }
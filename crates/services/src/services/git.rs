@@ -1,20 +1,27 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use chrono::{DateTime, Utc};
 use git2::{
-    BranchType, Delta, DiffFindOptions, DiffOptions, Error as GitError, FetchOptions, Reference,
-    Remote, Repository, Sort, build::CheckoutBuilder,
+    BranchType, Cred, CredentialType, Delta, DiffFindOptions, DiffOptions, Error as GitError,
+    FetchOptions, Reference, Remote, RemoteCallbacks, Repository, RepositoryState, Sort,
+    build::CheckoutBuilder,
 };
 use regex;
 use serde::Serialize;
 use thiserror::Error;
 use ts_rs::TS;
-use utils::diff::{Diff, DiffChangeKind, FileDiffDetails};
+use utils::{
+    diff::{Diff, DiffChangeKind, FileDiffDetails},
+    path::normalize_path_separators,
+};
 
 // Import for file ranking functionality
 use super::file_ranker::FileStat;
-use super::git_cli::{ChangeType, GitCli, StatusDiffEntry, StatusDiffOptions};
-use crate::services::github_service::GitHubRepoInfo;
+use super::git_cli::{ChangeType, GitCli, GitCliError, StatusDiffEntry, StatusDiffOptions};
+use crate::services::{config::CommitSigningFormat, github_service::GitHubRepoInfo};
 
 #[derive(Debug, Error)]
 pub enum GitServiceError {
@@ -40,11 +47,41 @@ pub enum GitServiceError {
     TokenUnavailable,
     #[error("Rebase in progress; resolve or abort it before retrying")]
     RebaseInProgress,
+    #[error("Commit not found: {0}")]
+    CommitNotFound(String),
+    #[error("Commit signing failed (check the signing key and agent): {0}")]
+    CommitSigningFailed(String),
+    #[error("Worktree changed while it was being diffed: {0}")]
+    WorktreeChanging(String),
+    #[error("{0} is checked out in another worktree: {1}")]
+    BranchCheckedOut(String, String),
+}
+
+impl GitServiceError {
+    /// Whether the same operation may succeed if retried shortly, because another git
+    /// process is holding a lock libgit2 needs or the worktree changed mid-diff
+    pub fn is_transient(&self) -> bool {
+        match self {
+            GitServiceError::Git(e) => e.code() == git2::ErrorCode::Locked,
+            GitServiceError::WorktreeChanging(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// How commits created on the user's behalf are signed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitSigning {
+    pub format: CommitSigningFormat,
+    /// Key passed to `-S`; git's `user.signingkey` is used when unset
+    pub key: Option<String>,
 }
 
 /// Service for managing Git operations in task execution workflows
 #[derive(Clone)]
-pub struct GitService {}
+pub struct GitService {
+    commit_signing: Option<CommitSigning>,
+}
 
 #[derive(Debug, Serialize, TS)]
 pub struct GitBranch {
@@ -55,6 +92,63 @@ pub struct GitBranch {
     pub last_commit_date: DateTime<Utc>,
 }
 
+/// Author, committer and message of a single commit
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CommitMetadata {
+    pub sha: String,
+    pub message: String,
+    pub author_name: Option<String>,
+    pub author_email: Option<String>,
+    pub committer_name: Option<String>,
+    pub committer_email: Option<String>,
+    /// Commit (committer) time
+    #[ts(type = "Date")]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Size of the work on a branch since it forked from its base
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, TS)]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub commits: usize,
+}
+
+/// Credentials for cloning a remote repository
+#[derive(Debug, Clone, Default)]
+pub struct CloneOptions {
+    /// Access token for HTTPS remotes
+    pub token: Option<String>,
+    /// SSH key to use when the SSH agent has none; defaults to `~/.ssh/id_ed25519`,
+    /// then `~/.ssh/id_rsa`
+    pub ssh_key_path: Option<PathBuf>,
+}
+
+/// How far a clone has got, as reported by git
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CloneProgress {
+    pub received_objects: usize,
+    pub total_objects: usize,
+    pub indexed_deltas: usize,
+    pub total_deltas: usize,
+    pub received_bytes: usize,
+}
+
+/// The half-finished operation `abort_operation` backed out of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum AbortedOperation {
+    /// Nothing was in progress, so nothing was changed
+    None,
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    /// Conflicted files left behind without an operation to abort, e.g. by a squash merge
+    Conflicts,
+}
+
 #[derive(Debug, Clone)]
 pub struct HeadInfo {
     pub branch: String,
@@ -80,6 +174,12 @@ pub enum DiffTarget<'p> {
         repo_path: &'p Path,
         commit_sha: &'p str,
     },
+    /// One commit's tree vs another's, e.g. the tips of two attempts' branches
+    Commits {
+        repo_path: &'p Path,
+        from_sha: &'p str,
+        to_sha: &'p str,
+    },
 }
 
 impl Default for GitService {
@@ -91,7 +191,31 @@ impl Default for GitService {
 impl GitService {
     /// Create a new GitService for the given repository path
     pub fn new() -> Self {
-        Self {}
+        Self {
+            commit_signing: None,
+        }
+    }
+
+    /// A copy of this service whose commits, squash merges and rebases are signed
+    /// with `signing` (or left to git's own config when `None`)
+    pub fn with_commit_signing(&self, signing: Option<CommitSigning>) -> Self {
+        Self {
+            commit_signing: signing,
+        }
+    }
+
+    /// Map a failed CLI command that writes commits, reporting signing failures
+    /// as `CommitSigningFailed` so they aren't mistaken for repository problems
+    fn commit_error(&self, context: &str, e: GitCliError) -> GitServiceError {
+        match e {
+            GitCliError::CommandFailed(msg)
+                if self.commit_signing.is_some()
+                    && msg.contains("failed to write commit object") =>
+            {
+                GitServiceError::CommitSigningFailed(msg)
+            }
+            e => GitServiceError::InvalidRepository(format!("{context}: {e}")),
+        }
     }
 
     /// Open the repository
@@ -201,25 +325,54 @@ impl GitService {
     }
 
     pub fn commit(&self, path: &Path, message: &str) -> Result<bool, GitServiceError> {
+        self.commit_scoped(path, None, message)
+    }
+
+    /// Like [`GitService::commit`], but only stages changes under `subpath`
+    /// (relative to the worktree root) when one is given.
+    pub fn commit_scoped(
+        &self,
+        path: &Path,
+        subpath: Option<&str>,
+        message: &str,
+    ) -> Result<bool, GitServiceError> {
         // Use Git CLI to respect sparse-checkout semantics for staging and commit
         let git = GitCli::new();
         let has_changes = git
-            .has_changes(path)
+            .has_changes_in(path, subpath, true)
             .map_err(|e| GitServiceError::InvalidRepository(format!("git status failed: {e}")))?;
         if !has_changes {
             tracing::debug!("No changes to commit!");
             return Ok(false);
         }
 
-        git.add_all(path)
+        git.add_all_in(path, subpath)
             .map_err(|e| GitServiceError::InvalidRepository(format!("git add failed: {e}")))?;
         // Only ensure identity once we know we're about to commit
         self.ensure_cli_commit_identity(path)?;
-        git.commit(path, message)
-            .map_err(|e| GitServiceError::InvalidRepository(format!("git commit failed: {e}")))?;
+        git.commit(path, message, self.commit_signing.as_ref())
+            .map_err(|e| self.commit_error("git commit failed", e))?;
         Ok(true)
     }
 
+    /// Commit only if tracked files changed, ignoring untracked files such as
+    /// build output. Returns whether a commit was created.
+    pub fn commit_tracked_changes(
+        &self,
+        path: &Path,
+        subpath: Option<&str>,
+        message: &str,
+    ) -> Result<bool, GitServiceError> {
+        let has_tracked_changes = GitCli::new()
+            .has_changes_in(path, subpath, false)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git status failed: {e}")))?;
+        if !has_tracked_changes {
+            tracing::debug!("No tracked changes to commit");
+            return Ok(false);
+        }
+        self.commit_scoped(path, subpath, message)
+    }
+
     /// Get diffs between branches or worktree changes
     pub fn get_diffs(
         &self,
@@ -243,8 +396,9 @@ impl GitService {
                 };
                 let entries = git
                     .diff_status(worktree_path, base_branch, cli_opts)
-                    .map_err(|e| {
-                        GitServiceError::InvalidRepository(format!("git diff failed: {e}"))
+                    .map_err(|e| match e {
+                        GitCliError::StagingFailed(msg) => GitServiceError::WorktreeChanging(msg),
+                        e => GitServiceError::InvalidRepository(format!("git diff failed: {e}")),
                     })?;
                 Ok(entries
                     .into_iter()
@@ -333,6 +487,67 @@ impl GitService {
 
                 self.convert_diff_to_file_diffs(diff, &repo)
             }
+            DiffTarget::Commits {
+                repo_path,
+                from_sha,
+                to_sha,
+            } => {
+                let repo = self.open_repo(repo_path)?;
+                let from_tree = Self::find_commit(&repo, from_sha)?.tree()?;
+                let to_tree = Self::find_commit(&repo, to_sha)?.tree()?;
+
+                let mut diff_opts = DiffOptions::new();
+                diff_opts.include_typechange(true);
+
+                // Add path filtering if specified
+                if let Some(paths) = path_filter {
+                    for path in paths {
+                        diff_opts.pathspec(*path);
+                    }
+                }
+
+                let mut diff =
+                    repo.diff_tree_to_tree(Some(&from_tree), Some(&to_tree), Some(&mut diff_opts))?;
+
+                // Enable rename detection
+                let mut find_opts = DiffFindOptions::new();
+                diff.find_similar(Some(&mut find_opts))?;
+
+                self.convert_diff_to_file_diffs(diff, &repo)
+            }
+        }
+    }
+
+    /// Resolve `sha` to a commit, reporting unknown or malformed shas as `CommitNotFound`
+    fn find_commit<'r>(
+        repo: &'r Repository,
+        sha: &str,
+    ) -> Result<git2::Commit<'r>, GitServiceError> {
+        git2::Oid::from_str(sha)
+            .and_then(|oid| repo.find_commit(oid))
+            .map_err(|_| GitServiceError::CommitNotFound(sha.to_string()))
+    }
+
+    /// The best common ancestor of two branches, or `None` if they share no history
+    pub fn get_merge_base(
+        &self,
+        repo_path: &Path,
+        branch_a: &str,
+        branch_b: &str,
+    ) -> Result<Option<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let a = Self::find_branch(&repo, branch_a)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        let b = Self::find_branch(&repo, branch_b)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        match repo.merge_base(a, b) {
+            Ok(oid) => Ok(Some(oid.to_string())),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -401,13 +616,13 @@ impl GitService {
                     }
                 }
 
-                file_diffs.push(Diff {
+                file_diffs.push(Diff::new(
                     change,
                     old_path,
                     new_path,
                     old_content,
                     new_content,
-                });
+                ));
 
                 true
             },
@@ -419,11 +634,12 @@ impl GitService {
         Ok(file_diffs)
     }
 
-    /// Extract file path from a Diff (for indexing and ConversationPatch)
+    /// Extract file path from a Diff (for indexing and ConversationPatch), always `/`-separated
     pub fn diff_path(diff: &Diff) -> String {
         diff.new_path
-            .clone()
-            .or_else(|| diff.old_path.clone())
+            .as_deref()
+            .or(diff.old_path.as_deref())
+            .map(normalize_path_separators)
             .unwrap_or_default()
     }
 
@@ -570,13 +786,7 @@ impl GitService {
             change = DiffChangeKind::PermissionChange;
         }
 
-        Diff {
-            change,
-            old_path: old_path_opt,
-            new_path: new_path_opt,
-            old_content,
-            new_content,
-        }
+        Diff::new(change, old_path_opt, new_path_opt, old_content, new_content)
     }
 
     /// Merge changes from a worktree branch back to the main repository
@@ -592,6 +802,18 @@ impl GitService {
         let worktree_repo = self.open_repo(worktree_path)?;
         let main_repo = self.open_repo(repo_path)?;
 
+        // Check for conflicts up front so a failed merge never leaves the main
+        // working tree half-merged
+        {
+            let base_commit = Self::find_branch(&main_repo, base_branch_name)?
+                .get()
+                .peel_to_commit()?;
+            let task_commit = Self::find_branch(&main_repo, branch_name)?
+                .get()
+                .peel_to_commit()?;
+            Self::merge_in_memory(&main_repo, &base_commit, &task_commit)?;
+        }
+
         // If main repo is currently on the base branch, perform a safe CLI
         // squash merge directly in the main working tree, provided there are
         // no staged changes (to avoid accidental inclusion).
@@ -612,10 +834,14 @@ impl GitService {
             // Ensure identity for the CLI commit
             self.ensure_cli_commit_identity(repo_path)?;
             let sha = git
-                .merge_squash_commit(repo_path, base_branch_name, branch_name, commit_message)
-                .map_err(|e| {
-                    GitServiceError::InvalidRepository(format!("git merge --squash failed: {e}"))
-                })?;
+                .merge_squash_commit(
+                    repo_path,
+                    base_branch_name,
+                    branch_name,
+                    commit_message,
+                    self.commit_signing.as_ref(),
+                )
+                .map_err(|e| self.commit_error("git merge --squash failed", e))?;
             // Also update task branch ref to merged commit for continuity
             let task_refname = format!("refs/heads/{branch_name}");
             git.update_ref(repo_path, &task_refname, &sha)
@@ -723,6 +949,54 @@ impl GitService {
         }
     }
 
+    /// Fail with `WorktreeDirty`, listing the modified files, if the worktree has
+    /// uncommitted changes to tracked files
+    pub fn ensure_worktree_clean(&self, worktree_path: &Path) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        self.check_worktree_clean(&repo)
+    }
+
+    /// Stash uncommitted changes to tracked files under `stash_ref` rather than the
+    /// repo-wide stash, which every worktree shares, then reset them. Earlier stashes
+    /// under the same ref stay in its reflog. Returns false if there was nothing to stash.
+    pub fn stash_changes(
+        &self,
+        worktree_path: &Path,
+        stash_ref: &str,
+        message: &str,
+    ) -> Result<bool, GitServiceError> {
+        if self.is_worktree_clean(worktree_path)? {
+            return Ok(false);
+        }
+        // Stashing creates commits, so it needs an identity just like committing
+        self.ensure_cli_commit_identity(worktree_path)?;
+        let cli = super::git_cli::GitCli::new();
+        let stash_failed =
+            |e: GitCliError| GitServiceError::InvalidRepository(format!("git stash failed: {e}"));
+        let stash = cli
+            .git(worktree_path, ["stash", "create", message])
+            .map_err(stash_failed)?;
+        let stash = stash.trim();
+        if stash.is_empty() {
+            return Ok(false);
+        }
+        cli.git(
+            worktree_path,
+            [
+                "update-ref",
+                "--create-reflog",
+                "-m",
+                message,
+                stash_ref,
+                stash,
+            ],
+        )
+        .map_err(stash_failed)?;
+        cli.git(worktree_path, ["reset", "--hard", "--quiet", "HEAD"])
+            .map_err(stash_failed)?;
+        Ok(true)
+    }
+
     /// Check if the worktree is clean (no uncommitted changes to tracked files)
     fn check_worktree_clean(&self, repo: &Repository) -> Result<(), GitServiceError> {
         let mut status_options = git2::StatusOptions::new();
@@ -830,6 +1104,138 @@ impl GitService {
         ))
     }
 
+    /// Get the metadata of a commit. Fails with `CommitNotFound` when the SHA
+    /// is malformed or not in the repository.
+    pub fn get_commit(
+        &self,
+        repo_path: &Path,
+        commit_sha: &str,
+    ) -> Result<CommitMetadata, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let not_found = || GitServiceError::CommitNotFound(commit_sha.to_string());
+        let oid = git2::Oid::from_str(commit_sha).map_err(|_| not_found())?;
+        let commit = repo.find_commit(oid).map_err(|e| match e.code() {
+            git2::ErrorCode::NotFound => not_found(),
+            _ => GitServiceError::Git(e),
+        })?;
+        Ok(Self::commit_metadata(&commit))
+    }
+
+    fn commit_metadata(commit: &git2::Commit) -> CommitMetadata {
+        let author = commit.author();
+        let committer = commit.committer();
+        let timestamp = DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default();
+        CommitMetadata {
+            sha: commit.id().to_string(),
+            message: commit.message().unwrap_or_default().trim_end().to_string(),
+            author_name: author.name().map(|s| s.to_string()),
+            author_email: author.email().map(|s| s.to_string()),
+            committer_name: committer.name().map(|s| s.to_string()),
+            committer_email: committer.email().map(|s| s.to_string()),
+            timestamp,
+        }
+    }
+
+    /// List the commits on `branch_name` that aren't on `base_branch`, newest
+    /// first, returning at most `limit` of them
+    pub fn list_commits(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch: &str,
+        limit: usize,
+    ) -> Result<Vec<CommitMetadata>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let tip = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        let base = Self::find_branch(&repo, base_branch)?
+            .get()
+            .peel_to_commit()?
+            .id();
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        revwalk.push(tip)?;
+        revwalk.hide(base)?;
+
+        revwalk
+            .take(limit)
+            .map(|oid| -> Result<_, GitServiceError> {
+                Ok(Self::commit_metadata(&repo.find_commit(oid?)?))
+            })
+            .collect()
+    }
+
+    /// Files changed, lines inserted and deleted, and commits made on `tip_sha` since it
+    /// forked from `base_sha`. Only counts are computed, no diff is built up.
+    pub fn diff_stats(
+        &self,
+        repo_path: &Path,
+        base_sha: &str,
+        tip_sha: &str,
+    ) -> Result<DiffStats, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let tip = git2::Oid::from_str(tip_sha)?;
+        let base = git2::Oid::from_str(base_sha)?;
+        let fork_point = repo.merge_base(tip, base)?;
+        let diff = repo.diff_tree_to_tree(
+            Some(&repo.find_commit(fork_point)?.tree()?),
+            Some(&repo.find_commit(tip)?.tree()?),
+            None,
+        )?;
+        let stats = diff.stats()?;
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(tip)?;
+        revwalk.hide(base)?;
+
+        Ok(DiffStats {
+            files_changed: stats.files_changed(),
+            insertions: stats.insertions(),
+            deletions: stats.deletions(),
+            commits: revwalk.count(),
+        })
+    }
+
+    /// Check that a commit is reachable from the tip of `branch_name`. Fails
+    /// with `CommitNotFound` when the SHA is malformed or not in the repository.
+    pub fn is_commit_on_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        commit_sha: &str,
+    ) -> Result<bool, GitServiceError> {
+        let commit = self.get_commit(repo_path, commit_sha)?;
+        let repo = self.open_repo(repo_path)?;
+        let tip = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        let oid = git2::Oid::from_str(&commit.sha)?;
+        Ok(tip == oid || repo.graph_descendant_of(tip, oid)?)
+    }
+
+    /// Check that a commit is one of `branch_name`'s own: reachable from its tip but not
+    /// from `base_branch_name`'s. A missing base branch excludes nothing.
+    pub fn is_commit_on_branch_only(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+        commit_sha: &str,
+    ) -> Result<bool, GitServiceError> {
+        if !self.is_commit_on_branch(repo_path, branch_name, commit_sha)? {
+            return Ok(false);
+        }
+        match self.is_commit_on_branch(repo_path, base_branch_name, commit_sha) {
+            Ok(on_base) => Ok(!on_base),
+            Err(GitServiceError::BranchNotFound(_)) => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Get the subject/summary line for a given commit OID
     pub fn get_commit_subject(
         &self,
@@ -1069,6 +1475,72 @@ impl GitService {
         Ok(branches)
     }
 
+    /// Merge `task_commit` into `base_commit` without touching any working tree or ref.
+    /// Fails with `MergeConflicts` listing the conflicted paths.
+    fn merge_in_memory(
+        repo: &Repository,
+        base_commit: &git2::Commit,
+        task_commit: &git2::Commit,
+    ) -> Result<git2::Index, GitServiceError> {
+        let mut merge_opts = git2::MergeOptions::new();
+        merge_opts.find_renames(true); // improve rename handling
+        let index = repo.merge_commits(base_commit, task_commit, Some(&merge_opts))?;
+        Self::ensure_no_conflicts(&index)?;
+        Ok(index)
+    }
+
+    /// Fail with `MergeConflicts` listing the conflicted paths of an in-memory index
+    fn ensure_no_conflicts(index: &git2::Index) -> Result<(), GitServiceError> {
+        if index.has_conflicts() {
+            let mut paths: Vec<String> = index
+                .conflicts()?
+                .filter_map(Result::ok)
+                .filter_map(|conflict| conflict.our.or(conflict.their).or(conflict.ancestor))
+                .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+                .collect();
+            paths.dedup();
+            return Err(GitServiceError::MergeConflicts(paths.join(", ")));
+        }
+        Ok(())
+    }
+
+    /// Write a commit of `tree_id` on top of `parent` without touching any ref, signing
+    /// it through the git CLI when commit signing is configured
+    fn write_commit(
+        &self,
+        repo: &Repository,
+        tree_id: git2::Oid,
+        parent: &git2::Commit,
+        signature: &git2::Signature,
+        commit_message: &str,
+    ) -> Result<git2::Oid, GitServiceError> {
+        if let Some(signing) = &self.commit_signing {
+            // libgit2 can't produce signatures itself, so let git sign the commit
+            let repo_dir = repo.workdir().unwrap_or(repo.path());
+            self.ensure_cli_commit_identity(repo_dir)?;
+            let sha = GitCli::new()
+                .commit_tree(
+                    repo_dir,
+                    &tree_id.to_string(),
+                    &parent.id().to_string(),
+                    commit_message,
+                    signing,
+                )
+                .map_err(|e| self.commit_error("git commit-tree failed", e))?;
+            Ok(git2::Oid::from_str(&sha)?)
+        } else {
+            let tree = repo.find_tree(tree_id)?;
+            Ok(repo.commit(
+                None,           // Don't update any reference yet
+                signature,      // Author
+                signature,      // Committer
+                commit_message, // Custom message
+                &tree,          // Commit content
+                &[parent],      // Single parent
+            )?)
+        }
+    }
+
     /// Perform a squash merge of task branch into base branch, but fail on conflicts
     fn perform_squash_merge(
         &self,
@@ -1079,33 +1551,14 @@ impl GitService {
         commit_message: &str,
         base_branch_name: &str,
     ) -> Result<git2::Oid, GitServiceError> {
-        // In-memory merge to detect conflicts without touching the working tree
-        let mut merge_opts = git2::MergeOptions::new();
-        // Safety and correctness options
-        merge_opts.find_renames(true); // improve rename handling
-        merge_opts.fail_on_conflict(true); // bail out instead of generating conflicted index
-        let mut index = repo.merge_commits(base_commit, task_commit, Some(&merge_opts))?;
-
-        // If there are conflicts, return an error
-        if index.has_conflicts() {
-            return Err(GitServiceError::MergeConflicts(
-                "Merge failed due to conflicts. Please resolve conflicts manually.".to_string(),
-            ));
-        }
+        let mut index = Self::merge_in_memory(repo, base_commit, task_commit)?;
 
         // Write the merged tree back to the repository
         let tree_id = index.write_tree_to(repo)?;
-        let tree = repo.find_tree(tree_id)?;
 
         // Create a squash commit: use merged tree with base_commit as sole parent
-        let squash_commit_id = repo.commit(
-            None,           // Don't update any reference yet
-            signature,      // Author
-            signature,      // Committer
-            commit_message, // Custom message
-            &tree,          // Merged tree content
-            &[base_commit], // Single parent: base branch commit
-        )?;
+        let squash_commit_id =
+            self.write_commit(repo, tree_id, base_commit, signature, commit_message)?;
 
         // Update the base branch reference to point to the new commit
         let refname = format!("refs/heads/{base_branch_name}");
@@ -1114,6 +1567,202 @@ impl GitService {
         Ok(squash_commit_id)
     }
 
+    /// Undo `commit_sha` on `base_branch_name` with a new revert commit and return its
+    /// sha. When the branch has since changed the same lines the revert fails with
+    /// `MergeConflicts` listing the conflicted paths, and the branch is left untouched.
+    pub fn revert_commit(
+        &self,
+        repo_path: &Path,
+        base_branch_name: &str,
+        commit_sha: &str,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let target = Self::find_commit(&repo, commit_sha)?;
+        if !self.is_commit_on_branch(repo_path, base_branch_name, commit_sha)? {
+            return Err(GitServiceError::InvalidRepository(format!(
+                "Commit {commit_sha} is not on branch {base_branch_name}"
+            )));
+        }
+        let base_commit = Self::find_branch(&repo, base_branch_name)?
+            .get()
+            .peel_to_commit()?;
+
+        // PR merges may be true merge commits; revert those against their first parent
+        let mainline = (target.parent_count() > 1).then_some(1);
+        let index = repo.revert_commit(&target, &base_commit, mainline.unwrap_or(0), None)?;
+        Self::ensure_no_conflicts(&index)?;
+
+        let commit_message = format!(
+            "Revert \"{}\"\n\nThis reverts commit {}.",
+            target.summary().unwrap_or_default(),
+            target.id()
+        );
+
+        // Like merges, a checked-out base branch is reverted through the CLI so the
+        // working tree follows along
+        if let Ok(head) = repo.head()
+            && head.shorthand() == Some(base_branch_name)
+        {
+            let git = GitCli::new();
+            if git.has_staged_changes(repo_path).map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git diff --cached failed: {e}"))
+            })? {
+                return Err(GitServiceError::WorktreeDirty(
+                    base_branch_name.to_string(),
+                    "staged changes present".to_string(),
+                ));
+            }
+            self.ensure_cli_commit_identity(repo_path)?;
+            return git
+                .revert_commit(
+                    repo_path,
+                    commit_sha,
+                    mainline,
+                    &commit_message,
+                    self.commit_signing.as_ref(),
+                )
+                .map_err(|e| self.commit_error("git revert failed", e));
+        }
+
+        let mut index = index;
+        let tree_id = index.write_tree_to(&repo)?;
+        let signature = self.signature_with_fallback(&repo)?;
+        let revert_commit_id =
+            self.write_commit(&repo, tree_id, &base_commit, &signature, &commit_message)?;
+        let refname = format!("refs/heads/{base_branch_name}");
+        repo.reference(&refname, revert_commit_id, true, "Revert merge")?;
+
+        Ok(revert_commit_id.to_string())
+    }
+
+    /// Fetch the remote `base_branch` tracks and fast-forward the local branch to the
+    /// fetched upstream when it is merely behind, so attempts branch from, diff against
+    /// and merge into the same current base. Local commits that aren't pushed yet are
+    /// never skipped: a branch that has diverged from its upstream is left alone.
+    pub fn fetch_base_branch(
+        &self,
+        repo_path: &Path,
+        base_branch: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let (remote, upstream) = match repo.find_branch(base_branch, BranchType::Local) {
+            Ok(local) => {
+                let Ok(upstream) = local.upstream() else {
+                    // Nothing to fetch for a branch that doesn't track a remote
+                    return Ok(());
+                };
+                let remote = repo
+                    .branch_upstream_remote(&format!("refs/heads/{base_branch}"))?
+                    .as_str()
+                    .map(str::to_string);
+                (remote, upstream.name()?.map(str::to_string))
+            }
+            // A remote branch such as `origin/main`
+            Err(_) => (
+                base_branch
+                    .split_once('/')
+                    .map(|(remote, _)| remote.to_string()),
+                None,
+            ),
+        };
+        let Some(remote) = remote.filter(|remote| repo.find_remote(remote).is_ok()) else {
+            return Ok(());
+        };
+
+        GitCli::new().fetch(repo_path, &remote).map_err(|e| {
+            GitServiceError::InvalidRepository(format!("git fetch {remote} failed: {e}"))
+        })?;
+
+        // A remote base such as `origin/main` was updated by the fetch itself
+        let Some(upstream) = upstream else {
+            return Ok(());
+        };
+        let local = Self::find_branch(&repo, base_branch)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        let remote_tip = Self::find_branch(&repo, &upstream)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        if local != remote_tip && repo.graph_descendant_of(remote_tip, local)? {
+            self.fast_forward_branch(repo_path, base_branch, &upstream)?;
+        }
+        Ok(())
+    }
+
+    /// Fast-forward `branch_name` to the tip of `target_branch` and return the new tip.
+    /// Fails with `BranchesDiverged` when `branch_name` has commits `target_branch` lacks,
+    /// and with `BranchCheckedOut` when a linked worktree has it checked out, since moving
+    /// the ref would leave that worktree's files behind. A branch checked out in the repo
+    /// itself is moved with the CLI so its working tree follows.
+    pub fn fast_forward_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        target_branch: &str,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let current = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        let target = Self::find_branch(&repo, target_branch)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        if current == target {
+            return Ok(target.to_string());
+        }
+        if !repo.graph_descendant_of(target, current)? {
+            return Err(GitServiceError::BranchesDiverged(format!(
+                "{branch_name} has commits that are not on {target_branch}"
+            )));
+        }
+        if let Some(worktree_path) = Self::linked_worktree_with_branch(&repo, branch_name)? {
+            return Err(GitServiceError::BranchCheckedOut(
+                branch_name.to_string(),
+                worktree_path.display().to_string(),
+            ));
+        }
+
+        if let Ok(head) = repo.head()
+            && head.shorthand() == Some(branch_name)
+        {
+            GitCli::new()
+                .merge_ff_only(repo_path, target_branch)
+                .map_err(|e| {
+                    GitServiceError::InvalidRepository(format!("git merge --ff-only failed: {e}"))
+                })?;
+        } else {
+            let refname = format!("refs/heads/{branch_name}");
+            repo.reference(&refname, target, true, "Fast-forward")?;
+        }
+
+        Ok(target.to_string())
+    }
+
+    /// Path of the linked worktree that has `branch_name` checked out, if any
+    fn linked_worktree_with_branch(
+        repo: &Repository,
+        branch_name: &str,
+    ) -> Result<Option<PathBuf>, GitServiceError> {
+        for name in repo.worktrees()?.iter().flatten() {
+            let worktree = repo.find_worktree(name)?;
+            // Worktrees whose directory is gone can't have anything checked out
+            let Ok(worktree_repo) = Repository::open_from_worktree(&worktree) else {
+                continue;
+            };
+            if let Ok(head) = worktree_repo.head()
+                && head.is_branch()
+                && head.shorthand() == Some(branch_name)
+            {
+                return Ok(Some(worktree.path().to_path_buf()));
+            }
+        }
+        Ok(None)
+    }
+
     /// Rebase a worktree branch onto a new base
     pub fn rebase_branch(
         &self,
@@ -1159,16 +1808,63 @@ impl GitService {
         // Ensure identity for any commits produced by rebase
         self.ensure_cli_commit_identity(worktree_path)?;
         // Use git CLI rebase to carry out the operation safely
-        git.rebase_onto(worktree_path, &new_base_branch_name, old_base_branch)
-            .map_err(|e| {
-                GitServiceError::InvalidRepository(format!("git rebase --onto failed: {e}"))
-            })?;
+        if let Err(e) = git.rebase_onto(
+            worktree_path,
+            &new_base_branch_name,
+            old_base_branch,
+            self.commit_signing.as_ref(),
+        ) {
+            let e = self.commit_error("git rebase --onto failed", e);
+            // Unlike a conflict there's nothing to resolve, so don't leave the rebase
+            // stopped on the commit it couldn't sign
+            if matches!(e, GitServiceError::CommitSigningFailed(_)) {
+                let _ = git.git(worktree_path, ["rebase", "--abort"]);
+            }
+            return Err(e);
+        }
 
         // Return resulting HEAD commit
         let final_commit = worktree_repo.head()?.peel_to_commit()?;
         Ok(final_commit.id().to_string())
     }
 
+    /// Back out of a merge, rebase, cherry-pick or revert left half-finished in
+    /// `worktree_path`, restoring the branch and files to where they were before it
+    /// started. Conflicted files with no operation in progress are reset to HEAD, keeping
+    /// unrelated local changes. Returns what was aborted.
+    pub fn abort_operation(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<AbortedOperation, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let (operation, args) = match repo.state() {
+            RepositoryState::Merge => (AbortedOperation::Merge, ["merge", "--abort"]),
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => {
+                (AbortedOperation::Rebase, ["rebase", "--abort"])
+            }
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                (AbortedOperation::CherryPick, ["cherry-pick", "--abort"])
+            }
+            RepositoryState::Revert | RepositoryState::RevertSequence => {
+                (AbortedOperation::Revert, ["revert", "--abort"])
+            }
+            RepositoryState::ApplyMailbox => (AbortedOperation::Rebase, ["am", "--abort"]),
+            RepositoryState::Clean | RepositoryState::Bisect => {
+                if !repo.index()?.has_conflicts() {
+                    return Ok(AbortedOperation::None);
+                }
+                (AbortedOperation::Conflicts, ["reset", "--merge"])
+            }
+        };
+        GitCli::new().git(worktree_path, args).map_err(|e| {
+            GitServiceError::InvalidRepository(format!("git {} failed: {e}", args.join(" ")))
+        })?;
+        Ok(operation)
+    }
+
     pub fn find_branch_type(
         &self,
         repo_path: &Path,
@@ -1268,11 +1964,11 @@ impl GitService {
         }
     }
 
-    /// Extract GitHub owner and repo name from git repo path
-    pub fn get_github_repo_info(
+    /// Name and URL of the repository's default remote
+    pub fn get_default_remote(
         &self,
         repo_path: &Path,
-    ) -> Result<GitHubRepoInfo, GitServiceError> {
+    ) -> Result<(String, String), GitServiceError> {
         let repo = self.open_repo(repo_path)?;
         let remote_name = self.default_remote_name(&repo);
         let remote = repo.find_remote(&remote_name).map_err(|_| {
@@ -1281,13 +1977,23 @@ impl GitService {
 
         let url = remote
             .url()
-            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))?;
+            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))?
+            .to_string();
+        Ok((remote_name, url))
+    }
+
+    /// Extract GitHub owner and repo name from git repo path
+    pub fn get_github_repo_info(
+        &self,
+        repo_path: &Path,
+    ) -> Result<GitHubRepoInfo, GitServiceError> {
+        let (_, url) = self.get_default_remote(repo_path)?;
 
         // Parse GitHub URL (supports both HTTPS and SSH formats)
         let github_regex = regex::Regex::new(r"github\.com[:/]([^/]+)/(.+?)(?:\.git)?/?$")
             .map_err(|e| GitServiceError::InvalidRepository(format!("Regex error: {e}")))?;
 
-        if let Some(captures) = github_regex.captures(url) {
+        if let Some(captures) = github_regex.captures(&url) {
             let owner = captures.get(1).unwrap().as_str().to_string();
             let repo_name = captures.get(2).unwrap().as_str().to_string();
             Ok(GitHubRepoInfo { owner, repo_name })
@@ -1340,13 +2046,26 @@ impl GitService {
         worktree_path: &Path,
         branch_name: &str,
         github_token: &str,
+    ) -> Result<(), GitServiceError> {
+        let remote_name = self.default_remote_name(&Repository::open(worktree_path)?);
+        self.push_branch(worktree_path, branch_name, &remote_name, github_token)
+    }
+
+    /// Push `branch_name` to `remote_name` over HTTPS, authenticating with `token`, and
+    /// make the pushed branch its upstream. SSH remote URLs are rewritten to HTTPS.
+    pub fn push_branch(
+        &self,
+        worktree_path: &Path,
+        branch_name: &str,
+        remote_name: &str,
+        token: &str,
     ) -> Result<(), GitServiceError> {
         let repo = Repository::open(worktree_path)?;
         self.check_worktree_clean(&repo)?;
 
-        // Get the remote
-        let remote_name = self.default_remote_name(&repo);
-        let remote = repo.find_remote(&remote_name)?;
+        let remote = repo.find_remote(remote_name).map_err(|_| {
+            GitServiceError::InvalidRepository(format!("No '{remote_name}' remote found"))
+        })?;
 
         let remote_url = remote
             .url()
@@ -1365,10 +2084,10 @@ impl GitService {
         // Create refspec for pushing the branch
         let refspec = format!("refs/heads/{branch_name}:refs/heads/{branch_name}");
 
-        // Set up authentication callback using the GitHub token
+        // Set up authentication callback using the token
         let mut callbacks = git2::RemoteCallbacks::new();
         callbacks.credentials(|_url, username_from_url, _allowed_types| {
-            git2::Cred::userpass_plaintext(username_from_url.unwrap_or("git"), github_token)
+            git2::Cred::userpass_plaintext(username_from_url.unwrap_or("git"), token)
         });
 
         // Configure push options
@@ -1390,7 +2109,7 @@ impl GitService {
             }
             _ => e.into(),
         })?;
-        self.fetch_from_remote(&repo, github_token, &remote)?;
+        self.fetch_from_remote(&repo, token, &remote)?;
         let mut branch = Self::find_branch(&repo, branch_name)?;
         if !branch.get().is_remote() {
             branch.set_upstream(Some(&format!("{remote_name}/{branch_name}")))?;
@@ -1401,12 +2120,17 @@ impl GitService {
 
     fn convert_to_https_url(&self, url: &str) -> String {
         // Convert SSH URL to HTTPS URL if necessary
-        if url.starts_with("git@github.com:") {
-            // Convert git@github.com:owner/repo.git to https://github.com/owner/repo.git
-            url.replace("git@github.com:", "https://github.com/")
-        } else if url.starts_with("ssh://git@github.com/") {
-            // Convert ssh://git@github.com/owner/repo.git to https://github.com/owner/repo.git
-            url.replace("ssh://git@github.com/", "https://github.com/")
+        if let Some(rest) = url.strip_prefix("ssh://git@") {
+            // Convert ssh://git@host[:port]/owner/repo.git to https://host/owner/repo.git
+            let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+            let host = host.split(':').next().unwrap_or(host);
+            format!("https://{host}/{path}")
+        } else if let Some((host, path)) = url
+            .strip_prefix("git@")
+            .and_then(|rest| rest.split_once(':'))
+        {
+            // Convert git@host:owner/repo.git to https://host/owner/repo.git
+            format!("https://{host}/{path}")
         } else {
             url.to_string()
         }
@@ -1458,45 +2182,152 @@ impl GitService {
         Ok(())
     }
 
-    /// Clone a repository to the specified directory
-    #[cfg(feature = "cloud")]
+    /// Whether `input` names a remote repository (`https://`, `ssh://`, `file://`, ...
+    /// or scp-like `git@host:owner/repo`) rather than a local path
+    pub fn is_remote_url(input: &str) -> bool {
+        let input = input.trim();
+        if ["https://", "http://", "ssh://", "git://", "file://"]
+            .iter()
+            .any(|scheme| input.starts_with(scheme))
+        {
+            return true;
+        }
+        // scp-like syntax: user@host:path, with no slash before the colon
+        match (input.find('@'), input.find(':')) {
+            (Some(at), Some(colon)) => at < colon && !input[..colon].contains('/'),
+            _ => false,
+        }
+    }
+
+    /// Directory name for a clone of `url`: its last path segment without `.git`
+    pub fn repo_name_from_url(url: &str) -> Option<String> {
+        let name = url
+            .trim()
+            .trim_end_matches('/')
+            .rsplit(['/', ':'])
+            .next()?
+            .trim_end_matches(".git");
+        (!name.is_empty() && name != "." && name != "..").then(|| name.to_string())
+    }
+
+    /// Host and repository path of a remote URL, e.g. `github.com` and `owner/repo` for
+    /// both `https://github.com/owner/repo.git` and `git@github.com:owner/repo.git`.
+    /// Local `file://` URLs have no host and give None.
+    pub fn parse_remote_url(url: &str) -> Option<(String, String)> {
+        let url = url.trim();
+        let (host, path) = match reqwest::Url::parse(url) {
+            Ok(parsed) => (parsed.host_str()?.to_lowercase(), parsed.path().to_string()),
+            // scp-like syntax: user@host:path
+            Err(_) => {
+                let (user_host, path) = url.split_once(':')?;
+                let (_, host) = user_host.rsplit_once('@')?;
+                (host.to_lowercase(), path.to_string())
+            }
+        };
+        let path = path.trim_matches('/').trim_end_matches(".git");
+        (!host.is_empty() && !path.is_empty()).then(|| (host, path.to_string()))
+    }
+
+    /// Relative directory for a clone of `url`: its owner path and name (`owner/repo`),
+    /// so same-named repositories of different owners don't collide. Local URLs only
+    /// have the name.
+    pub fn clone_dir_from_url(url: &str) -> Option<PathBuf> {
+        let Some((_, path)) = Self::parse_remote_url(url) else {
+            return Self::repo_name_from_url(url).map(PathBuf::from);
+        };
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if segments.iter().any(|s| *s == "." || *s == "..") {
+            return None;
+        }
+        Some(segments.into_iter().collect())
+    }
+
+    /// Clone `clone_url` into `target_path`, calling `progress` as objects arrive. An
+    /// existing clone of the same URL is reused; any other non-empty directory is refused.
     pub fn clone_repository(
         clone_url: &str,
         target_path: &Path,
-        token: Option<&str>,
+        options: &CloneOptions,
+        mut progress: impl FnMut(CloneProgress),
     ) -> Result<Repository, GitServiceError> {
-        if let Some(parent) = target_path.parent() {
+        if target_path.exists() {
+            if let Ok(repo) = Repository::open(target_path) {
+                let same_remote = repo
+                    .find_remote("origin")
+                    .ok()
+                    .and_then(|remote| remote.url().map(|url| url == clone_url))
+                    .unwrap_or(false);
+                if same_remote {
+                    tracing::info!(
+                        "{} is already a clone of {}, reusing it",
+                        target_path.display(),
+                        clone_url
+                    );
+                    return Ok(repo);
+                }
+            }
+            if std::fs::read_dir(target_path)?.next().is_some() {
+                return Err(GitServiceError::InvalidPath(format!(
+                    "{} already exists and is not a clone of {}",
+                    target_path.display(),
+                    clone_url
+                )));
+            }
+        } else if let Some(parent) = target_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
-        // Set up callbacks for authentication if token is provided
         let mut callbacks = RemoteCallbacks::new();
-        if let Some(token) = token {
-            callbacks.credentials(|_url, username_from_url, _allowed_types| {
-                Cred::userpass_plaintext(username_from_url.unwrap_or("git"), token)
-            });
-        } else {
-            // Fallback to SSH agent and key file authentication
-            callbacks.credentials(|_url, username_from_url, _| {
-                // Try SSH agent first
-                if let Some(username) = username_from_url {
-                    if let Ok(cred) = Cred::ssh_key_from_agent(username) {
-                        return Ok(cred);
-                    }
+        // libgit2 asks again after every rejected credential, so give up eventually
+        let mut attempts = 0;
+        callbacks.credentials(|_url, username_from_url, allowed_types| {
+            attempts += 1;
+            if attempts > 4 {
+                return Err(GitError::from_str("Authentication failed"));
+            }
+            let username = username_from_url.unwrap_or("git");
+            if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT)
+                && let Some(token) = &options.token
+            {
+                return Cred::userpass_plaintext(username, token);
+            }
+            if allowed_types.contains(CredentialType::SSH_KEY) {
+                if attempts == 1
+                    && let Ok(cred) = Cred::ssh_key_from_agent(username)
+                {
+                    return Ok(cred);
                 }
-                // Fallback to key file (~/.ssh/id_rsa)
-                let home = dirs::home_dir()
-                    .ok_or_else(|| git2::Error::from_str("Could not find home directory"))?;
-                let key_path = home.join(".ssh").join("id_rsa");
-                Cred::ssh_key(username_from_url.unwrap_or("git"), None, &key_path, None)
+                let key_path = match &options.ssh_key_path {
+                    Some(path) => path.clone(),
+                    None => {
+                        let ssh_dir = dirs::home_dir()
+                            .ok_or_else(|| GitError::from_str("Could not find home directory"))?
+                            .join(".ssh");
+                        let ed25519 = ssh_dir.join("id_ed25519");
+                        if ed25519.exists() {
+                            ed25519
+                        } else {
+                            ssh_dir.join("id_rsa")
+                        }
+                    }
+                };
+                return Cred::ssh_key(username, None, &key_path, None);
+            }
+            Cred::default()
+        });
+        callbacks.transfer_progress(|stats| {
+            progress(CloneProgress {
+                received_objects: stats.received_objects(),
+                total_objects: stats.total_objects(),
+                indexed_deltas: stats.indexed_deltas(),
+                total_deltas: stats.total_deltas(),
+                received_bytes: stats.received_bytes(),
             });
-        }
+            true
+        });
 
-        // Set up fetch options with our callbacks
         let mut fetch_opts = FetchOptions::new();
         fetch_opts.remote_callbacks(callbacks);
-
-        // Create a repository builder with fetch options
         let mut builder = git2::build::RepoBuilder::new();
         builder.fetch_options(fetch_opts);
 
@@ -17,13 +17,17 @@
 //! network operations when useful.
 use std::{
     ffi::{OsStr, OsString},
+    io::Read,
     path::Path,
-    process::Command,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
 };
 
 use thiserror::Error;
 use utils::shell::resolve_executable_path;
 
+use crate::services::git::CommitSigning;
+
 #[derive(Debug, Error)]
 pub enum GitCliError {
     #[error("git executable not found or not runnable")]
@@ -32,8 +36,17 @@ pub enum GitCliError {
     CommandFailed(String),
     #[error("rebase in progress in this worktree")]
     RebaseInProgress,
+    #[error("git command timed out after {0:?}")]
+    TimedOut(Duration),
+    /// Staging the worktree into a private index failed, which happens when it changes
+    /// under git: a file vanishing mid-scan or a nested repository without commits yet
+    #[error("staging the worktree failed: {0}")]
+    StagingFailed(String),
 }
 
+/// How long a fetch may take before it is abandoned, e.g. when the remote is unreachable
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
 #[derive(Clone, Default)]
 pub struct GitCli;
 
@@ -123,6 +136,25 @@ impl GitCli {
         Ok(!out.is_empty())
     }
 
+    /// Like `has_changes`, optionally limited to a pathspec and to tracked files.
+    pub fn has_changes_in(
+        &self,
+        worktree_path: &Path,
+        pathspec: Option<&str>,
+        include_untracked: bool,
+    ) -> Result<bool, GitCliError> {
+        let mut args: Vec<OsString> = vec!["status".into(), "--porcelain".into()];
+        if !include_untracked {
+            args.push("--untracked-files=no".into());
+        }
+        if let Some(pathspec) = pathspec {
+            args.push("--".into());
+            args.push(OsString::from(pathspec));
+        }
+        let out = self.git(worktree_path, args)?;
+        Ok(!out.is_empty())
+    }
+
     /// Diff status vs a base branch using a temporary index (always includes untracked).
     /// Path filter limits the reported paths.
     pub fn diff_status(
@@ -144,7 +176,12 @@ impl GitCli {
         let _ = self.git_with_env(worktree_path, ["read-tree", "HEAD"], &envs)?;
 
         // Stage all in temp index
-        let _ = self.git_with_env(worktree_path, ["add", "-A"], &envs)?;
+        let _ = self
+            .git_with_env(worktree_path, ["add", "-A"], &envs)
+            .map_err(|e| match e {
+                GitCliError::CommandFailed(msg) => GitCliError::StagingFailed(msg),
+                e => e,
+            })?;
 
         // git diff --cached
         let mut args: Vec<OsString> = vec![
@@ -235,12 +272,74 @@ impl GitCli {
         Ok(())
     }
 
-    /// Commit staged changes with the given message.
-    pub fn commit(&self, worktree_path: &Path, message: &str) -> Result<(), GitCliError> {
-        self.git(worktree_path, ["commit", "-m", message])?;
+    /// Stage all changes, optionally limited to a pathspec.
+    pub fn add_all_in(
+        &self,
+        worktree_path: &Path,
+        pathspec: Option<&str>,
+    ) -> Result<(), GitCliError> {
+        match pathspec {
+            Some(pathspec) => self.git(worktree_path, ["add", "-A", "--", pathspec])?,
+            None => self.git(worktree_path, ["add", "-A"])?,
+        };
+        Ok(())
+    }
+
+    /// Commit staged changes with the given message, signing it when requested.
+    pub fn commit(
+        &self,
+        worktree_path: &Path,
+        message: &str,
+        signing: Option<&CommitSigning>,
+    ) -> Result<(), GitCliError> {
+        self.git(
+            worktree_path,
+            Self::with_signing(signing, "commit", ["-m", message]),
+        )?;
         Ok(())
     }
 
+    /// Create a signed commit object for `tree` with a single `parent` without
+    /// touching any ref. Returns the new commit sha.
+    pub fn commit_tree(
+        &self,
+        repo_path: &Path,
+        tree: &str,
+        parent: &str,
+        message: &str,
+        signing: &CommitSigning,
+    ) -> Result<String, GitCliError> {
+        let out = self.git(
+            repo_path,
+            Self::with_signing(
+                Some(signing),
+                "commit-tree",
+                [tree, "-p", parent, "-m", message],
+            ),
+        )?;
+        Ok(out.trim().to_string())
+    }
+
+    /// Build `[-c gpg.format=<format>] <subcommand> [-S[<key>]] <args...>` so the
+    /// subcommand signs the commits it writes when `signing` is set.
+    fn with_signing<'a>(
+        signing: Option<&CommitSigning>,
+        subcommand: &str,
+        args: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<OsString> {
+        let mut out: Vec<OsString> = Vec::new();
+        if let Some(signing) = signing {
+            out.push("-c".into());
+            out.push(format!("gpg.format={}", signing.format.as_git_format()).into());
+        }
+        out.push(subcommand.into());
+        if let Some(signing) = signing {
+            out.push(format!("-S{}", signing.key.as_deref().unwrap_or_default()).into());
+        }
+        out.extend(args.into_iter().map(OsString::from));
+        out
+    }
+
     // Parse `git diff --name-status` output into structured entries.
     // Handles rename/copy scores like `R100` by matching the first letter.
     fn parse_name_status(output: &str) -> Vec<StatusDiffEntry> {
@@ -293,13 +392,17 @@ impl GitCli {
         worktree_path: &Path,
         new_base: &str,
         old_base: &str,
+        signing: Option<&CommitSigning>,
     ) -> Result<(), GitCliError> {
         // If a rebase is in progress, refuse to proceed. The caller can
         // choose to abort or continue; we avoid destructive actions here.
         if self.is_rebase_in_progress(worktree_path).unwrap_or(false) {
             return Err(GitCliError::RebaseInProgress);
         }
-        self.git(worktree_path, ["rebase", "--onto", new_base, old_base])?;
+        self.git(
+            worktree_path,
+            Self::with_signing(signing, "rebase", ["--onto", new_base, old_base]),
+        )?;
         Ok(())
     }
 
@@ -338,17 +441,53 @@ impl GitCli {
     }
 
     /// Checkout base branch, squash-merge from_branch, and commit with message. Returns new HEAD sha.
+    /// If the commit fails (e.g. it can't be signed) the squashed changes are reset again.
     pub fn merge_squash_commit(
         &self,
         repo_path: &Path,
         base_branch: &str,
         from_branch: &str,
         message: &str,
+        signing: Option<&CommitSigning>,
     ) -> Result<String, GitCliError> {
         self.git(repo_path, ["checkout", base_branch]).map(|_| ())?;
         self.git(repo_path, ["merge", "--squash", "--no-commit", from_branch])
             .map(|_| ())?;
-        self.git(repo_path, ["commit", "-m", message]).map(|_| ())?;
+        if let Err(e) = self.commit(repo_path, message, signing) {
+            let _ = self.git(repo_path, ["reset", "--merge"]);
+            return Err(e);
+        }
+        let sha = self
+            .git(repo_path, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        Ok(sha)
+    }
+
+    /// Revert `commit` on the checked-out branch and commit with message. Returns new HEAD sha.
+    /// `mainline` picks the parent to revert against when `commit` is a merge commit.
+    /// If the revert or commit fails the working tree is reset again.
+    pub fn revert_commit(
+        &self,
+        repo_path: &Path,
+        commit: &str,
+        mainline: Option<u32>,
+        message: &str,
+        signing: Option<&CommitSigning>,
+    ) -> Result<String, GitCliError> {
+        let mainline = mainline.map(|m| m.to_string());
+        let mut args = vec!["revert", "--no-commit"];
+        if let Some(mainline) = &mainline {
+            args.extend(["-m", mainline.as_str()]);
+        }
+        args.push(commit);
+        let result = self
+            .git(repo_path, args)
+            .and_then(|_| self.commit(repo_path, message, signing));
+        if let Err(e) = result {
+            let _ = self.git(repo_path, ["reset", "--merge"]);
+            return Err(e);
+        }
         let sha = self
             .git(repo_path, ["rev-parse", "HEAD"])?
             .trim()
@@ -356,6 +495,47 @@ impl GitCli {
         Ok(sha)
     }
 
+    /// Fetch all branches of `remote` with the user's own git credentials. Never prompts
+    /// (for HTTPS or SSH), so a remote that needs interactive auth fails instead of
+    /// hanging, and gives up after `FETCH_TIMEOUT`.
+    pub fn fetch(&self, repo_path: &Path, remote: &str) -> Result<(), GitCliError> {
+        self.fetch_within(repo_path, remote, FETCH_TIMEOUT)
+    }
+
+    pub fn fetch_within(
+        &self,
+        repo_path: &Path,
+        remote: &str,
+        timeout: Duration,
+    ) -> Result<(), GitCliError> {
+        // Keep any SSH command the user configured, only forbidding its prompts. The env
+        // var outranks `core.sshCommand`, so setting it must start from whichever applies.
+        let ssh_command = std::env::var("GIT_SSH_COMMAND")
+            .ok()
+            .filter(|command| !command.trim().is_empty())
+            .or_else(|| {
+                self.git(repo_path, ["config", "--get", "core.sshCommand"])
+                    .ok()
+                    .map(|command| command.trim().to_string())
+                    .filter(|command| !command.is_empty())
+            })
+            .unwrap_or_else(|| "ssh".to_string());
+        let envs = vec![
+            (OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0")),
+            (
+                OsString::from("GIT_SSH_COMMAND"),
+                OsString::from(format!("{ssh_command} -o BatchMode=yes")),
+            ),
+        ];
+        self.git_with_timeout(repo_path, ["fetch", "--prune", remote], &envs, timeout)
+    }
+
+    /// Fast-forward the checked-out branch to `target`, refusing anything but a fast-forward.
+    pub fn merge_ff_only(&self, repo_path: &Path, target: &str) -> Result<(), GitCliError> {
+        self.git(repo_path, ["merge", "--ff-only", target])
+            .map(|_| ())
+    }
+
     /// Update a ref to a specific sha in the repo.
     pub fn update_ref(
         &self,
@@ -449,6 +629,65 @@ impl GitCli {
         }
         Ok(String::from_utf8_lossy(&out.stdout).to_string())
     }
+
+    /// Like `git_with_env` for commands whose output isn't needed, but kills git and
+    /// fails with `TimedOut` once it has run for `timeout`.
+    fn git_with_timeout<I, S>(
+        &self,
+        repo_path: &Path,
+        args: I,
+        envs: &[(OsString, OsString)],
+        timeout: Duration,
+    ) -> Result<(), GitCliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.ensure_available()?;
+        let git = resolve_executable_path("git").ok_or(GitCliError::NotAvailable)?;
+        let mut cmd = Command::new(&git);
+        cmd.arg("-C").arg(repo_path);
+        for (k, v) in envs {
+            cmd.env(k, v);
+        }
+        for a in args {
+            cmd.arg(a);
+        }
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| GitCliError::CommandFailed(e.to_string()))?;
+        // Drained on the side so a chatty command can't block on a full pipe
+        let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+        let stderr = std::thread::spawn(move || {
+            let mut stderr = String::new();
+            let _ = stderr_pipe.read_to_string(&mut stderr);
+            stderr
+        });
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child
+                .try_wait()
+                .map_err(|e| GitCliError::CommandFailed(e.to_string()))?
+            {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(GitCliError::TimedOut(timeout));
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        };
+        if !status.success() {
+            let stderr = stderr.join().unwrap_or_default();
+            return Err(GitCliError::CommandFailed(stderr.trim().to_string()));
+        }
+        Ok(())
+    }
 }
 /// Parsed entry from `git status --porcelain`
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -36,6 +36,8 @@ pub enum GitHubServiceError {
     InsufficientPermissions,
     #[error("GitHub repository not found or no access")]
     RepoNotFoundOrNoAccess,
+    #[error("A pull request already exists for this branch")]
+    PullRequestExists,
     #[ts(skip)]
     #[serde(skip)]
     #[error(transparent)]
@@ -89,6 +91,7 @@ impl GitHubServiceError {
             GitHubServiceError::TokenInvalid
                 | GitHubServiceError::InsufficientPermissions
                 | GitHubServiceError::RepoNotFoundOrNoAccess
+                | GitHubServiceError::PullRequestExists
         )
     }
 }
@@ -170,6 +173,7 @@ impl GitHubService {
             .when(|e| {
                 !matches!(e, GitHubServiceError::TokenInvalid)
                     && !matches!(e, GitHubServiceError::Branch(_))
+                    && !matches!(e, GitHubServiceError::PullRequestExists)
             })
             .notify(|err: &GitHubServiceError, dur: Duration| {
                 tracing::warn!(
@@ -248,6 +252,14 @@ impl GitHubService {
                             .contains("token expired")
                     {
                         GitHubServiceError::TokenInvalid
+                    } else if source.status_code.as_u16() == 422
+                        && source
+                            .errors
+                            .iter()
+                            .flatten()
+                            .any(|err| err.to_string().contains("already exists"))
+                    {
+                        GitHubServiceError::PullRequestExists
                     } else {
                         GitHubServiceError::PullRequest(format!(
                             "GitHub API error: {} (status: {})",
@@ -1,8 +1,11 @@
 use std::{
+    collections::HashSet,
     fs,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
+use chrono::{DateTime, Duration, Utc};
 use db::models::image::{CreateImage, Image};
 use regex::{Captures, Regex};
 use sha2::{Digest, Sha256};
@@ -30,6 +33,10 @@ pub enum ImageError {
     ResponseBuildError(String),
 }
 
+/// Unlinked images younger than this are kept, as the task they were uploaded
+/// for may not have been saved yet
+const ORPHAN_GRACE_PERIOD: Duration = Duration::hours(1);
+
 #[derive(Clone)]
 pub struct ImageService {
     cache_dir: PathBuf,
@@ -38,29 +45,33 @@ pub struct ImageService {
 }
 
 impl ImageService {
-    pub fn new(pool: SqlitePool) -> Result<Self, ImageError> {
+    pub fn new(pool: SqlitePool, max_size_bytes: u64) -> Result<Self, ImageError> {
         let cache_dir = utils::cache_dir().join("images");
         fs::create_dir_all(&cache_dir)?;
         Ok(Self {
             cache_dir,
             pool,
-            max_size_bytes: 20 * 1024 * 1024, // 20MB default
+            max_size_bytes,
         })
     }
 
-    pub async fn store_image(
-        &self,
+    pub fn max_size_bytes(&self) -> u64 {
+        self.max_size_bytes
+    }
+
+    /// Check an upload against the size limit and the image type allowlist.
+    /// The extension must be allowed and the file contents must match it.
+    /// Returns the (extension, mime type) to store the image under.
+    pub fn validate_image<'a>(
         data: &[u8],
-        original_filename: &str,
-    ) -> Result<Image, ImageError> {
+        original_filename: &'a str,
+        max_size_bytes: u64,
+    ) -> Result<(&'a str, &'static str), ImageError> {
         let file_size = data.len() as u64;
-
-        if file_size > self.max_size_bytes {
-            return Err(ImageError::TooLarge(file_size, self.max_size_bytes));
+        if file_size > max_size_bytes {
+            return Err(ImageError::TooLarge(file_size, max_size_bytes));
         }
 
-        let hash = format!("{:x}", Sha256::digest(data));
-
         // Extract extension from original filename
         let extension = Path::new(original_filename)
             .extension()
@@ -68,19 +79,32 @@ impl ImageService {
             .unwrap_or("png");
 
         let mime_type = match extension.to_lowercase().as_str() {
-            "png" => Some("image/png".to_string()),
-            "jpg" | "jpeg" => Some("image/jpeg".to_string()),
-            "gif" => Some("image/gif".to_string()),
-            "webp" => Some("image/webp".to_string()),
-            "bmp" => Some("image/bmp".to_string()),
-            "svg" => Some("image/svg+xml".to_string()),
-            _ => None,
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            _ => return Err(ImageError::InvalidFormat),
         };
 
-        if mime_type.is_none() {
+        if sniff_mime_type(data) != Some(mime_type) {
             return Err(ImageError::InvalidFormat);
         }
 
+        Ok((extension, mime_type))
+    }
+
+    pub async fn store_image(
+        &self,
+        data: &[u8],
+        original_filename: &str,
+    ) -> Result<Image, ImageError> {
+        let (extension, mime_type) =
+            Self::validate_image(data, original_filename, self.max_size_bytes)?;
+        let file_size = data.len() as u64;
+        let mime_type = Some(mime_type.to_string());
+
+        let hash = format!("{:x}", Sha256::digest(data));
+
         let existing_image = Image::find_by_hash(&self.pool, &hash).await?;
 
         if let Some(existing) = existing_image {
@@ -106,24 +130,27 @@ impl ImageService {
         Ok(image)
     }
 
-    pub async fn delete_orphaned_images(&self) -> Result<(), ImageError> {
-        let orphaned_images = Image::find_orphaned_images(&self.pool).await?;
-        if orphaned_images.is_empty() {
-            tracing::debug!("No orphaned images found during cleanup");
-            return Ok(());
-        }
+    /// Delete cached images that no task references any more, along with any
+    /// files in the cache directory the database doesn't know about.
+    /// Returns the number of bytes reclaimed.
+    pub async fn cleanup_orphaned(&self) -> Result<u64, ImageError> {
+        let cutoff = Utc::now() - ORPHAN_GRACE_PERIOD;
+        self.cleanup_orphaned_before(cutoff).await
+    }
 
-        tracing::debug!(
-            "Found {} orphaned images to clean up",
-            orphaned_images.len()
-        );
+    async fn cleanup_orphaned_before(&self, cutoff: DateTime<Utc>) -> Result<u64, ImageError> {
+        let mut reclaimed_bytes = 0u64;
         let mut deleted_count = 0;
         let mut failed_count = 0;
 
-        for image in orphaned_images {
+        for image in Image::find_orphaned_images(&self.pool, cutoff).await? {
+            let size = fs::metadata(self.get_absolute_path(&image))
+                .map(|m| m.len())
+                .unwrap_or(0);
             match self.delete_image(image.id).await {
                 Ok(_) => {
                     deleted_count += 1;
+                    reclaimed_bytes += size;
                     tracing::debug!("Deleted orphaned image: {}", image.id);
                 }
                 Err(e) => {
@@ -133,13 +160,49 @@ impl ImageService {
             }
         }
 
-        tracing::info!(
-            "Image cleanup completed: {} deleted, {} failed",
-            deleted_count,
-            failed_count
-        );
+        // Files left behind without a database row (e.g. an upload interrupted
+        // between writing the file and inserting the record)
+        let known: HashSet<String> = Image::find_all_file_paths(&self.pool)
+            .await?
+            .into_iter()
+            .collect();
+        let cutoff_time: SystemTime = cutoff.into();
+        for entry in fs::read_dir(&self.cache_dir)?.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let is_stale = metadata
+                .modified()
+                .is_ok_and(|modified| modified < cutoff_time);
+            if !metadata.is_file() || known.contains(&file_name) || !is_stale {
+                continue;
+            }
+            match fs::remove_file(entry.path()) {
+                Ok(_) => {
+                    deleted_count += 1;
+                    reclaimed_bytes += metadata.len();
+                    tracing::debug!("Deleted untracked cached image file: {}", file_name);
+                }
+                Err(e) => {
+                    failed_count += 1;
+                    tracing::error!("Failed to delete cached image file {}: {}", file_name, e);
+                }
+            }
+        }
 
-        Ok(())
+        if deleted_count > 0 || failed_count > 0 {
+            tracing::info!(
+                "Image cleanup completed: {} deleted, {} failed, {} bytes reclaimed",
+                deleted_count,
+                failed_count,
+                reclaimed_bytes
+            );
+        } else {
+            tracing::debug!("No orphaned images found during cleanup");
+        }
+
+        Ok(reclaimed_bytes)
     }
 
     pub fn get_absolute_path(&self, image: &Image) -> PathBuf {
@@ -234,3 +297,114 @@ impl ImageService {
         .into_owned()
     }
 }
+
+/// Identify an image by its magic bytes
+fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::{
+        image::TaskImage,
+        task::Task,
+        test_support::{create_project, create_task, test_pool},
+    };
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn accepts_allowed_image() {
+        let (extension, mime_type) =
+            ImageService::validate_image(PNG_HEADER, "screenshot.PNG", 1024).unwrap();
+        assert_eq!(extension, "PNG");
+        assert_eq!(mime_type, "image/png");
+    }
+
+    #[test]
+    fn rejects_oversized_image() {
+        let mut data = PNG_HEADER.to_vec();
+        data.resize(2048, 0);
+        assert!(matches!(
+            ImageService::validate_image(&data, "big.png", 1024),
+            Err(ImageError::TooLarge(2048, 1024))
+        ));
+    }
+
+    #[test]
+    fn rejects_disallowed_type() {
+        let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>";
+        assert!(matches!(
+            ImageService::validate_image(svg, "diagram.svg", 1024),
+            Err(ImageError::InvalidFormat)
+        ));
+        // An allowed extension doesn't help when the contents aren't an image
+        assert!(matches!(
+            ImageService::validate_image(b"#!/bin/sh\nrm -rf /", "evil.png", 1024),
+            Err(ImageError::InvalidFormat)
+        ));
+    }
+
+    async fn test_service() -> (ImageService, TempDir) {
+        let pool = test_pool().await;
+        let cache_dir = TempDir::new().unwrap();
+        let service = ImageService {
+            cache_dir: cache_dir.path().to_path_buf(),
+            pool,
+            max_size_bytes: 1024 * 1024,
+        };
+        (service, cache_dir)
+    }
+
+    #[tokio::test]
+    async fn cleanup_removes_images_of_deleted_tasks_only() {
+        let (service, _cache_dir) = test_service().await;
+        let pool = service.pool.clone();
+
+        let project = create_project(&pool).await;
+        let live_task = create_task(&pool, project.id, "live").await;
+        let deleted_task = create_task(&pool, project.id, "deleted").await;
+
+        let mut kept_data = PNG_HEADER.to_vec();
+        kept_data.push(1);
+        let mut removed_data = PNG_HEADER.to_vec();
+        removed_data.push(2);
+        let kept = service.store_image(&kept_data, "kept.png").await.unwrap();
+        let removed = service
+            .store_image(&removed_data, "removed.png")
+            .await
+            .unwrap();
+        TaskImage::associate_many(&pool, live_task.id, &[kept.id])
+            .await
+            .unwrap();
+        TaskImage::associate_many(&pool, deleted_task.id, &[removed.id])
+            .await
+            .unwrap();
+
+        Task::delete(&pool, deleted_task.id).await.unwrap();
+
+        let reclaimed = service
+            .cleanup_orphaned_before(Utc::now() + Duration::seconds(1))
+            .await
+            .unwrap();
+
+        assert_eq!(reclaimed, removed_data.len() as u64);
+        assert!(service.get_image(kept.id).await.unwrap().is_some());
+        assert!(service.get_absolute_path(&kept).exists());
+        assert!(service.get_image(removed.id).await.unwrap().is_none());
+        assert!(!service.get_absolute_path(&removed).exists());
+    }
+}
@@ -3,7 +3,9 @@ pub mod auth;
 pub mod browser_chat_service;
 pub mod config;
 pub mod container;
+pub mod dev_server;
 pub mod events;
+pub mod execution_limiter;
 pub mod file_ranker;
 pub mod file_search_cache;
 pub mod filesystem;
@@ -11,8 +13,10 @@ pub mod filesystem_watcher;
 pub mod git;
 pub mod git_cli;
 pub mod github_service;
+pub mod gitlab_service;
 pub mod image;
 pub mod notification;
 pub mod pr_monitor;
+pub mod rate_limiter;
 pub mod sentry;
 pub mod worktree_manager;
@@ -1,9 +1,20 @@
-use std::sync::OnceLock;
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
-use db::models::execution_process::{ExecutionContext, ExecutionProcessStatus};
+use db::models::{
+    execution_process::{ExecutionContext, ExecutionProcessStatus},
+    task::{Task, TaskEvent},
+    task_attempt::TaskAttempt,
+};
+use tokio::sync::{
+    RwLock,
+    broadcast::{self, error::RecvError},
+};
 use utils;
 
-use crate::services::config::SoundFile;
+use crate::services::config::{Config, SoundFile};
 
 /// Service for handling cross-platform notifications including sound alerts and push notifications
 #[derive(Debug, Clone)]
@@ -13,6 +24,9 @@ use crate::services::config::NotificationConfig;
 /// Cache for WSL root path from PowerShell
 static WSL_ROOT_PATH_CACHE: OnceLock<Option<String>> = OnceLock::new();
 
+/// How long a webhook delivery may take before it is abandoned
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
 impl NotificationService {
     pub async fn notify_execution_halted(mut config: NotificationConfig, ctx: &ExecutionContext) {
         // If the process was intentionally killed by user, suppress sound
@@ -45,6 +59,20 @@ impl NotificationService {
         Self::notify(config, &title, &message).await;
     }
 
+    /// Notify that an attempt's branch was merged into its base branch
+    pub async fn notify_attempt_merged(
+        config: NotificationConfig,
+        task: &Task,
+        task_attempt: &TaskAttempt,
+    ) {
+        let title = format!("Task Merged: {}", task.title);
+        let message = format!(
+            "🔀 '{}' merged into {}\nBranch: {:?}\nExecutor: {}",
+            task.title, task_attempt.base_branch, task_attempt.branch, task_attempt.executor
+        );
+        Self::notify(config, &title, &message).await;
+    }
+
     /// Send both sound and push notifications if enabled
     pub async fn notify(config: NotificationConfig, title: &str, message: &str) {
         if config.sound_enabled {
@@ -56,6 +84,60 @@ impl NotificationService {
         }
     }
 
+    /// POST a task event to the configured webhook. Failures are only logged.
+    pub async fn notify_webhook(client: &reqwest::Client, url: &str, event: &TaskEvent) {
+        let result = client
+            .post(url)
+            .json(event)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to deliver task event for task {} to webhook: {}",
+                event.task().id,
+                e
+            );
+        }
+    }
+
+    /// Forward task events to `webhook_url` for as long as the app runs. The URL is
+    /// re-read for every event, so config changes apply without a restart.
+    pub fn spawn_task_event_webhooks(config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap();
+        Self::forward_task_events(Task::subscribe_events(), config, client)
+    }
+
+    /// Deliver each event in its own task, so a slow endpoint doesn't hold up later events
+    fn forward_task_events(
+        mut events: broadcast::Receiver<TaskEvent>,
+        config: Arc<RwLock<Config>>,
+        client: reqwest::Client,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Dropped {} task events for the webhook", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let webhook_url = config.read().await.webhook_url.clone();
+                if let Some(url) = webhook_url {
+                    let client = client.clone();
+                    tokio::spawn(async move {
+                        Self::notify_webhook(&client, &url, &event).await;
+                    });
+                }
+            }
+        })
+    }
+
     /// Play a system sound notification across platforms
     async fn play_sound_notification(sound_file: &SoundFile) {
         let file_path = match sound_file.get_path().await {
@@ -256,3 +338,115 @@ impl NotificationService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::models::task::TaskStatus;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        sync::mpsc,
+    };
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn status_changed(title: &str) -> TaskEvent {
+        TaskEvent::StatusChanged {
+            task: Task {
+                id: Uuid::new_v4(),
+                project_id: Uuid::new_v4(),
+                title: title.to_string(),
+                description: None,
+                status: TaskStatus::InProgress,
+                parent_task_attempt: None,
+                preferred_attempt_id: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            old_status: TaskStatus::Todo,
+            new_status: TaskStatus::InProgress,
+        }
+    }
+
+    /// Serve webhook requests, reporting each request's body. Requests whose body
+    /// mentions "hang" are never answered.
+    async fn webhook_endpoint() -> (String, mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (bodies, received) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let bodies = bodies.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0; 4096];
+                    let body = loop {
+                        let n = socket.read(&mut buf).await.unwrap();
+                        if n == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request).to_string();
+                        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                            let length = head
+                                .lines()
+                                .find_map(|line| {
+                                    line.to_ascii_lowercase()
+                                        .strip_prefix("content-length:")
+                                        .map(|value| value.trim().parse::<usize>().unwrap())
+                                })
+                                .unwrap_or(0);
+                            if body.len() >= length {
+                                break body.to_string();
+                            }
+                        }
+                    };
+                    let hang = body.contains("hang");
+                    bodies.send(body).unwrap();
+                    if hang {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                    }
+                    socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                        .await
+                        .unwrap();
+                });
+            }
+        });
+        (url, received)
+    }
+
+    #[tokio::test]
+    async fn slow_webhook_does_not_hold_up_later_events() {
+        let (url, mut received) = webhook_endpoint().await;
+        let mut config = Config::default();
+        config.webhook_url = Some(url);
+        let (sender, events) = broadcast::channel(16);
+        let forwarder = NotificationService::forward_task_events(
+            events,
+            Arc::new(RwLock::new(config)),
+            reqwest::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .unwrap(),
+        );
+
+        sender.send(status_changed("hang")).unwrap();
+        sender.send(status_changed("quick")).unwrap();
+
+        // Both deliveries reach the endpoint well before the first one could time out
+        let mut bodies = Vec::new();
+        for _ in 0..2 {
+            let body = tokio::time::timeout(Duration::from_secs(2), received.recv())
+                .await
+                .expect("webhook delivery stalled behind a slow endpoint")
+                .unwrap();
+            bodies.push(body);
+        }
+        assert!(bodies.iter().any(|body| body.contains("quick")));
+        forwarder.abort();
+    }
+}
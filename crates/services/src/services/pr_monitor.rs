@@ -15,6 +15,7 @@ use tracing::{debug, error, info};
 
 use crate::services::{
     config::Config,
+    container::ContainerService,
     github_service::{GitHubRepoInfo, GitHubService, GitHubServiceError},
 };
 
@@ -31,17 +32,24 @@ enum PrMonitorError {
 }
 
 /// Service to monitor GitHub PRs and update task status when they are merged
-pub struct PrMonitorService {
+pub struct PrMonitorService<C> {
     db: DBService,
     config: Arc<RwLock<Config>>,
+    /// Prunes merged worktrees, skipping attempts it still runs
+    container: C,
     poll_interval: Duration,
 }
 
-impl PrMonitorService {
-    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+impl<C: ContainerService + Send + Sync + 'static> PrMonitorService<C> {
+    pub async fn spawn(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+        container: C,
+    ) -> tokio::task::JoinHandle<()> {
         let service = Self {
             db,
             config,
+            container,
             poll_interval: Duration::from_secs(60), // Check every minute
         };
         tokio::spawn(async move {
@@ -77,6 +85,11 @@ impl PrMonitorService {
         info!("Checking {} open PRs", open_prs.len());
 
         for pr_merge in open_prs {
+            // Merge requests opened on GitLab aren't tracked here
+            if GitHubRepoInfo::from_pr_url(&pr_merge.pr_info.url).is_err() {
+                debug!("Skipping non-GitHub PR {}", pr_merge.pr_info.url);
+                continue;
+            }
             if let Err(e) = self.check_pr_status(&pr_merge).await {
                 error!(
                     "Error checking PR #{} for attempt {}: {}",
@@ -126,6 +139,12 @@ impl PrMonitorService {
                     pr_merge.pr_info.number, task_attempt.task_id
                 );
                 Task::update_status(&self.db.pool, task_attempt.task_id, TaskStatus::Done).await?;
+
+                if self.config.read().await.prune_worktrees_after_merge
+                    && let Err(e) = self.container.prune_merged_worktrees().await
+                {
+                    error!("Failed to prune worktrees after PR merge: {}", e);
+                }
             }
         }
 
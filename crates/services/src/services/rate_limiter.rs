@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::Serialize;
+use tokio::{
+    sync::{Mutex, RwLock},
+    time::Instant,
+};
+use ts_rs::TS;
+
+/// Token buckets limiting how often coding agents start, one per configured executor
+/// profile key. A bucket holds up to a minute's worth of starts and refills continuously.
+#[derive(Default)]
+pub struct ExecutorRateLimiter {
+    buckets: RwLock<HashMap<String, Arc<Bucket>>>,
+}
+
+struct Bucket {
+    /// Held while waiting for a token, so waiters are served in arrival order
+    state: Mutex<BucketState>,
+    requests_per_minute: AtomicU32,
+    waiting: AtomicUsize,
+}
+
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl BucketState {
+    fn refill(&mut self, requests_per_minute: u32) {
+        let now = Instant::now();
+        let per_second = f64::from(requests_per_minute) / 60.0;
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(f64::from(requests_per_minute));
+        self.refilled_at = now;
+    }
+}
+
+/// Counts a start as waiting on its bucket until dropped, so a cancelled waiter doesn't
+/// stay counted
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl<'a> Waiting<'a> {
+    fn new(waiting: &'a AtomicUsize) -> Self {
+        waiting.fetch_add(1, Ordering::SeqCst);
+        Self(waiting)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Snapshot of one bucket, for observability
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RateLimitStatus {
+    pub key: String,
+    pub requests_per_minute: u32,
+    /// Starts that can happen right now without waiting
+    pub available: u32,
+    /// Starts currently waiting for a token
+    pub waiting: usize,
+}
+
+impl ExecutorRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait until the bucket for `key` has a token and take it. The limit is passed on
+    /// every call so config changes apply to the next start.
+    pub async fn acquire(&self, key: &str, requests_per_minute: u32) {
+        if requests_per_minute == 0 {
+            return;
+        }
+        let bucket = self.bucket(key, requests_per_minute).await;
+        bucket
+            .requests_per_minute
+            .store(requests_per_minute, Ordering::SeqCst);
+        let _waiting = Waiting::new(&bucket.waiting);
+        let mut state = bucket.state.lock().await;
+        state.refill(requests_per_minute);
+        if state.tokens < 1.0 {
+            let wait = (1.0 - state.tokens) * 60.0 / f64::from(requests_per_minute);
+            tracing::info!(
+                "Rate limit for {} reached, delaying agent start by {:.1}s",
+                key,
+                wait
+            );
+            tokio::time::sleep(Duration::from_secs_f64(wait)).await;
+            state.refill(requests_per_minute);
+        }
+        state.tokens = (state.tokens - 1.0).max(0.0);
+    }
+
+    /// Take a token for `key` if one is available right now and nobody is waiting for it
+    pub async fn try_acquire(&self, key: &str, requests_per_minute: u32) -> bool {
+        if requests_per_minute == 0 {
+            return true;
+        }
+        let bucket = self.bucket(key, requests_per_minute).await;
+        bucket
+            .requests_per_minute
+            .store(requests_per_minute, Ordering::SeqCst);
+        let Ok(mut state) = bucket.state.try_lock() else {
+            return false;
+        };
+        state.refill(requests_per_minute);
+        if state.tokens < 1.0 {
+            return false;
+        }
+        state.tokens -= 1.0;
+        true
+    }
+
+    /// Current state of every bucket that has been used, sorted by key
+    pub async fn status(&self) -> Vec<RateLimitStatus> {
+        let buckets: Vec<_> = self
+            .buckets
+            .read()
+            .await
+            .iter()
+            .map(|(key, bucket)| (key.clone(), bucket.clone()))
+            .collect();
+        let mut statuses = Vec::with_capacity(buckets.len());
+        for (key, bucket) in buckets {
+            let requests_per_minute = bucket.requests_per_minute.load(Ordering::SeqCst);
+            let waiting = bucket.waiting.load(Ordering::SeqCst);
+            // A waiter holds the lock while it sleeps, and by then the bucket is empty
+            let available = match bucket.state.try_lock() {
+                Ok(mut state) => {
+                    state.refill(requests_per_minute);
+                    state.tokens.floor() as u32
+                }
+                Err(_) => 0,
+            };
+            statuses.push(RateLimitStatus {
+                key,
+                requests_per_minute,
+                available,
+                waiting,
+            });
+        }
+        statuses.sort_by(|a, b| a.key.cmp(&b.key));
+        statuses
+    }
+
+    async fn bucket(&self, key: &str, requests_per_minute: u32) -> Arc<Bucket> {
+        if let Some(bucket) = self.buckets.read().await.get(key) {
+            return bucket.clone();
+        }
+        self.buckets
+            .write()
+            .await
+            .entry(key.to_string())
+            .or_insert_with(|| {
+                Arc::new(Bucket {
+                    state: Mutex::new(BucketState {
+                        tokens: f64::from(requests_per_minute),
+                        refilled_at: Instant::now(),
+                    }),
+                    requests_per_minute: AtomicU32::new(requests_per_minute),
+                    waiting: AtomicUsize::new(0),
+                })
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn burst_up_to_the_limit_then_waits_for_refill() {
+        let limiter = Arc::new(ExecutorRateLimiter::new());
+        let started = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire("CLAUDE_CODE", 3).await;
+        }
+        assert_eq!(Instant::now(), started);
+
+        let status = limiter.status().await;
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].key, "CLAUDE_CODE");
+        assert_eq!(status[0].requests_per_minute, 3);
+        assert_eq!(status[0].available, 0);
+        assert_eq!(status[0].waiting, 0);
+
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire("CLAUDE_CODE", 3).await })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(limiter.status().await[0].waiting, 1);
+
+        waiter.await.unwrap();
+        assert!(Instant::now().duration_since(started) >= Duration::from_secs(20));
+        assert_eq!(limiter.status().await[0].waiting, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancelled_waiter_is_no_longer_counted() {
+        let limiter = ExecutorRateLimiter::new();
+        assert!(limiter.try_acquire("CODEX", 1).await);
+        assert!(!limiter.try_acquire("CODEX", 1).await);
+
+        let waited =
+            tokio::time::timeout(Duration::from_secs(1), limiter.acquire("CODEX", 1)).await;
+        assert!(waited.is_err());
+        assert_eq!(limiter.status().await[0].waiting, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_means_unlimited() {
+        let limiter = ExecutorRateLimiter::new();
+        for _ in 0..100 {
+            limiter.acquire("GEMINI", 0).await;
+        }
+        assert!(limiter.status().await.is_empty());
+    }
+}
@@ -38,11 +38,43 @@ pub enum WorktreeError {
     BranchNotFound(String),
     #[error("Repository error: {0}")]
     Repository(String),
+    #[error(
+        "Not enough free disk space for a worktree in {path}: {available} bytes free, at least {required} needed. Free up space or lower the minimum in settings"
+    )]
+    InsufficientDiskSpace {
+        path: String,
+        available: u64,
+        required: u64,
+    },
+    #[error(
+        "Cannot write to the worktree directory {path} ({reason}). Check that the disk isn't read-only or full"
+    )]
+    BaseDirNotWritable { path: String, reason: String },
 }
 
 pub struct WorktreeManager;
 
+/// Disk space used by one worktree directory
+#[derive(Debug, Clone)]
+pub struct WorktreeDiskUsage {
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
 impl WorktreeManager {
+    /// Whether `name` works both as a git branch name and as a single directory name on
+    /// every platform
+    pub fn is_valid_worktree_name(name: &str) -> bool {
+        !name.is_empty()
+            && name != "."
+            && name != ".."
+            && !name.ends_with(['.', ' '])
+            && !name.chars().any(|c| {
+                c.is_control() || matches!(c, '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*')
+            })
+            && git2::Reference::is_valid_name(&format!("refs/heads/{name}"))
+    }
+
     /// Create a worktree with a new branch
     pub async fn create_worktree(
         repo_path: &Path,
@@ -488,4 +520,173 @@ impl WorktreeManager {
     pub fn get_worktree_base_dir() -> std::path::PathBuf {
         utils::path::get_vibe_kanban_temp_dir().join("worktrees")
     }
+
+    /// Fail early, with an actionable error, when worktrees can't be created under
+    /// `base_dir` because its disk is read-only or has less than `min_free_bytes` free.
+    /// Zero skips the free space check.
+    pub fn check_base_dir_capacity(
+        base_dir: &Path,
+        min_free_bytes: u64,
+    ) -> Result<(), WorktreeError> {
+        let not_writable = |e: std::io::Error| WorktreeError::BaseDirNotWritable {
+            path: base_dir.display().to_string(),
+            reason: e.to_string(),
+        };
+        std::fs::create_dir_all(base_dir).map_err(not_writable)?;
+        tempfile::tempfile_in(base_dir).map_err(not_writable)?;
+
+        if min_free_bytes > 0
+            && let Some(available) = Self::available_space(base_dir)?
+            && available < min_free_bytes
+        {
+            return Err(WorktreeError::InsufficientDiskSpace {
+                path: base_dir.display().to_string(),
+                available,
+                required: min_free_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Bytes free for unprivileged use on the filesystem holding `path`, where the
+    /// platform can tell
+    #[cfg(unix)]
+    fn available_space(path: &Path) -> Result<Option<u64>, WorktreeError> {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| WorktreeError::InvalidPath(e.to_string()))?;
+        let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: `c_path` is NUL-terminated and `stat` is only read after success
+        if unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let stat = unsafe { stat.assume_init() };
+        #[allow(clippy::unnecessary_cast)] // the field types differ between platforms
+        Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+    }
+
+    #[cfg(not(unix))]
+    fn available_space(_path: &Path) -> Result<Option<u64>, WorktreeError> {
+        Ok(None)
+    }
+
+    /// Disk usage of every worktree under the worktree base dir, largest first
+    pub async fn disk_usage() -> Result<Vec<WorktreeDiskUsage>, WorktreeError> {
+        Self::disk_usage_in(&Self::get_worktree_base_dir()).await
+    }
+
+    /// Disk usage of each directory directly under `base_dir`, largest first.
+    /// Directories are measured concurrently on the blocking pool.
+    pub async fn disk_usage_in(base_dir: &Path) -> Result<Vec<WorktreeDiskUsage>, WorktreeError> {
+        if !base_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut measurements = Vec::new();
+        let mut entries = tokio::fs::read_dir(base_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+            let path = entry.path();
+            measurements.push(tokio::task::spawn_blocking(move || {
+                let bytes = Self::dir_size(&path);
+                WorktreeDiskUsage { path, bytes }
+            }));
+        }
+
+        let mut usage = futures::future::try_join_all(measurements)
+            .await
+            .map_err(|e| WorktreeError::TaskJoin(format!("{e}")))?;
+        usage.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+        Ok(usage)
+    }
+
+    /// Total size of the files under `path`. Symlinks are not followed and
+    /// unreadable entries are skipped.
+    pub fn dir_size(path: &Path) -> u64 {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return 0;
+        };
+        entries
+            .flatten()
+            .map(|entry| match entry.metadata() {
+                Ok(meta) if meta.is_dir() => Self::dir_size(&entry.path()),
+                Ok(meta) => meta.len(),
+                Err(_) => 0,
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn worktree_names_must_be_legal_branches_and_directories() {
+        assert!(WorktreeManager::is_valid_worktree_name("vk-1a2b-fix-login"));
+        assert!(WorktreeManager::is_valid_worktree_name("team_1a2b"));
+        for name in [
+            "", "..", "vk/1a2b", "vk:1a2b", "vk 1a2b", "vk-1a2b.", "vk..1a2b",
+        ] {
+            assert!(!WorktreeManager::is_valid_worktree_name(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn base_dir_capacity_check_reports_full_or_unwritable_disks() {
+        let base = TempDir::new().unwrap();
+        let worktrees = base.path().join("worktrees");
+        WorktreeManager::check_base_dir_capacity(&worktrees, 0).unwrap();
+        assert!(worktrees.is_dir());
+
+        if cfg!(unix) {
+            assert!(matches!(
+                WorktreeManager::check_base_dir_capacity(&worktrees, u64::MAX),
+                Err(WorktreeError::InsufficientDiskSpace {
+                    required: u64::MAX,
+                    ..
+                })
+            ));
+        }
+
+        // A file where the directory should be can't be written into, even as root
+        let blocked = base.path().join("blocked");
+        std::fs::write(&blocked, "").unwrap();
+        assert!(matches!(
+            WorktreeManager::check_base_dir_capacity(&blocked.join("worktrees"), 0),
+            Err(WorktreeError::BaseDirNotWritable { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn disk_usage_sums_each_worktree() {
+        let base = TempDir::new().unwrap();
+        let small = base.path().join("vk-small");
+        let large = base.path().join("vk-large");
+        std::fs::create_dir_all(small.join("src")).unwrap();
+        std::fs::create_dir_all(large.join("target/debug")).unwrap();
+        std::fs::write(small.join("src/lib.rs"), vec![0u8; 100]).unwrap();
+        std::fs::write(large.join("README.md"), vec![0u8; 50]).unwrap();
+        std::fs::write(large.join("target/debug/app"), vec![0u8; 4000]).unwrap();
+        // Stray files in the base dir aren't worktrees
+        std::fs::write(base.path().join("stray.txt"), b"x").unwrap();
+
+        let usage = WorktreeManager::disk_usage_in(base.path()).await.unwrap();
+        let summary: Vec<_> = usage.iter().map(|u| (u.path.clone(), u.bytes)).collect();
+        assert_eq!(summary, [(large, 4050), (small, 100)]);
+    }
+
+    #[tokio::test]
+    async fn disk_usage_of_missing_base_dir_is_empty() {
+        let base = TempDir::new().unwrap();
+        let usage = WorktreeManager::disk_usage_in(&base.path().join("missing"))
+            .await
+            .unwrap();
+        assert!(usage.is_empty());
+    }
 }
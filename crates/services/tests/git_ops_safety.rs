@@ -5,8 +5,11 @@ use std::{
 };
 
 use git2::{Repository, build::CheckoutBuilder};
-use services::services::git::GitService;
 use services::services::git_cli::GitCli; // used only to set up sparse-checkout
+use services::services::{
+    config::CommitSigningFormat,
+    git::{AbortedOperation, CommitSigning, GitService, GitServiceError},
+};
 use tempfile::TempDir;
 // Avoid direct git CLI usage in tests; exercise GitService instead.
 
@@ -526,6 +529,67 @@ fn rebase_refuses_to_abort_existing_rebase() {
     // Note: We do not auto-abort; user should resolve or abort explicitly
 }
 
+#[test]
+fn abort_operation_backs_out_of_a_conflicted_rebase() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+    let svc = GitService::new();
+    let before = svc.get_head_info(&worktree_path).unwrap();
+    let _ = svc
+        .rebase_branch(
+            &repo_path,
+            &worktree_path,
+            Some("new-base"),
+            "old-base",
+            None,
+        )
+        .expect_err("rebase should stop on the conflict");
+
+    assert_eq!(
+        svc.abort_operation(&worktree_path).unwrap(),
+        AbortedOperation::Rebase
+    );
+    let after = svc.get_head_info(&worktree_path).unwrap();
+    assert_eq!(after.branch, before.branch);
+    assert_eq!(after.oid, before.oid);
+    assert!(svc.is_worktree_clean(&worktree_path).unwrap());
+    // Nothing left to abort, and the branch can be rebased again
+    assert_eq!(
+        svc.abort_operation(&worktree_path).unwrap(),
+        AbortedOperation::None
+    );
+    assert!(!matches!(
+        svc.rebase_branch(
+            &repo_path,
+            &worktree_path,
+            Some("new-base"),
+            "old-base",
+            None
+        ),
+        Err(GitServiceError::RebaseInProgress)
+    ));
+}
+
+#[test]
+fn abort_operation_backs_out_of_a_conflicted_merge() {
+    let td = TempDir::new().unwrap();
+    let (_repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+    let svc = GitService::new();
+    let before = svc.get_head_info(&worktree_path).unwrap();
+    GitCli::new()
+        .git(&worktree_path, ["merge", "new-base"])
+        .expect_err("merge should stop on the conflict");
+
+    assert_eq!(
+        svc.abort_operation(&worktree_path).unwrap(),
+        AbortedOperation::Merge
+    );
+    assert_eq!(svc.get_head_info(&worktree_path).unwrap().oid, before.oid);
+    assert!(svc.is_worktree_clean(&worktree_path).unwrap());
+    let content = fs::read_to_string(worktree_path.join("conflict.txt")).unwrap();
+    assert_eq!(content, "feature version\n");
+}
+
 #[test]
 fn rebase_fast_forwards_when_no_unique_commits() {
     let td = TempDir::new().unwrap();
@@ -661,6 +725,203 @@ fn merge_conflict_does_not_move_base_ref() {
     assert_eq!(before, after, "main ref must remain unchanged on conflict");
 }
 
+#[test]
+fn merge_conflict_reports_paths_without_touching_base_worktree() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_direct_conflict_repo(&td);
+
+    // main is checked out in the repo, so this would take the CLI merge path
+    let service = GitService::new();
+    let res = service.merge_changes(
+        &repo_path,
+        &worktree_path,
+        "feature",
+        "main",
+        "squash merge",
+    );
+
+    match res {
+        Err(GitServiceError::MergeConflicts(paths)) => assert_eq!(paths, "conflict.txt"),
+        other => panic!("expected merge conflicts, got {other:?}"),
+    }
+    let content = fs::read_to_string(repo_path.join("conflict.txt")).unwrap();
+    assert_eq!(content, "main change\n");
+    assert!(service.is_worktree_clean(&repo_path).unwrap());
+}
+
+#[test]
+fn libgit2_revert_undoes_merge_without_touching_main_worktree() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+
+    // main isn't checked out in the repo (old-base is), so both go through libgit2
+    let merge_sha = s
+        .merge_changes(&repo_path, &worktree_path, "feature", "main", "squash")
+        .unwrap();
+    let revert_sha = s.revert_commit(&repo_path, "main", &merge_sha).unwrap();
+
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), revert_sha);
+    let repo = Repository::open(&repo_path).unwrap();
+    let revert = repo
+        .find_commit(git2::Oid::from_str(&revert_sha).unwrap())
+        .unwrap();
+    assert_eq!(revert.parent_id(0).unwrap().to_string(), merge_sha);
+    assert!(revert.tree().unwrap().get_name("feat.txt").is_none());
+    assert!(revert.message().unwrap().contains(&merge_sha));
+    // The checked-out branch is left alone
+    assert_eq!(repo.head().unwrap().shorthand(), Some("old-base"));
+    assert!(s.is_worktree_clean(&repo_path).unwrap());
+
+    // Commits that never landed on the branch can't be reverted there
+    let new_base_sha = s.get_branch_oid(&repo_path, "new-base").unwrap();
+    assert!(s.revert_commit(&repo_path, "main", &new_base_sha).is_err());
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), revert_sha);
+}
+
+fn ssh_signing(key: &Path) -> Option<CommitSigning> {
+    Some(CommitSigning {
+        format: CommitSigningFormat::Ssh,
+        key: Some(key.to_string_lossy().into_owned()),
+    })
+}
+
+#[test]
+#[ignore = "needs ssh-keygen"]
+fn signed_squash_merge_is_signed_on_both_merge_paths() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let key = td.path().join("signing_key");
+    let keygen = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key)
+        .status()
+        .expect("run ssh-keygen");
+    assert!(keygen.success(), "ssh-keygen failed");
+    let service = GitService::new().with_commit_signing(ssh_signing(&key));
+    let repo = Repository::open(&repo_path).unwrap();
+
+    // old-base is checked out in the repo: CLI squash merge
+    let sha = service
+        .merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "old-base",
+            "signed cli merge",
+        )
+        .unwrap();
+    let oid = git2::Oid::from_str(&sha).unwrap();
+    assert!(repo.extract_signature(&oid, None).is_ok());
+
+    // main isn't checked out: in-memory merge committed via commit-tree
+    let sha = service
+        .merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "signed merge",
+        )
+        .unwrap();
+    let oid = git2::Oid::from_str(&sha).unwrap();
+    assert!(repo.extract_signature(&oid, None).is_ok());
+    assert_eq!(service.get_branch_oid(&repo_path, "main").unwrap(), sha);
+}
+
+#[test]
+fn squash_merge_with_unusable_signing_key_leaves_base_untouched() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let service =
+        GitService::new().with_commit_signing(ssh_signing(&td.path().join("missing.pub")));
+    let before = service.get_branch_oid(&repo_path, "old-base").unwrap();
+
+    let res = service.merge_changes(
+        &repo_path,
+        &worktree_path,
+        "feature",
+        "old-base",
+        "unsigned merge",
+    );
+
+    assert!(
+        matches!(res, Err(GitServiceError::CommitSigningFailed(_))),
+        "got {res:?}"
+    );
+    assert_eq!(
+        service.get_branch_oid(&repo_path, "old-base").unwrap(),
+        before
+    );
+    assert!(service.is_worktree_clean(&repo_path).unwrap());
+    assert!(!repo_path.join("feat.txt").exists());
+}
+
+#[test]
+fn fast_forward_refuses_a_branch_checked_out_in_another_worktree() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    // A descendant of feature, committed without checking anything out
+    let feature = repo
+        .find_branch("feature", git2::BranchType::Local)
+        .unwrap()
+        .get()
+        .peel_to_commit()
+        .unwrap();
+    let sig = repo.signature().unwrap();
+    repo.commit(
+        Some("refs/heads/ahead"),
+        &sig,
+        &sig,
+        "ahead",
+        &feature.tree().unwrap(),
+        &[&feature],
+    )
+    .unwrap();
+    let service = GitService::new();
+
+    let res = service.fast_forward_branch(&repo_path, "feature", "ahead");
+
+    assert!(
+        matches!(res, Err(GitServiceError::BranchCheckedOut(ref branch, _)) if branch == "feature"),
+        "got {res:?}"
+    );
+    assert_eq!(
+        service.get_branch_oid(&repo_path, "feature").unwrap(),
+        feature.id().to_string()
+    );
+    assert!(service.is_worktree_clean(&worktree_path).unwrap());
+}
+
+#[test]
+fn rebase_with_unusable_signing_key_is_aborted() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let service =
+        GitService::new().with_commit_signing(ssh_signing(&td.path().join("missing.pub")));
+    let before = service.get_branch_oid(&repo_path, "feature").unwrap();
+
+    let res = service.rebase_branch(
+        &repo_path,
+        &worktree_path,
+        Some("new-base"),
+        "old-base",
+        None,
+    );
+
+    assert!(
+        matches!(res, Err(GitServiceError::CommitSigningFailed(_))),
+        "got {res:?}"
+    );
+    assert!(!GitCli::new().is_rebase_in_progress(&worktree_path).unwrap());
+    assert_eq!(
+        service.get_branch_oid(&repo_path, "feature").unwrap(),
+        before
+    );
+    assert!(service.is_worktree_clean(&worktree_path).unwrap());
+}
+
 #[test]
 fn merge_delete_vs_modify_conflict_behaves_safely() {
     // main modifies file, feature deletes it -> conflict
@@ -964,3 +1225,100 @@ fn merge_rename_vs_modify_conflict_does_not_move_ref() {
         }
     }
 }
+
+#[test]
+fn push_branch_pushes_to_named_remote_and_sets_upstream() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let remote_path = td.path().join("remote.git");
+    Repository::init_bare(&remote_path).unwrap();
+
+    let s = GitService::new();
+    s.set_remote(&repo_path, "upstream", remote_path.to_str().unwrap())
+        .unwrap();
+    s.push_branch(&worktree_path, "feature", "upstream", "unused-token")
+        .unwrap();
+
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    let local_head = wt_repo.head().unwrap().peel_to_commit().unwrap().id();
+    let remote = Repository::open_bare(&remote_path).unwrap();
+    assert_eq!(
+        remote
+            .find_reference("refs/heads/feature")
+            .unwrap()
+            .target()
+            .unwrap(),
+        local_head
+    );
+    let branch = wt_repo
+        .find_branch("feature", git2::BranchType::Local)
+        .unwrap();
+    assert_eq!(
+        branch.upstream().unwrap().name().unwrap(),
+        Some("upstream/feature")
+    );
+
+    let missing = s.push_branch(&worktree_path, "feature", "nope", "unused-token");
+    assert!(matches!(
+        missing,
+        Err(GitServiceError::InvalidRepository(_))
+    ));
+}
+
+#[test]
+fn fetch_gives_up_on_a_hanging_remote() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_only_service(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    repo.remote("origin", "ext::sleep 30").unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("protocol.ext.allow", "always")
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let result =
+        GitCli::new().fetch_within(&repo_path, "origin", std::time::Duration::from_millis(500));
+    assert!(matches!(
+        result,
+        Err(services::services::git_cli::GitCliError::TimedOut(_))
+    ));
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+}
+
+#[cfg(unix)]
+#[test]
+fn fetch_keeps_the_configured_ssh_command() {
+    if std::env::var_os("GIT_SSH_COMMAND").is_some() {
+        // The env var takes precedence over the repo config this test sets
+        return;
+    }
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_only_service(&td);
+    let args_file = td.path().join("ssh-args");
+    let wrapper = td.path().join("ssh-wrapper");
+    write_file(
+        td.path(),
+        "ssh-wrapper",
+        &format!(
+            "#!/bin/sh\necho \"$@\" > '{}'\nexit 1\n",
+            args_file.display()
+        ),
+    );
+    std::fs::set_permissions(
+        &wrapper,
+        std::os::unix::fs::PermissionsExt::from_mode(0o755),
+    )
+    .unwrap();
+    let repo = Repository::open(&repo_path).unwrap();
+    repo.remote("origin", "ssh://git@example.invalid/repo.git")
+        .unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("core.sshCommand", wrapper.to_str().unwrap())
+        .unwrap();
+
+    assert!(GitCli::new().fetch(&repo_path, "origin").is_err());
+    let args = std::fs::read_to_string(&args_file).unwrap();
+    assert!(args.contains("BatchMode=yes"), "wrapper got: {args}");
+}
@@ -4,9 +4,16 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use services::services::git::{DiffTarget, GitService};
+use executors::logs::utils::{ConversationPatch, patch::escape_json_pointer_segment};
+use services::services::{
+    config::CommitSigningFormat,
+    git::{CloneOptions, CommitSigning, DiffTarget, GitService, GitServiceError},
+};
 use tempfile::TempDir;
-use utils::diff::DiffChangeKind;
+use utils::{
+    diff::{Diff, DiffChangeKind},
+    path::normalize_path_separators,
+};
 
 fn write_file<P: AsRef<Path>>(base: P, rel: &str, content: &str) {
     let path = base.as_ref().join(rel);
@@ -97,6 +104,47 @@ fn commit_fails_when_index_locked() {
     assert!(res.is_err());
 }
 
+#[test]
+fn worktree_changing_mid_diff_is_transient_but_a_missing_repo_is_not() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    write_file(&repo_path, "y.txt", "y\n");
+    // A nested repository without commits yet can't be staged
+    let nested = repo_path.join("nested");
+    git2::Repository::init(&nested).unwrap();
+    let s = GitService::new();
+    let diff_worktree = || {
+        s.get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: &repo_path,
+                branch_name: "main",
+                base_branch: "main",
+            },
+            None,
+        )
+    };
+    let err = diff_worktree().unwrap_err();
+    assert!(matches!(err, GitServiceError::WorktreeChanging(_)));
+    assert!(err.is_transient());
+
+    // Once the worktree settles the same diff goes through
+    fs::remove_dir_all(&nested).unwrap();
+    assert_eq!(diff_worktree().unwrap().len(), 1);
+
+    let missing = td.path().join("gone");
+    let err = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: &missing,
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+        )
+        .unwrap_err();
+    assert!(!err.is_transient());
+}
+
 #[test]
 fn staged_but_uncommitted_changes_is_dirty() {
     let td = TempDir::new().unwrap();
@@ -240,6 +288,77 @@ fn commit_and_is_worktree_clean() {
     );
 }
 
+#[test]
+fn noop_cleanup_produces_no_commit() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    write_file(&repo_path, "foo.txt", "hello\n");
+    let s = GitService::new();
+    assert!(s.commit(&repo_path, "add foo").unwrap());
+    let head_before = s.get_head_info(&repo_path).unwrap().oid;
+
+    // Cleanup script rewrote a file with identical content and left build output behind
+    write_file(&repo_path, "foo.txt", "hello\n");
+    write_file(&repo_path, "target/out.log", "built\n");
+
+    let committed = s
+        .commit_tracked_changes(&repo_path, None, "Cleanup script changes")
+        .unwrap();
+    assert!(!committed);
+    assert_eq!(s.get_head_info(&repo_path).unwrap().oid, head_before);
+
+    // A cleanup that touches tracked files is still committed
+    write_file(&repo_path, "foo.txt", "hello, formatted\n");
+    let committed = s
+        .commit_tracked_changes(&repo_path, None, "Cleanup script changes")
+        .unwrap();
+    assert!(committed);
+    assert_ne!(s.get_head_info(&repo_path).unwrap().oid, head_before);
+}
+
+#[test]
+fn scoped_commit_only_includes_subpath() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    write_file(&repo_path, "packages/web/index.js", "console.log(1);\n");
+    write_file(&repo_path, "packages/api/main.rs", "fn main() {}\n");
+    let s = GitService::new();
+    assert!(s.commit(&repo_path, "add packages").unwrap());
+
+    write_file(&repo_path, "packages/web/index.js", "console.log(2);\n");
+    write_file(
+        &repo_path,
+        "packages/api/main.rs",
+        "fn main() { todo!() }\n",
+    );
+
+    let committed = s
+        .commit_scoped(&repo_path, Some("packages/web"), "web changes")
+        .unwrap();
+    assert!(committed);
+
+    let head = s.get_head_info(&repo_path).unwrap().oid;
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Commit {
+                repo_path: Path::new(&repo_path),
+                commit_sha: &head,
+            },
+            None,
+        )
+        .unwrap();
+    let paths: Vec<_> = diffs.iter().filter_map(|d| d.new_path.as_deref()).collect();
+    assert_eq!(paths, vec!["packages/web/index.js"]);
+    // Changes outside the subpath are left uncommitted
+    assert!(!s.is_worktree_clean(&repo_path).unwrap());
+
+    // Nothing left under the subpath, so a second scoped commit is a no-op
+    assert!(
+        !s.commit_scoped(&repo_path, Some("packages/web"), "again")
+            .unwrap()
+    );
+}
+
 #[test]
 fn commit_in_detached_head_succeeds_via_service() {
     let td = TempDir::new().unwrap();
@@ -290,6 +409,66 @@ fn branch_status_ahead_and_behind() {
     assert_eq!((ahead2, behind2), (2, 1));
 }
 
+#[test]
+fn commits_diff_compares_two_branch_tips() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    write_file(&repo_path, "base.txt", "base\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+    let base = s.get_branch_oid(&repo_path, "main").unwrap();
+
+    s.create_branch(&repo_path, "first").unwrap();
+    s.create_branch(&repo_path, "second").unwrap();
+    s.checkout_branch(&repo_path, "first").unwrap();
+    write_file(&repo_path, "base.txt", "first\n");
+    let _ = s.commit(&repo_path, "first").unwrap();
+    s.checkout_branch(&repo_path, "second").unwrap();
+    write_file(&repo_path, "second.txt", "second\n");
+    let _ = s.commit(&repo_path, "second").unwrap();
+
+    assert_eq!(
+        s.get_merge_base(&repo_path, "first", "second").unwrap(),
+        Some(base)
+    );
+
+    let first = s.get_branch_oid(&repo_path, "first").unwrap();
+    let second = s.get_branch_oid(&repo_path, "second").unwrap();
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Commits {
+                repo_path: &repo_path,
+                from_sha: &first,
+                to_sha: &second,
+            },
+            None,
+        )
+        .unwrap();
+    let mut diffs: Vec<_> = diffs.iter().collect();
+    diffs.sort_by_key(|d| GitService::diff_path(d));
+    assert!(matches!(
+        diffs.as_slice(),
+        [modified, added]
+            if GitService::diff_path(modified) == "base.txt"
+                && matches!(modified.change, DiffChangeKind::Modified)
+                && GitService::diff_path(added) == "second.txt"
+                && matches!(added.change, DiffChangeKind::Added)
+    ));
+
+    assert!(matches!(
+        s.get_diffs(
+            DiffTarget::Commits {
+                repo_path: &repo_path,
+                from_sha: &first,
+                to_sha: "0123456789abcdef0123456789abcdef01234567",
+            },
+            None,
+        ),
+        Err(GitServiceError::CommitNotFound(_))
+    ));
+}
+
 #[test]
 fn get_all_branches_lists_current_and_others() {
     let td = TempDir::new().unwrap();
@@ -337,6 +516,118 @@ fn get_github_repo_info_parses_origin() {
     assert_eq!(info.repo_name, "bar");
 }
 
+#[test]
+fn get_commit_returns_metadata() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    s.commit(&repo_path, "Add a\n\nLonger body").unwrap();
+    let head = s.get_head_info(&repo_path).unwrap();
+
+    let commit = s.get_commit(&repo_path, &head.oid).unwrap();
+    assert_eq!(commit.sha, head.oid);
+    assert_eq!(commit.message, "Add a\n\nLonger body");
+    assert_eq!(commit.author_name.as_deref(), Some("Test User"));
+    assert_eq!(commit.committer_email.as_deref(), Some("test@example.com"));
+}
+
+#[test]
+fn get_commit_unknown_sha_is_typed_error() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    let missing = "0123456789abcdef0123456789abcdef01234567";
+    assert!(matches!(
+        s.get_commit(&repo_path, missing),
+        Err(GitServiceError::CommitNotFound(sha)) if sha == missing
+    ));
+    assert!(matches!(
+        s.get_commit(&repo_path, "not-a-sha"),
+        Err(GitServiceError::CommitNotFound(_))
+    ));
+}
+
+#[test]
+fn commit_on_branch_only_accepts_reachable_commits() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "base.txt", "base\n");
+    s.commit(&repo_path, "base").unwrap();
+    let base = s.get_head_info(&repo_path).unwrap().oid;
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "f1.txt", "f1\n");
+    s.commit(&repo_path, "f1").unwrap();
+    let f1 = s.get_head_info(&repo_path).unwrap().oid;
+    write_file(&repo_path, "f2.txt", "f2\n");
+    s.commit(&repo_path, "f2").unwrap();
+    let f2 = s.get_head_info(&repo_path).unwrap().oid;
+
+    s.checkout_branch(&repo_path, "main").unwrap();
+    write_file(&repo_path, "main.txt", "m1\n");
+    s.commit(&repo_path, "m1").unwrap();
+    let m1 = s.get_head_info(&repo_path).unwrap().oid;
+
+    for sha in [&base, &f1, &f2] {
+        assert!(s.is_commit_on_branch(&repo_path, "feature", sha).unwrap());
+    }
+    assert!(!s.is_commit_on_branch(&repo_path, "feature", &m1).unwrap());
+    assert!(matches!(
+        s.is_commit_on_branch(&repo_path, "feature", "not-a-sha"),
+        Err(GitServiceError::CommitNotFound(_))
+    ));
+
+    // Commits the branch shares with its base aren't its own
+    for sha in [&f1, &f2] {
+        assert!(
+            s.is_commit_on_branch_only(&repo_path, "feature", "main", sha)
+                .unwrap()
+        );
+    }
+    for sha in [&base, &m1] {
+        assert!(
+            !s.is_commit_on_branch_only(&repo_path, "feature", "main", sha)
+                .unwrap()
+        );
+    }
+    assert!(
+        s.is_commit_on_branch_only(&repo_path, "feature", "gone", &base)
+            .unwrap()
+    );
+}
+
+#[test]
+fn list_commits_newest_first_and_capped() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "base.txt", "base\n");
+    s.commit(&repo_path, "base").unwrap();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    for i in 1..=3 {
+        write_file(&repo_path, &format!("f{i}.txt"), "f\n");
+        s.commit(&repo_path, &format!("f{i}")).unwrap();
+    }
+    // A later commit on main must not show up
+    s.checkout_branch(&repo_path, "main").unwrap();
+    write_file(&repo_path, "main.txt", "m1\n");
+    s.commit(&repo_path, "m1").unwrap();
+
+    let commits = s.list_commits(&repo_path, "feature", "main", 10).unwrap();
+    let messages: Vec<_> = commits.iter().map(|c| c.message.as_str()).collect();
+    assert_eq!(messages, ["f3", "f2", "f1"]);
+
+    let capped = s.list_commits(&repo_path, "feature", "main", 2).unwrap();
+    assert_eq!(capped.len(), 2);
+    assert_eq!(capped[0].sha, commits[0].sha);
+}
+
 #[test]
 fn get_branch_diffs_between_branches() {
     let td = TempDir::new().unwrap();
@@ -594,3 +885,181 @@ fn squash_merge_libgit2_sets_author_without_user() {
         assert_eq!(email.as_deref(), Some("noreply@vibekanban.com"));
     }
 }
+
+#[test]
+fn windows_diff_paths_match_watcher_paths() {
+    // git2 can report native separators on Windows while the watcher path is normalized
+    let diff = Diff::new(
+        DiffChangeKind::Modified,
+        Some("src\\components\\App.tsx".to_string()),
+        Some("src\\components\\App.tsx".to_string()),
+        None,
+        None,
+    );
+    let changed_path = normalize_path_separators("src\\components\\App.tsx");
+
+    let entry_index = GitService::diff_path(&diff);
+    assert_eq!(entry_index, "src/components/App.tsx");
+    assert_eq!(entry_index, changed_path);
+
+    let pointer = |patch: json_patch::Patch| {
+        serde_json::to_value(patch).unwrap()[0]["path"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+    let add = pointer(ConversationPatch::add_diff(
+        escape_json_pointer_segment(&entry_index),
+        diff,
+    ));
+    let remove = pointer(ConversationPatch::remove_diff(escape_json_pointer_segment(
+        &changed_path,
+    )));
+    assert_eq!(add, remove);
+    assert_eq!(add, "/entries/src~1components~1App.tsx");
+}
+
+/// Generate a passphrase-less SSH key in `dir`
+fn ssh_signing_key(dir: &Path) -> PathBuf {
+    let key = dir.join("signing_key");
+    let status = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key)
+        .status()
+        .expect("run ssh-keygen");
+    assert!(status.success(), "ssh-keygen failed");
+    key
+}
+
+#[test]
+#[ignore = "needs ssh-keygen"]
+fn commit_is_signed_when_signing_is_enabled() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let key = ssh_signing_key(td.path());
+    let s = GitService::new().with_commit_signing(Some(CommitSigning {
+        format: CommitSigningFormat::Ssh,
+        key: Some(key.to_string_lossy().into_owned()),
+    }));
+
+    write_file(&repo_path, "signed.txt", "signed\n");
+    assert!(s.commit(&repo_path, "signed commit").unwrap());
+
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let (signature, _) = repo.extract_signature(&head.id(), None).unwrap();
+    assert!(
+        String::from_utf8_lossy(&signature).contains("BEGIN SSH SIGNATURE"),
+        "HEAD should carry an SSH signature"
+    );
+}
+
+#[test]
+fn commit_fails_instead_of_committing_unsigned_when_key_is_missing() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let head_before = GitService::new().get_head_info(&repo_path).unwrap().oid;
+    let s = GitService::new().with_commit_signing(Some(CommitSigning {
+        format: CommitSigningFormat::Ssh,
+        key: Some(td.path().join("missing.pub").to_string_lossy().into_owned()),
+    }));
+
+    write_file(&repo_path, "unsigned.txt", "nope\n");
+    let res = s.commit(&repo_path, "should not land");
+    assert!(
+        matches!(res, Err(GitServiceError::CommitSigningFailed(_))),
+        "got {res:?}"
+    );
+    assert_eq!(
+        GitService::new().get_head_info(&repo_path).unwrap().oid,
+        head_before
+    );
+}
+
+#[test]
+fn remote_urls_are_told_apart_from_local_paths() {
+    for url in [
+        "https://github.com/owner/repo.git",
+        "ssh://git@gitlab.com/group/repo",
+        "git@github.com:owner/repo.git",
+        "file:///srv/git/repo",
+    ] {
+        assert!(GitService::is_remote_url(url), "{url}");
+        assert_eq!(GitService::repo_name_from_url(url).as_deref(), Some("repo"));
+    }
+    for path in ["/home/me/repo", "~/code/repo", "C:\\code\\repo", "./repo"] {
+        assert!(!GitService::is_remote_url(path), "{path}");
+    }
+}
+
+#[test]
+fn remote_urls_are_split_into_host_and_owner_path() {
+    for url in [
+        "https://github.com/owner/repo.git",
+        "git@github.com:owner/repo.git",
+        "ssh://git@GitHub.com/owner/repo",
+    ] {
+        assert_eq!(
+            GitService::parse_remote_url(url),
+            Some(("github.com".to_string(), "owner/repo".to_string())),
+            "{url}"
+        );
+    }
+    // A look-alike host is its own host, not github.com
+    assert_eq!(
+        GitService::parse_remote_url("https://github.com.evil.example/owner/repo")
+            .unwrap()
+            .0,
+        "github.com.evil.example"
+    );
+    assert_eq!(GitService::parse_remote_url("file:///srv/git/repo"), None);
+
+    assert_eq!(
+        GitService::clone_dir_from_url("https://gitlab.com/group/sub/repo.git"),
+        Some(PathBuf::from("group/sub/repo"))
+    );
+    assert_ne!(
+        GitService::clone_dir_from_url("https://github.com/a/foo"),
+        GitService::clone_dir_from_url("https://github.com/b/foo")
+    );
+    assert_eq!(
+        GitService::clone_dir_from_url("file:///srv/git/repo"),
+        Some(PathBuf::from("repo"))
+    );
+}
+
+#[test]
+fn clone_reports_progress_and_reuses_an_existing_clone() {
+    let td = TempDir::new().unwrap();
+    let source = init_repo_main(&td);
+    write_file(&source, "README.md", "hello\n");
+    GitService::new().commit(&source, "initial").unwrap();
+    let url = format!("file://{}", source.display());
+    let target = td.path().join("clones/repo");
+
+    let mut updates = Vec::new();
+    GitService::clone_repository(&url, &target, &CloneOptions::default(), |progress| {
+        updates.push(progress)
+    })
+    .unwrap();
+    assert_eq!(
+        fs::read_to_string(target.join("README.md")).unwrap(),
+        "hello\n"
+    );
+    let last = updates.last().expect("no progress reported");
+    assert!(last.total_objects > 0);
+    assert_eq!(last.received_objects, last.total_objects);
+
+    // Cloning the same URL again reuses the clone
+    GitService::clone_repository(&url, &target, &CloneOptions::default(), |_| {}).unwrap();
+
+    // Any other non-empty directory is left alone
+    let occupied = td.path().join("occupied");
+    write_file(&occupied, "notes.txt", "mine\n");
+    let result = GitService::clone_repository(&url, &occupied, &CloneOptions::default(), |_| {});
+    assert!(matches!(result, Err(GitServiceError::InvalidPath(_))));
+    assert_eq!(
+        fs::read_to_string(occupied.join("notes.txt")).unwrap(),
+        "mine\n"
+    );
+}
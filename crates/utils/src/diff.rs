@@ -20,6 +20,35 @@ pub struct Diff {
     pub new_path: Option<String>,
     pub old_content: Option<String>,
     pub new_content: Option<String>,
+    /// Highlight.js language id guessed from the file name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub language: Option<String>,
+    /// Lockfile or generated file, collapsed by default when reviewing
+    #[serde(default)]
+    pub generated: bool,
+}
+
+impl Diff {
+    /// A diff with its language and generated-file hints filled in from the path
+    pub fn new(
+        change: DiffChangeKind,
+        old_path: Option<String>,
+        new_path: Option<String>,
+        old_content: Option<String>,
+        new_content: Option<String>,
+    ) -> Self {
+        let path = new_path.as_deref().or(old_path.as_deref()).unwrap_or("");
+        Self {
+            language: language_for_path(path).map(str::to_string),
+            generated: is_generated_path(path),
+            change,
+            old_path,
+            new_path,
+            old_content,
+            new_content,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -34,6 +63,109 @@ pub enum DiffChangeKind {
     PermissionChange,
 }
 
+/// Highlight.js language ids by file extension, matching the frontend's table
+const LANGUAGES_BY_EXTENSION: &[(&str, &str)] = &[
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("ts", "typescript"),
+    ("jsx", "jsx"),
+    ("tsx", "tsx"),
+    ("html", "xml"),
+    ("htm", "xml"),
+    ("xml", "xml"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("less", "less"),
+    ("json", "json"),
+    ("md", "markdown"),
+    ("yml", "yaml"),
+    ("yaml", "yaml"),
+    ("toml", "ini"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "bash"),
+    ("ps1", "powershell"),
+    ("php", "php"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cpp", "cpp"),
+    ("cc", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("scala", "scala"),
+    ("go", "go"),
+    ("rs", "rust"),
+    ("swift", "swift"),
+    ("dart", "dart"),
+    ("py", "python"),
+    ("rb", "ruby"),
+    ("pl", "perl"),
+    ("lua", "lua"),
+    ("r", "r"),
+    ("sql", "sql"),
+    ("tex", "latex"),
+];
+
+/// Files written by package managers rather than people
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "bun.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "go.sum",
+    "flake.lock",
+];
+
+/// File name endings of build and codegen output
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".min.js",
+    ".min.css",
+    ".map",
+    ".pb.go",
+    "_pb2.py",
+    ".g.dart",
+    ".freezed.dart",
+    ".snap",
+];
+
+/// The Highlight.js language for `path`, from its file name or extension
+pub fn language_for_path(path: &str) -> Option<&'static str> {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    match file_name {
+        "Dockerfile" => return Some("dockerfile"),
+        "Makefile" => return Some("makefile"),
+        _ => {}
+    }
+    let (_, extension) = file_name.rsplit_once('.')?;
+    let extension = extension.to_ascii_lowercase();
+    LANGUAGES_BY_EXTENSION
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, language)| *language)
+}
+
+/// Whether `path` is a lockfile or generated file, not worth reviewing line by line
+pub fn is_generated_path(path: &str) -> bool {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    LOCKFILES.contains(&file_name)
+        || GENERATED_SUFFIXES
+            .iter()
+            .any(|suffix| file_name.ends_with(suffix))
+        || file_name.contains(".generated.")
+}
+
 // ==============================
 // Unified diff utility functions
 // ==============================
@@ -222,3 +354,38 @@ pub fn concatenate_diff_hunks(file_path: &str, hunks: &[String]) -> String {
 
     unified_diff
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_get_language_and_generated_hints_from_their_path() {
+        let diff = Diff::new(
+            DiffChangeKind::Modified,
+            Some("src/Main.RS".to_string()),
+            Some("src/Main.RS".to_string()),
+            None,
+            None,
+        );
+        assert_eq!(diff.language.as_deref(), Some("rust"));
+        assert!(!diff.generated);
+
+        // Deleted files are named by their old path
+        let diff = Diff::new(
+            DiffChangeKind::Deleted,
+            Some("frontend/package-lock.json".to_string()),
+            None,
+            None,
+            None,
+        );
+        assert_eq!(diff.language.as_deref(), Some("json"));
+        assert!(diff.generated);
+
+        assert_eq!(language_for_path("docker/Dockerfile"), Some("dockerfile"));
+        assert_eq!(language_for_path("LICENSE"), None);
+        assert!(is_generated_path("dist/app.min.js"));
+        assert!(is_generated_path("src/api.generated.ts"));
+        assert!(!is_generated_path("src/lock.rs"));
+    }
+}
@@ -7,6 +7,8 @@ pub const EV_STDERR: &str = "stderr";
 pub const EV_JSON_PATCH: &str = "json_patch";
 pub const EV_SESSION_ID: &str = "session_id";
 pub const EV_FINISHED: &str = "finished";
+// Not "error": EventSource fires its own connection failures under that name
+pub const EV_ERROR: &str = "stream_error";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum LogMsg {
@@ -15,6 +17,10 @@ pub enum LogMsg {
     JsonPatch(Patch),
     SessionId(String),
     Finished,
+    /// A stream stopped updating, or skipped an update, for the given reason
+    Error {
+        message: String,
+    },
 }
 
 impl LogMsg {
@@ -25,6 +31,7 @@ impl LogMsg {
             LogMsg::JsonPatch(_) => EV_JSON_PATCH,
             LogMsg::SessionId(_) => EV_SESSION_ID,
             LogMsg::Finished => EV_FINISHED,
+            LogMsg::Error { .. } => EV_ERROR,
         }
     }
 
@@ -38,6 +45,7 @@ impl LogMsg {
             }
             LogMsg::SessionId(s) => Event::default().event(EV_SESSION_ID).data(s.clone()),
             LogMsg::Finished => Event::default().event(EV_FINISHED).data(""),
+            LogMsg::Error { message } => Event::default().event(EV_ERROR).data(message.clone()),
         }
     }
 
@@ -53,6 +61,7 @@ impl LogMsg {
             }
             LogMsg::SessionId(s) => EV_SESSION_ID.len() + s.len() + OVERHEAD,
             LogMsg::Finished => EV_FINISHED.len() + OVERHEAD,
+            LogMsg::Error { message } => EV_ERROR.len() + message.len() + OVERHEAD,
         }
     }
 }
@@ -1,9 +1,12 @@
 use std::{
-    collections::VecDeque,
-    sync::{Arc, RwLock},
+    backtrace::Backtrace,
+    collections::{HashMap, VecDeque},
+    ops::Deref,
+    sync::{Arc, Mutex, RwLock},
 };
 
 use axum::response::sse::Event;
+use chrono::{DateTime, Utc};
 use futures::{StreamExt, TryStreamExt, future};
 use tokio::{sync::broadcast, task::JoinHandle};
 use tokio_stream::wrappers::BroadcastStream;
@@ -13,10 +16,20 @@ use crate::{log_msg::LogMsg, stream_lines::LinesStreamExt};
 // 100 MB Limit
 const HISTORY_BYTES: usize = 100000 * 1024;
 
+/// Crates whose frames identify who took a handle to a store
+const HOLDER_CRATES: &[&str] = &[
+    "server::",
+    "services::",
+    "deployment::",
+    "local_deployment::",
+    "executors::",
+];
+
 #[derive(Clone)]
 struct StoredMsg {
     msg: LogMsg,
     bytes: usize,
+    received_at: DateTime<Utc>,
 }
 
 struct Inner {
@@ -27,6 +40,8 @@ struct Inner {
 pub struct MsgStore {
     inner: RwLock<Inner>,
     sender: broadcast::Sender<LogMsg>,
+    /// Call sites holding a handle to this store and how many, when tracking is on
+    handouts: Mutex<Option<HashMap<String, usize>>>,
 }
 
 impl Default for MsgStore {
@@ -44,9 +59,55 @@ impl MsgStore {
                 total_bytes: 0,
             }),
             sender,
+            handouts: Mutex::new(None),
+        }
+    }
+
+    /// Start recording where handles to this store are handed out, to diagnose stores
+    /// that outlive their execution. Capturing call sites is costly, so this is opt-in.
+    pub fn track_handouts(&self) {
+        self.handouts
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new);
+    }
+
+    /// Count the caller as a holder and return its site, or `None` unless tracking
+    fn record_handout(&self) -> Option<String> {
+        let mut handouts = self.handouts.lock().unwrap();
+        let handouts = handouts.as_mut()?;
+        let site = handout_site(&Backtrace::force_capture().to_string());
+        *handouts.entry(site.clone()).or_default() += 1;
+        Some(site)
+    }
+
+    fn release_handout(&self, site: &str) {
+        let mut handouts = self.handouts.lock().unwrap();
+        if let Some(handouts) = handouts.as_mut()
+            && let Some(count) = handouts.get_mut(site)
+        {
+            *count -= 1;
+            if *count == 0 {
+                handouts.remove(site);
+            }
         }
     }
 
+    /// Where the handles still held were handed out, most frequent first, or `None` when
+    /// not tracking
+    pub fn handout_summary(&self) -> Option<String> {
+        let handouts = self.handouts.lock().unwrap();
+        let mut sites: Vec<_> = handouts.as_ref()?.iter().collect();
+        sites.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        Some(
+            sites
+                .into_iter()
+                .map(|(site, count)| format!("{site} (x{count})"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+
     pub fn push(&self, msg: LogMsg) {
         let _ = self.sender.send(msg.clone()); // live listeners
         let bytes = msg.approx_bytes();
@@ -59,7 +120,11 @@ impl MsgStore {
                 break;
             }
         }
-        inner.history.push_back(StoredMsg { msg, bytes });
+        inner.history.push_back(StoredMsg {
+            msg,
+            bytes,
+            received_at: Utc::now(),
+        });
         inner.total_bytes = inner.total_bytes.saturating_add(bytes);
     }
 
@@ -95,6 +160,17 @@ impl MsgStore {
             .collect()
     }
 
+    /// History with the time each message was pushed
+    pub fn get_timestamped_history(&self) -> Vec<(DateTime<Utc>, LogMsg)> {
+        self.inner
+            .read()
+            .unwrap()
+            .history
+            .iter()
+            .map(|s| (s.received_at, s.msg.clone()))
+            .collect()
+    }
+
     /// History then live, as `LogMsg`.
     pub fn history_plus_stream(
         &self,
@@ -161,15 +237,109 @@ impl MsgStore {
         S: futures::Stream<Item = Result<LogMsg, E>> + Send + 'static,
         E: std::fmt::Display + Send + 'static,
     {
+        let store = MsgStoreHandle::new(self);
         tokio::spawn(async move {
             tokio::pin!(stream);
 
             while let Some(next) = stream.next().await {
                 match next {
-                    Ok(msg) => self.push(msg),
-                    Err(e) => self.push(LogMsg::Stderr(format!("stream error: {e}"))),
+                    Ok(msg) => store.push(msg),
+                    Err(e) => store.push(LogMsg::Stderr(format!("stream error: {e}"))),
                 }
             }
         })
     }
 }
+
+/// A handle to a store that counts its caller as a holder until dropped, when the store
+/// tracks handouts. Arcs cloned out of it are not tracked.
+pub struct MsgStoreHandle {
+    store: Arc<MsgStore>,
+    site: Option<String>,
+}
+
+impl MsgStoreHandle {
+    pub fn new(store: Arc<MsgStore>) -> Self {
+        let site = store.record_handout();
+        Self { store, site }
+    }
+}
+
+impl Deref for MsgStoreHandle {
+    type Target = Arc<MsgStore>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.store
+    }
+}
+
+impl Drop for MsgStoreHandle {
+    fn drop(&mut self) {
+        if let Some(site) = self.site.take() {
+            self.store.release_handout(&site);
+        }
+    }
+}
+
+/// The innermost frame of a captured backtrace that belongs to one of our crates and
+/// isn't the store accessor itself
+fn handout_site(backtrace: &str) -> String {
+    backtrace
+        .lines()
+        .filter_map(|line| line.trim().split_once(": ").map(|(_, symbol)| symbol))
+        .find(|symbol| {
+            HOLDER_CRATES.iter().any(|c| symbol.starts_with(c))
+                && !symbol.contains("get_msg_store_by_id")
+        })
+        .map(|symbol| symbol.trim_end_matches("::{{closure}}").to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handouts_are_only_recorded_when_tracking() {
+        let store = Arc::new(MsgStore::new());
+        let _untracked = MsgStoreHandle::new(store.clone());
+        assert_eq!(store.handout_summary(), None);
+
+        store.track_handouts();
+        let _first = MsgStoreHandle::new(store.clone());
+        let _second = MsgStoreHandle::new(store.clone());
+        let summary = store.handout_summary().unwrap();
+        assert!(summary.ends_with("(x2)"), "{summary}");
+    }
+
+    #[test]
+    fn dropped_handles_are_no_longer_reported() {
+        let store = Arc::new(MsgStore::new());
+        store.track_handouts();
+        let first = MsgStoreHandle::new(store.clone());
+        let second = MsgStoreHandle::new(store.clone());
+
+        drop(first);
+        let summary = store.handout_summary().unwrap();
+        assert!(summary.ends_with("(x1)"), "{summary}");
+
+        drop(second);
+        assert_eq!(store.handout_summary().as_deref(), Some(""));
+    }
+
+    #[test]
+    fn handout_site_skips_foreign_and_accessor_frames() {
+        let backtrace = "   0: std::backtrace::Backtrace::force_capture
+             at /rustc/library/std/src/backtrace.rs:312:9
+   1: utils::msg_store::MsgStoreHandle::new
+   2: services::services::container::ContainerService::get_msg_store_by_id::{{closure}}
+   3: services::services::container::ContainerService::stream_normalized_logs::{{closure}}
+             at ./crates/services/src/services/container.rs:580:36
+   4: tokio::runtime::task::harness::poll";
+        assert_eq!(
+            handout_site(backtrace),
+            "services::services::container::ContainerService::stream_normalized_logs"
+        );
+        assert_eq!(handout_site("   0: tokio::runtime::park"), "unknown");
+    }
+}
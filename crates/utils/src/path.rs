@@ -81,6 +81,37 @@ pub fn make_path_relative(path: &str, worktree_path: &str) -> String {
     }
 }
 
+/// Use `/` as the separator regardless of platform, so repo-relative paths from
+/// git and from the filesystem (backslashes on Windows) compare equal.
+pub fn normalize_path_separators(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// Normalize a directory inside a repository to a relative, forward-slash path,
+/// e.g. `./packages\web/` becomes `packages/web`. Returns `None` if the path is
+/// empty, absolute, or escapes the repository.
+pub fn normalize_repo_subpath(subpath: &str) -> Option<String> {
+    let unified = normalize_path_separators(subpath.trim());
+    if unified.starts_with('/') || Path::new(&unified).is_absolute() {
+        return None;
+    }
+
+    let mut parts = Vec::new();
+    for part in unified.split('/') {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            part => parts.push(part),
+        }
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
 /// Normalize macOS prefix /private/var/ and /private/tmp/ to their public aliases without resolving paths.
 /// This allows prefix normalization to work when the full paths don't exist.
 fn normalize_macos_private_alias<P: AsRef<Path>>(p: P) -> PathBuf {
@@ -153,6 +184,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_repo_subpath() {
+        assert_eq!(
+            normalize_repo_subpath("packages/web").as_deref(),
+            Some("packages/web")
+        );
+        assert_eq!(
+            normalize_repo_subpath("./packages\\web/").as_deref(),
+            Some("packages/web")
+        );
+        assert_eq!(normalize_repo_subpath("."), None);
+        assert_eq!(normalize_repo_subpath("/etc"), None);
+        assert_eq!(normalize_repo_subpath("packages/../../other"), None);
+    }
+
     #[cfg(target_os = "macos")]
     #[test]
     fn test_make_path_relative_macos_private_alias() {
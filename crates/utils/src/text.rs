@@ -2,6 +2,11 @@ use regex::Regex;
 use uuid::Uuid;
 
 pub fn git_branch_id(input: &str) -> String {
+    git_branch_slug(input, 10)
+}
+
+/// Lowercase, hyphen-separated slug of `input`, at most `max_chars` characters long
+pub fn git_branch_slug(input: &str, max_chars: usize) -> String {
     // 1. lowercase
     let lower = input.to_lowercase();
 
@@ -12,8 +17,8 @@ pub fn git_branch_id(input: &str) -> String {
     // 3. trim extra hyphens
     let trimmed = slug.trim_matches('-');
 
-    // 4. take up to 10 chars, then trim trailing hyphens again
-    let cut: String = trimmed.chars().take(10).collect();
+    // 4. take up to max_chars chars, then trim trailing hyphens again
+    let cut: String = trimmed.chars().take(max_chars).collect();
     cut.trim_end_matches('-').to_string()
 }
 
@@ -22,3 +27,100 @@ pub fn short_uuid(u: &Uuid) -> String {
     let full = u.simple().to_string();
     full.chars().take(4).collect() // grab the first 4 chars
 }
+
+/// Longest prefix of `s` that fits in `max_bytes` without splitting a UTF-8 character
+pub fn truncate_to_char_boundary(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Replace `{name}` placeholders in `template` with `lookup(name)`. `{{` and `}}` stand
+/// for literal braces. Fails on a name `lookup` doesn't know or on an unbalanced brace.
+pub fn expand_placeholders(
+    template: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("Unclosed placeholder '{{{name}'")),
+                    }
+                }
+                let value =
+                    lookup(&name).ok_or_else(|| format!("Unknown placeholder '{{{name}}}'"))?;
+                out.push_str(&value);
+            }
+            '}' => return Err("Unmatched '}' (use '}}' for a literal brace)".to_string()),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_placeholders_substitutes_and_unescapes_braces() {
+        let lookup = |name: &str| (name == "title").then(|| "Fix login".to_string());
+        assert_eq!(
+            expand_placeholders("Task: {title} {{literal}}", lookup),
+            Ok("Task: Fix login {literal}".to_string())
+        );
+        assert_eq!(
+            expand_placeholders("{title} and {author}", lookup),
+            Err("Unknown placeholder '{author}'".to_string())
+        );
+        assert!(expand_placeholders("{title", lookup).is_err());
+        assert!(expand_placeholders("title}", lookup).is_err());
+    }
+
+    #[test]
+    fn truncate_to_char_boundary_keeps_short_strings() {
+        assert_eq!(truncate_to_char_boundary("hello", 10), "hello");
+        assert_eq!(truncate_to_char_boundary("hello", 5), "hello");
+        assert_eq!(truncate_to_char_boundary("hello", 3), "hel");
+    }
+
+    #[test]
+    fn truncate_to_char_boundary_does_not_split_multibyte_chars() {
+        // "é" is 2 bytes, so byte 4 falls inside the third character
+        let s = "ééé";
+        assert_eq!(truncate_to_char_boundary(s, 4), "éé");
+        assert_eq!(truncate_to_char_boundary(s, 5), "éé");
+        assert_eq!(truncate_to_char_boundary(s, 1), "");
+    }
+
+    #[test]
+    fn truncate_emoji_summary_at_default_cap() {
+        // 1 + 1024 * 4 = 4097 bytes: byte 4096 falls inside the last emoji
+        let summary = format!("a{}", "🚀".repeat(1024));
+        assert_eq!(summary.len(), 4097);
+
+        let truncated = truncate_to_char_boundary(&summary, 4096);
+        assert_eq!(truncated.len(), 4093);
+        assert!(truncated.ends_with('🚀'));
+    }
+}
@@ -50,10 +50,15 @@ export default function DiffCard({
 
   const oldName = diff.oldPath || undefined;
   const newName = diff.newPath || oldName || 'unknown';
+  // Prefer the server's hint; older servers don't send one
   const oldLang =
-    getHighLightLanguageFromPath(oldName || newName || '') || 'plaintext';
+    diff.language ||
+    getHighLightLanguageFromPath(oldName || newName || '') ||
+    'plaintext';
   const newLang =
-    getHighLightLanguageFromPath(newName || oldName || '') || 'plaintext';
+    diff.language ||
+    getHighLightLanguageFromPath(newName || oldName || '') ||
+    'plaintext';
   const { label, Icon } = labelAndIcon(diff);
 
   // Build a diff from raw contents so the viewer can expand beyond hunks
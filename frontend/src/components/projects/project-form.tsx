@@ -91,6 +91,7 @@ export function ProjectForm({
         dev_script: null,
         cleanup_script: null,
         copy_files: null,
+        dev_server_url_pattern: null,
       };
 
       await projectsApi.create(createData);
@@ -138,6 +139,7 @@ export function ProjectForm({
           dev_script: devScript.trim() || null,
           cleanup_script: cleanupScript.trim() || null,
           copy_files: copyFiles.trim() || null,
+          dev_server_url_pattern: project.dev_server_url_pattern,
         };
 
         await projectsApi.update(project.id, updateData);
@@ -170,6 +172,7 @@ export function ProjectForm({
           dev_script: null,
           cleanup_script: null,
           copy_files: null,
+          dev_server_url_pattern: null,
         };
 
         await projectsApi.create(createData);
@@ -5,6 +5,7 @@ import { Button } from '@/components/ui/button';
 import DiffCard from '@/components/DiffCard';
 import { useDiffSummary } from '@/hooks/useDiffSummary';
 import type { TaskAttempt } from 'shared/types';
+import { attemptsApi } from '@/lib/api';
 
 interface DiffTabProps {
   selectedAttempt: TaskAttempt | null;
@@ -24,7 +25,7 @@ function DiffTab({ selectedAttempt }: DiffTabProps) {
     }
   }, [diffs, loading]);
 
-  // Default-collapse certain change kinds on first load
+  // Default-collapse some change kinds and generated files on first load
   useEffect(() => {
     if (diffs.length === 0) return;
     if (collapsedIds.size > 0) return; // preserve user toggles if any
@@ -36,7 +37,7 @@ function DiffTab({ selectedAttempt }: DiffTabProps) {
     ]);
     const initial = new Set(
       diffs
-        .filter((d) => kindsToCollapse.has(d.change))
+        .filter((d) => kindsToCollapse.has(d.change) || d.generated)
         .map((d, i) => d.newPath || d.oldPath || String(i))
     );
     if (initial.size > 0) setCollapsedIds(initial);
@@ -59,6 +60,13 @@ function DiffTab({ selectedAttempt }: DiffTabProps) {
     setCollapsedIds(allCollapsed ? new Set() : new Set(ids));
   }, [allCollapsed, ids]);
 
+  const handleRefresh = useCallback(() => {
+    if (!selectedAttempt) return;
+    attemptsApi.refreshDiff(selectedAttempt.id).catch((err) => {
+      console.error('Failed to refresh diff:', err);
+    });
+  }, [selectedAttempt]);
+
   if (error) {
     return (
       <div className="bg-red-50 border border-red-200 rounded-lg p-4 m-4">
@@ -93,14 +101,20 @@ function DiffTab({ selectedAttempt }: DiffTabProps) {
                 -{deleted}
               </span>
             </span>
-            <Button
-              variant="outline"
-              size="xs"
-              onClick={handleCollapseAll}
-              className="shrink-0"
-            >
-              {allCollapsed ? 'Expand All' : 'Collapse All'}
-            </Button>
+            <div className="flex items-center gap-2 shrink-0">
+              <Button
+                variant="outline"
+                size="xs"
+                onClick={handleRefresh}
+                disabled={!selectedAttempt}
+                title="Recompute the diff if changes are missing"
+              >
+                Refresh
+              </Button>
+              <Button variant="outline" size="xs" onClick={handleCollapseAll}>
+                {allCollapsed ? 'Expand All' : 'Collapse All'}
+              </Button>
+            </div>
           </div>
         </div>
       )}
@@ -36,6 +36,8 @@ function ProcessCard({ process }: ProcessCardProps) {
 
   const getStatusIcon = (status: ExecutionProcessStatus) => {
     switch (status) {
+      case 'queued':
+        return <Clock className="h-4 w-4 text-blue-400" />;
       case 'running':
         return <Play className="h-4 w-4 text-blue-500" />;
       case 'completed':
@@ -30,6 +30,8 @@ function ProcessesTab({ attemptId }: ProcessesTabProps) {
 
   const getStatusIcon = (status: ExecutionProcessStatus) => {
     switch (status) {
+      case 'queued':
+        return <Clock className="h-4 w-4 text-blue-400" />;
       case 'running':
         return <Play className="h-4 w-4 text-blue-500" />;
       case 'completed':
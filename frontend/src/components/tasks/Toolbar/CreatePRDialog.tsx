@@ -54,7 +54,7 @@ function CreatePrDialog() {
     setCreatingPR(true);
 
     const result = await attemptsApi.createPR(data.attempt.id, {
-      title: prTitle,
+      title: prTitle || null,
       body: prBody || null,
       base_branch: prBaseBranch || null,
     });
@@ -68,7 +68,9 @@ function CreatePrDialog() {
       setPrBaseBranch('main');
       closeCreatePRDialog();
     } else {
-      if (result.error) {
+      if (result.error === GitHubServiceError.PULL_REQUEST_EXISTS) {
+        setError('A pull request already exists for this branch.');
+      } else if (result.error) {
         closeCreatePRDialog();
         switch (result.error) {
           case GitHubServiceError.TOKEN_INVALID:
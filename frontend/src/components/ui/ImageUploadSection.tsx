@@ -60,8 +60,6 @@ export function ImageUploadSection({
         'image/jpg',
         'image/gif',
         'image/webp',
-        'image/bmp',
-        'image/svg+xml',
       ];
 
       const invalidFiles: string[] = [];
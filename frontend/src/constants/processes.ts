@@ -15,6 +15,7 @@ export const PROCESS_RUN_REASONS = {
 
 // Process statuses
 export const PROCESS_STATUSES = {
+  QUEUED: 'queued' as ExecutionProcessStatus,
   RUNNING: 'running' as ExecutionProcessStatus,
   COMPLETED: 'completed' as ExecutionProcessStatus,
   FAILED: 'failed' as ExecutionProcessStatus,
@@ -21,6 +21,8 @@ export function useAttemptCreation(taskId: string) {
         task_id: taskId,
         executor_profile_id: profile,
         base_branch: baseBranch,
+        subpath: null,
+        scratch: null,
       }),
     onSuccess: (newAttempt: TaskAttempt) => {
       // Optimistically add to cache to prevent UI flicker
@@ -27,7 +27,7 @@ export function useAttemptExecution(attemptId?: string, taskId?: string) {
           (process.run_reason === 'codingagent' ||
             process.run_reason === 'setupscript' ||
             process.run_reason === 'cleanupscript') &&
-          process.status === 'running'
+          (process.status === 'running' || process.status === 'queued')
       ),
     }),
   });
@@ -1,9 +1,22 @@
 import { useCallback } from 'react';
-import type { PatchType } from 'shared/types';
+import type {
+  AttemptComparison,
+  CommitMetadata,
+  PatchType,
+} from 'shared/types';
 import { useJsonPatchStream } from './useJsonPatchStream';
 
 interface DiffState {
   entries: Record<string, PatchType>;
+  // Set for merged attempts: the merge commit the diff is taken from
+  commit?: CommitMetadata;
+  // Set when work continued after a merge: the already merged diff, keyed like
+  // `entries`, which then only holds what is new since the merge
+  merged?: Record<string, PatchType>;
+  // Merge commit marking the boundary between `merged` and `entries`
+  sinceMerge?: string;
+  // Set when comparing two attempts: both branch tips and their merge base
+  comparison?: AttemptComparison;
 }
 
 interface UseDiffStreamResult {
@@ -14,10 +27,14 @@ interface UseDiffStreamResult {
 
 export const useDiffStream = (
   attemptId: string | null,
-  enabled: boolean
+  enabled: boolean,
+  // Show a single commit on the attempt branch instead of the attempt diff
+  commitSha?: string
 ): UseDiffStreamResult => {
   const endpoint = attemptId
-    ? `/api/task-attempts/${attemptId}/diff`
+    ? commitSha
+      ? `/api/task-attempts/${attemptId}/commit-diff?sha=${encodeURIComponent(commitSha)}`
+      : `/api/task-attempts/${attemptId}/diff`
     : undefined;
 
   const initialData = useCallback(
@@ -36,3 +53,30 @@ export const useDiffStream = (
 
   return { data, isConnected, error };
 };
+
+// Diff from one attempt's branch tip to another attempt of the same task
+export const useAttemptComparisonStream = (
+  attemptId: string | null,
+  otherAttemptId: string | null,
+  enabled: boolean
+): UseDiffStreamResult => {
+  const endpoint =
+    attemptId && otherAttemptId
+      ? `/api/task-attempts/${attemptId}/compare?with=${otherAttemptId}`
+      : undefined;
+
+  const initialData = useCallback(
+    (): DiffState => ({
+      entries: {},
+    }),
+    []
+  );
+
+  const { data, isConnected, error } = useJsonPatchStream(
+    endpoint,
+    enabled && !!endpoint,
+    initialData
+  );
+
+  return { data, isConnected, error };
+};
@@ -86,12 +86,20 @@ export const useJsonPatchStream = <T>(
 
           // React re-render: dataRef.current is already a new object
           setData(dataRef.current);
+          // An update got through, so an earlier recoverable error no longer applies.
+          // Fatal errors end the stream, so no update follows them.
+          setError(null);
         } catch (err) {
           console.error('Failed to apply JSON patch:', err);
           setError('Failed to process stream update');
         }
       });
 
+      // The server explains why it stopped (or skipped) an update
+      eventSource.addEventListener('stream_error', (event) => {
+        setError((event as MessageEvent).data || 'Stream error');
+      });
+
       eventSource.addEventListener('finished', () => {
         eventSource.close();
         eventSourceRef.current = null;
@@ -99,7 +107,8 @@ export const useJsonPatchStream = <T>(
       });
 
       eventSource.onerror = () => {
-        setError('Connection failed');
+        // Keep a reason the server already sent over the generic one
+        setError((prev) => prev ?? 'Connection failed');
         eventSourceRef.current = null;
         setIsConnected(false);
       };
@@ -1,33 +1,55 @@
 // Import all necessary types from shared types
 
 import {
+  AbortedOperation,
   ApiResponse,
+  AttemptBranchStatus,
+  AttemptDiffStats,
   BranchStatus,
   CheckTokenResponse,
   Config,
   CommitInfo,
+  CommitMetadata,
   CreateFollowUpAttempt,
   CreateGitHubPrRequest,
-  CreateTask,
+  CreateTaskRequest,
+  CreatedTask,
   CreateTaskAttemptBody,
   CreateTaskTemplate,
   DeviceFlowStartResponse,
   DevicePollStatus,
+  DevServerStatus,
   DirectoryListResponse,
   DirectoryEntry,
   EditorType,
+  ExecutionPreview,
   ExecutionProcess,
+  ExecutionProcessRunReason,
+  ExecutionProcessStatus,
+  ExecutionTimelineEntry,
+  ExecutorAction,
+  ExecutorSession,
   GitBranch,
   Project,
   CreateProject,
+  ProjectExecutionCount,
+  PromoteAttemptRequest,
+  RateLimitStatus,
   RebaseTaskAttemptRequest,
+  RevertMergeRequest,
   RepositoryInfo,
   SearchResult,
+  SetSummaryRequest,
   Task,
   TaskAttempt,
+  TaskAttemptWithBranchStatus,
   TaskTemplate,
   TaskWithAttemptStatus,
+  TokenUsage,
+  UpdateAgentInstructions,
+  UpdateConcurrencyLimit,
   UpdateProject,
+  UpdatePromptTemplate,
   UpdateTask,
   UpdateTaskTemplate,
   UserSystemInfo,
@@ -38,6 +60,7 @@ import {
   ImageResponse,
   RestoreAttemptRequest,
   RestoreAttemptResult,
+  WorktreeUsage,
 } from 'shared/types';
 
 // Re-export types for convenience
@@ -211,6 +234,75 @@ export const projectsApi = {
     return handleApiResponse<Project>(response);
   },
 
+  updateExecutorOverrides: async (
+    id: string,
+    overrides: Project['executor_overrides']
+  ): Promise<Project> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/executor-overrides`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(overrides),
+      }
+    );
+    return handleApiResponse<Project>(response);
+  },
+
+  updateEnvVars: async (
+    id: string,
+    envVars: Project['env_vars']
+  ): Promise<Project> => {
+    const response = await makeRequest(`/api/projects/${id}/env-vars`, {
+      method: 'PUT',
+      body: JSON.stringify(envVars),
+    });
+    return handleApiResponse<Project>(response);
+  },
+
+  updatePromptTemplate: async (
+    id: string,
+    data: UpdatePromptTemplate
+  ): Promise<Project> => {
+    const response = await makeRequest(`/api/projects/${id}/prompt-template`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Project>(response);
+  },
+
+  updateAgentInstructions: async (
+    id: string,
+    data: UpdateAgentInstructions
+  ): Promise<Project> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/agent-instructions`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<Project>(response);
+  },
+
+  updateConcurrencyLimit: async (
+    id: string,
+    data: UpdateConcurrencyLimit
+  ): Promise<Project> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/concurrency-limit`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<Project>(response);
+  },
+
+  getExecutionCounts: async (): Promise<ProjectExecutionCount[]> => {
+    const response = await makeRequest('/api/projects/execution-counts');
+    return handleApiResponse<ProjectExecutionCount[]>(response);
+  },
+
   delete: async (id: string): Promise<void> => {
     const response = await makeRequest(`/api/projects/${id}`, {
       method: 'DELETE',
@@ -258,15 +350,15 @@ export const tasksApi = {
     return handleApiResponse<Task>(response);
   },
 
-  create: async (data: CreateTask): Promise<Task> => {
+  create: async (data: CreateTaskRequest): Promise<CreatedTask> => {
     const response = await makeRequest(`/api/tasks`, {
       method: 'POST',
       body: JSON.stringify(data),
     });
-    return handleApiResponse<Task>(response);
+    return handleApiResponse<CreatedTask>(response);
   },
 
-  createAndStart: async (data: CreateTask): Promise<TaskWithAttemptStatus> => {
+  createAndStart: async (data: CreateTaskRequest): Promise<TaskWithAttemptStatus> => {
     const response = await makeRequest(`/api/tasks/create-and-start`, {
       method: 'POST',
       body: JSON.stringify(data),
@@ -299,9 +391,16 @@ export const attemptsApi = {
     return handleApiResponse<Task[]>(response);
   },
 
-  getAll: async (taskId: string): Promise<TaskAttempt[]> => {
+  getAll: async (taskId: string): Promise<TaskAttemptWithBranchStatus[]> => {
     const response = await makeRequest(`/api/task-attempts?task_id=${taskId}`);
-    return handleApiResponse<TaskAttempt[]>(response);
+    return handleApiResponse<TaskAttemptWithBranchStatus[]>(response);
+  },
+
+  getDiffStats: async (taskId: string): Promise<AttemptDiffStats[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/diff-stats?task_id=${taskId}`
+    );
+    return handleApiResponse<AttemptDiffStats[]>(response);
   },
 
   create: async (data: CreateTaskAttemptBody): Promise<TaskAttempt> => {
@@ -396,6 +495,13 @@ export const attemptsApi = {
     return handleApiResponse<BranchStatus>(response);
   },
 
+  getAheadBehind: async (attemptId: string): Promise<AttemptBranchStatus> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/ahead-behind`
+    );
+    return handleApiResponse<AttemptBranchStatus>(response);
+  },
+
   merge: async (attemptId: string): Promise<void> => {
     const response = await makeRequest(
       `/api/task-attempts/${attemptId}/merge`,
@@ -406,6 +512,48 @@ export const attemptsApi = {
     return handleApiResponse<void>(response);
   },
 
+  promote: async (
+    attemptId: string,
+    data: PromoteAttemptRequest
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/promote`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  previewExecution: async (
+    attemptId: string,
+    action: ExecutorAction
+  ): Promise<ExecutionPreview> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/preview-execution`,
+      {
+        method: 'POST',
+        body: JSON.stringify(action),
+      }
+    );
+    return handleApiResponse<ExecutionPreview>(response);
+  },
+
+  revertMerge: async (
+    attemptId: string,
+    data: RevertMergeRequest
+  ): Promise<string> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/revert-merge`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<string>(response);
+  },
+
   push: async (attemptId: string): Promise<void> => {
     const response = await makeRequest(`/api/task-attempts/${attemptId}/push`, {
       method: 'POST',
@@ -427,6 +575,23 @@ export const attemptsApi = {
     return handleApiResponse<void>(response);
   },
 
+  // Re-send the full diff on open diff streams, for changes the watcher missed
+  refreshDiff: async (attemptId: string): Promise<boolean> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/diff/refresh`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<boolean>(response);
+  },
+
+  abortOperation: async (attemptId: string): Promise<AbortedOperation> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/abort-operation`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<AbortedOperation>(response);
+  },
+
   createPR: async (
     attemptId: string,
     data: CreateGitHubPrRequest
@@ -438,6 +603,26 @@ export const attemptsApi = {
     return handleApiResponseAsResult<string, GitHubServiceError>(response);
   },
 
+  retry: async (attemptId: string): Promise<TaskAttempt> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/retry`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<TaskAttempt>(response);
+  },
+
+  runSetupScript: async (attemptId: string): Promise<ExecutionProcess> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/setup-script`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<ExecutionProcess>(response);
+  },
+
   startDevServer: async (attemptId: string): Promise<void> => {
     const response = await makeRequest(
       `/api/task-attempts/${attemptId}/start-dev-server`,
@@ -447,6 +632,42 @@ export const attemptsApi = {
     );
     return handleApiResponse<void>(response);
   },
+
+  stopDevServer: async (attemptId: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/stop-dev-server`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  getDevServerStatus: async (attemptId: string): Promise<DevServerStatus> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/dev-server`
+    );
+    return handleApiResponse<DevServerStatus>(response);
+  },
+
+  getUsage: async (attemptId: string): Promise<TokenUsage> => {
+    const response = await makeRequest(`/api/task-attempts/${attemptId}/usage`);
+    return handleApiResponse<TokenUsage>(response);
+  },
+
+  getSessions: async (attemptId: string): Promise<ExecutorSession[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/sessions`
+    );
+    return handleApiResponse<ExecutorSession[]>(response);
+  },
+
+  getTimeline: async (attemptId: string): Promise<ExecutionTimelineEntry[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/timeline`
+    );
+    return handleApiResponse<ExecutionTimelineEntry[]>(response);
+  },
 };
 
 // Extra helpers
@@ -476,6 +697,27 @@ export const commitsApi = {
     );
     return handleApiResponse(response);
   },
+  list: async (attemptId: string): Promise<CommitMetadata[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/commits`
+    );
+    return handleApiResponse<CommitMetadata[]>(response);
+  },
+};
+
+// Worktree APIs
+export const worktreesApi = {
+  getUsage: async (): Promise<WorktreeUsage> => {
+    const response = await makeRequest('/api/worktrees/usage');
+    return handleApiResponse<WorktreeUsage>(response);
+  },
+
+  pruneMerged: async (): Promise<string[]> => {
+    const response = await makeRequest('/api/worktrees/prune-merged', {
+      method: 'POST',
+    });
+    return handleApiResponse<string[]>(response);
+  },
 };
 
 // Execution Process APIs
@@ -489,6 +731,25 @@ export const executionProcessesApi = {
     return handleApiResponse<ExecutionProcess[]>(response);
   },
 
+  getHistory: async (
+    attemptId: string,
+    filters: {
+      status?: ExecutionProcessStatus;
+      run_reason?: ExecutionProcessRunReason;
+      limit?: number;
+      offset?: number;
+    } = {}
+  ): Promise<ExecutionTimelineEntry[]> => {
+    const params = new URLSearchParams({ task_attempt_id: attemptId });
+    for (const [key, value] of Object.entries(filters)) {
+      if (value !== undefined) params.set(key, String(value));
+    }
+    const response = await makeRequest(
+      `/api/execution-processes/history?${params.toString()}`
+    );
+    return handleApiResponse<ExecutionTimelineEntry[]>(response);
+  },
+
   getDetails: async (processId: string): Promise<ExecutionProcess> => {
     const response = await makeRequest(`/api/execution-processes/${processId}`);
     return handleApiResponse<ExecutionProcess>(response);
@@ -503,6 +764,27 @@ export const executionProcessesApi = {
     );
     return handleApiResponse<void>(response);
   },
+
+  getUsage: async (processId: string): Promise<TokenUsage | null> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/usage`
+    );
+    return handleApiResponse<TokenUsage | null>(response);
+  },
+
+  setSummary: async (
+    processId: string,
+    data: SetSummaryRequest
+  ): Promise<ExecutorSession> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/summary`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ExecutorSession>(response);
+  },
 };
 
 // File System APIs
@@ -537,6 +819,10 @@ export const configApi = {
     });
     return handleApiResponse<Config>(response);
   },
+  getRateLimits: async (): Promise<RateLimitStatus[]> => {
+    const response = await makeRequest('/api/rate-limits');
+    return handleApiResponse<RateLimitStatus[]>(response);
+  },
 };
 
 // GitHub Device Auth APIs
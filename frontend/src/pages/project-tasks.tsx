@@ -77,8 +77,12 @@ export function ProjectTasks() {
       const found = attempts.find((a) => a.id === attemptId);
       if (found) return found;
     }
-    return attempts[0] || null; // Most recent fallback
-  }, [attempts, attemptId]);
+    // Fall back to the promoted attempt, then the most recent one
+    const preferred = attempts.find(
+      (a) => a.id === selectedTask?.preferred_attempt_id
+    );
+    return preferred || attempts[0] || null;
+  }, [attempts, attemptId, selectedTask?.preferred_attempt_id]);
 
   // Navigation callback for attempt selection
   const setSelectedAttempt = useCallback(
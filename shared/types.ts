@@ -8,13 +8,60 @@ export type DirectoryEntry = { name: string, path: string, is_directory: boolean
 
 export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
 
-export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, created_at: Date, updated_at: Date, };
+export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, 
+/**
+ * Regex used to find the dev server URL in its output; the first capture
+ * group (or the whole match) is taken as the URL
+ */
+dev_server_url_pattern: string | null, 
+/**
+ * Extra params and environment per executor, applied when it is spawned
+ */
+executor_overrides: { [key in BaseCodingAgent]?: ExecutorOverride }, 
+/**
+ * Environment variables set for every execution in the project. Executor-specific
+ * env takes precedence; these take precedence over the host environment.
+ */
+env_vars: { [key in string]?: string }, 
+/**
+ * Template for agent prompts; see `PROMPT_TEMPLATE_VARIABLES`. Unset means the
+ * default task prompt.
+ */
+prompt_template: string | null, 
+/**
+ * Standing instructions for coding agents (coding standards, forbidden actions).
+ * Sent as a system prompt where the agent has one, otherwise ahead of the prompt.
+ */
+agent_instructions: string | null, 
+/**
+ * Most coding agents of this project that may run at once, within the global
+ * limit. Unset means only the global limit applies.
+ */
+max_concurrent_executions: number | null, created_at: Date, updated_at: Date, };
 
-export type ProjectWithBranch = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, current_branch: string | null, created_at: Date, updated_at: Date, };
+export type ProjectWithBranch = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, dev_server_url_pattern: string | null, executor_overrides: { [key in BaseCodingAgent]?: ExecutorOverride }, env_vars: { [key in string]?: string }, prompt_template: string | null, agent_instructions: string | null, max_concurrent_executions: number | null, current_branch: string | null, created_at: Date, updated_at: Date, };
 
-export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, };
+export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, dev_server_url_pattern: string | null, };
 
-export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, };
+export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, dev_server_url_pattern: string | null, };
+
+export type UpdatePromptTemplate = { 
+/**
+ * `None` or an empty template restores the default prompt
+ */
+prompt_template: string | null, };
+
+export type UpdateAgentInstructions = { 
+/**
+ * `None` or empty text removes the instructions
+ */
+agent_instructions: string | null, };
+
+export type UpdateConcurrencyLimit = { 
+/**
+ * `None` leaves the project under the global limit only
+ */
+max_concurrent_executions: number | null, };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, };
 
@@ -24,6 +71,20 @@ export type SearchMode = "taskform" | "settings";
 
 export type ExecutorAction = { typ: ExecutorActionType, next_action: ExecutorAction | null, };
 
+export type ExecutionPreview = { 
+/**
+ * Command line handed to the platform shell
+ */
+command: string, working_dir: string, 
+/**
+ * Environment set on top of the inherited one, with values masked
+ */
+env: { [key in string]?: string }, 
+/**
+ * Text fed to the process on stdin
+ */
+prompt: string | null, };
+
 export type McpConfig = { servers: { [key in string]?: JsonValue }, servers_path: Array<string>, template: JsonValue, vibe_kanban: JsonValue, is_toml_config: boolean, };
 
 export type ExecutorActionType = { "type": "CodingAgentInitialRequest" } & CodingAgentInitialRequest | { "type": "CodingAgentFollowUpRequest" } & CodingAgentFollowUpRequest | { "type": "ScriptRequest" } & ScriptRequest | { "type": "BrowserChatRequest" } & BrowserChatRequest;
@@ -46,9 +107,19 @@ export type UpdateTaskTemplate = { title: string | null, description: string | n
 
 export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
 
-export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, };
+export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, 
+/**
+ * Attempt promoted as the canonical one, which the board and new work default to
+ */
+preferred_attempt_id: string | null, created_at: string, updated_at: string, };
 
-export type TaskWithAttemptStatus = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, executor: string, };
+export type TaskWithAttemptStatus = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, preferred_attempt_id: string | null, created_at: string, updated_at: string, has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, 
+/**
+ * Executor of the active attempt
+ */
+executor: string, };
+
+export type TaskEvent = { "event": "task.status_changed", task: Task, old_status: TaskStatus, new_status: TaskStatus, };
 
 export type CreateTask = { project_id: string, title: string, description: string | null, parent_task_attempt: string | null, image_ids: Array<string> | null, };
 
@@ -76,13 +147,142 @@ export type GetMcpServerResponse = { mcp_config: McpConfig, config_path: string,
 
 export type CreateFollowUpAttempt = { prompt: string, variant: string | null, image_ids: Array<string> | null, };
 
-export type CreateGitHubPrRequest = { title: string, body: string | null, base_branch: string | null, };
+export type CreateGitHubPrRequest = { 
+/**
+ * Defaults to the task title
+ */
+title: string | null, 
+/**
+ * Defaults to the latest agent summary, then the task description
+ */
+body: string | null, base_branch: string | null, };
 
 export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
 
-export enum GitHubServiceError { TOKEN_INVALID = "TOKEN_INVALID", INSUFFICIENT_PERMISSIONS = "INSUFFICIENT_PERMISSIONS", REPO_NOT_FOUND_OR_NO_ACCESS = "REPO_NOT_FOUND_OR_NO_ACCESS" }
+export enum GitHubServiceError { TOKEN_INVALID = "TOKEN_INVALID", INSUFFICIENT_PERMISSIONS = "INSUFFICIENT_PERMISSIONS", REPO_NOT_FOUND_OR_NO_ACCESS = "REPO_NOT_FOUND_OR_NO_ACCESS", PULL_REQUEST_EXISTS = "PULL_REQUEST_EXISTS" }
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, telemetry_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean | null, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, telemetry_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, gitlab: GitLabConfig, analytics_enabled: boolean | null, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, 
+/**
+ * Largest image, in bytes, accepted for task attachments. Read at startup, so a
+ * change applies after the server restarts.
+ */
+image_max_size_bytes: bigint, 
+/**
+ * Don't commit after a cleanup script that left tracked files unchanged
+ */
+skip_noop_cleanup_commits: boolean, 
+/**
+ * Longest final agent message, in bytes, kept as the summary/commit message
+ */
+execution_summary_max_bytes: number, 
+/**
+ * Kill setup scripts that run longer than this many seconds (0 = no limit)
+ */
+setup_script_timeout_secs: bigint, 
+/**
+ * Kill cleanup scripts that run longer than this many seconds (0 = no limit)
+ */
+cleanup_script_timeout_secs: bigint, 
+/**
+ * Stop coding agents that run longer than this many seconds (0 = no limit)
+ */
+coding_agent_timeout_secs: bigint, 
+/**
+ * Seconds an SSE stream may stay idle before a keep-alive is sent
+ */
+sse_keepalive_interval_secs: bigint, 
+/**
+ * Seconds to wait after SIGINT/SIGTERM before escalating when stopping a process
+ */
+stop_grace_period_secs: bigint, 
+/**
+ * Maximum number of commits returned when listing an attempt's commits
+ */
+attempt_commits_limit: number, 
+/**
+ * Evict the oldest idle worktrees once all worktrees together use more
+ * than this many bytes (0 = no limit)
+ */
+worktree_max_disk_bytes: bigint, 
+/**
+ * Delete persisted logs of processes that finished more than this many
+ * days ago (0 = keep forever)
+ */
+log_retention_days: number, 
+/**
+ * Refuse to create more attempts for a task once it has this many whose
+ * worktree hasn't been deleted (unset = unlimited)
+ */
+max_attempts_per_task: number | null, 
+/**
+ * How to handle uncommitted changes (e.g. manual edits) in a worktree
+ * when a coding agent is about to run in it
+ */
+on_dirty_worktree: DirtyWorktreePolicy, 
+/**
+ * Sign agent and merge commits; fail instead of committing unsigned
+ */
+commit_sign: boolean, 
+/**
+ * Key to sign with (GPG key id, SSH key path, ...); falls back to git's
+ * `user.signingkey` when unset
+ */
+commit_signing_key: string | null, commit_signing_format: CommitSigningFormat, 
+/**
+ * URL that receives a JSON POST for every task event, e.g. `task.status_changed`
+ */
+webhook_url: string | null, 
+/**
+ * Coding agent starts allowed per minute, keyed by executor (`CLAUDE_CODE`) or
+ * profile variant (`CLAUDE_CODE:PLAN`). Starts over the limit wait for their turn.
+ */
+executor_rate_limits: { [key in string]?: number }, 
+/**
+ * Automatic re-runs of coding agents that hit a transient provider error
+ */
+agent_retry: AgentRetryConfig, 
+/**
+ * Naming scheme for attempt worktrees and branches
+ */
+worktree_naming: WorktreeNamingConfig, 
+/**
+ * Fetch the project's remote before creating an attempt worktree, so attempts start
+ * from the latest base branch. Falls back to the local ref when the fetch fails.
+ */
+fetch_before_create: boolean, 
+/**
+ * How live diffs watch worktrees for changes
+ */
+filesystem_watcher: FilesystemWatcherConfig, 
+/**
+ * Record where each execution's log store is handed out, so a store still held
+ * after its process finished can be traced to its holders. Debugging aid, off by default.
+ */
+track_msg_store_holders: boolean, 
+/**
+ * Most coding agents that may run at once across all projects; further starts
+ * queue until one finishes. Unset or zero means no limit.
+ */
+max_concurrent_executions: number | null, 
+/**
+ * Free disk space required in the worktree directory before creating a worktree,
+ * so a nearly full disk fails up front instead of partway through. Zero disables.
+ */
+worktree_min_free_bytes: bigint, 
+/**
+ * Remove an attempt's worktree as soon as its PR is merged instead of waiting for
+ * it to expire. Direct merges always remove the worktree.
+ */
+prune_worktrees_after_merge: boolean, 
+/**
+ * Kill browser chat automations that run longer than this, e.g. stuck on a login wall
+ */
+browser_chat_timeouts: BrowserChatTimeoutConfig, 
+/**
+ * Node.js binary for browser chat automation, a name on PATH or a full path (e.g.
+ * under nvm or volta). Unset uses `node` from PATH.
+ */
+browser_chat_node_path: string | null, };
 
 export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
 
@@ -94,8 +294,97 @@ export enum EditorType { VS_CODE = "VS_CODE", CURSOR = "CURSOR", WINDSURF = "WIN
 
 export type GitHubConfig = { pat: string | null, oauth_token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, };
 
+export type GitLabConfig = { 
+/**
+ * Personal or project access token with the `api` scope
+ */
+token: string | null, 
+/**
+ * Base URL of a self-hosted instance (defaults to https://gitlab.com)
+ */
+base_url: string | null, };
+
 export enum SoundFile { ABSTRACT_SOUND1 = "ABSTRACT_SOUND1", ABSTRACT_SOUND2 = "ABSTRACT_SOUND2", ABSTRACT_SOUND3 = "ABSTRACT_SOUND3", ABSTRACT_SOUND4 = "ABSTRACT_SOUND4", COW_MOOING = "COW_MOOING", PHONE_VIBRATION = "PHONE_VIBRATION", ROOSTER = "ROOSTER" }
 
+export type DirtyWorktreePolicy = "REFUSE" | "STASH" | "IGNORE";
+
+export type CommitSigningFormat = "OPENPGP" | "SSH" | "X509";
+
+export type AgentRetryConfig = { 
+/**
+ * Re-runs allowed per action (0 = never retry)
+ */
+max_retries: number, 
+/**
+ * Seconds before the first re-run; doubles with every further one
+ */
+backoff_secs: bigint, 
+/**
+ * Provider error types reported in the agent's JSON output that mark a failure as
+ * retryable, e.g. `overloaded_error`
+ */
+error_types: Array<string>, 
+/**
+ * HTTP statuses of provider errors in the agent's JSON output that mark a failure
+ * as retryable
+ */
+status_codes: Array<number>, 
+/**
+ * Exit codes that mark a failure as retryable
+ */
+exit_codes: Array<bigint>, };
+
+export type WorktreeNamingConfig = { 
+/**
+ * Name with `{prefix}`, `{short_id}` (start of the attempt id) and `{slug}` (from the
+ * task title) placeholders
+ */
+template: string, prefix: string, 
+/**
+ * Longest slug taken from the task title, in characters
+ */
+max_slug_length: number, 
+/**
+ * Longest name, in characters. Longer names get their slug shortened and suffixed
+ * with a hash of the full title so they stay unique.
+ */
+max_name_length: number, };
+
+export type WatcherBackend = "AUTO" | "NATIVE" | "POLLING";
+
+export type FilesystemWatcherConfig = { backend: WatcherBackend, 
+/**
+ * How often the polling backend rescans, in milliseconds
+ */
+poll_interval_ms: bigint, };
+
+export type BrowserChatTimeoutConfig = { 
+/**
+ * Seconds before a Claude automation is killed (0 = no limit)
+ */
+claude_secs: bigint, 
+/**
+ * Seconds before an M365 Copilot automation is killed (0 = no limit). Its
+ * sign-in flow usually takes longer.
+ */
+m365_copilot_secs: bigint, };
+
+export type RateLimitStatus = { key: string, requests_per_minute: number, 
+/**
+ * Starts that can happen right now without waiting
+ */
+available: number, 
+/**
+ * Starts currently waiting for a token
+ */
+waiting: number, };
+
+export type ProjectExecutionCount = { project_id: string, running: number, 
+/**
+ * Starts waiting for the global or project limit
+ */
+waiting: number, };
+
 export type DeviceFlowStartResponse = { user_code: string, verification_uri: string, expires_in: number, interval: number, };
 
 export enum DevicePollStatus { SLOW_DOWN = "SLOW_DOWN", AUTHORIZATION_PENDING = "AUTHORIZATION_PENDING", SUCCESS = "SUCCESS" }
@@ -104,7 +393,25 @@ export enum CheckTokenResponse { VALID = "VALID", INVALID = "INVALID" }
 
 export type GitBranch = { name: string, is_current: boolean, is_remote: boolean, last_commit_date: Date, };
 
-export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: string | null, oldContent: string | null, newContent: string | null, };
+export type CommitMetadata = { sha: string, message: string, author_name: string | null, author_email: string | null, committer_name: string | null, committer_email: string | null, 
+/**
+ * Commit (committer) time
+ */
+timestamp: Date, };
+
+export type AbortedOperation = "none" | "merge" | "rebase" | "cherry_pick" | "revert" | "conflicts";
+
+export type DiffStats = { files_changed: number, insertions: number, deletions: number, commits: number, };
+
+export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: string | null, oldContent: string | null, newContent: string | null, 
+/**
+ * Highlight.js language id guessed from the file name
+ */
+language?: string, 
+/**
+ * Lockfile or generated file, collapsed by default when reviewing
+ */
+generated: boolean, };
 
 export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange";
 
@@ -122,6 +429,16 @@ base: string,
  */
 params: Array<string> | null, };
 
+export type ExecutorOverride = { 
+/**
+ * Params appended to the executor command, e.g. `--model opus`
+ */
+extra_params?: Array<string>, 
+/**
+ * Extra environment variables for the executor process
+ */
+env?: { [key in string]?: string }, };
+
 export type ExecutorProfileId = { 
 /**
  * The executor type (e.g., "CLAUDE_CODE", "AMP")
@@ -136,25 +453,25 @@ export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } |
 
 export type BaseAgentCapability = "RESTORE_CHECKPOINT";
 
-export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, dangerously_skip_permissions?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, dangerously_skip_permissions?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
-export type Gemini = { append_prompt: AppendPrompt, model: GeminiModel, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Gemini = { append_prompt: AppendPrompt, model: GeminiModel, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
 export type GeminiModel = "default" | "flash";
 
-export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
-export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, approval?: ApprovalPolicy | null, oss?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, approval?: ApprovalPolicy | null, oss?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
 export type SandboxMode = "read-only" | "workspace-write" | "danger-full-access";
 
 export type ApprovalPolicy = "untrusted" | "on-failure" | "on-request" | "never";
 
-export type Cursor = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Cursor = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
-export type Opencode = { append_prompt: AppendPrompt, model?: string | null, agent?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Opencode = { append_prompt: AppendPrompt, model?: string | null, agent?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
-export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
 export type AppendPrompt = string | null;
 
@@ -162,22 +479,59 @@ export type CodingAgentInitialRequest = { prompt: string,
 /**
  * Executor profile specification
  */
-executor_profile_id: ExecutorProfileId, };
+executor_profile_id: ExecutorProfileId, 
+/**
+ * Project-level extra params and environment for the executor
+ */
+executor_override?: ExecutorOverride | null, 
+/**
+ * Agent session to continue instead of starting a fresh one, e.g. the parent attempt's
+ * session for a continuation task. Falls back to a fresh session if it can't be resumed.
+ */
+resume_session_id?: string | null, 
+/**
+ * Project-level standing instructions for the agent
+ */
+agent_instructions?: string | null, };
 
 export type CodingAgentFollowUpRequest = { prompt: string, session_id: string, 
 /**
  * Executor profile specification
  */
-executor_profile_id: ExecutorProfileId, };
+executor_profile_id: ExecutorProfileId, 
+/**
+ * Project-level extra params and environment for the executor
+ */
+executor_override?: ExecutorOverride | null, 
+/**
+ * Project-level standing instructions for the agent
+ */
+agent_instructions?: string | null, };
 
 export type CreateTaskAttemptBody = { task_id: string, 
 /**
  * Executor profile specification
  */
-executor_profile_id: ExecutorProfileId, base_branch: string, };
+executor_profile_id: ExecutorProfileId, base_branch: string, 
+/**
+ * Directory within the project repo to scope the attempt to (monorepos)
+ */
+subpath: string | null, 
+/**
+ * Run in a disposable temp dir without a worktree or branch
+ */
+scratch: boolean | null, };
 
 export type RebaseTaskAttemptRequest = { new_base_branch: string | null, };
 
+export type PromoteAttemptRequest = { 
+/**
+ * Also fast-forward this branch to the attempt's branch tip
+ */
+fast_forward_branch: string | null, };
+
+export type RevertMergeRequest = { merge_id: string, };
+
 export type RestoreAttemptRequest = { 
 /**
  * Process to restore to (target = its after_head_commit)
@@ -198,15 +552,96 @@ export type CommitInfo = { sha: string, subject: string, };
 
 export type CommitCompareResult = { head_oid: string, target_oid: string, ahead_from_head: number, behind_from_head: number, is_linear: boolean, };
 
-export type BranchStatus = { commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, base_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, };
+export type BranchStatus = { commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, base_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
+/**
+ * Set for scratch attempts, which have no branch or worktree; every count is then
+ * empty
+ */
+scratch: boolean, };
+
+export type TaskAttemptWithBranchStatus = { branch_status: AttemptBranchStatus, 
+/**
+ * Whether any of the attempt's executions is still live, dev servers and queued starts
+ * included
+ */
+is_running: boolean, id: string, task_id: string, container_ref: string | null, branch: string | null, base_branch: string, subpath: string | null, executor: string, worktree_deleted: boolean, scratch: boolean, setup_completed_at: string | null, created_at: string, updated_at: string, };
 
-export type TaskAttempt = { id: string, task_id: string, container_ref: string | null, branch: string | null, base_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, created_at: string, updated_at: string, };
+export type WorktreeUsage = { total_bytes: number, 
+/**
+ * Largest first
+ */
+worktrees: Array<AttemptWorktreeUsage>, };
+
+export type AttemptWorktreeUsage = { 
+/**
+ * None when no task attempt owns the worktree directory
+ */
+attempt_id: string | null, path: string, bytes: number, };
+
+export type CreateTaskRequest = { 
+/**
+ * Start a first attempt right away. Only applies when `executor_profile_id` is set.
+ */
+auto_start?: boolean, 
+/**
+ * Executor profile for the auto-started attempt
+ */
+executor_profile_id?: ExecutorProfileId, project_id: string, title: string, description: string | null, parent_task_attempt: string | null, image_ids: Array<string> | null, };
+
+export type CreatedTask = { attempt_id: string | null, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, 
+/**
+ * Attempt promoted as the canonical one, which the board and new work default to
+ */
+preferred_attempt_id: string | null, created_at: string, updated_at: string, };
+
+export type SetSummaryRequest = { summary: string, };
+
+export type DevServerStatus = { running: boolean, 
+/**
+ * Latest dev server execution process for the attempt, if one was ever started
+ */
+execution_process_id: string | null, 
+/**
+ * URL the dev server announced in its output, when it could be detected
+ */
+url: string | null, port: number | null, };
+
+export type AttemptBranchStatus = { commits_ahead: number, commits_behind: number, 
+/**
+ * False when the attempt's branch or its base branch doesn't exist (yet); the
+ * counts are then zero
+ */
+branches_exist: boolean, };
+
+export type AttemptDiffStats = { task_attempt_id: string, stats: DiffStats | null, };
+
+export type AttemptComparison = { merge_base: string, from: CommitMetadata, to: CommitMetadata, };
+
+export type TaskAttempt = { id: string, task_id: string, container_ref: string | null, branch: string | null, base_branch: string, subpath: string | null, executor: string, worktree_deleted: boolean, scratch: boolean, setup_completed_at: string | null, created_at: string, updated_at: string, };
+
+export type ExecutionTimelineEntry = { execution_process_id: string, run_reason: ExecutionProcessRunReason, status: ExecutionProcessStatus, exit_code: bigint | null, kill_reason: ExecutionProcessKillReason | null, started_at: string, completed_at: string | null, 
+/**
+ * Run time so far for processes that haven't finished
+ */
+duration_ms: number, };
 
 export type ExecutionProcess = { id: string, task_attempt_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, 
 /**
  * Git HEAD commit OID captured after the process ends
  */
-after_head_commit: string | null, status: ExecutionProcessStatus, exit_code: bigint | null, 
+after_head_commit: string | null, 
+/**
+ * URL announced by a dev server process once it started listening
+ */
+dev_server_url: string | null, status: ExecutionProcessStatus, exit_code: bigint | null, 
+/**
+ * Set when the process was stopped rather than exiting on its own
+ */
+kill_reason: ExecutionProcessKillReason | null, 
+/**
+ * How many times the action was re-run after a retryable failure before this run
+ */
+retry_count: bigint, 
 /**
  * dropped: true if this process is excluded from the current
  * history view (due to restore/trimming). Hidden from logs/timeline;
@@ -214,15 +649,31 @@ after_head_commit: string | null, status: ExecutionProcessStatus, exit_code: big
  */
 dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
 
-export type ExecutionProcessStatus = "running" | "completed" | "failed" | "killed";
+export type ExecutionProcessStatus = "queued" | "running" | "completed" | "failed" | "killed";
 
 export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver" | "browserchat";
 
+export type ExecutionProcessKillReason = "user" | "timeout" | "shutdown" | "unknown";
+
+export type ExecutorSession = { id: string, task_attempt_id: string, execution_process_id: string, session_id: string | null, prompt: string | null, summary: string | null, 
+/**
+ * The summary was set by the user and is kept over the extracted one
+ */
+summary_overridden: boolean, input_tokens: bigint | null, output_tokens: bigint | null, cost_usd: number | null, created_at: string, updated_at: string, };
+
 export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
 
-export type DirectMerge = { id: string, task_attempt_id: string, merge_commit: string, target_branch_name: string, created_at: string, };
+export type DirectMerge = { id: string, task_attempt_id: string, merge_commit: string, target_branch_name: string, created_at: string, 
+/**
+ * Set once the merge has been reverted on the target branch
+ */
+revert_commit: string | null, reverted_at: string | null, };
 
-export type PrMerge = { id: string, task_attempt_id: string, created_at: string, target_branch_name: string, pr_info: PullRequestInfo, };
+export type PrMerge = { id: string, task_attempt_id: string, created_at: string, target_branch_name: string, pr_info: PullRequestInfo, 
+/**
+ * Set once the merged PR has been reverted on the target branch
+ */
+revert_commit: string | null, reverted_at: string | null, };
 
 export type MergeStatus = "open" | "merged" | "closed" | "unknown";
 
@@ -234,6 +685,8 @@ export type EventPatchInner = { db_op: string, record: RecordTypes, };
 
 export type RecordTypes = { "type": "TASK", "data": Task } | { "type": "TASK_ATTEMPT", "data": TaskAttempt } | { "type": "EXECUTION_PROCESS", "data": ExecutionProcess } | { "type": "DELETED_TASK", "data": { rowid: bigint, project_id: string | null, task_id: string | null, } } | { "type": "DELETED_TASK_ATTEMPT", "data": { rowid: bigint, task_id: string | null, } } | { "type": "DELETED_EXECUTION_PROCESS", "data": { rowid: bigint, task_attempt_id: string | null, } };
 
+export type BoardEvent = { "event": "snapshot", tasks: Array<TaskWithAttemptStatus>, } | { "event": "task.created", task: TaskWithAttemptStatus, } | { "event": "task.updated", task: TaskWithAttemptStatus, } | { "event": "task.deleted", task_id: string, } | { "event": "attempt.status_changed", task_id: string, task_attempt_id: string, execution_process_id: string, run_reason: ExecutionProcessRunReason, status: ExecutionProcessStatus, };
+
 export type CommandExitStatus = { "type": "exit_code", code: number, } | { "type": "success", success: boolean, };
 
 export type CommandRunResult = { exit_status: CommandExitStatus | null, output: string | null, };
@@ -266,6 +719,8 @@ value: JsonValue, };
 
 export type ToolResultValueType = { "type": "markdown" } | { "type": "json" };
 
+export type TokenUsage = { input_tokens: bigint | null, output_tokens: bigint | null, cost_usd: number | null, };
+
 export type PatchType = { "type": "NORMALIZED_ENTRY", "content": NormalizedEntry } | { "type": "STDOUT", "content": string } | { "type": "STDERR", "content": string } | { "type": "DIFF", "content": Diff };
 
 export type JsonValue = number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null;